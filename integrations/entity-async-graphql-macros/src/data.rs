@@ -57,7 +57,7 @@ impl From<GqlEnt> for GqlEntFieldAttrMap {
 
 /// Information about a struct deriving ent
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(ent), supports(struct_named))]
+#[darling(allow_unknown_fields, attributes(ent), supports(struct_named))]
 pub struct GqlEnt {
     pub ident: Ident,
     pub data: ast::Data<(), GqlEntField>,
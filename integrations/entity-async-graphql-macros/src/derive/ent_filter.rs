@@ -149,7 +149,7 @@ pub fn do_derive_ent_filter(
                 #(
                     if let ::std::option::Option::Some(filter) = x.#edge_struct_field_names {
                         let edge_query = #root::Query::from(filter);
-                        for edge_filter in edge_query {
+                        for edge_filter in #root::Query::into_filters(edge_query) {
                             query.add_filter(#root::Filter::where_edge(
                                 ::std::stringify!(#edge_struct_field_names),
                                 edge_filter,
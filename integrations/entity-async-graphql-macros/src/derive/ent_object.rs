@@ -122,11 +122,11 @@ pub fn do_derive_ent_object(root: Path, ent: StructEnt) -> darling::Result<Token
                 StructEntEdgeKind::One => quote!(#ent_ty),
                 StructEntEdgeKind::Many => quote!(::std::vec::Vec<#ent_ty>),
             };
-            let load_method_name = format_ident!("load_{}", e.name);
+            let load_method_name = format_ident!("load_{}_async", e.name);
             fns.push(quote! {
                 #[graphql(name = #gql_name)]
                 async fn #method_name(&self) -> #async_graphql_root::Result<#ret_ty> {
-                    self.#load_method_name().map_err(|x|
+                    self.#load_method_name().await.map_err(|x|
                         #async_graphql_root::Error::new(::std::string::ToString::to_string(&x))
                     )
                 }
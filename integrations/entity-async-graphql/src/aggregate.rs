@@ -0,0 +1,118 @@
+use crate::{GqlEntFilter, GqlValue};
+use async_graphql::{Enum, Error, InputObject, Object};
+use derive_more::{From, Into};
+use entity::{Aggregate, AggregateGroup, Aggregation, Query};
+use std::convert::TryFrom;
+
+/// Represents the kind of calculation performed by an [`Aggregate`]
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Enum)]
+pub enum GqlAggregateKind {
+    /// Counts the ents
+    Count,
+
+    /// Adds together the numbers of a field
+    Sum,
+
+    /// Averages the numbers of a field
+    Avg,
+
+    /// Finds the smallest value of a field
+    Min,
+
+    /// Finds the largest value of a field
+    Max,
+
+    /// Collects the distinct values of a field
+    Distinct,
+}
+
+/// Represents a wrapper around an [`Aggregate`] that exposes a GraphQL API.
+#[derive(Clone, InputObject)]
+#[graphql(rename_fields = "snake_case")]
+pub struct GqlAggregate {
+    /// Kind of calculation to perform
+    kind: GqlAggregateKind,
+
+    /// Name of the field to calculate over, required by all kinds other
+    /// than count
+    field: Option<String>,
+}
+
+impl TryFrom<GqlAggregate> for Aggregate {
+    type Error = Error;
+
+    /// Converts [`GqlAggregate`] to [`Aggregate`], failing if the kind of
+    /// aggregate requires a field and none was provided
+    fn try_from(x: GqlAggregate) -> Result<Self, Self::Error> {
+        let field = || {
+            x.field
+                .clone()
+                .ok_or_else(|| Error::new(format!("{:?} aggregate requires a field", x.kind)))
+        };
+
+        Ok(match x.kind {
+            GqlAggregateKind::Count => Aggregate::Count,
+            GqlAggregateKind::Sum => Aggregate::Sum(field()?),
+            GqlAggregateKind::Avg => Aggregate::Avg(field()?),
+            GqlAggregateKind::Min => Aggregate::Min(field()?),
+            GqlAggregateKind::Max => Aggregate::Max(field()?),
+            GqlAggregateKind::Distinct => Aggregate::Distinct(field()?),
+        })
+    }
+}
+
+/// Represents a wrapper around an [`Aggregation`] that exposes a GraphQL API.
+#[derive(Clone, InputObject)]
+#[graphql(rename_fields = "snake_case")]
+pub struct GqlAggregation {
+    /// Filter used to find the ents to aggregate
+    filter: GqlEntFilter,
+
+    /// Aggregates to calculate for each group of ents
+    aggregates: Vec<GqlAggregate>,
+
+    /// Name of the field whose values are used to group ents
+    group_by: Option<String>,
+}
+
+impl TryFrom<GqlAggregation> for Aggregation {
+    type Error = Error;
+
+    /// Converts [`GqlAggregation`] to [`Aggregation`], failing if any of
+    /// its aggregates cannot be converted
+    fn try_from(x: GqlAggregation) -> Result<Self, Self::Error> {
+        let mut aggregation = Aggregation::new(Query::from(x.filter));
+
+        for aggregate in x.aggregates {
+            aggregation = aggregation.aggregate(Aggregate::try_from(aggregate)?);
+        }
+
+        if let Some(name) = x.group_by {
+            aggregation = aggregation.group_by(name);
+        }
+
+        Ok(aggregation)
+    }
+}
+
+/// Represents a wrapper around an `AggregateGroup` to expose as graphql
+#[derive(From, Into)]
+pub struct GqlAggregateGroup(AggregateGroup);
+
+#[Object]
+impl GqlAggregateGroup {
+    #[graphql(name = "key")]
+    async fn gql_key(&self) -> Option<GqlValue> {
+        self.0.key().cloned().map(GqlValue::from)
+    }
+
+    #[graphql(name = "values")]
+    async fn gql_values(&self) -> Vec<GqlValue> {
+        self.0
+            .values()
+            .iter()
+            .cloned()
+            .map(GqlValue::from)
+            .collect()
+    }
+}
@@ -28,4 +28,9 @@ impl GqlField {
     async fn gql_immutable(&self) -> bool {
         self.0.is_immutable()
     }
+
+    #[graphql(name = "unique")]
+    async fn gql_unique(&self) -> bool {
+        self.0.is_unique()
+    }
 }
@@ -59,7 +59,8 @@ impl GqlDynEnt {
     #[graphql(name = "load_edge")]
     async fn gql_load_edge(&self, name: String) -> Result<Vec<Self>> {
         self.0
-            .load_edge(&name)
+            .load_edge_async(&name)
+            .await
             .map(|x| x.into_iter().map(Self::from).collect())
             .map_err(|x| Error::new(x.to_string()))
     }
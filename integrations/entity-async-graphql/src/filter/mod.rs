@@ -69,7 +69,7 @@ impl From<GqlEntFilter> for Query {
         if let Some(gql_filters) = x.edges {
             for f in gql_filters {
                 let edge_query = Query::from(f.filter.as_ref().clone());
-                for edge_filter in edge_query {
+                for edge_filter in edge_query.into_filters() {
                     query.add_filter(Filter::where_edge(&f.name, edge_filter));
                 }
             }
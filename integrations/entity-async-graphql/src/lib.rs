@@ -1,6 +1,8 @@
+mod aggregate;
 mod ent;
 mod filter;
 
+pub use aggregate::*;
 pub use ent::*;
 pub use filter::*;
 
@@ -12,10 +14,10 @@ mod tests {
     use super::*;
 
     use async_graphql::{value, Context, EmptyMutation, EmptySubscription, Object, Schema};
-    use entity::{Database, DatabaseRc, Edge, Field, Id, UntypedEnt, Value};
+    use entity::{Aggregation, Database, DatabaseRc, Edge, Field, Id, UntypedEnt, Value};
     use entity_inmemory::InmemoryDatabase;
     use entity_sled::SledDatabase;
-    use std::collections::HashMap;
+    use std::{collections::HashMap, convert::TryFrom};
 
     macro_rules! impl_tests {
         ($db_type:ty, $new_db:expr) => {
@@ -158,6 +160,18 @@ mod tests {
                         Err(async_graphql::Error::new("Must provide one argument"))
                     }
                 }
+
+                async fn aggregate<'ctx>(
+                    &self,
+                    ctx: &'ctx Context<'_>,
+                    aggregation: GqlAggregation,
+                ) -> async_graphql::Result<Vec<GqlAggregateGroup>> {
+                    let db = ctx.data::<DatabaseRc>()?;
+                    let aggregation = Aggregation::try_from(aggregation)?;
+
+                    db.aggregate(aggregation).map(|x| x.into_iter().map(GqlAggregateGroup::from).collect())
+                        .map_err(|x| async_graphql::Error::new(x.to_string()))
+                }
             }
 
             #[test]
@@ -205,6 +219,51 @@ mod tests {
                     })
                 );
             }
+
+            #[test]
+            fn supports_aggregation() {
+                let schema = Schema::build(TestQuery, EmptyMutation, EmptySubscription)
+                    .data(DatabaseRc::new(Box::new(new_test_database())))
+                    .finish();
+                let input = r#"
+                    {
+                        aggregate(aggregation: {
+                            filter: { id: { less_than: 7 } },
+                            aggregates: [{ kind: COUNT }, { kind: SUM, field: "a" }],
+                            group_by: "b",
+                        }) {
+                            key
+                            values
+                        }
+                    }
+                "#;
+                let response = futures::executor::block_on(schema.execute(input.trim()));
+                assert_eq!(
+                    response.data,
+                    value!({
+                        "aggregate": [
+                            { "key": 2, "values": [1, 1] },
+                            { "key": 4, "values": [1, 3] },
+                            { "key": 6, "values": [1, 5] },
+                            { "key": null, "values": [3, null] },
+                        ],
+                    })
+                );
+
+                // Aggregates other than count require a field
+                let input = r#"
+                    {
+                        aggregate(aggregation: {
+                            filter: { id: { less_than: 7 } },
+                            aggregates: [{ kind: SUM }],
+                        }) {
+                            values
+                        }
+                    }
+                "#;
+                let response = futures::executor::block_on(schema.execute(input.trim()));
+                assert_eq!(response.errors.len(), 1);
+            }
         };
     }
 
@@ -1,10 +1,14 @@
 use entity::{
-    Database, DatabaseError, DatabaseResult, EdgeDeletionPolicy, Ent, Filter, Id, IdAllocator,
-    Predicate, Primitive, Query, Value, EPHEMERAL_ID,
+    AggregateGroup, Aggregation, Assoc, Change, ChangeSender, Cursor, Database, DatabaseError,
+    DatabaseResult, EdgeDeletionPolicy, Ent, Filter, Id, IdAllocator, IndexKey, IndexScan,
+    InverseEdgeChange, OrderBy, OrderDirection, Page, Patch, Path, Predicate, Primitive, Query,
+    Subscription, Transaction, TransactionOp, Traversal, Value, EPHEMERAL_ID,
 };
 use std::{
-    collections::{HashMap, HashSet},
-    sync::Mutex,
+    collections::{hash_map::Entry, BTreeSet, HashMap, HashSet},
+    ops::Bound::{Excluded, Included, Unbounded},
+    sync::{Mutex, MutexGuard},
+    thread::{self, ThreadId},
 };
 
 type EntIdSet = HashSet<Id>;
@@ -22,6 +26,83 @@ pub struct InmemoryDatabase {
 
     /// Id allocator for ents
     alloc: Mutex<IdAllocator>,
+
+    /// Secondary indexes of field values and edges, which are built from the
+    /// stored ents the first time they are needed (such as after
+    /// deserializing)
+    #[cfg_attr(feature = "serde-1", serde(skip))]
+    indexes: Mutex<Option<Indexes>>,
+
+    /// Associations of ents with the ents on their edges, organized by the
+    /// id of each ent and then by the name of the edge
+    #[cfg_attr(feature = "serde-1", serde(default))]
+    assocs: Mutex<HashMap<Id, HashMap<String, Vec<Assoc>>>>,
+
+    /// Transactions in progress, keyed by the thread that began them
+    #[cfg_attr(feature = "serde-1", serde(skip))]
+    transactions: Mutex<HashMap<ThreadId, PendingTransaction>>,
+
+    /// Senders of changes to subscriptions alongside the queries used to
+    /// filter the changes, dropped once their subscription is dropped
+    #[cfg_attr(feature = "serde-1", serde(skip))]
+    subscribers: Mutex<Vec<(Query, ChangeSender)>>,
+}
+
+/// Represents changes staged by a thread alongside the ids allocated for
+/// them, which are returned to the allocator if the changes are discarded
+#[derive(Default)]
+struct PendingTransaction {
+    transaction: Transaction,
+    allocated: Vec<Id>,
+}
+
+/// Represents exclusive access to all storage of the database, used to
+/// apply writes without other threads observing them partially applied
+struct Storage<'a> {
+    ents: MutexGuard<'a, HashMap<Id, Box<dyn Ent>>>,
+    ents_of_type: MutexGuard<'a, HashMap<String, EntIdSet>>,
+    alloc: MutexGuard<'a, IdAllocator>,
+    indexes: MutexGuard<'a, Option<Indexes>>,
+    assocs: MutexGuard<'a, HashMap<Id, HashMap<String, Vec<Assoc>>>>,
+
+    /// Changes made to ents while storage is locked, which are only recorded
+    /// if there are any subscribers to send them to
+    changes: Option<Vec<Change>>,
+}
+
+/// Represents the secondary indexes of the database
+#[derive(Default)]
+struct Indexes {
+    /// Indexes of field values, organized by the type of ent and then by the
+    /// name of the field
+    fields: HashMap<String, HashMap<String, FieldIndex>>,
+
+    /// Ids of ents alongside the names of their edges, organized by the id
+    /// of each ent contained by those edges
+    edges: HashMap<Id, HashSet<(Id, String)>>,
+
+    /// Ids of the ents holding each value of a unique field
+    unique: HashMap<UniqueKey, Id>,
+}
+
+/// Represents the value of a unique field of a type of ent, identified by
+/// the type, the name of the field, and the key of the value
+type UniqueKey = (String, String, IndexKey);
+
+/// Represents the index of a single field for a single type of ent
+#[derive(Default)]
+struct FieldIndex {
+    /// Ids of ents by the key of their field's value, used to look up
+    /// values that are equal
+    by_key: HashMap<IndexKey, EntIdSet>,
+
+    /// Keys of all indexed values in order, used to look up values that
+    /// fall within a range
+    ordered: BTreeSet<IndexKey>,
+
+    /// Total ents with the field that are not marked as indexed, meaning
+    /// that all ents of the type need to be checked
+    unindexed: usize,
 }
 
 impl InmemoryDatabase {
@@ -38,6 +119,10 @@ impl Default for InmemoryDatabase {
             ents: Mutex::new(HashMap::new()),
             ents_of_type: Mutex::new(HashMap::new()),
             alloc: Mutex::new(IdAllocator::new()),
+            indexes: Mutex::new(None),
+            assocs: Mutex::new(HashMap::new()),
+            transactions: Mutex::new(HashMap::new()),
+            subscribers: Mutex::new(Vec::new()),
         }
     }
 }
@@ -50,10 +135,332 @@ impl Database for InmemoryDatabase {
     }
 
     fn find_all(&self, query: Query) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+        let ids = self.find_ids(&query);
+        query.order_and_page(ids.into_iter().filter_map(|id| self.get(id).transpose()))
+    }
+
+    fn find_page(&self, query: Query, page_size: usize) -> DatabaseResult<Page> {
+        let order = query.order().to_vec();
+        let query = query.limit(page_size.saturating_add(1));
+        let ids = self.find_ids(&query);
+
+        // If the ids can be ordered without the ents, only the ents of the
+        // page itself are retrieved
+        let ents = match self.seek_ids(&query, &ids) {
+            Some(ids) => ids
+                .into_iter()
+                .filter_map(|id| self.get(id).transpose())
+                .skip(query.get_offset())
+                .collect::<DatabaseResult<Vec<Box<dyn Ent>>>>()?,
+            None => {
+                query.order_and_page(ids.into_iter().filter_map(|id| self.get(id).transpose()))?
+            }
+        };
+
+        Ok(Page::from_results(&order, ents, page_size))
+    }
+
+    fn count(&self, query: Query) -> DatabaseResult<usize> {
+        let ids = self.find_ids(&query);
+        match query.count_ids(ids.iter().copied().filter(|id| self.has_id(*id))) {
+            Some(count) => Ok(count),
+
+            // Positions relative to the query's cursor can only be determined
+            // using the ents themselves
+            None => query
+                .order_and_page(ids.into_iter().filter_map(|id| self.get(id).transpose()))
+                .map(|ents| ents.len()),
+        }
+    }
+
+    fn exists(&self, query: Query) -> DatabaseResult<bool> {
+        let limit = query.get_limit().unwrap_or(1).min(1);
+        self.count(query.limit(limit)).map(|count| count > 0)
+    }
+
+    fn aggregate(&self, aggregation: Aggregation) -> DatabaseResult<Vec<AggregateGroup>> {
+        let query = aggregation.query();
+        let ents = self
+            .find_ids(query)
+            .into_iter()
+            .filter_map(|id| self.get(id).transpose());
+
+        // Unless the query is paged, every ent passing its filters is
+        // aggregated, so they can be aggregated one at a time
+        if query.is_paged() {
+            aggregation.apply(query.order_and_page(ents)?.into_iter().map(Ok))
+        } else {
+            aggregation.apply(ents)
+        }
+    }
+
+    fn incoming_edges(&self, id: Id) -> DatabaseResult<Vec<(Id, String)>> {
+        let mut incoming: Vec<(Id, String)> = self.incoming(id).into_iter().collect();
+        incoming.sort();
+        Ok(incoming)
+    }
+
+    fn assocs(&self, id: Id, edge: &str) -> DatabaseResult<Vec<Assoc>> {
+        // Staged ents may have ids on their edges that are not recorded yet
+        let ent = match self.get(id)? {
+            Some(ent) => ent,
+            None => return Ok(Vec::new()),
+        };
+        let ids = ent
+            .edge(edge)
+            .map(|value| value.to_ids())
+            .unwrap_or_default();
+
+        let assocs = self.assocs.lock().unwrap();
+        let recorded = assocs
+            .get(&id)
+            .and_then(|edges| edges.get(edge))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        Ok(Assoc::for_ids(ids, recorded, ent.last_updated()))
+    }
+
+    fn set_assoc_data(&self, id: Id, edge: &str, target: Id, data: Value) -> DatabaseResult<bool> {
+        let mut storage = self.lock_storage();
+        let (ids, created) = match storage.ents.get(&id) {
+            Some(ent) => match ent.edge(edge) {
+                Some(value) => (value.to_ids(), ent.last_updated()),
+                None => return Ok(false),
+            },
+            None => return Ok(false),
+        };
+
+        // Ents stored before their associations were recorded have their
+        // associations recorded now
+        let assocs = storage
+            .assocs
+            .entry(id)
+            .or_default()
+            .entry(edge.to_string())
+            .or_default();
+        *assocs = Assoc::for_ids(ids, assocs, created);
+
+        match assocs.iter_mut().find(|assoc| assoc.id() == target) {
+            Some(assoc) => {
+                *assoc.data_mut() = Some(data);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn traverse(&self, traversal: Traversal) -> DatabaseResult<Vec<(Path, Box<dyn Ent>)>> {
+        // Ents reached through an edge with a filter are loaded once and
+        // checked against the filter directly rather than finding them with
+        // a query
+        traversal.apply(|id, filter| {
+            self.get(id).map(|maybe_ent| {
+                maybe_ent.filter(|ent| {
+                    filter.map_or(true, |filter| filter_ent(self, ent.as_ref(), filter, None))
+                })
+            })
+        })
+    }
+
+    fn get(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>> {
+        // If the current thread has staged changes to the ent, those take
+        // precedence over what is actually stored
+        let maybe_ent = match self.with_pending(|pending| {
+            pending
+                .transaction
+                .staged(id)
+                .map(|maybe_ent| maybe_ent.map(dyn_clone::clone_box))
+        }) {
+            Some(Some(staged)) => staged,
+            _ => self
+                .ents
+                .lock()
+                .unwrap()
+                .get(&id)
+                .map(|ent| dyn_clone::clone_box(ent.as_ref())),
+        };
+
+        // If we found an ent without a database connection, attempt to fill
+        // it in with the global database if it exists
+        Ok(if let Some(mut ent) = maybe_ent {
+            if !ent.is_connected() {
+                ent.connect(entity::global::db());
+            }
+            Some(ent)
+        } else {
+            None
+        })
+    }
+
+    fn remove(&self, id: Id) -> DatabaseResult<bool> {
+        // If the current thread has a transaction in progress, stage the
+        // removal to be processed when the transaction is committed
+        if self.in_transaction() {
+            let exists = self.has_id(id);
+            self.with_pending(|pending| pending.transaction.remove(id));
+            return Ok(exists);
+        }
+
+        Ok(self.write(|storage| storage.remove(id)))
+    }
+
+    fn insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id> {
+        self.insert_ent(ent, None)
+    }
+
+    fn compare_and_insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id> {
+        let expected = ent.last_updated();
+        self.insert_ent(ent, Some(expected))
+    }
+
+    fn compare_and_insert_at(&self, ent: Box<dyn Ent>, expected: u64) -> DatabaseResult<Id> {
+        self.insert_ent(ent, Some(expected))
+    }
+
+    fn update_fields(&self, id: Id, patch: Patch) -> DatabaseResult<Box<dyn Ent>> {
+        // If the current thread has a transaction in progress, stage the
+        // patched ent to be written as long as the ent is unchanged when the
+        // transaction is committed
+        if self.in_transaction() {
+            let mut ent = self.get(id)?.ok_or(DatabaseError::MissingEnt { id })?;
+            let last_updated = ent.last_updated();
+            patch_ent(ent.as_mut(), &patch)?;
+            self.with_pending(|pending| {
+                pending.transaction.expect(id, last_updated);
+                pending.transaction.insert(ent.clone());
+            });
+            return Ok(ent);
+        }
+
+        self.write(|storage| {
+            let mut ent = storage
+                .ents
+                .get(&id)
+                .cloned()
+                .ok_or(DatabaseError::MissingEnt { id })?;
+            patch_ent(ent.as_mut(), &patch)?;
+
+            let ops = vec![TransactionOp::Insert(ent.clone())];
+            storage.check(&ops)?;
+            storage.apply(ops);
+
+            Ok(ent)
+        })
+    }
+
+    fn begin_transaction(&self) -> DatabaseResult<()> {
+        match self
+            .transactions
+            .lock()
+            .unwrap()
+            .entry(thread::current().id())
+        {
+            Entry::Occupied(_) => Err(DatabaseError::TransactionInProgress),
+            Entry::Vacant(entry) => {
+                entry.insert(PendingTransaction::default());
+                Ok(())
+            }
+        }
+    }
+
+    fn commit_transaction(&self) -> DatabaseResult<()> {
+        let pending = self.take_pending()?;
+
+        // All storage is locked for the duration so no other thread can
+        // observe the transaction partially applied
+        self.write(|storage| {
+            if let Err(x) = storage.check(pending.transaction.ops()) {
+                // Return any ids allocated for the discarded ents
+                storage.alloc.extend(pending.allocated);
+                return Err(x);
+            }
+
+            storage.apply(pending.transaction);
+
+            Ok(())
+        })
+    }
+
+    fn subscribe(&self, query: Query) -> DatabaseResult<Subscription> {
+        let (sender, subscription) = Subscription::channel();
+        self.subscribers.lock().unwrap().push((query, sender));
+        Ok(subscription)
+    }
+
+    fn rollback_transaction(&self) -> DatabaseResult<()> {
+        let pending = self.take_pending()?;
+
+        // Return any ids allocated for the discarded ents
+        self.alloc.lock().unwrap().extend(pending.allocated);
+
+        Ok(())
+    }
+}
+
+impl InmemoryDatabase {
+    /// Returns ids of all ents stored in the database
+    pub fn ids(&self) -> EntIdSet {
+        let mut ids: EntIdSet = self.ents.lock().unwrap().keys().copied().collect();
+
+        self.with_pending(|pending| {
+            for op in pending.transaction.ops() {
+                match op {
+                    TransactionOp::Insert(ent) => {
+                        ids.insert(ent.id());
+                    }
+                    TransactionOp::Remove(id) => {
+                        ids.remove(id);
+                    }
+                    TransactionOp::Expect { .. } => {}
+                }
+            }
+        });
+
+        ids
+    }
+
+    /// Returns ids of all ents that pass the filters of the query, in no
+    /// particular order
+    fn find_ids(&self, query: &Query) -> EntIdSet {
         let mut pipeline: Option<EntIdSet> = None;
 
-        for filter in query {
-            let mut_pipeline = pipeline.get_or_insert_with(|| prefill_ids(self, &filter));
+        for filter in query.filters() {
+            // If our filter is a set operation, we combine the ids found so
+            // far with the ids found by its query rather than filtering them
+            match filter {
+                Filter::Union(other) => {
+                    let other = self.query_ids(other);
+                    pipeline.get_or_insert_with(EntIdSet::new).extend(other);
+                    continue;
+                }
+                Filter::Intersect(other) => {
+                    let other = self.query_ids(other);
+                    match pipeline.as_mut() {
+                        Some(ids) => ids.retain(|id| other.contains(id)),
+                        None => pipeline = Some(other),
+                    }
+                    continue;
+                }
+                Filter::Except(other) => {
+                    let other = self.query_ids(other);
+                    pipeline
+                        .get_or_insert_with(|| self.ids())
+                        .retain(|id| !other.contains(id));
+                    continue;
+                }
+                _ => {}
+            }
+
+            // If the filter can be served by an index, narrow down the ids
+            // to those found by the index before checking the filter itself
+            if let Some(candidates) = indexed_ids(self, filter) {
+                match pipeline.as_mut() {
+                    Some(ids) => ids.retain(|id| candidates.contains(id)),
+                    None => pipeline = Some(candidates),
+                }
+            }
+
+            let mut_pipeline = pipeline.get_or_insert_with(|| prefill_ids(self, filter));
 
             // If our filter is the special IntoEdge case, we don't want to
             // actually filter out ids but rather transform them into the ids
@@ -68,7 +475,7 @@ impl Database for InmemoryDatabase {
                                     .map(|maybe_ent| {
                                         maybe_ent
                                             .and_then(|ent| {
-                                                ent.edge(&name).map(|edge| edge.to_ids())
+                                                ent.edge(name).map(|edge| edge.to_ids())
                                             })
                                             .unwrap_or_default()
                                     })
@@ -77,94 +484,168 @@ impl Database for InmemoryDatabase {
                             .collect(),
                     )
                 }
+                // Similarly, IntoInverseEdge transforms ids into the ids of
+                // ents whose edge contains them
+                Filter::IntoInverseEdge(name) => {
+                    pipeline = Some(
+                        mut_pipeline
+                            .iter()
+                            .flat_map(|id| self.ids_for_inverse_edge(*id, name))
+                            .collect(),
+                    )
+                }
                 // Otherwise, the filter is a traditional case where we will
                 // strip out ids by the filter
                 f => {
-                    mut_pipeline.retain(|id| filter_id(self, id, &f));
+                    mut_pipeline.retain(|id| filter_id(self, id, f, None));
                 }
             }
         }
 
-        pipeline
+        pipeline.unwrap_or_default()
+    }
+
+    /// Returns ids of all ents returned by the query, only retrieving the
+    /// ents if they are needed to order and page them
+    fn query_ids(&self, query: &Query) -> EntIdSet {
+        let ids = self.find_ids(query);
+        if !query.is_paged() {
+            return ids.into_iter().filter(|id| self.has_id(*id)).collect();
+        }
+
+        query
+            .order_and_page(ids.into_iter().filter_map(|id| self.get(id).transpose()))
+            .map(|ents| ents.iter().map(|ent| ent.id()).collect())
             .unwrap_or_default()
-            .into_iter()
-            .filter_map(|id| self.get(id).transpose())
-            .collect()
     }
 
-    fn get(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>> {
-        let maybe_ent = self
-            .ents
+    /// Returns the ids in the order of the query that are positioned after
+    /// its cursor, up to its offset plus limit, or none if the ids cannot be
+    /// ordered without retrieving their ents
+    ///
+    /// Ids can be ordered by themselves, or by a single field that is
+    /// indexed for every type of ent that has it, which is walked from the
+    /// cursor's position rather than ordering every id.
+    fn seek_ids(&self, query: &Query, ids: &EntIdSet) -> Option<Vec<Id>> {
+        let count = query.get_limit()?.saturating_add(query.get_offset());
+        let after = query.get_after();
+
+        match query.order() {
+            [] => Some(seek_by_id(ids, OrderDirection::Ascending, after, count)),
+            [order] => match order.by() {
+                OrderBy::Id => Some(seek_by_id(ids, order.direction(), after, count)),
+
+                // Staged ents are not part of the indexes yet
+                OrderBy::Field(name) if self.with_pending(|_| ()).is_none() => self
+                    .lock_storage()
+                    .seek(name, order.direction(), ids, after, count),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns true if database contains the provided id
+    pub fn has_id(&self, id: Id) -> bool {
+        match self.with_pending(|pending| pending.transaction.staged(id).map(|x| x.is_some())) {
+            Some(Some(exists)) => exists,
+            _ => self.ents.lock().unwrap().contains_key(&id),
+        }
+    }
+
+    /// Returns ids of all ents for the given type
+    pub fn ids_for_type(&self, r#type: &str) -> EntIdSet {
+        let mut ids = self
+            .ents_of_type
             .lock()
             .unwrap()
-            .get(&id)
-            .map(|ent| dyn_clone::clone_box(ent.as_ref()));
+            .get(r#type)
+            .cloned()
+            .unwrap_or_default();
 
-        // If we found an ent without a database connection, attempt to fill
-        // it in with the global database if it exists
-        Ok(if let Some(mut ent) = maybe_ent {
-            if !ent.is_connected() {
-                ent.connect(entity::global::db());
+        self.with_pending(|pending| {
+            for op in pending.transaction.ops() {
+                match op {
+                    TransactionOp::Insert(ent) if ent.r#type() == r#type => {
+                        ids.insert(ent.id());
+                    }
+                    TransactionOp::Insert(ent) => {
+                        ids.remove(&ent.id());
+                    }
+                    TransactionOp::Remove(id) => {
+                        ids.remove(id);
+                    }
+                    TransactionOp::Expect { .. } => {}
+                }
             }
-            Some(ent)
-        } else {
-            None
-        })
+        });
+
+        ids
     }
 
-    fn remove(&self, id: Id) -> DatabaseResult<bool> {
-        // Remove the ent and, if it has an associated schema, we process
-        // each of the edges identified in the schema based on deletion attributes
-        if let Some(ent) = self.ents.lock().unwrap().remove(&id) {
-            for edge in ent.edges() {
-                match edge.deletion_policy() {
-                    // If shallow deletion, we only want to remove the connections
-                    // back to this ent from the corresponding ents
-                    EdgeDeletionPolicy::ShallowDelete => {
-                        for edge_id in edge.to_ids() {
-                            if let Some(ent) = self.ents.lock().unwrap().get_mut(&edge_id) {
-                                for mut edge in ent.edges() {
-                                    let _ = edge.value_mut().remove_ids(Some(edge_id));
-                                    let name = edge.name().to_string();
-                                    let _ = ent.update_edge(&name, edge.into_value());
-                                }
+    /// Returns ids of all ents that could have a value for the named field
+    /// found by the scan, which includes every ent of a type whose field is
+    /// not indexed
+    pub fn ids_for_field(&self, name: &str, scan: &IndexScan) -> EntIdSet {
+        let mut ids = self.lock_storage().find(name, scan);
+
+        // Staged ents may have a value for the field that is not indexed yet
+        self.with_pending(|pending| {
+            ids.extend(pending.transaction.staged_ids().0);
+        });
+
+        ids
+    }
+
+    /// Returns ids of all ents whose edge of the given name contains the id
+    pub fn ids_for_inverse_edge(&self, id: Id, name: &str) -> EntIdSet {
+        self.incoming(id)
+            .into_iter()
+            .filter(|(_, edge)| edge == name)
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Returns ids of all ents alongside the names of their edges that
+    /// contain the id
+    fn incoming(&self, id: Id) -> HashSet<(Id, String)> {
+        let mut incoming = self
+            .lock_storage()
+            .indexes()
+            .edges
+            .get(&id)
+            .cloned()
+            .unwrap_or_default();
+
+        self.with_pending(|pending| {
+            for op in pending.transaction.ops() {
+                match op {
+                    TransactionOp::Insert(ent) => {
+                        incoming.retain(|(source, _)| *source != ent.id());
+                        for edge in ent.edges() {
+                            if edge.to_ids().contains(&id) {
+                                incoming.insert((ent.id(), edge.name().to_string()));
                             }
                         }
                     }
-                    // If deep deletion, we want to remove the ents connected
-                    // by the edge
-                    EdgeDeletionPolicy::DeepDelete => {
-                        for id in edge.to_ids() {
-                            let _ = self.remove(id);
-                        }
+                    TransactionOp::Remove(removed) => {
+                        incoming.retain(|(source, _)| source != removed);
                     }
-                    // If deletion policy is nothing, then do nothing
-                    EdgeDeletionPolicy::Nothing => {}
+                    TransactionOp::Expect { .. } => {}
                 }
             }
+        });
 
-            // Remove the id from our type mapping if it is there
-            self.ents_of_type
-                .lock()
-                .unwrap()
-                .entry(ent.r#type().to_string())
-                .and_modify(|e| {
-                    e.remove(&id);
-                });
-
-            // Add the id to the freed ids available in the allocator
-            self.alloc.lock().unwrap().extend(vec![id]);
-
-            Ok(true)
-        } else {
-            Ok(false)
-        }
+        incoming
     }
 
-    fn insert(&self, mut ent: Box<dyn Ent>) -> DatabaseResult<Id> {
+    /// Inserts the ent, first checking that any stored ent with a matching
+    /// id was last updated at the expected time if one is provided
+    fn insert_ent(&self, mut ent: Box<dyn Ent>, expected: Option<u64>) -> DatabaseResult<Id> {
         // Get the id of the ent, swapping out the ephemeral id
         let id = ent.id();
-        let id = if id == EPHEMERAL_ID {
+        let allocated = id == EPHEMERAL_ID;
+        let id = if allocated {
             if let Some(id) = self.alloc.lock().unwrap().next() {
                 id
             } else {
@@ -186,80 +667,706 @@ impl Database for InmemoryDatabase {
             source: Box::from(e),
         })?;
 
-        // Add our ent's id to the set of ids associated with the ent's type
-        self.ents_of_type
-            .lock()
-            .unwrap()
-            .entry(ent.r#type().to_string())
-            .or_insert_with(HashSet::new)
-            .insert(id);
+        // If the current thread has a transaction in progress, stage the
+        // ent to be written when the transaction is committed
+        let mut transactions = self.transactions.lock().unwrap();
+        if let Some(pending) = transactions.get_mut(&thread::current().id()) {
+            if allocated {
+                pending.allocated.push(id);
+            }
+            if let Some(last_updated) = expected {
+                pending.transaction.expect(id, last_updated);
+            }
+            pending.transaction.insert(ent);
+            return Ok(id);
+        }
+        drop(transactions);
 
-        // Add our ent to the primary database
-        self.ents.lock().unwrap().insert(id, ent);
+        self.write(|storage| {
+            let mut ops = Vec::new();
+            if let Some(last_updated) = expected {
+                ops.push(TransactionOp::Expect { id, last_updated });
+            }
+            ops.push(TransactionOp::Insert(ent));
 
-        Ok(id)
+            if let Err(x) = storage.check(&ops) {
+                // Return the id allocated for the discarded ent
+                if allocated {
+                    storage.alloc.extend(vec![id]);
+                }
+                return Err(x);
+            }
+            storage.apply(ops);
+
+            Ok(id)
+        })
     }
-}
 
-impl InmemoryDatabase {
-    /// Returns ids of all ents stored in the database
-    pub fn ids(&self) -> EntIdSet {
-        self.ents.lock().unwrap().keys().copied().collect()
+    /// Returns true if the current thread has a transaction in progress
+    pub fn in_transaction(&self) -> bool {
+        self.with_pending(|_| ()).is_some()
     }
 
-    /// Returns true if database contains the provided id
-    pub fn has_id(&self, id: Id) -> bool {
-        self.ents.lock().unwrap().contains_key(&id)
+    /// Invokes the function with the current thread's transaction if the
+    /// thread has one in progress
+    fn with_pending<R, F: FnOnce(&mut PendingTransaction) -> R>(&self, f: F) -> Option<R> {
+        self.transactions
+            .lock()
+            .unwrap()
+            .get_mut(&thread::current().id())
+            .map(f)
     }
 
-    /// Returns ids of all ents for the given type
-    pub fn ids_for_type(&self, r#type: &str) -> EntIdSet {
-        self.ents_of_type
+    /// Removes and returns the current thread's transaction
+    fn take_pending(&self) -> DatabaseResult<PendingTransaction> {
+        self.transactions
             .lock()
             .unwrap()
-            .get(r#type)
-            .cloned()
-            .unwrap_or_default()
+            .remove(&thread::current().id())
+            .ok_or(DatabaseError::NoTransaction)
     }
-}
 
-/// Called once when first beginning to filter to determine which ent ids
-/// to start with based on the leading filter
-///
-/// 1. If lead filter by id equality, will only include those ids that match
-///    the predicate
-/// 2. If lead filter by type equality, will only include those ids that equal
-///    the type (or many types if wrapped in Or)
-/// 3. Any other variation of id/type filter or other kind of filter will
-///    result in the more expensive pulling of all ids
-fn prefill_ids(db: &InmemoryDatabase, filter: &Filter) -> EntIdSet {
-    fn from_id_predicate(
-        db: &InmemoryDatabase,
-        p: &Predicate,
-        mut ids: EntIdSet,
-    ) -> Option<EntIdSet> {
-        match p {
-            Predicate::Equals(Value::Primitive(Primitive::Number(id))) => Some({
-                ids.insert(id.to_usize());
-                ids
-            }),
-            Predicate::Or(list) => list.iter().fold(Some(ids), |ids, p| match ids {
-                Some(ids) => from_id_predicate(db, p, ids),
-                None => None,
-            }),
-            _ => None,
+    /// Acquires all storage locks, always in the same order to avoid deadlocks
+    fn lock_storage(&self) -> Storage<'_> {
+        Storage {
+            ents: self.ents.lock().unwrap(),
+            ents_of_type: self.ents_of_type.lock().unwrap(),
+            alloc: self.alloc.lock().unwrap(),
+            indexes: self.indexes.lock().unwrap(),
+            assocs: self.assocs.lock().unwrap(),
+            changes: None,
         }
     }
 
-    fn from_type_predicate(
-        db: &InmemoryDatabase,
-        p: &Predicate,
-        mut ids: EntIdSet,
-    ) -> Option<EntIdSet> {
-        match p {
-            Predicate::Equals(Value::Text(t)) => Some({
-                ids.extend(db.ids_for_type(t));
-                ids
+    /// Applies writes with all storage locked, sending the changes made by
+    /// the writes to subscribers once storage is unlocked
+    fn write<R, F: FnOnce(&mut Storage<'_>) -> R>(&self, f: F) -> R {
+        let mut storage = self.lock_storage();
+        if !self.subscribers.lock().unwrap().is_empty() {
+            storage.changes = Some(Vec::new());
+        }
+
+        let result = f(&mut storage);
+        let changes = storage.changes.take();
+        drop(storage);
+
+        if let Some(changes) = changes {
+            self.publish(changes);
+        }
+
+        result
+    }
+
+    /// Sends each change to the subscribers whose query it matches, dropping
+    /// any subscribers that are no longer receiving changes
+    fn publish(&self, changes: Vec<Change>) {
+        self.subscribers.lock().unwrap().retain(|(query, sender)| {
+            changes
+                .iter()
+                .filter(|change| change.matches(query) != Some(false))
+                .all(|change| sender.send(change.clone()).is_ok())
+        });
+    }
+}
+
+impl Storage<'_> {
+    /// Checks that each ent expected by the operations was last updated at
+    /// the expected time and that each ent inserted does not use a value of
+    /// a unique field held by another ent, accounting for the ents written
+    /// by the operations before it, prior to any of the operations being
+    /// applied
+    fn check(&mut self, ops: &[TransactionOp]) -> DatabaseResult<()> {
+        // Make sure the indexes are built before looking up unique values
+        self.indexes();
+        let unique = &self.indexes.as_ref().expect("Indexes not built").unique;
+
+        let mut written: HashMap<Id, Option<&dyn Ent>> = HashMap::new();
+        let mut claimed: HashMap<UniqueKey, Option<Id>> = HashMap::new();
+
+        for op in ops {
+            // Release the unique values held by any ent being replaced
+            let replaced = match op {
+                TransactionOp::Insert(ent) => Some(ent.id()),
+                TransactionOp::Remove(id) => Some(*id),
+                TransactionOp::Expect { .. } => None,
+            }
+            .and_then(|id| match written.get(&id) {
+                Some(ent) => *ent,
+                None => self.ents.get(&id).map(AsRef::as_ref),
+            });
+            for key in replaced.map(unique_keys).unwrap_or_default() {
+                claimed.insert(key, None);
+            }
+
+            match op {
+                TransactionOp::Insert(ent) => {
+                    for key in unique_keys(ent.as_ref()) {
+                        let holder = match claimed.get(&key) {
+                            Some(holder) => *holder,
+                            None => unique.get(&key).copied(),
+                        };
+                        match holder {
+                            Some(id) if id != ent.id() => {
+                                return Err(DatabaseError::NotUnique { name: key.1, id })
+                            }
+                            _ => {}
+                        }
+                        claimed.insert(key, Some(ent.id()));
+                    }
+                    written.insert(ent.id(), Some(ent.as_ref()));
+                }
+                TransactionOp::Remove(id) => {
+                    written.insert(*id, None);
+                }
+                TransactionOp::Expect { id, last_updated } => {
+                    let actual = match written.get(id) {
+                        Some(ent) => ent.map(|ent| ent.last_updated()),
+                        None => self.ents.get(id).map(|ent| ent.last_updated()),
+                    };
+                    match actual {
+                        Some(actual) if actual != *last_updated => {
+                            return Err(DatabaseError::Conflict {
+                                id: *id,
+                                expected: *last_updated,
+                                actual,
+                            })
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies each of the operations in order, which should have already
+    /// been checked
+    fn apply<I: IntoIterator<Item = TransactionOp>>(&mut self, ops: I) {
+        for op in ops {
+            match op {
+                TransactionOp::Insert(ent) => self.insert(ent),
+                TransactionOp::Remove(id) => {
+                    self.remove(id);
+                }
+                TransactionOp::Expect { .. } => {}
+            }
+        }
+    }
+
+    /// Writes the ent using its id, overwriting any existing ent and keeping
+    /// the inverse edges of the ents it connects in sync
+    fn insert(&mut self, ent: Box<dyn Ent>) {
+        let id = ent.id();
+
+        // Replace the indexed values of any ent we are overwriting
+        let existing = self.ents.remove(&id);
+        if let Some(existing) = existing.as_deref() {
+            self.indexes().remove(existing);
+        }
+        self.indexes().insert(ent.as_ref());
+        self.reassoc(id, Some(ent.as_ref()));
+        let changes = InverseEdgeChange::between(existing.as_deref(), Some(ent.as_ref()));
+
+        // Add our ent's id to the set of ids associated with the ent's type
+        self.ents_of_type
+            .entry(ent.r#type().to_string())
+            .or_insert_with(HashSet::new)
+            .insert(id);
+
+        // Add our ent to the primary database
+        self.record(existing.as_deref(), Some(ent.as_ref()));
+        self.ents.insert(id, ent);
+
+        self.sync_inverse_edges(changes);
+    }
+
+    /// Removes the ent with the given id, processing each of its edges based
+    /// on their deletion policy
+    fn remove(&mut self, id: Id) -> bool {
+        let ent = match self.ents.remove(&id) {
+            Some(ent) => ent,
+            None => return false,
+        };
+        self.indexes().remove(ent.as_ref());
+        self.reassoc(id, None);
+        self.record(Some(ent.as_ref()), None);
+        self.sync_inverse_edges(InverseEdgeChange::between(Some(ent.as_ref()), None));
+
+        for edge in ent.edges() {
+            match edge.deletion_policy() {
+                // If shallow deletion, we only want to remove the connections
+                // back to this ent from the corresponding ents
+                EdgeDeletionPolicy::ShallowDelete => {
+                    for edge_id in edge.to_ids() {
+                        if let Some(mut ent) = self.ents.remove(&edge_id) {
+                            let existing = dyn_clone::clone_box(ent.as_ref());
+                            self.indexes().remove(ent.as_ref());
+                            for mut edge in ent.edges() {
+                                let _ = edge.value_mut().remove_ids(Some(id));
+                                let name = edge.name().to_string();
+                                let _ = ent.update_edge(&name, edge.into_value());
+                            }
+                            self.indexes().insert(ent.as_ref());
+                            self.reassoc(edge_id, Some(ent.as_ref()));
+                            self.record(Some(existing.as_ref()), Some(ent.as_ref()));
+                            self.ents.insert(edge_id, ent);
+                        }
+                    }
+                }
+                // If deep deletion, we want to remove the ents connected
+                // by the edge
+                EdgeDeletionPolicy::DeepDelete => {
+                    for id in edge.to_ids() {
+                        self.remove(id);
+                    }
+                }
+                // If deletion policy is nothing, then do nothing
+                EdgeDeletionPolicy::Nothing => {}
+            }
+        }
+
+        // Remove the id from our type mapping if it is there
+        self.ents_of_type
+            .entry(ent.r#type().to_string())
+            .and_modify(|e| {
+                e.remove(&id);
+            });
+
+        // Add the id to the freed ids available in the allocator
+        self.alloc.extend(vec![id]);
+
+        true
+    }
+
+    /// Applies the changes to the inverse edges of stored ents, along with
+    /// any further changes caused by updating those edges
+    fn sync_inverse_edges(&mut self, mut changes: Vec<InverseEdgeChange>) {
+        while let Some(change) = changes.pop() {
+            let id = change.id();
+            if let Some(mut ent) = self.ents.remove(&id) {
+                let existing = dyn_clone::clone_box(ent.as_ref());
+                if change.apply(ent.as_mut()) {
+                    let _ = ent.mark_updated();
+                    self.indexes().remove(existing.as_ref());
+                    self.indexes().insert(ent.as_ref());
+                    self.reassoc(id, Some(ent.as_ref()));
+                    self.record(Some(existing.as_ref()), Some(ent.as_ref()));
+                    changes.extend(InverseEdgeChange::between(
+                        Some(existing.as_ref()),
+                        Some(ent.as_ref()),
+                    ));
+                }
+                self.ents.insert(id, ent);
+            }
+        }
+    }
+
+    /// Records the change between the snapshots of an ent if there are any
+    /// subscribers to send it to
+    fn record(&mut self, before: Option<&dyn Ent>, after: Option<&dyn Ent>) {
+        if let Some(changes) = self.changes.as_mut() {
+            changes.extend(Change::between(
+                before.map(dyn_clone::clone_box),
+                after.map(dyn_clone::clone_box),
+            ));
+        }
+    }
+
+    /// Updates the associations of the ent with the given id to match the
+    /// ents on its edges, where any new association is created at the time
+    /// that the ent was last updated
+    fn reassoc(&mut self, id: Id, ent: Option<&dyn Ent>) {
+        let mut recorded = self.assocs.remove(&id).unwrap_or_default();
+
+        if let Some(ent) = ent {
+            let assocs = ent
+                .edges()
+                .into_iter()
+                .map(|edge| {
+                    let recorded = recorded.remove(edge.name()).unwrap_or_default();
+                    let assocs = Assoc::for_ids(edge.to_ids(), &recorded, ent.last_updated());
+                    (edge.name().to_string(), assocs)
+                })
+                .collect();
+            self.assocs.insert(id, assocs);
+        }
+    }
+
+    /// Returns ids of all ents that could have a value for the named field
+    /// found by the scan
+    fn find(&mut self, name: &str, scan: &IndexScan) -> EntIdSet {
+        let mut ids = EntIdSet::new();
+        let mut unindexed_types = Vec::new();
+
+        for (r#type, fields) in self.indexes().fields.iter() {
+            match fields.get(name) {
+                Some(index) if index.unindexed > 0 => unindexed_types.push(r#type.to_string()),
+                Some(index) => ids.extend(index.find(scan)),
+                None => {}
+            }
+        }
+
+        for r#type in unindexed_types {
+            ids.extend(self.ents_of_type.get(&r#type).into_iter().flatten());
+        }
+
+        ids
+    }
+
+    /// Returns the ids ordered by the named field that are positioned after
+    /// the cursor, up to the given count, or none if the field is not
+    /// indexed for every type of ent that has it
+    ///
+    /// Ids are ordered the same way as [`entity::Order::compare`], walking
+    /// the keys of the field's indexes from the cursor's position. Ids
+    /// without a key are ordered after all others when ascending and before
+    /// all others when descending.
+    fn seek(
+        &mut self,
+        name: &str,
+        direction: OrderDirection,
+        ids: &EntIdSet,
+        after: Option<&Cursor>,
+        count: usize,
+    ) -> Option<Vec<Id>> {
+        let after = match after {
+            Some(cursor) => Some((cursor.keys().first()?.as_ref(), cursor.id())),
+            None => None,
+        };
+        let ascending = direction == OrderDirection::Ascending;
+        self.indexes();
+
+        // Ids without a key are those missing from every index of the field
+        let ents = &self.ents;
+        let unkeyed = |after_id: Option<Id>| {
+            let mut unkeyed: Vec<Id> = ids
+                .iter()
+                .copied()
+                .filter(|id| after_id.map_or(true, |after_id| *id > after_id))
+                .filter(|id| {
+                    ents.get(id)
+                        .and_then(|ent| ent.field(name))
+                        .and_then(|value| IndexKey::from_value(&value))
+                        .is_none()
+                })
+                .collect();
+            unkeyed.sort_unstable();
+            unkeyed
+        };
+
+        let indexes: Vec<&FieldIndex> = self
+            .indexes
+            .as_ref()?
+            .fields
+            .values()
+            .filter_map(|fields| fields.get(name))
+            .collect();
+        if indexes.iter().any(|index| index.unindexed > 0) {
+            return None;
+        }
+
+        let mut seeked = Vec::new();
+        let (bounds, tie) = match after {
+            Some((Some(key), id)) if ascending => {
+                ((Included(key.clone()), Unbounded), Some((key, id)))
+            }
+            Some((Some(key), id)) => ((Unbounded, Included(key.clone())), Some((key, id))),
+            Some((None, id)) => {
+                seeked.extend(unkeyed(Some(id)));
+                if ascending {
+                    seeked.truncate(count);
+                    return Some(seeked);
+                }
+                ((Unbounded, Unbounded), None)
+            }
+            None => {
+                if !ascending {
+                    seeked.extend(unkeyed(None));
+                }
+                ((Unbounded, Unbounded), None)
+            }
+        };
+
+        let mut ranges: Vec<_> = indexes
+            .iter()
+            .map(|index| {
+                let range = index.ordered.range(bounds.clone());
+                let range: Box<dyn Iterator<Item = &IndexKey>> = if ascending {
+                    Box::new(range)
+                } else {
+                    Box::new(range.rev())
+                };
+                range.peekable()
+            })
+            .collect();
+
+        // Walks the keys of all indexes together, visiting each key once
+        while seeked.len() < count {
+            let key = ranges.iter_mut().filter_map(|range| range.peek().copied());
+            let key = match if ascending { key.min() } else { key.max() } {
+                Some(key) => key,
+                None => break,
+            };
+            for range in ranges.iter_mut() {
+                if range.peek() == Some(&key) {
+                    range.next();
+                }
+            }
+
+            // Ids sharing the cursor's key are ordered by id after it
+            let mut at_key: Vec<Id> = indexes
+                .iter()
+                .filter_map(|index| index.by_key.get(key))
+                .flatten()
+                .copied()
+                .filter(|id| ids.contains(id))
+                .filter(|id| match tie {
+                    Some((tie_key, after_id)) if tie_key == key => *id > after_id,
+                    _ => true,
+                })
+                .collect();
+            at_key.sort_unstable();
+            seeked.extend(at_key);
+        }
+
+        if ascending && seeked.len() < count {
+            seeked.extend(unkeyed(None));
+        }
+
+        seeked.truncate(count);
+        Some(seeked)
+    }
+
+    /// Returns the secondary indexes, building them from the stored ents if
+    /// they have not been built yet
+    fn indexes(&mut self) -> &mut Indexes {
+        let ents = &self.ents;
+        self.indexes.get_or_insert_with(|| {
+            let mut indexes = Indexes::default();
+            for ent in ents.values() {
+                indexes.insert(ent.as_ref());
+            }
+            indexes
+        })
+    }
+}
+
+impl Indexes {
+    /// Adds the fields of the ent to the indexes of its type, and the ent to
+    /// the incoming edges of each ent contained by its edges
+    fn insert(&mut self, ent: &dyn Ent) {
+        for edge in ent.edges() {
+            for id in edge.to_ids() {
+                self.edges
+                    .entry(id)
+                    .or_default()
+                    .insert((ent.id(), edge.name().to_string()));
+            }
+        }
+
+        for key in unique_keys(ent) {
+            self.unique.insert(key, ent.id());
+        }
+
+        let fields = self.fields.entry(ent.r#type().to_string()).or_default();
+
+        for def in ent.field_definitions() {
+            if let Some(value) = ent.field(def.name()) {
+                let index = fields.entry(def.name().to_string()).or_default();
+
+                // Values without a key can never be found by a scan, so they
+                // are left out of the index of an indexed field entirely
+                if !def.is_indexed() {
+                    index.unindexed += 1;
+                } else if let Some(key) = IndexKey::from_value(&value) {
+                    index.ordered.insert(key.clone());
+                    index.by_key.entry(key).or_default().insert(ent.id());
+                }
+            }
+        }
+    }
+
+    /// Removes the fields of the ent from the indexes of its type, and the
+    /// ent from the incoming edges of each ent contained by its edges
+    fn remove(&mut self, ent: &dyn Ent) {
+        for edge in ent.edges() {
+            for id in edge.to_ids() {
+                if let Entry::Occupied(mut entry) = self.edges.entry(id) {
+                    entry.get_mut().remove(&(ent.id(), edge.name().to_string()));
+                    if entry.get().is_empty() {
+                        entry.remove();
+                    }
+                }
+            }
+        }
+
+        for key in unique_keys(ent) {
+            if let Entry::Occupied(entry) = self.unique.entry(key) {
+                if *entry.get() == ent.id() {
+                    entry.remove();
+                }
+            }
+        }
+
+        let fields = match self.fields.get_mut(ent.r#type()) {
+            Some(fields) => fields,
+            None => return,
+        };
+
+        for def in ent.field_definitions() {
+            if let Some(value) = ent.field(def.name()) {
+                let index = match fields.get_mut(def.name()) {
+                    Some(index) => index,
+                    None => continue,
+                };
+
+                if !def.is_indexed() {
+                    index.unindexed = index.unindexed.saturating_sub(1);
+                } else if let Some(key) = IndexKey::from_value(&value) {
+                    if let Entry::Occupied(mut entry) = index.by_key.entry(key) {
+                        entry.get_mut().remove(&ent.id());
+                        if entry.get().is_empty() {
+                            index.ordered.remove(entry.key());
+                            entry.remove();
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl FieldIndex {
+    /// Returns ids of all ents whose value is found by the scan
+    fn find(&self, scan: &IndexScan) -> EntIdSet {
+        match scan {
+            IndexScan::Keys(keys) => keys
+                .iter()
+                .filter_map(|key| self.by_key.get(key))
+                .flatten()
+                .copied()
+                .collect(),
+
+            IndexScan::Range(start, end) => {
+                // Ranges that are empty would cause a panic when scanning
+                let is_empty = match (start, end) {
+                    (Included(s), Included(e)) => s > e,
+                    (Included(s), Excluded(e))
+                    | (Excluded(s), Included(e))
+                    | (Excluded(s), Excluded(e)) => s >= e,
+                    _ => false,
+                };
+
+                if is_empty {
+                    return EntIdSet::new();
+                }
+
+                self.ordered
+                    .range((start.clone(), end.clone()))
+                    .filter_map(|key| self.by_key.get(key))
+                    .flatten()
+                    .copied()
+                    .collect()
+            }
+
+            IndexScan::Union(scans) => scans.iter().flat_map(|scan| self.find(scan)).collect(),
+
+            IndexScan::Intersection(scans) => {
+                let mut scans = scans.iter();
+                let mut ids = scans.next().map(|scan| self.find(scan)).unwrap_or_default();
+                for scan in scans {
+                    let other = self.find(scan);
+                    ids.retain(|id| other.contains(id));
+                }
+                ids
+            }
+        }
+    }
+}
+
+/// Returns the ids in order of themselves that are positioned after the
+/// cursor, up to the given count
+fn seek_by_id(
+    ids: &EntIdSet,
+    direction: OrderDirection,
+    after: Option<&Cursor>,
+    count: usize,
+) -> Vec<Id> {
+    let mut ids: Vec<Id> = ids
+        .iter()
+        .copied()
+        .filter(|id| match (after, direction) {
+            (Some(cursor), OrderDirection::Ascending) => *id > cursor.id(),
+            (Some(cursor), OrderDirection::Descending) => *id < cursor.id(),
+            (None, _) => true,
+        })
+        .collect();
+
+    ids.sort_unstable();
+    if direction == OrderDirection::Descending {
+        ids.reverse();
+    }
+    ids.truncate(count);
+    ids
+}
+
+/// Returns the values of the unique fields of the ent, where values that are
+/// absent or have no key are never considered to be in use
+fn unique_keys(ent: &dyn Ent) -> Vec<UniqueKey> {
+    ent.field_definitions()
+        .into_iter()
+        .filter(|def| def.is_unique())
+        .filter_map(|def| {
+            let key = IndexKey::from_value(&ent.field(def.name())?)?;
+            if key == IndexKey::None {
+                return None;
+            }
+            Some((ent.r#type().to_string(), def.name().to_string(), key))
+        })
+        .collect()
+}
+
+/// Called once when first beginning to filter to determine which ent ids
+/// to start with based on the leading filter
+///
+/// 1. If lead filter by id equality, will only include those ids that match
+///    the predicate
+/// 2. If lead filter by type equality, will only include those ids that equal
+///    the type (or many types if wrapped in Or)
+/// 3. If lead filter is a combination of filters, will only include those
+///    ids included by every filter (when all must pass) or by any filter
+///    (when any must pass)
+/// 4. Any other variation of id/type filter or other kind of filter will
+///    result in the more expensive pulling of all ids
+fn prefill_ids(db: &InmemoryDatabase, filter: &Filter) -> EntIdSet {
+    fn from_id_predicate(
+        db: &InmemoryDatabase,
+        p: &Predicate,
+        mut ids: EntIdSet,
+    ) -> Option<EntIdSet> {
+        match p {
+            Predicate::Equals(Value::Primitive(Primitive::Number(id))) => Some({
+                ids.insert(id.to_usize());
+                ids
+            }),
+            Predicate::Or(list) => list.iter().fold(Some(ids), |ids, p| match ids {
+                Some(ids) => from_id_predicate(db, p, ids),
+                None => None,
+            }),
+            _ => None,
+        }
+    }
+
+    fn from_type_predicate(
+        db: &InmemoryDatabase,
+        p: &Predicate,
+        mut ids: EntIdSet,
+    ) -> Option<EntIdSet> {
+        match p {
+            Predicate::Equals(Value::Text(t)) => Some({
+                ids.extend(db.ids_for_type(t));
+                ids
             }),
             Predicate::Or(list) => list.iter().fold(Some(ids), |ids, p| match ids {
                 Some(ids) => from_type_predicate(db, p, ids),
@@ -282,31 +1389,146 @@ fn prefill_ids(db: &InmemoryDatabase, filter: &Filter) -> EntIdSet {
             from_type_predicate(db, p.as_untyped(), EntIdSet::new()).unwrap_or_else(|| db.ids())
         }
 
+        Filter::And(filters) => {
+            let mut filters = filters.iter();
+            let mut ids = match filters.next() {
+                Some(filter) => prefill_ids(db, filter),
+                None => db.ids(),
+            };
+            for filter in filters {
+                let other = prefill_ids(db, filter);
+                ids.retain(|id| other.contains(id));
+            }
+            ids
+        }
+
+        Filter::Or(filters) => filters
+            .iter()
+            .flat_map(|filter| prefill_ids(db, filter))
+            .collect(),
+
         // Otherwise, currently no cached/indexed way to look up (yet)
-        // TODO: Support database field indexing so equality of a field can
-        //       be used for faster id lookup; do the same for timestamp fields
+        // TODO: Support indexing timestamp fields for faster id lookup
         _ => db.ids(),
     }
 }
 
-fn filter_id(db: &InmemoryDatabase, id: &Id, filter: &Filter) -> bool {
+/// Looks up the ids of ents that could pass the filter using the secondary
+/// indexes of field values, returning none if the filter cannot be served
+/// by an index
+fn indexed_ids(db: &InmemoryDatabase, filter: &Filter) -> Option<EntIdSet> {
+    match filter {
+        Filter::Field(name, p) => {
+            IndexScan::from_predicate(p).map(|scan| db.ids_for_field(name, &scan))
+        }
+
+        // Ents passing all filters must be found by every index that can
+        // serve one of the filters
+        Filter::And(filters) => {
+            let mut found: Option<EntIdSet> = None;
+            for filter in filters {
+                if let Some(other) = indexed_ids(db, filter) {
+                    match found.as_mut() {
+                        Some(ids) => ids.retain(|id| other.contains(id)),
+                        None => found = Some(other),
+                    }
+                }
+            }
+            found
+        }
+
+        // Ents passing any filter can only be found by indexes if every
+        // filter can be served by one
+        Filter::Or(filters) => {
+            let mut found = EntIdSet::new();
+            for filter in filters {
+                found.extend(indexed_ids(db, filter)?);
+            }
+            Some(found)
+        }
+
+        _ => None,
+    }
+}
+
+/// Applies the patch to the ent, marking the ent as updated once every
+/// change succeeds
+fn patch_ent(ent: &mut dyn Ent, patch: &Patch) -> DatabaseResult<()> {
+    let id = ent.id();
+    patch
+        .apply(ent)
+        .map_err(|source| DatabaseError::EntMutationFailed { id, source })?;
+    ent.clear_cache();
+    ent.mark_updated()
+        .map_err(|source| DatabaseError::EntMutationFailed { id, source })
+}
+
+/// Returns true if the ent with the given id passes the filter, where the
+/// ent is reached through the edge of another ent if filtering that edge
+fn filter_id(db: &InmemoryDatabase, id: &Id, filter: &Filter, via: Option<(Id, &str)>) -> bool {
     match filter {
         Filter::Id(p) => p.check(*id),
-        Filter::Type(p) => with_ent(db, id, |ent| p.check(ent.r#type().to_string())),
-        Filter::Created(p) => with_ent(db, id, |ent| p.check(ent.created())),
-        Filter::LastUpdated(p) => with_ent(db, id, |ent| p.check(ent.last_updated())),
-        Filter::Field(name, p) => with_ent(db, id, |ent| match ent.field(name) {
+        Filter::Type(_)
+        | Filter::Created(_)
+        | Filter::LastUpdated(_)
+        | Filter::Field(_, _)
+        | Filter::Edge(_, _)
+        | Filter::IntoEdge(_) => with_ent(db, id, |ent| filter_ent(db, ent.as_ref(), filter, via)),
+        Filter::AssocCreated(p) => with_assoc(db, id, via, |assoc| p.check(assoc.created())),
+        Filter::AssocData(p) => with_assoc(db, id, via, |assoc| match assoc.data() {
+            Some(value) => p.check(value),
+            None => p.check(&Value::Optional(None)),
+        }),
+        Filter::And(filters) => filters.iter().all(|f| filter_id(db, id, f, via)),
+        Filter::Or(filters) => filters.iter().any(|f| filter_id(db, id, f, via)),
+        Filter::Not(f) => !filter_id(db, id, f, via),
+
+        Filter::IntoInverseEdge(name) => !db.ids_for_inverse_edge(*id, name).is_empty(),
+
+        // NOTE: Set operations likewise only combine ids at the top level of
+        //       a query, so when nested the ent passes if it would remain
+        //       after applying the operation to the ent alone
+        Filter::Union(_) => true,
+        Filter::Intersect(other) => db.query_ids(other).contains(id),
+        Filter::Except(other) => !db.query_ids(other).contains(id),
+    }
+}
+
+/// Returns true if the already loaded ent passes the filter, checking the
+/// ent directly rather than loading it again wherever possible
+fn filter_ent(
+    db: &InmemoryDatabase,
+    ent: &dyn Ent,
+    filter: &Filter,
+    via: Option<(Id, &str)>,
+) -> bool {
+    match filter {
+        Filter::Type(p) => p.check(ent.r#type().to_string()),
+        Filter::Created(p) => p.check(ent.created()),
+        Filter::LastUpdated(p) => p.check(ent.last_updated()),
+        Filter::Field(name, p) => match ent.field(name) {
             Some(value) => p.check(&value),
             None => false,
-        }),
-        Filter::Edge(name, f) => with_ent(db, id, |ent| match ent.edge(name) {
-            Some(edge) => edge.to_ids().iter().any(|id| filter_id(db, id, f)),
+        },
+        Filter::Edge(name, f) => match ent.edge(name) {
+            Some(edge) => edge
+                .to_ids()
+                .iter()
+                .any(|edge_id| filter_id(db, edge_id, f, Some((ent.id(), name)))),
             None => false,
-        }),
-
-        // NOTE: Logically, this should be impossible to reach since we only
-        //       call this when we know that the filter is not a transformation
-        Filter::IntoEdge(_) => unreachable!("Bug: Transformation in filter"),
+        },
+        Filter::And(filters) => filters.iter().all(|f| filter_ent(db, ent, f, via)),
+        Filter::Or(filters) => filters.iter().any(|f| filter_ent(db, ent, f, via)),
+        Filter::Not(f) => !filter_ent(db, ent, f, via),
+
+        // NOTE: Transformations only happen at the top level of a query, so
+        //       when nested within another filter, the ent instead passes
+        //       if it has any ents on its edge
+        Filter::IntoEdge(name) => match ent.edge(name) {
+            Some(edge) => !edge.to_ids().is_empty(),
+            None => false,
+        },
+        _ => filter_id(db, &ent.id(), filter, via),
     }
 }
 
@@ -316,6 +1538,26 @@ fn with_ent<F: Fn(Box<dyn Ent>) -> bool>(db: &InmemoryDatabase, id: &Id, f: F) -
         .unwrap_or_default()
 }
 
+fn with_assoc<F: Fn(&Assoc) -> bool>(
+    db: &InmemoryDatabase,
+    id: &Id,
+    via: Option<(Id, &str)>,
+    f: F,
+) -> bool {
+    match via {
+        Some((source, edge)) => db
+            .assocs(source, edge)
+            .map(|assocs| {
+                assocs
+                    .iter()
+                    .find(|assoc| assoc.id() == *id)
+                    .map_or(false, f)
+            })
+            .unwrap_or_default(),
+        None => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -587,23 +1829,688 @@ mod tests {
     }
 
     #[test]
-    fn get_all_should_return_all_ents_with_associated_ids() {
+    fn remove_should_remove_connections_back_to_ent_for_shallow_delete_edges() {
         let db = InmemoryDatabase::default();
 
-        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(1))).unwrap();
-        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(2))).unwrap();
-        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(3))).unwrap();
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                1,
+                vec![],
+                vec![Edge::new_with_deletion_policy(
+                    "a",
+                    vec![2],
+                    EdgeDeletionPolicy::ShallowDelete,
+                )],
+            )))
+            .unwrap();
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                2,
+                vec![],
+                vec![Edge::new("b", vec![1, 3])],
+            )))
+            .unwrap();
 
-        let results = db
-            .get_all(vec![1, 2, 3])
-            .expect("Failed to retrieve ents")
-            .iter()
-            .map(|ent| ent.id())
-            .collect::<HashSet<Id>>();
-        assert_eq!(results, [1, 2, 3].iter().copied().collect());
+        assert!(db.remove(1).expect("Failed to remove ent"));
 
-        let results = db
-            .get_all(vec![1, 3])
+        let ent = db.get(2).unwrap().expect("Ent missing");
+        assert_eq!(ent.edge("b").expect("Edge missing").to_ids(), vec![3]);
+        assert_eq!(db.incoming_edges(1).unwrap(), vec![]);
+        assert_eq!(db.incoming_edges(3).unwrap(), vec![(2, String::from("b"))]);
+    }
+
+    #[test]
+    fn insert_and_remove_should_keep_inverse_edges_in_sync() {
+        let db = InmemoryDatabase::default();
+        let insert = |id, edges| {
+            let _ = db
+                .insert(Box::from(UntypedEnt::from_collections(id, vec![], edges)))
+                .unwrap();
+        };
+        let edge_ids = |id, name| {
+            let ent = db.get(id).unwrap().expect("Ent missing");
+            ent.edge(name).expect("Edge missing").to_ids()
+        };
+
+        insert(1, vec![Edge::new("posts", Vec::<Id>::new())]);
+        insert(2, vec![Edge::new("posts", Vec::<Id>::new())]);
+
+        // Writing an edge adds the ent to the inverse edge of its ents
+        insert(3, vec![Edge::new("author", 1).with_inverse("posts")]);
+        assert_eq!(edge_ids(1, "posts"), vec![3]);
+
+        // Changing an edge moves the ent between the inverse edges
+        insert(3, vec![Edge::new("author", 2).with_inverse("posts")]);
+        assert_eq!(edge_ids(1, "posts"), Vec::<Id>::new());
+        assert_eq!(edge_ids(2, "posts"), vec![3]);
+
+        // Removing an ent removes it from the inverse edges
+        assert!(db.remove(3).unwrap());
+        assert_eq!(edge_ids(2, "posts"), Vec::<Id>::new());
+
+        // When both sides are declared, the sync happens in either direction
+        insert(4, vec![Edge::new("author", None).with_inverse("posts")]);
+        insert(1, vec![Edge::new("posts", vec![4]).with_inverse("author")]);
+        assert_eq!(edge_ids(4, "author"), vec![1]);
+
+        insert(4, vec![Edge::new("author", Some(2)).with_inverse("posts")]);
+        assert_eq!(edge_ids(1, "posts"), Vec::<Id>::new());
+        assert_eq!(edge_ids(2, "posts"), vec![4]);
+
+        insert(
+            2,
+            vec![Edge::new("posts", Vec::<Id>::new()).with_inverse("author")],
+        );
+        assert_eq!(edge_ids(4, "author"), Vec::<Id>::new());
+    }
+
+    #[test]
+    fn compare_and_insert_at_should_fail_if_stored_ent_was_not_updated_at_expected_time() {
+        let db = InmemoryDatabase::default();
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(1))).unwrap();
+        let stale = db.get(1).unwrap().expect("Ent missing");
+        let expected = stale.last_updated();
+
+        // The ent can be marked as updated after it was loaded
+        let mut ent = dyn_clone::clone_box(stale.as_ref());
+        ent.mark_updated().unwrap();
+        let _ = db.compare_and_insert_at(ent, expected).unwrap();
+        let updated = db.get(1).unwrap().expect("Ent missing");
+        assert!(updated.last_updated() > expected);
+
+        // Writing against the time the ent was loaded again conflicts
+        match db.compare_and_insert_at(dyn_clone::clone_box(stale.as_ref()), expected) {
+            Err(DatabaseError::Conflict {
+                id,
+                expected: x,
+                actual,
+            }) => {
+                assert_eq!(id, 1);
+                assert_eq!(x, expected);
+                assert_eq!(actual, updated.last_updated());
+            }
+            x => panic!("Unexpected result: {:?}", x),
+        }
+
+        // Conflicts within a transaction fail its commit
+        db.begin_transaction().unwrap();
+        let _ = db.compare_and_insert_at(stale, expected).unwrap();
+        assert!(matches!(
+            db.commit_transaction(),
+            Err(DatabaseError::Conflict { id: 1, .. })
+        ));
+        assert_eq!(
+            db.get(1).unwrap().expect("Ent missing").last_updated(),
+            updated.last_updated()
+        );
+    }
+
+    #[test]
+    fn compare_and_insert_should_fail_if_stored_ent_was_updated_since() {
+        let db = InmemoryDatabase::default();
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(1))).unwrap();
+        let stale = db.get(1).unwrap().expect("Ent missing");
+
+        // Writing the ent as it was loaded succeeds and updates it
+        let _ = db
+            .compare_and_insert(dyn_clone::clone_box(stale.as_ref()))
+            .unwrap();
+        let updated = db.get(1).unwrap().expect("Ent missing");
+        assert!(updated.last_updated() > stale.last_updated());
+
+        // Writing the ent as it was loaded again conflicts
+        match db.compare_and_insert(dyn_clone::clone_box(stale.as_ref())) {
+            Err(DatabaseError::Conflict {
+                id,
+                expected,
+                actual,
+            }) => {
+                assert_eq!(id, 1);
+                assert_eq!(expected, stale.last_updated());
+                assert_eq!(actual, updated.last_updated());
+            }
+            x => panic!("Unexpected result: {:?}", x),
+        }
+
+        // Conflicts within a transaction fail its commit without writing
+        // any of its changes
+        db.begin_transaction().unwrap();
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(2))).unwrap();
+        let _ = db.compare_and_insert(stale).unwrap();
+        assert!(matches!(
+            db.commit_transaction(),
+            Err(DatabaseError::Conflict { id: 1, .. })
+        ));
+        assert!(db.get(2).unwrap().is_none(), "Ent was unexpectedly written");
+        assert_eq!(
+            db.get(1).unwrap().expect("Ent missing").last_updated(),
+            updated.last_updated()
+        );
+
+        // Ents that are not stored are always written
+        let _ = db
+            .compare_and_insert(Box::from(UntypedEnt::empty_with_id(3)))
+            .unwrap();
+        assert!(db.get(3).unwrap().is_some(), "Ent was not written");
+    }
+
+    #[test]
+    fn update_fields_should_apply_all_changes_of_patch_or_none() {
+        let db = std::sync::Arc::new(InmemoryDatabase::default());
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                1,
+                vec![
+                    Field::new("name", String::from("alice")),
+                    Field::new_with_attributes("code", 7, vec![FieldAttribute::Immutable]),
+                ],
+                vec![Edge::new("friends", vec![2, 3])],
+            )))
+            .unwrap();
+        let name = || db.get(1).unwrap().expect("Ent missing").field("name");
+        let before = db.get(1).unwrap().expect("Ent missing");
+
+        let ent = db
+            .update_fields(
+                1,
+                Patch::new()
+                    .set_field("name", "bob")
+                    .add_ids("friends", vec![4])
+                    .remove_ids("friends", vec![2]),
+            )
+            .unwrap();
+        assert!(ent.last_updated() > before.last_updated());
+
+        let stored = db.get(1).unwrap().expect("Ent missing");
+        assert_eq!(stored.field("name"), Some(Value::from("bob")));
+        assert_eq!(stored.edge("friends").unwrap().to_ids(), vec![3, 4]);
+        assert_eq!(stored.last_updated(), ent.last_updated());
+
+        let q = Query::default().where_field("name", P::equals(String::from("bob")));
+        query_and_assert(&db, q, &[1]);
+
+        // Patches with any change that fails write nothing
+        assert!(matches!(
+            db.update_fields(
+                1,
+                Patch::new().set_field("name", "carol").set_field("code", 8)
+            ),
+            Err(DatabaseError::EntMutationFailed {
+                id: 1,
+                source: EntMutationError::FieldImmutable { .. },
+            })
+        ));
+        assert!(matches!(
+            db.update_fields(1, Patch::new().add_ids("missing", vec![2])),
+            Err(DatabaseError::EntMutationFailed {
+                id: 1,
+                source: EntMutationError::NoEdge { .. },
+            })
+        ));
+        assert!(matches!(
+            db.update_fields(2, Patch::new()),
+            Err(DatabaseError::MissingEnt { id: 2 })
+        ));
+        assert_eq!(name(), Some(Value::from("bob")));
+
+        // Patches within a transaction conflict with changes made to the
+        // ent by others before the transaction is committed
+        db.begin_transaction().unwrap();
+        let _ = db
+            .update_fields(1, Patch::new().set_field("name", "dave"))
+            .unwrap();
+        assert_eq!(name(), Some(Value::from("dave")));
+
+        let other_db = std::sync::Arc::clone(&db);
+        std::thread::spawn(move || {
+            other_db
+                .update_fields(1, Patch::new().set_field("name", "erin"))
+                .unwrap()
+        })
+        .join()
+        .unwrap();
+
+        assert!(matches!(
+            db.commit_transaction(),
+            Err(DatabaseError::Conflict { id: 1, .. })
+        ));
+        assert_eq!(name(), Some(Value::from("erin")));
+    }
+
+    #[test]
+    fn hooked_update_fields_should_compare_against_ent_before_it_was_patched() {
+        struct MarkUpdated;
+
+        impl Hook for MarkUpdated {
+            fn before_update(&self, _existing: &dyn Ent, ent: &mut dyn Ent) -> HookResult {
+                ent.mark_updated().map_err(Box::from)
+            }
+        }
+
+        let mut hooks = Hooks::new();
+        hooks.register_for::<UntypedEnt, _>(MarkUpdated);
+        let db = std::sync::Arc::new(HookedDatabase::new(InmemoryDatabase::default(), hooks));
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                1,
+                vec![Field::new("name", String::from("alice"))],
+                vec![],
+            )))
+            .unwrap();
+        let stale = db.get(1).unwrap().expect("Ent missing");
+
+        // Ents marked as updated while being patched are still written
+        let ent = db
+            .update_fields(1, Patch::new().set_field("name", "bob"))
+            .unwrap();
+        assert_eq!(ent.field("name"), Some(Value::from("bob")));
+
+        // Ents updated since they were loaded still conflict
+        assert!(matches!(
+            db.compare_and_insert(stale),
+            Err(DatabaseError::Conflict { id: 1, .. })
+        ));
+        assert_eq!(
+            db.get(1).unwrap().expect("Ent missing").field("name"),
+            Some(Value::from("bob"))
+        );
+
+        // Patches within a transaction are checked against the ent before it
+        // was patched when the transaction is committed
+        db.begin_transaction().unwrap();
+        let _ = db
+            .update_fields(1, Patch::new().set_field("name", "dave"))
+            .unwrap();
+
+        let other_db = std::sync::Arc::clone(&db);
+        std::thread::spawn(move || {
+            other_db
+                .update_fields(1, Patch::new().set_field("name", "erin"))
+                .unwrap()
+        })
+        .join()
+        .unwrap();
+
+        assert!(matches!(
+            db.commit_transaction(),
+            Err(DatabaseError::Conflict { id: 1, .. })
+        ));
+        assert_eq!(
+            db.get(1).unwrap().expect("Ent missing").field("name"),
+            Some(Value::from("erin"))
+        );
+    }
+
+    #[test]
+    fn privacy_update_fields_should_compare_against_ent_before_it_was_patched() {
+        let mut policies = Policies::new();
+        policies.register_for::<MarkedEnt>(
+            Policy::new()
+                .read(PolicyRule::allow_all())
+                .write(PolicyRule::allow_if_field_is_viewer("owner")),
+        );
+        let db = std::sync::Arc::new(PrivacyDatabase::new(
+            InmemoryDatabase::default(),
+            policies,
+            Viewer::Ent(10),
+        ));
+        let _ = db
+            .insert(Box::from(MarkedEnt(UntypedEnt::from_collections(
+                1,
+                vec![
+                    Field::new("owner", 10),
+                    Field::new("name", String::from("alice")),
+                ],
+                vec![],
+            ))))
+            .unwrap();
+        let stale = db.get(1).unwrap().expect("Ent missing");
+
+        // Ents marked as updated while being patched are still written
+        let ent = db
+            .update_fields(1, Patch::new().set_field("name", "bob"))
+            .unwrap();
+        assert_eq!(ent.field("name"), Some(Value::from("bob")));
+        assert!(ent.last_updated() > stale.last_updated());
+
+        // Ents updated since they were loaded still conflict, whether
+        // compared against their own last updated time or an expected one
+        assert!(matches!(
+            db.compare_and_insert(dyn_clone::clone_box(stale.as_ref())),
+            Err(DatabaseError::Conflict { id: 1, .. })
+        ));
+        let expected = stale.last_updated();
+        assert!(matches!(
+            db.compare_and_insert_at(stale, expected),
+            Err(DatabaseError::Conflict { id: 1, .. })
+        ));
+        assert_eq!(
+            db.get(1).unwrap().expect("Ent missing").field("name"),
+            Some(Value::from("bob"))
+        );
+
+        // Patches within a transaction are checked against the ent before it
+        // was patched when the transaction is committed
+        db.begin_transaction().unwrap();
+        let _ = db
+            .update_fields(1, Patch::new().set_field("name", "dave"))
+            .unwrap();
+
+        let other_db = std::sync::Arc::clone(&db);
+        std::thread::spawn(move || {
+            other_db
+                .update_fields(1, Patch::new().set_field("name", "erin"))
+                .unwrap()
+        })
+        .join()
+        .unwrap();
+
+        assert!(matches!(
+            db.commit_transaction(),
+            Err(DatabaseError::Conflict { id: 1, .. })
+        ));
+        assert_eq!(
+            db.get(1).unwrap().expect("Ent missing").field("name"),
+            Some(Value::from("erin"))
+        );
+    }
+
+    #[test]
+    fn privacy_subscribe_should_stop_forwarding_once_subscription_is_dropped() {
+        let mut policies = Policies::new();
+        policies.register_for::<UntypedEnt>(
+            Policy::new().read(PolicyRule::allow_if_field_is_viewer("owner")),
+        );
+        let db = PrivacyDatabase::new(InmemoryDatabase::default(), policies, Viewer::Ent(10));
+        let changes = db.subscribe(Query::default()).unwrap();
+        let timeout = std::time::Duration::from_secs(5);
+
+        // Only changes to ents that the viewer can read are forwarded
+        for (id, owner) in vec![(1, 20), (2, 10)] {
+            let _ = db
+                .inner()
+                .insert(Box::from(UntypedEnt::from_collections(
+                    id,
+                    vec![Field::new("owner", owner)],
+                    vec![],
+                )))
+                .unwrap();
+        }
+        assert_eq!(changes.recv_timeout(timeout).map(|c| c.id()), Some(2));
+
+        // Dropped subscriptions stop being forwarded changes even if no more
+        // changes are written
+        drop(changes);
+        let start = std::time::Instant::now();
+        while !db
+            .inner()
+            .subscribers
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|(_, sender)| sender.is_closed())
+        {
+            assert!(start.elapsed() < timeout, "Subscription was not stopped");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn subscribe_should_receive_changes_matching_query_once_written() {
+        let db = InmemoryDatabase::default();
+        let all = db.subscribe(Query::default()).unwrap();
+        let big = db
+            .subscribe(Query::default().where_field("a", P::greater_than(2)))
+            .unwrap();
+
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                1,
+                vec![Field::new("a", 1)],
+                vec![],
+            )))
+            .unwrap();
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                2,
+                vec![Field::new("a", 5)],
+                vec![],
+            )))
+            .unwrap();
+
+        let change = all.try_recv().expect("Missing change");
+        assert_eq!(change.kind(), ChangeKind::Insert);
+        assert_eq!(change.id(), 1);
+        assert_eq!(change.r#type(), UntypedEnt::default().r#type());
+        assert!(change.before().is_none());
+        assert_eq!(change.after().unwrap().field("a"), Some(Value::from(1)));
+        assert_eq!(all.try_recv().map(|c| c.id()), Some(2));
+        assert_eq!(big.try_recv().map(|c| c.id()), Some(2));
+        assert!(all.try_recv().is_none());
+        assert!(big.try_recv().is_none());
+
+        // Updates are received if the ent matches before or after the change
+        let _ = db.update_fields(1, Patch::new().set_field("a", 3)).unwrap();
+        let change = big.try_recv().expect("Missing change");
+        assert_eq!(change.kind(), ChangeKind::Update);
+        assert_eq!(change.before().unwrap().field("a"), Some(Value::from(1)));
+        assert_eq!(change.after().unwrap().field("a"), Some(Value::from(3)));
+        assert_eq!(all.try_recv().map(|c| c.kind()), Some(ChangeKind::Update));
+
+        // Changes staged by a transaction are only received once committed
+        db.begin_transaction().unwrap();
+        let _ = db.remove(2).unwrap();
+        assert!(big.try_recv().is_none());
+        db.commit_transaction().unwrap();
+
+        let change = big.try_recv().expect("Missing change");
+        assert_eq!(change.kind(), ChangeKind::Remove);
+        assert_eq!(change.id(), 2);
+        assert_eq!(change.before().unwrap().field("a"), Some(Value::from(5)));
+        assert!(change.after().is_none());
+
+        // Dropped subscriptions stop receiving changes without affecting
+        // any other subscriptions
+        drop(all);
+        let _ = db.remove(1).unwrap();
+        assert_eq!(big.try_recv().map(|c| c.id()), Some(1));
+        assert_eq!(db.subscribers.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn begin_transaction_should_fail_if_transaction_already_in_progress() {
+        let db = InmemoryDatabase::default();
+
+        db.begin_transaction().expect("Failed to begin transaction");
+        assert!(matches!(
+            db.begin_transaction(),
+            Err(DatabaseError::TransactionInProgress)
+        ));
+    }
+
+    #[test]
+    fn commit_transaction_should_fail_if_no_transaction_in_progress() {
+        let db = InmemoryDatabase::default();
+
+        assert!(matches!(
+            db.commit_transaction(),
+            Err(DatabaseError::NoTransaction)
+        ));
+        assert!(matches!(
+            db.rollback_transaction(),
+            Err(DatabaseError::NoTransaction)
+        ));
+    }
+
+    #[test]
+    fn commit_transaction_should_apply_all_staged_changes() {
+        let db = InmemoryDatabase::default();
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(1))).unwrap();
+
+        db.begin_transaction().expect("Failed to begin transaction");
+        let id = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                EPHEMERAL_ID,
+                vec![],
+                vec![Edge::new("a", 1)],
+            )))
+            .expect("Failed to stage insert");
+        assert!(db.remove(1).expect("Failed to stage remove"));
+
+        // Staged changes are not written to storage until committed
+        assert!(db.ents.lock().unwrap().contains_key(&1));
+        assert!(!db.ents.lock().unwrap().contains_key(&id));
+
+        db.commit_transaction()
+            .expect("Failed to commit transaction");
+
+        assert!(db.get(1).unwrap().is_none(), "Did not remove ent");
+        assert!(db.get(id).unwrap().is_some(), "Did not insert ent");
+        assert!(!db.in_transaction());
+    }
+
+    #[test]
+    fn rollback_transaction_should_discard_all_staged_changes() {
+        let db = InmemoryDatabase::default();
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(1))).unwrap();
+
+        db.begin_transaction().expect("Failed to begin transaction");
+        let id = db
+            .insert(Box::from(UntypedEnt::empty_with_id(EPHEMERAL_ID)))
+            .expect("Failed to stage insert");
+        assert!(db.remove(1).expect("Failed to stage remove"));
+        db.rollback_transaction()
+            .expect("Failed to roll back transaction");
+
+        assert!(db.get(1).unwrap().is_some(), "Unexpectedly removed ent");
+        assert!(db.get(id).unwrap().is_none(), "Unexpectedly inserted ent");
+
+        // Id allocated within the transaction should have been freed
+        assert_eq!(db.alloc.lock().unwrap().freed(), &[id]);
+    }
+
+    #[test]
+    fn transaction_should_make_staged_changes_visible_only_to_current_thread() {
+        let db = std::sync::Arc::new(InmemoryDatabase::default());
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(1))).unwrap();
+
+        db.begin_transaction().expect("Failed to begin transaction");
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(2))).unwrap();
+        let _ = db.remove(1).unwrap();
+
+        let ts = <UntypedEnt as EntType>::type_str();
+        let q = Query::default().where_type(TP::equals(ts.to_string()));
+        query_and_assert(&db, q.clone(), &[2]);
+
+        let other_db = std::sync::Arc::clone(&db);
+        let ids = std::thread::spawn(move || {
+            let ts = <UntypedEnt as EntType>::type_str();
+            other_db
+                .find_all(Query::default().where_type(TP::equals(ts.to_string())))
+                .unwrap()
+                .iter()
+                .map(|ent| ent.id())
+                .collect::<Vec<Id>>()
+        })
+        .join()
+        .unwrap();
+        assert_eq!(ids, vec![1]);
+
+        db.commit_transaction()
+            .expect("Failed to commit transaction");
+        query_and_assert(&db, q, &[2]);
+    }
+
+    #[test]
+    fn transaction_should_include_ents_committed_while_in_progress() {
+        let db = db_to_rc(InmemoryDatabase::default());
+
+        let result = db.transaction(|_| {
+            let mut ent = UntypedEnt::empty_with_id(EPHEMERAL_ID);
+            ent.connect(DatabaseRc::downgrade(&db));
+            ent.commit()?;
+            Ok(ent.id())
+        });
+        let id = result.expect("Transaction failed");
+        assert!(db.get(id).unwrap().is_some(), "Ent was not committed");
+
+        let result: DatabaseResult<()> = db.transaction(|db| {
+            let _ = db.insert(Box::from(UntypedEnt::empty_with_id(999)))?;
+            Err(DatabaseError::Disconnected)
+        });
+        assert!(result.is_err());
+        assert!(db.get(999).unwrap().is_none(), "Ent was not rolled back");
+    }
+
+    #[test]
+    fn commit_should_fail_if_ent_was_updated_since_it_was_loaded() {
+        let db = db_to_rc(InmemoryDatabase::default());
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                1,
+                vec![Field::new("n", 1)],
+                vec![],
+            )))
+            .unwrap();
+        let load = || {
+            let mut ent = db
+                .get(1)
+                .unwrap()
+                .expect("Ent missing")
+                .to_ent::<UntypedEnt>()
+                .expect("Wrong ent type");
+            ent.connect(DatabaseRc::downgrade(&db));
+            ent
+        };
+        let mut a = load();
+        let mut b = load();
+
+        // Committing updates the ent so that it can be committed again
+        a.commit().expect("Failed to commit ent");
+        a.commit().expect("Failed to commit ent again");
+        assert_eq!(
+            a.last_updated(),
+            db.get(1).unwrap().expect("Ent missing").last_updated()
+        );
+
+        assert!(matches!(
+            b.commit(),
+            Err(DatabaseError::Conflict { id: 1, .. })
+        ));
+
+        // Refreshing the ent brings it up to date so it can be committed
+        b.refresh().expect("Failed to refresh ent");
+        b.commit().expect("Failed to commit refreshed ent");
+
+        // Changing the ent locally does not count as it being updated since
+        // it was loaded
+        let mut c = load();
+        c.update_field("n", Value::from(2)).unwrap();
+        c.commit().expect("Failed to commit changed ent");
+        assert_eq!(
+            db.get(1).unwrap().expect("Ent missing").field("n"),
+            Some(Value::from(2))
+        );
+    }
+
+    #[test]
+    fn get_all_should_return_all_ents_with_associated_ids() {
+        let db = InmemoryDatabase::default();
+
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(1))).unwrap();
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(2))).unwrap();
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(3))).unwrap();
+
+        let results = db
+            .get_all(vec![1, 2, 3])
+            .expect("Failed to retrieve ents")
+            .iter()
+            .map(|ent| ent.id())
+            .collect::<HashSet<Id>>();
+        assert_eq!(results, [1, 2, 3].iter().copied().collect());
+
+        let results = db
+            .get_all(vec![1, 3])
             .expect("Failed to retrieve ents")
             .iter()
             .map(|ent| ent.id())
@@ -673,83 +2580,569 @@ mod tests {
         let q = Query::default().where_type(TP::equals(String::from("unknown")));
         query_and_assert(&db, q, &[]);
 
-        // If already in a pipeline, should only filter the existing ids
-        let q = Query::default()
-            .where_id(TP::equals(1) | TP::equals(2) | TP::equals(4))
-            .where_type(TP::equals(ts.to_string()));
-        query_and_assert(&db, q, &[1, 2, 4]);
+        // If already in a pipeline, should only filter the existing ids
+        let q = Query::default()
+            .where_id(TP::equals(1) | TP::equals(2) | TP::equals(4))
+            .where_type(TP::equals(ts.to_string()));
+        query_and_assert(&db, q, &[1, 2, 4]);
+    }
+
+    #[test]
+    fn find_all_should_support_filtering_by_created_timestamp() {
+        let db = new_test_database();
+
+        // Re-create all ents with enough time split between them for us to
+        // properly test creation time
+        for i in 1..=12 {
+            let ent = UntypedEnt::empty_with_id(i);
+            db.insert(Box::from(ent))
+                .expect(&format!("Failed to replace ent {}", i));
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        // Get all ents created after our third ent
+        let time = db.get(3).unwrap().expect("Missing ent 3").created();
+        let q = Query::default().where_created(TP::greater_than(time));
+        query_and_assert(&db, q, &[4, 5, 6, 7, 8, 9, 10, 11, 12]);
+
+        // If already in a pipeline, should only filter the existing ids
+        let time = db.get(3).unwrap().expect("Missing ent 3").created();
+        let q = Query::default()
+            .where_id(TP::less_than(8))
+            .where_created(TP::greater_than(time));
+        query_and_assert(&db, q, &[4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn find_all_should_support_filtering_by_last_updated_timestamp() {
+        let db = new_test_database();
+
+        // Update all ents with enough time split between them for us to
+        // properly test last updated time
+        for i in (1..=12).rev() {
+            use entity::DatabaseExt;
+            let mut ent = db
+                .get_typed::<UntypedEnt>(i)
+                .unwrap()
+                .expect(&format!("Missing ent {}", i));
+            ent.mark_updated().unwrap();
+            db.insert(Box::from(ent))
+                .expect(&format!("Failed to update ent {}", i));
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        // Get all ents updated after our third ent
+        let time = db.get(3).unwrap().expect("Missing ent 3").last_updated();
+        let q = Query::default().where_last_updated(TP::greater_than(time));
+        query_and_assert(&db, q, &[1, 2]);
+
+        // If already in a pipeline, should only filter the existing ids
+        let time = db.get(3).unwrap().expect("Missing ent 3").created();
+        let q = Query::default()
+            .where_id(TP::equals(2))
+            .where_last_updated(TP::greater_than(time));
+        query_and_assert(&db, q, &[2]);
+    }
+
+    #[test]
+    fn find_all_should_support_filtering_by_field() {
+        let db = new_test_database();
+
+        // If ent's field passes condition, it will be included in return
+        let q = Query::default().where_field("a", P::equals(3));
+        query_and_assert(&db, q, &[5]);
+
+        // If already have ents in pipeline, they will be filtered by "field"
+        let q = Query::default()
+            .where_id(TP::equals(4) | TP::equals(6))
+            .where_field("a", P::greater_than(1));
+        query_and_assert(&db, q, &[6]);
+    }
+
+    /// Creates a new database with ents 1-5 that have an indexed field "n"
+    /// whose values are numbers of different types, none, and text
+    fn new_indexed_test_database() -> InmemoryDatabase {
+        let db = InmemoryDatabase::default();
+
+        let values = vec![
+            Value::from(1u8),
+            Value::from(2i64),
+            Value::from(3.5f32),
+            Value::from(-4i32),
+            Value::from(None::<u32>),
+        ];
+        for (id, value) in values.into_iter().enumerate() {
+            insert_indexed(&db, id + 1, value);
+        }
+
+        db
+    }
+
+    fn insert_indexed(db: &InmemoryDatabase, id: Id, value: Value) {
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                id,
+                vec![Field::new_with_attributes(
+                    "n",
+                    value,
+                    vec![FieldAttribute::Indexed],
+                )],
+                vec![],
+            )))
+            .unwrap();
+    }
+
+    fn scan_ids(db: &InmemoryDatabase, p: Predicate) -> Vec<Id> {
+        let scan = IndexScan::from_predicate(&p).expect("Predicate not indexable");
+        let mut ids: Vec<Id> = db.ids_for_field("n", &scan).into_iter().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    #[test]
+    fn find_all_should_support_filtering_by_indexed_field() {
+        let db = new_indexed_test_database();
+
+        // Equality and set membership only look up matching values
+        assert_eq!(scan_ids(&db, P::equals(2u32)), vec![2]);
+        assert_eq!(scan_ids(&db, P::in_set(vec![1, -4])), vec![1, 4]);
+        assert_eq!(scan_ids(&db, P::IsNone), vec![5]);
+        query_and_assert(
+            &db,
+            Query::default().where_field("n", P::equals(2u32)),
+            &[2],
+        );
+        query_and_assert(
+            &db,
+            Query::default()
+                .where_field("n", P::in_set(vec![Value::from(1u8), Value::from(-4i32)])),
+            &[1, 4],
+        );
+
+        // Ranges only look up values in order within the range
+        assert_eq!(scan_ids(&db, P::greater_than(1)), vec![2, 3]);
+        assert_eq!(scan_ids(&db, P::less_than_or_equals(1)), vec![1, 4]);
+        assert_eq!(
+            scan_ids(&db, P::in_range(Value::from(-10)..=Value::from(2))),
+            vec![1, 2, 4]
+        );
+        query_and_assert(
+            &db,
+            Query::default().where_field("n", P::greater_than(1)),
+            &[2, 3],
+        );
+        query_and_assert(
+            &db,
+            Query::default().where_field("n", P::in_range(Value::from(-10)..=Value::from(2))),
+            &[1, 2, 4],
+        );
+
+        // Indexes narrow down ents already in the pipeline
+        let q = Query::default()
+            .where_id(TP::equals(1) | TP::equals(3))
+            .where_field("n", P::greater_than(0));
+        query_and_assert(&db, q, &[1, 3]);
+    }
+
+    #[test]
+    fn find_all_should_filter_indexed_floats_by_their_exact_value() {
+        let db = new_indexed_test_database();
+        insert_indexed(&db, 6, Value::from(1.2f64));
+        insert_indexed(&db, 7, Value::from(1.7f64));
+        insert_indexed(&db, 8, Value::from(-1.5f64));
+
+        // Floats sharing an integral part are keyed apart from one another
+        assert_eq!(scan_ids(&db, P::equals(1.2f64)), vec![6]);
+        assert_eq!(scan_ids(&db, P::greater_than(1.5f64)), vec![2, 3, 7]);
+        assert_eq!(scan_ids(&db, P::less_than(-1)), vec![4, 8]);
+        query_and_assert(
+            &db,
+            Query::default().where_field("n", P::greater_than(1)),
+            &[2, 3, 6, 7],
+        );
+        query_and_assert(
+            &db,
+            Query::default().where_field("n", P::in_range(Value::from(1.5)..=Value::from(3))),
+            &[2, 7],
+        );
+    }
+
+    #[test]
+    fn indexes_should_stay_in_sync_with_inserted_and_removed_ents() {
+        let db = new_indexed_test_database();
+
+        // Overwriting an ent replaces its indexed value
+        insert_indexed(&db, 2, Value::from(10));
+        assert_eq!(scan_ids(&db, P::equals(2)), Vec::<Id>::new());
+        assert_eq!(scan_ids(&db, P::equals(10)), vec![2]);
+
+        // Removing an ent removes its indexed value
+        assert!(db.remove(1).unwrap());
+        assert_eq!(scan_ids(&db, P::equals(1)), Vec::<Id>::new());
+        query_and_assert(
+            &db,
+            Query::default().where_field("n", P::greater_than(0)),
+            &[2, 3],
+        );
+
+        // Staged ents are included until they are committed to the index
+        db.begin_transaction().unwrap();
+        insert_indexed(&db, 6, Value::from(7));
+        query_and_assert(&db, Query::default().where_field("n", P::equals(7)), &[6]);
+        db.commit_transaction().unwrap();
+        assert_eq!(scan_ids(&db, P::equals(7)), vec![6]);
+    }
+
+    #[test]
+    fn writes_should_fail_if_unique_field_value_is_used_by_another_ent() {
+        let db = InmemoryDatabase::default();
+        let user = |id: Id, email: &str| -> Box<dyn Ent> {
+            Box::from(UntypedEnt::from_collections(
+                id,
+                vec![Field::new_with_attributes(
+                    "email",
+                    String::from(email),
+                    vec![FieldAttribute::Unique],
+                )],
+                vec![],
+            ))
+        };
+
+        // Rewriting an ent with the value it already holds is allowed
+        db.insert(user(1, "a@example.com")).unwrap();
+        db.insert(user(1, "a@example.com")).unwrap();
+        assert!(matches!(
+            db.insert(user(2, "a@example.com")),
+            Err(DatabaseError::NotUnique { ref name, id: 1 }) if name == "email"
+        ));
+        assert!(!db.has_id(2));
+
+        // Values are released once the ent holding them changes or is removed
+        db.insert(user(1, "b@example.com")).unwrap();
+        db.insert(user(2, "a@example.com")).unwrap();
+        let patch = || Patch::new().set_field("email", "b@example.com");
+        assert!(matches!(
+            db.update_fields(2, patch()),
+            Err(DatabaseError::NotUnique { id: 1, .. })
+        ));
+        db.remove(1).unwrap();
+        db.update_fields(2, patch()).unwrap();
+
+        // Transactions are checked as a whole once committed
+        db.begin_transaction().unwrap();
+        db.insert(user(3, "c@example.com")).unwrap();
+        db.insert(user(4, "c@example.com")).unwrap();
+        assert!(matches!(
+            db.commit_transaction(),
+            Err(DatabaseError::NotUnique { id: 3, .. })
+        ));
+        assert!(!db.has_id(3));
+        assert!(!db.has_id(4));
+
+        db.begin_transaction().unwrap();
+        db.insert(user(2, "d@example.com")).unwrap();
+        db.insert(user(3, "b@example.com")).unwrap();
+        db.commit_transaction().unwrap();
+        assert_eq!(
+            db.get(3).unwrap().expect("Ent missing").field("email"),
+            Some(Value::from("b@example.com"))
+        );
+
+        // Values are compared exactly, and values without a key such as
+        // lists are never considered the same
+        let score = |id: Id, value: Value| -> Box<dyn Ent> {
+            Box::from(UntypedEnt::from_collections(
+                id,
+                vec![Field::new_with_attributes(
+                    "score",
+                    value,
+                    vec![FieldAttribute::Unique],
+                )],
+                vec![],
+            ))
+        };
+        db.insert(score(5, Value::from(1.2))).unwrap();
+        db.insert(score(6, Value::from(1.7))).unwrap();
+        assert!(matches!(
+            db.insert(score(7, Value::from(1.2))),
+            Err(DatabaseError::NotUnique { ref name, id: 5 }) if name == "score"
+        ));
+        db.insert(score(8, Value::from(vec![1, 2]))).unwrap();
+        db.insert(score(9, Value::from(vec![1, 2]))).unwrap();
+    }
+
+    #[test]
+    fn find_all_should_check_all_ents_of_type_if_field_is_not_indexed() {
+        let db = new_indexed_test_database();
+
+        // Ent of the same type with the field not marked as indexed
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                6,
+                vec![Field::new("n", 2)],
+                vec![],
+            )))
+            .unwrap();
+
+        assert_eq!(scan_ids(&db, P::equals(2)), vec![1, 2, 3, 4, 5, 6]);
+        query_and_assert(
+            &db,
+            Query::default().where_field("n", P::equals(2)),
+            &[2, 6],
+        );
+
+        // Once no ents of the type have the field unindexed, the index is used
+        assert!(db.remove(6).unwrap());
+        assert_eq!(scan_ids(&db, P::equals(2)), vec![2]);
+    }
+
+    #[test]
+    fn find_all_should_support_ordering_and_paging() {
+        let db = new_test_database();
+        let find_ids = |query: Query| {
+            db.find_all(query)
+                .expect("Failed to retrieve ents")
+                .iter()
+                .map(|ent| ent.id())
+                .collect::<Vec<Id>>()
+        };
+
+        // Ents are ordered by each clause in turn, followed by their ids
+        let q = Query::default()
+            .where_id(TP::always())
+            .order_by_id(OrderDirection::Descending);
+        assert_eq!(find_ids(q), vec![12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1]);
+
+        let q = Query::default()
+            .where_id(TP::less_than(7))
+            .order_by_field("a", OrderDirection::Descending);
+        assert_eq!(find_ids(q), vec![1, 2, 3, 6, 5, 4]);
+
+        let q = Query::default()
+            .where_id(TP::less_than(7))
+            .order_by_field("a", OrderDirection::Ascending)
+            .order_by_id(OrderDirection::Descending);
+        assert_eq!(find_ids(q), vec![4, 5, 6, 3, 2, 1]);
+
+        // Offset and limit are applied after ordering
+        let q = Query::default()
+            .where_id(TP::less_than(7))
+            .order_by_field("a", OrderDirection::Ascending)
+            .offset(1)
+            .limit(3);
+        assert_eq!(find_ids(q), vec![5, 6, 1]);
+
+        // Without any ordering clauses, ents are ordered by id when paging
+        let q = Query::default().where_id(TP::always()).offset(10);
+        assert_eq!(find_ids(q), vec![11, 12]);
+
+        let q = Query::default().where_id(TP::always()).limit(0);
+        assert_eq!(find_ids(q), Vec::<Id>::new());
+    }
+
+    #[test]
+    fn find_page_should_resume_after_cursor_of_previous_page() {
+        let db = new_test_database();
+        let query = || {
+            Query::default()
+                .where_id(TP::less_than(7))
+                .order_by_field("a", OrderDirection::Ascending)
+        };
+        let find_page = |query: Query| {
+            let (ents, cursor) = db
+                .find_page(query, 2)
+                .expect("Failed to retrieve page")
+                .into_parts();
+            (ents.iter().map(|ent| ent.id()).collect::<Vec<Id>>(), cursor)
+        };
+
+        let (ids, cursor) = find_page(query());
+        assert_eq!(ids, vec![4, 5]);
+        let cursor = cursor.expect("Missing cursor for next page");
+
+        // Cursors survive being passed around as strings
+        let cursor: Cursor = cursor.to_string().parse().unwrap();
+
+        // Ents removed after the first page are not part of later pages
+        // and do not shift where the query resumes
+        assert!(db.remove(1).expect("Failed to remove ent"));
+
+        let (ids, cursor) = find_page(query().after(cursor));
+        assert_eq!(ids, vec![6, 2]);
+        let cursor = cursor.expect("Missing cursor for next page");
+
+        let (ids, cursor) = find_page(query().after(cursor));
+        assert_eq!(ids, vec![3]);
+        assert!(cursor.is_none(), "Unexpected cursor for last page");
+
+        // A page that exactly fits the remaining ents has no next page
+        let (ids, cursor) = find_page(query().where_id(TP::greater_than(4)));
+        assert_eq!(ids, vec![5, 6]);
+        assert!(cursor.is_none(), "Unexpected cursor for last page");
+    }
+
+    #[test]
+    fn find_page_should_seek_in_the_same_order_as_find_all() {
+        let db = new_indexed_test_database();
+        insert_indexed(&db, 6, Value::from(2u8));
+        for id in vec![7, 8] {
+            let _ = db.insert(Box::from(UntypedEnt::empty_with_id(id))).unwrap();
+        }
+
+        // Pages are sought by id or by the indexed field, including ents
+        // without the field, and must line up with the ents found at once
+        for direction in vec![OrderDirection::Ascending, OrderDirection::Descending] {
+            let queries = vec![
+                Query::default()
+                    .where_id(TP::always())
+                    .order_by_id(direction),
+                Query::default()
+                    .where_id(TP::always())
+                    .order_by_field("n", direction),
+            ];
+
+            for query in queries {
+                let expected: Vec<Id> = db
+                    .find_all(query.clone())
+                    .expect("Failed to retrieve ents")
+                    .iter()
+                    .map(|ent| ent.id())
+                    .collect();
+
+                for page_size in 1..=3 {
+                    let mut ids = Vec::new();
+                    let mut page = db.find_page(query.clone(), page_size).unwrap();
+                    loop {
+                        ids.extend(page.items().iter().map(|ent| ent.id()));
+                        match page.next_cursor() {
+                            Some(cursor) => {
+                                let query = query.clone().after(cursor.clone());
+                                page = db.find_page(query, page_size).unwrap();
+                            }
+                            None => break,
+                        }
+                    }
+                    assert_eq!(ids, expected, "{:?} in pages of {}", query, page_size);
+                }
+            }
+        }
     }
 
     #[test]
-    fn find_all_should_support_filtering_by_created_timestamp() {
+    fn count_should_return_total_ents_that_would_be_found() {
         let db = new_test_database();
 
-        // Re-create all ents with enough time split between them for us to
-        // properly test creation time
-        for i in 1..=12 {
-            let ent = UntypedEnt::empty_with_id(i);
-            db.insert(Box::from(ent))
-                .expect(&format!("Failed to replace ent {}", i));
-            std::thread::sleep(std::time::Duration::from_millis(1));
-        }
+        let q = Query::default();
+        assert_eq!(db.count(q).expect("Failed to count ents"), 0);
 
-        // Get all ents created after our third ent
-        let time = db.get(3).unwrap().expect("Missing ent 3").created();
-        let q = Query::default().where_created(TP::greater_than(time));
-        query_and_assert(&db, q, &[4, 5, 6, 7, 8, 9, 10, 11, 12]);
+        let q = Query::default().where_id(TP::always());
+        assert_eq!(db.count(q).expect("Failed to count ents"), 12);
 
-        // If already in a pipeline, should only filter the existing ids
-        let time = db.get(3).unwrap().expect("Missing ent 3").created();
+        let q = Query::default().where_field("a", P::greater_than(1));
+        assert_eq!(db.count(q).expect("Failed to count ents"), 2);
+
+        // Dangling edge ids are not counted, as no ent would be found
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                13,
+                vec![],
+                vec![Edge::new("b", vec![1, 999])],
+            )))
+            .unwrap();
         let q = Query::default()
-            .where_id(TP::less_than(8))
-            .where_created(TP::greater_than(time));
-        query_and_assert(&db, q, &[4, 5, 6, 7]);
+            .where_id(TP::equals(13))
+            .where_into_edge("b");
+        assert_eq!(db.count(q).expect("Failed to count ents"), 1);
+
+        // Offset, limit, and cursor are honored
+        let q = Query::default().where_id(TP::always()).offset(10).limit(2);
+        assert_eq!(db.count(q).expect("Failed to count ents"), 2);
+
+        let cursor = Cursor::new(&[], db.get(8).unwrap().unwrap().as_ref());
+        let q = Query::default().where_id(TP::always()).after(cursor);
+        assert_eq!(db.count(q).expect("Failed to count ents"), 5);
+
+        let order = [Order::ascending(OrderBy::Field(String::from("a")))];
+        let cursor = Cursor::new(&order, db.get(5).unwrap().unwrap().as_ref());
+        let q = Query::default()
+            .where_id(TP::less_than(7))
+            .order_by_field("a", OrderDirection::Ascending)
+            .after(cursor);
+        assert_eq!(db.count(q).expect("Failed to count ents"), 4);
     }
 
     #[test]
-    fn find_all_should_support_filtering_by_last_updated_timestamp() {
+    fn exists_should_return_true_if_any_ent_would_be_found() {
         let db = new_test_database();
 
-        // Update all ents with enough time split between them for us to
-        // properly test last updated time
-        for i in (1..=12).rev() {
-            use entity::DatabaseExt;
-            let mut ent = db
-                .get_typed::<UntypedEnt>(i)
-                .unwrap()
-                .expect(&format!("Missing ent {}", i));
-            ent.mark_updated().unwrap();
-            db.insert(Box::from(ent))
-                .expect(&format!("Failed to update ent {}", i));
-            std::thread::sleep(std::time::Duration::from_millis(1));
-        }
+        let q = Query::default().where_id(TP::equals(5));
+        assert!(db.exists(q).expect("Failed to check for ents"));
 
-        // Get all ents updated after our third ent
-        let time = db.get(3).unwrap().expect("Missing ent 3").last_updated();
-        let q = Query::default().where_last_updated(TP::greater_than(time));
-        query_and_assert(&db, q, &[1, 2]);
+        let q = Query::default().where_id(TP::equals(999));
+        assert!(!db.exists(q).expect("Failed to check for ents"));
 
-        // If already in a pipeline, should only filter the existing ids
-        let time = db.get(3).unwrap().expect("Missing ent 3").created();
-        let q = Query::default()
-            .where_id(TP::equals(2))
-            .where_last_updated(TP::greater_than(time));
-        query_and_assert(&db, q, &[2]);
+        let q = Query::default().where_id(TP::equals(5)).offset(1);
+        assert!(!db.exists(q).expect("Failed to check for ents"));
+
+        let q = Query::default().where_id(TP::equals(5)).limit(0);
+        assert!(!db.exists(q).expect("Failed to check for ents"));
     }
 
     #[test]
-    fn find_all_should_support_filtering_by_field() {
+    fn aggregate_should_calculate_aggregates_over_ents_found_by_query() {
         let db = new_test_database();
 
-        // If ent's field passes condition, it will be included in return
-        let q = Query::default().where_field("a", P::equals(3));
-        query_and_assert(&db, q, &[5]);
+        let aggregation = Aggregation::new(Query::default().where_id(TP::less_than(7)))
+            .count()
+            .sum("a")
+            .avg("a")
+            .min("a")
+            .max("a")
+            .distinct("b");
+        let groups = db.aggregate(aggregation).expect("Failed to aggregate");
+        assert_eq!(groups.len(), 1);
+        assert_eq!(
+            groups[0].values(),
+            &[
+                Value::from(6usize),
+                Value::from(Some(9)),
+                Value::from(Some(3.0)),
+                Value::from(Some(1)),
+                Value::from(Some(5)),
+                Value::from(vec![2, 4, 6]),
+            ]
+        );
 
-        // If already have ents in pipeline, they will be filtered by "field"
-        let q = Query::default()
-            .where_id(TP::equals(4) | TP::equals(6))
-            .where_field("a", P::greater_than(1));
-        query_and_assert(&db, q, &[6]);
+        // Groups are ordered by value, followed by ents without the field
+        let aggregation = Aggregation::new(Query::default().where_id(TP::always()))
+            .count()
+            .group_by("a");
+        let groups: Vec<(Option<Value>, Vec<Value>)> = db
+            .aggregate(aggregation)
+            .expect("Failed to aggregate")
+            .into_iter()
+            .map(AggregateGroup::into_parts)
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (Some(Value::from(1)), vec![Value::from(1usize)]),
+                (Some(Value::from(3)), vec![Value::from(1usize)]),
+                (Some(Value::from(5)), vec![Value::from(1usize)]),
+                (None, vec![Value::from(9usize)]),
+            ]
+        );
+
+        // Only the ents within the page of a paged query are aggregated
+        let aggregation = Aggregation::new(
+            Query::default()
+                .where_id(TP::less_than(7))
+                .order_by_field("a", OrderDirection::Ascending)
+                .limit(2),
+        )
+        .sum("a");
+        let groups = db.aggregate(aggregation).expect("Failed to aggregate");
+        assert_eq!(groups[0].values(), &[Value::from(Some(4))]);
     }
 
     #[test]
@@ -786,6 +3179,350 @@ mod tests {
         query_and_assert(&db, q, &[3, 4, 5]);
     }
 
+    #[test]
+    fn find_all_should_support_combining_filters() {
+        let db = new_test_database();
+
+        // Ents passing any of the filters are included
+        let q = Query::default().where_or(vec![
+            Filter::where_id(TP::equals(1)),
+            Filter::where_field("a", P::greater_than(2)),
+        ]);
+        query_and_assert(&db, q, &[1, 5, 6]);
+
+        // Ents passing all of the filters are included, where nested edges
+        // are checked for ents rather than transformed into them
+        let q = Query::default().where_and(vec![
+            Filter::where_into_edge("c"),
+            Filter::where_edge("a", Filter::where_id(TP::greater_than(1))),
+        ]);
+        query_and_assert(&db, q, &[12]);
+
+        // Ents not passing the filter are included
+        let q = Query::default()
+            .where_id(TP::less_than(7))
+            .where_not(Filter::where_field("a", P::less_than(5)));
+        query_and_assert(&db, q, &[1, 2, 3, 6]);
+    }
+
+    #[test]
+    fn find_all_should_support_filtering_into_inverse_edges() {
+        let db = new_test_database();
+
+        // Ents are transformed into the ents whose edge contains them
+        let q = Query::default()
+            .where_id(TP::equals(3))
+            .where_into_inverse_edge("b");
+        query_and_assert(&db, q, &[10, 11]);
+
+        let q = Query::default()
+            .where_id(TP::equals(1) | TP::equals(2))
+            .where_into_inverse_edge("a");
+        query_and_assert(&db, q, &[10, 11]);
+
+        // When nested, ents pass if any edge contains them
+        let q = Query::default()
+            .where_id(TP::less_than(10))
+            .where_not(Filter::where_into_inverse_edge("b"));
+        query_and_assert(&db, q, &[7, 8, 9]);
+    }
+
+    #[test]
+    fn incoming_edges_should_return_ents_with_edges_containing_id() {
+        let db = new_test_database();
+        let incoming = |id| db.incoming_edges(id).unwrap();
+
+        assert_eq!(
+            incoming(3),
+            vec![
+                (10, String::from("b")),
+                (11, String::from("b")),
+                (12, String::from("a")),
+            ]
+        );
+        assert_eq!(incoming(7), vec![]);
+
+        // Overwriting or removing an ent updates the edges containing ids
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(12, vec![], vec![])))
+            .unwrap();
+        let _ = db.remove(10).unwrap();
+        assert_eq!(incoming(3), vec![(11, String::from("b"))]);
+
+        // Staged changes are only reflected until rolled back
+        db.begin_transaction().unwrap();
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                13,
+                vec![],
+                vec![Edge::new("c", 3)],
+            )))
+            .unwrap();
+        let _ = db.remove(11).unwrap();
+        assert_eq!(incoming(3), vec![(13, String::from("c"))]);
+        db.rollback_transaction().unwrap();
+        assert_eq!(incoming(3), vec![(11, String::from("b"))]);
+    }
+
+    #[test]
+    fn assocs_should_record_when_ents_were_added_to_edges_and_their_data() {
+        let db = InmemoryDatabase::default();
+        for id in 1..=3 {
+            let _ = db.insert(Box::from(UntypedEnt::empty_with_id(id))).unwrap();
+        }
+        let insert = |ids: Vec<Id>| {
+            let _ = db
+                .insert(Box::from(UntypedEnt::from_collections(
+                    10,
+                    vec![],
+                    vec![Edge::new("friends", ids)],
+                )))
+                .unwrap();
+            db.get(10).unwrap().expect("Ent missing").last_updated()
+        };
+        let assocs = || db.assocs(10, "friends").unwrap();
+
+        let first = insert(vec![1, 2]);
+        assert_eq!(assocs(), vec![Assoc::new(1, first), Assoc::new(2, first)]);
+
+        // Ents kept on the edge keep their association while new ents are
+        // associated when written
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = insert(vec![3, 2]);
+        assert!(second > first);
+        assert_eq!(assocs(), vec![Assoc::new(2, first), Assoc::new(3, second)]);
+
+        assert!(db
+            .set_assoc_data(10, "friends", 3, Value::from("best"))
+            .unwrap());
+        assert!(!db
+            .set_assoc_data(10, "friends", 1, Value::from("old"))
+            .unwrap());
+        insert(vec![3, 2]);
+        assert_eq!(
+            assocs(),
+            vec![
+                Assoc::new(2, first),
+                Assoc::new(3, second).with_data("best")
+            ]
+        );
+
+        // Associations can be filtered when filtering by edge
+        let q = Query::default().where_edge(
+            "friends",
+            Filter::where_assoc_data(P::equals(String::from("best"))),
+        );
+        query_and_assert(&db, q, &[10]);
+
+        let q = Query::default()
+            .where_edge("friends", Filter::where_assoc_created(TP::less_than(first)));
+        query_and_assert(&db, q, &[]);
+
+        let q = Query::default()
+            .where_id(TP::always())
+            .where_and(vec![Filter::where_assoc_created(TP::always())]);
+        query_and_assert(&db, q, &[]);
+
+        assert!(db.remove(10).unwrap());
+        assert_eq!(assocs(), vec![]);
+    }
+
+    #[test]
+    fn assoc_range_should_read_associations_ordered_by_creation_time() {
+        let db = InmemoryDatabase::default();
+        let insert = |ids: Vec<Id>| {
+            let _ = db
+                .insert(Box::from(UntypedEnt::from_collections(
+                    10,
+                    vec![],
+                    vec![Edge::new("likes", ids)],
+                )))
+                .unwrap();
+            db.get(10).unwrap().expect("Ent missing").last_updated()
+        };
+        let range = |range: AssocRange| {
+            db.assoc_range(10, "likes", range)
+                .unwrap()
+                .into_iter()
+                .map(|assoc| assoc.id())
+                .collect::<Vec<Id>>()
+        };
+
+        let mut times = Vec::new();
+        for id in 1..=4 {
+            times.push(insert((1..=id).collect()));
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        assert_eq!(range(AssocRange::default()), vec![1, 2, 3, 4]);
+        assert_eq!(
+            range(AssocRange::default().newest_first().with_limit(2)),
+            vec![4, 3]
+        );
+        assert_eq!(
+            range(AssocRange::default().with_offset(1).with_limit(2)),
+            vec![2, 3]
+        );
+        assert_eq!(
+            range(AssocRange::default().with_created(times[1]..=times[2])),
+            vec![2, 3]
+        );
+        assert_eq!(
+            range(
+                AssocRange::default()
+                    .with_created(..times[2])
+                    .newest_first()
+            ),
+            vec![2, 1]
+        );
+        assert_eq!(db.assoc_count(10, "likes").unwrap(), 4);
+        assert_eq!(db.assoc_count(10, "missing").unwrap(), 0);
+
+        // Staged changes are only reflected until rolled back
+        db.begin_transaction().unwrap();
+        insert(vec![5, 2]);
+        assert_eq!(range(AssocRange::default()), vec![2, 5]);
+        assert_eq!(db.assoc_count(10, "likes").unwrap(), 2);
+        db.rollback_transaction().unwrap();
+        assert_eq!(range(AssocRange::default()), vec![1, 2, 3, 4]);
+        assert_eq!(db.assoc_count(10, "likes").unwrap(), 4);
+    }
+
+    #[test]
+    fn find_all_should_support_set_operations_on_queries() {
+        let db = new_test_database();
+
+        // Ents found by either query are included, where later filters apply
+        // to the combined ents
+        let q = Query::default()
+            .where_id(TP::equals(1))
+            .union(Query::default().where_field("a", P::greater_than(2)));
+        query_and_assert(&db, q.clone(), &[1, 5, 6]);
+        query_and_assert(&db, q.where_id(TP::less_than(6)), &[1, 5]);
+
+        // Ents found by both queries are included, where edges can be
+        // traversed by either query
+        let q = Query::default().where_id(TP::less_than(7)).intersect(
+            Query::default()
+                .where_id(TP::equals(12))
+                .where_into_edge("a"),
+        );
+        query_and_assert(&db, q, &[3]);
+
+        // Ents found by the first query and not the second are included
+        let q = Query::default()
+            .where_id(TP::equals(11))
+            .where_into_edge("b")
+            .except(
+                Query::default()
+                    .where_id(TP::equals(10))
+                    .where_into_edge("b"),
+            );
+        query_and_assert(&db, q, &[1, 2, 6]);
+
+        // Queries that are paged only contribute the ents they return
+        let q = Query::default().where_id(TP::equals(1)).union(
+            Query::default()
+                .where_id(TP::always())
+                .order_by_id(OrderDirection::Descending)
+                .limit(2),
+        );
+        query_and_assert(&db, q, &[1, 11, 12]);
+
+        // Leading set operations start from the ents found by the query, or
+        // from all ents if removing them
+        let q = Query::default().union(Query::default().where_id(TP::equals(999)));
+        query_and_assert(&db, q, &[]);
+        let q = Query::default().except(Query::default().where_id(TP::greater_than(2)));
+        query_and_assert(&db, q, &[1, 2]);
+
+        // Nested set operations check whether the ent would remain
+        let q = Query::default()
+            .where_id(TP::less_than(7))
+            .where_not(Filter::intersect(
+                Query::default().where_field("a", P::greater_than(2)),
+            ));
+        query_and_assert(&db, q, &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn traverse_should_follow_edges_of_ents() {
+        let db = InmemoryDatabase::default();
+        for (id, friends) in vec![(1, vec![2, 3]), (2, vec![1, 3]), (3, vec![4]), (4, vec![])] {
+            let _ = db
+                .insert(Box::from(UntypedEnt::from_collections(
+                    id,
+                    vec![Field::new("n", id as u32)],
+                    vec![Edge::new("friends", friends)],
+                )))
+                .unwrap();
+        }
+
+        // Ents within the max depth are reached once each
+        let t = Traversal::new(vec![1]).follow("friends").max_depth(2);
+        assert_eq!(db.traverse_ids(t).unwrap(), vec![2, 3, 4]);
+
+        // Ents are only reached through an edge if they pass its filter
+        let t = Traversal::new(vec![1])
+            .follow_where("friends", Filter::where_field("n", P::greater_than(2u32)));
+        assert_eq!(db.traverse_ids(t).unwrap(), vec![3, 4]);
+
+        let t = Traversal::new(vec![1]).follow_where(
+            "friends",
+            Filter::where_field("n", P::greater_than(1u32))
+                & Filter::where_field("n", P::less_than(4u32)),
+        );
+        assert_eq!(db.traverse_ids(t).unwrap(), vec![2, 3]);
+
+        let ents = db
+            .traverse_ents(Traversal::new(vec![2]).follow("friends").min_depth(2))
+            .unwrap();
+        assert_eq!(
+            ents.iter().map(|ent| ent.id()).collect::<Vec<Id>>(),
+            vec![4]
+        );
+
+        // Shortest paths are found breadth first, if any exist
+        let t = Traversal::new(vec![2]).follow("friends").depth_first();
+        let path = db.shortest_path(t, 4).unwrap().map(Path::into_ids);
+        assert_eq!(path, Some(vec![2, 3, 4]));
+
+        let t = Traversal::new(vec![4]).follow("friends");
+        assert_eq!(db.shortest_path(t, 1).unwrap(), None);
+    }
+
+    #[test]
+    fn find_all_should_use_indexes_for_combined_filters() {
+        let db = new_indexed_test_database();
+
+        // Any filter can only be served by indexes if each filter can be
+        let filter = Filter::or(vec![
+            Filter::where_field("n", P::equals(2u32)),
+            Filter::where_field("n", P::IsNone),
+        ]);
+        let mut ids: Vec<Id> = indexed_ids(&db, &filter).unwrap().into_iter().collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![2, 5]);
+        query_and_assert(&db, Query::default().chain(filter), &[2, 5]);
+
+        let filter = Filter::or(vec![
+            Filter::where_field("n", P::equals(2u32)),
+            Filter::where_id(TP::equals(3)),
+        ]);
+        assert_eq!(indexed_ids(&db, &filter), None);
+        query_and_assert(&db, Query::default().chain(filter), &[2, 3]);
+
+        // All filters are served by any indexes that can serve one of them
+        let filter = Filter::and(vec![
+            Filter::where_field("n", P::greater_than(0)),
+            Filter::where_id(TP::not_equals(2)),
+        ]);
+        let mut ids: Vec<Id> = indexed_ids(&db, &filter).unwrap().into_iter().collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![1, 2, 3]);
+        query_and_assert(&db, Query::default().chain(filter), &[1, 3]);
+    }
+
     #[derive(Clone, Debug, PartialEq, Eq)]
     #[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
     struct TestEnt(Id);
@@ -896,4 +3633,109 @@ mod tests {
             Err(DatabaseError::Disconnected)
         }
     }
+
+    /// Ent that marks itself as updated whenever one of its fields or edges
+    /// is changed, unlike untyped ents that it otherwise behaves like
+    #[derive(Clone, Debug, PartialEq)]
+    #[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+    struct MarkedEnt(UntypedEnt);
+
+    impl EntType for MarkedEnt {
+        fn type_data() -> EntTypeData {
+            EntTypeData::Concrete {
+                ty: concat!(module_path!(), "::MarkedEnt"),
+            }
+        }
+    }
+
+    #[cfg_attr(feature = "serde-1", typetag::serde)]
+    impl Ent for MarkedEnt {
+        fn id(&self) -> Id {
+            self.0.id()
+        }
+
+        fn set_id(&mut self, id: Id) {
+            self.0.set_id(id);
+        }
+
+        fn r#type(&self) -> &str {
+            Self::type_str()
+        }
+
+        fn created(&self) -> u64 {
+            self.0.created()
+        }
+
+        fn last_updated(&self) -> u64 {
+            self.0.last_updated()
+        }
+
+        fn mark_updated(&mut self) -> Result<(), EntMutationError> {
+            self.0.mark_updated()
+        }
+
+        fn field_definitions(&self) -> Vec<FieldDefinition> {
+            self.0.field_definitions()
+        }
+
+        fn field(&self, name: &str) -> Option<Value> {
+            self.0.field(name)
+        }
+
+        fn update_field(&mut self, name: &str, value: Value) -> Result<Value, EntMutationError> {
+            let old_value = self.0.update_field(name, value)?;
+            self.mark_updated()?;
+            Ok(old_value)
+        }
+
+        fn edge_definitions(&self) -> Vec<EdgeDefinition> {
+            self.0.edge_definitions()
+        }
+
+        fn edge(&self, name: &str) -> Option<EdgeValue> {
+            self.0.edge(name)
+        }
+
+        fn update_edge(
+            &mut self,
+            name: &str,
+            value: EdgeValue,
+        ) -> Result<EdgeValue, EntMutationError> {
+            let old_value = self.0.update_edge(name, value)?;
+            self.mark_updated()?;
+            Ok(old_value)
+        }
+
+        fn connect(&mut self, database: WeakDatabaseRc) {
+            self.0.connect(database);
+        }
+
+        fn disconnect(&mut self) {
+            self.0.disconnect();
+        }
+
+        fn is_connected(&self) -> bool {
+            self.0.is_connected()
+        }
+
+        fn load_edge(&self, name: &str) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+            self.0.load_edge(name)
+        }
+
+        fn clear_cache(&mut self) {
+            self.0.clear_cache();
+        }
+
+        fn refresh(&mut self) -> DatabaseResult<()> {
+            self.0.refresh()
+        }
+
+        fn commit(&mut self) -> DatabaseResult<()> {
+            self.0.commit()
+        }
+
+        fn remove(&self) -> DatabaseResult<bool> {
+            self.0.remove()
+        }
+    }
 }
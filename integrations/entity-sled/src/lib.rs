@@ -1,11 +1,34 @@
 use entity::{
-    Database, DatabaseError, DatabaseResult, EdgeDeletionPolicy, Ent, Filter, Id, IdAllocator,
-    Predicate, Primitive, Query, Value, EPHEMERAL_ID,
+    AggregateGroup, Aggregation, Assoc, AssocRange, Change, Cursor, Database, DatabaseError,
+    DatabaseResult, EdgeDeletionPolicy, Ent, Filter, Id, IdAllocator, IndexKey, IndexScan,
+    InverseEdgeChange, OrderBy, OrderDirection, Page, Patch, Path, Predicate, Primitive, Query,
+    Subscription, Transaction, TransactionOp, Traversal, Value, EPHEMERAL_ID,
+};
+use sled::transaction::{
+    ConflictableTransactionError, TransactionError, Transactional, TransactionalTree,
+};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    ops::Bound::{Excluded, Included, Unbounded},
+    sync::{mpsc::RecvTimeoutError, Arc, Mutex},
+    thread::{self, ThreadId},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
-use std::collections::HashSet;
 
 type EntIdSet = HashSet<Id>;
 
+/// How long a subscription's thread waits for a change before checking if
+/// the subscription has been dropped
+const SUBSCRIPTION_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often a subscription that has not read any changes marks itself as
+/// still live in the subscriber tree
+const SUBSCRIPTION_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a subscription can go unmarked before it is treated as gone,
+/// such as when the process holding it exits without dropping it
+const SUBSCRIPTION_EXPIRY: Duration = Duration::from_secs(30);
+
 /// Represents a sled database that performs synchronous insertion,
 /// retrieval, and removal. Sled maintains disk-backed data, so the `serde`
 /// feature has no purpose with this database.
@@ -13,7 +36,26 @@ type EntIdSet = HashSet<Id>;
 /// Sled itself is thread-safe, maintaining an internal `Arc` for each tree;
 /// therefore, this database can be cloned to increment those counters.
 #[derive(Clone)]
-pub struct SledDatabase(sled::Db);
+pub struct SledDatabase {
+    db: sled::Db,
+
+    /// Transactions in progress, keyed by the thread that began them
+    transactions: Arc<Mutex<HashMap<ThreadId, PendingTransaction>>>,
+}
+
+/// Represents changes staged by a thread alongside the ids allocated for
+/// them, which are returned to the allocator if the changes are discarded
+#[derive(Default)]
+struct PendingTransaction {
+    transaction: Transaction,
+    allocated: Vec<Id>,
+}
+
+type TxResult<T> = Result<T, ConflictableTransactionError<DatabaseError>>;
+
+/// Represents the snapshots of an ent before and after a change, as recorded
+/// in the change tree
+type ChangeRecord = (Option<Box<dyn Ent>>, Option<Box<dyn Ent>>);
 
 fn id_to_ivec(id: Id) -> sled::IVec {
     id.to_be_bytes().as_ref().into()
@@ -25,160 +67,342 @@ fn ivec_to_id(ivec: sled::IVec) -> Option<Id> {
     bytes.try_into().map(Id::from_be_bytes).ok()
 }
 
-const ENTS_OF_TYPE: &str = "ents_of_type";
-const ID_ALLOCATOR: &str = "id_allocator";
+/// Reads a big-endian number from the start of the bytes, such as the
+/// sequence number of a change, defaulting to 0 if there are too few bytes
+fn ivec_to_u64(bytes: &[u8]) -> u64 {
+    use std::convert::TryInto;
+    bytes
+        .get(..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_be_bytes)
+        .unwrap_or_default()
+}
 
-impl SledDatabase {
-    /// Creates a new instance of the database wrapping a `sled::Db`
-    pub fn new(db: sled::Db) -> Self {
-        Self(db)
-    }
+/// Returns the sequence number of the latest change recorded
+fn latest_change(changes: &sled::Tree) -> sled::Result<u64> {
+    Ok(changes
+        .get(LATEST_CHANGE)?
+        .map(|ivec| ivec_to_u64(&ivec))
+        .unwrap_or_default())
+}
 
-    /// Returns ids of all ents stored in the database
-    pub fn ids(&self) -> EntIdSet {
-        self.0
-            .iter()
-            .keys()
-            .filter_map(Result::ok)
-            .filter_map(ivec_to_id)
-            .collect()
+/// Returns the number of milliseconds since the unix epoch
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Marks the subscription as live, having read every change up to the given
+/// sequence number
+fn mark_subscriber(subscribers: &sled::Tree, key: &[u8], cursor: u64) -> sled::Result<()> {
+    let mut value = cursor.to_be_bytes().to_vec();
+    value.extend_from_slice(&now_millis().to_be_bytes());
+    subscribers.insert(key, value).map(|_| ())
+}
+
+/// Returns the lowest sequence number read by the live subscriptions, or
+/// `None` if there are none, removing any that have expired
+fn live_subscribers(subscribers: &sled::Tree) -> sled::Result<Option<u64>> {
+    let expired_before = now_millis().saturating_sub(SUBSCRIPTION_EXPIRY.as_millis() as u64);
+    let mut lowest: Option<u64> = None;
+    for entry in subscribers.iter() {
+        let (key, value) = entry?;
+        if ivec_to_u64(value.get(8..).unwrap_or_default()) < expired_before {
+            subscribers.remove(key)?;
+        } else {
+            let cursor = ivec_to_u64(&value);
+            lowest = Some(lowest.map_or(cursor, |lowest| lowest.min(cursor)));
+        }
     }
+    Ok(lowest)
+}
 
-    /// Returns true if database contains the provided id
-    pub fn has_id(&self, id: Id) -> bool {
-        self.0.contains_key(id_to_ivec(id)).ok().unwrap_or_default()
+/// Removes the changes that every live subscription has already read
+fn trim_changes(changes: &sled::Tree, subscribers: &sled::Tree) -> sled::Result<()> {
+    // The latest change is read first, so a subscription that becomes live
+    // afterwards has not read any of the changes at or before it
+    let latest = latest_change(changes)?;
+    let end = live_subscribers(subscribers)?.unwrap_or(latest);
+    for key in changes.range(1u64.to_be_bytes()..=end.to_be_bytes()).keys() {
+        changes.remove(key?)?;
     }
+    Ok(())
+}
 
-    /// Returns ids of all ents for the given type
-    pub fn ids_for_type(&self, r#type: &str) -> EntIdSet {
-        fn inner(this: &SledDatabase, r#type: &str) -> DatabaseResult<EntIdSet> {
-            match this
-                .0
-                .open_tree(ENTS_OF_TYPE)
-                .map_err(|e| DatabaseError::Connection {
-                    source: Box::from(e),
-                })?
-                .get(r#type)
-                .map_err(|e| DatabaseError::Connection {
-                    source: Box::from(e),
-                })? {
-                Some(ivec) => match bincode::deserialize::<EntIdSet>(&ivec) {
-                    Ok(x) => Ok(x),
-                    Err(x) => Err(DatabaseError::Connection {
-                        source: Box::from(x),
-                    }),
-                },
-                None => Ok(HashSet::new()),
-            }
+const ENTS_OF_TYPE: &str = "ents_of_type";
+const ID_ALLOCATOR: &str = "id_allocator";
+const FIELD_INDEXES: &str = "field_indexes";
+const EDGE_INDEXES: &str = "edge_indexes";
+const ASSOCS: &str = "assocs";
+const CHANGES: &str = "changes";
+const SUBSCRIBERS: &str = "subscribers";
+
+/// Key within the change tree of the sequence number of the latest change,
+/// which sorts before the sequence numbers keying the changes themselves
+const LATEST_CHANGE: &[u8] = &[];
+
+/// Marks an entry in the field index tree as containing an indexed value
+const INDEXED_VALUE: u8 = 0;
+
+/// Marks an entry in the field index tree as recording that some ent of the
+/// type has the field without indexing it
+const UNINDEXED_FIELD: u8 = 1;
+
+/// Marks an entry in the field index tree as claiming a value of a unique
+/// field, holding the id of the ent with the value
+const UNIQUE_VALUE: u8 = 2;
+
+/// Marks an entry in the assoc tree as keyed by the id of the associated ent
+const ASSOC_BY_ID: u8 = 0;
+
+/// Marks an entry in the assoc tree as keyed by the time the association
+/// was created
+const ASSOC_BY_TIME: u8 = 1;
+
+/// Returns the prefix shared by all entries in the field index tree for the
+/// field of the given type of ent
+///
+/// Each entry of an indexed value is the prefix followed by a marker, the
+/// ordered bytes of the field's value, and the id of the ent. A field that
+/// is not indexed has a single entry of the prefix followed by a marker.
+fn field_index_prefix(r#type: &str, field: &str) -> Vec<u8> {
+    let mut prefix = IndexKey::Text(r#type.to_string()).to_ordered_bytes();
+    prefix.extend(IndexKey::Text(field.to_string()).to_ordered_bytes());
+    prefix
+}
+
+/// Returns all entries in the field index tree for the indexed fields of
+/// the ent
+fn field_index_entries(ent: &dyn Ent) -> Vec<Vec<u8>> {
+    let mut entries = Vec::new();
+
+    for def in ent.field_definitions() {
+        if !def.is_indexed() {
+            continue;
         }
 
-        inner(self, r#type).ok().unwrap_or_default()
+        // Values without a key can never be found by a scan, so they are
+        // left out of the index entirely
+        if let Some(key) = ent.field(def.name()).and_then(|v| IndexKey::from_value(&v)) {
+            let mut entry = field_index_prefix(ent.r#type(), def.name());
+            entry.push(INDEXED_VALUE);
+            entry.extend(key.to_ordered_bytes());
+            entry.extend_from_slice(&ent.id().to_be_bytes());
+            entries.push(entry);
+        }
     }
 
-    /// Returns sled tree for id allocator
-    fn id_allocator_tree(&self) -> DatabaseResult<sled::Tree> {
-        self.0
-            .open_tree(ID_ALLOCATOR)
-            .map_err(|e| DatabaseError::Connection {
-                source: Box::from(e),
-            })
-    }
+    entries
+}
 
-    /// Provides a mutable reference to the id allocator, returning an optional
-    /// id in the case that we want to return the next id from the allocator.
-    ///
-    /// Any changes made to the allocator are persisted back to disk.
-    fn with_id_allocator<F: Fn(&mut IdAllocator) -> Option<Id>>(
-        &self,
-        f: F,
-    ) -> DatabaseResult<Option<Id>> {
-        self.id_allocator_tree()?
-            .transaction(move |tx_db| {
-                let mut id_alloc = match tx_db.get([0])? {
-                    Some(ivec) => match bincode::deserialize::<IdAllocator>(&ivec) {
-                        Ok(x) => x,
-                        Err(x) => {
-                            sled::transaction::abort(x)?;
-                            return Ok(None);
-                        }
-                    },
-                    None => IdAllocator::new(),
-                };
+/// Returns the entries in the field index tree recording that the ent's
+/// type has each field of the ent that has a value without being indexed
+///
+/// These entries are shared by all ents of the type, so they are only
+/// removed when the indexes are rebuilt.
+fn unindexed_field_entries(ent: &dyn Ent) -> Vec<Vec<u8>> {
+    let mut entries = Vec::new();
+
+    for def in ent.field_definitions() {
+        if !def.is_indexed() && ent.field(def.name()).is_some() {
+            let mut entry = field_index_prefix(ent.r#type(), def.name());
+            entry.push(UNINDEXED_FIELD);
+            entries.push(entry);
+        }
+    }
 
-                let maybe_id = f(&mut id_alloc);
+    entries
+}
 
-                let id_alloc_bytes = match bincode::serialize(&id_alloc) {
-                    Ok(x) => x,
-                    Err(x) => {
-                        sled::transaction::abort(x)?;
-                        return Ok(maybe_id);
-                    }
-                };
+/// Returns all entries in the field index tree that claim the values of the
+/// unique fields of the ent, alongside the name of the field for each entry
+///
+/// Each entry is the prefix of the field followed by a marker and the
+/// ordered bytes of the field's value, and holds the id of the ent. Values
+/// that are absent or have no key are never claimed.
+fn unique_index_entries(ent: &dyn Ent) -> Vec<(Vec<u8>, String)> {
+    let mut entries = Vec::new();
+
+    for def in ent.field_definitions() {
+        if !def.is_unique() {
+            continue;
+        }
 
-                tx_db.insert(&[0], id_alloc_bytes)?;
-                Ok(maybe_id)
-            })
-            .map_err(|e| DatabaseError::Connection {
-                source: Box::from(e),
-            })
+        match ent.field(def.name()).and_then(|v| IndexKey::from_value(&v)) {
+            Some(IndexKey::None) | None => {}
+            Some(key) => {
+                let mut entry = field_index_prefix(ent.r#type(), def.name());
+                entry.push(UNIQUE_VALUE);
+                entry.extend(key.to_ordered_bytes());
+                entries.push((entry, def.name().to_string()));
+            }
+        }
     }
 
-    /// Returns sled tree for ent types
-    fn ent_type_tree(&self) -> DatabaseResult<sled::Tree> {
-        self.0
-            .open_tree(ENTS_OF_TYPE)
-            .map_err(|e| DatabaseError::Connection {
-                source: Box::from(e),
-            })
+    entries
+}
+
+/// Returns all entries in the edge index tree for the edges of the ent,
+/// alongside the name of the edge for each entry
+///
+/// Each entry is the id of the ent contained by the edge, the ordered bytes
+/// of the edge's name, and the id of the ent with the edge.
+fn edge_index_entries(ent: &dyn Ent) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let mut entries = Vec::new();
+
+    for edge in ent.edges() {
+        let name = IndexKey::Text(edge.name().to_string()).to_ordered_bytes();
+        for id in edge.to_ids() {
+            let mut entry = id.to_be_bytes().to_vec();
+            entry.extend_from_slice(&name);
+            entry.extend_from_slice(&ent.id().to_be_bytes());
+            entries.push((entry, edge.name().as_bytes().to_vec()));
+        }
     }
 
-    /// Provides a mutable reference to the id set associated with an ent type.
-    /// Any changes made to the set are persisted back to disk.
-    fn with_ent_type_set<F: Fn(&mut EntIdSet)>(&self, r#type: &str, f: F) -> DatabaseResult<()> {
-        self.ent_type_tree()?
-            .transaction(move |tx_db| {
-                let mut set = match tx_db.get(r#type)? {
-                    Some(ivec) => match bincode::deserialize::<EntIdSet>(&ivec) {
-                        Ok(x) => x,
-                        Err(x) => {
-                            sled::transaction::abort(x)?;
-                            return Ok(());
-                        }
-                    },
-                    None => HashSet::new(),
-                };
+    entries
+}
 
-                f(&mut set);
+/// Returns the prefix shared by all entries in the assoc tree for the edge of
+/// the ent with the given id that use the given marker
+///
+/// Entries keyed by id are the prefix followed by the id of the associated
+/// ent and hold the serialized association. Entries keyed by time are the
+/// prefix followed by the creation time and id of the associated ent, and
+/// exist to keep associations ordered by when they were created.
+fn assoc_prefix(id: Id, edge: &str, marker: u8) -> Vec<u8> {
+    let mut prefix = id.to_be_bytes().to_vec();
+    prefix.extend(IndexKey::Text(edge.to_string()).to_ordered_bytes());
+    prefix.push(marker);
+    prefix
+}
 
-                let set_bytes = match bincode::serialize(&set) {
-                    Ok(x) => x,
-                    Err(x) => {
-                        sled::transaction::abort(x)?;
-                        return Ok(());
-                    }
-                };
+/// Returns the entry in the assoc tree keyed by the id of the associated ent
+fn assoc_by_id_entry(id: Id, edge: &str, target: Id) -> Vec<u8> {
+    let mut entry = assoc_prefix(id, edge, ASSOC_BY_ID);
+    entry.extend_from_slice(&target.to_be_bytes());
+    entry
+}
 
-                tx_db.insert(r#type, set_bytes)?;
-                Ok(())
-            })
-            .map_err(|e| DatabaseError::Connection {
-                source: Box::from(e),
-            })
+/// Returns the entry in the assoc tree keyed by the time the association
+/// was created
+fn assoc_by_time_entry(id: Id, edge: &str, assoc: &Assoc) -> Vec<u8> {
+    let mut entry = assoc_prefix(id, edge, ASSOC_BY_TIME);
+    entry.extend_from_slice(&assoc.created().to_be_bytes());
+    entry.extend_from_slice(&assoc.id().to_be_bytes());
+    entry
+}
+
+/// Deserializes an association held by the assoc tree for the ent with the
+/// given id
+fn ivec_to_assoc(id: Id, ivec: sled::IVec) -> DatabaseResult<Assoc> {
+    bincode::deserialize(ivec.as_ref()).map_err(|e| DatabaseError::CorruptedEnt {
+        id,
+        source: Box::from(e),
+    })
+}
+
+/// Returns the id of the ent at the end of an entry in an index tree
+fn index_entry_to_id(entry: sled::IVec) -> Option<Id> {
+    use std::convert::TryInto;
+    let at = entry.len().checked_sub(std::mem::size_of::<Id>())?;
+    let (_, bytes) = entry.as_ref().split_at(at);
+    bytes.try_into().map(Id::from_be_bytes).ok()
+}
+
+/// Returns the smallest bytes that are greater than all bytes starting with
+/// the given prefix
+fn prefix_successor(prefix: &[u8]) -> Vec<u8> {
+    let mut bytes = prefix.to_vec();
+    while let Some(b) = bytes.pop() {
+        if b < u8::MAX {
+            bytes.push(b + 1);
+            break;
+        }
     }
+    bytes
 }
 
-impl Database for SledDatabase {
-    fn get_all(&self, ids: Vec<Id>) -> DatabaseResult<Vec<Box<dyn Ent>>> {
-        ids.into_iter()
-            .filter_map(|id| self.get(id).transpose())
-            .collect()
+impl SledDatabase {
+    /// Creates a new instance of the database wrapping a `sled::Db`
+    pub fn new(db: sled::Db) -> Self {
+        Self {
+            db,
+            transactions: Arc::new(Mutex::new(HashMap::new())),
+        }
     }
 
-    fn find_all(&self, query: Query) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+    /// Returns ids of all ents stored in the database
+    pub fn ids(&self) -> EntIdSet {
+        let mut ids: EntIdSet = self
+            .db
+            .iter()
+            .keys()
+            .filter_map(Result::ok)
+            .filter_map(ivec_to_id)
+            .collect();
+
+        self.with_pending(|pending| {
+            for op in pending.transaction.ops() {
+                match op {
+                    TransactionOp::Insert(ent) => {
+                        ids.insert(ent.id());
+                    }
+                    TransactionOp::Remove(id) => {
+                        ids.remove(id);
+                    }
+                    TransactionOp::Expect { .. } => {}
+                }
+            }
+        });
+
+        ids
+    }
+
+    /// Returns ids of all ents that pass the filters of the query, in no
+    /// particular order
+    fn find_ids(&self, query: &Query) -> DatabaseResult<EntIdSet> {
         let mut pipeline: Option<EntIdSet> = None;
 
-        for filter in query {
-            let mut_pipeline = pipeline.get_or_insert_with(|| prefill_ids(self, &filter));
+        for filter in query.filters() {
+            // If our filter is a set operation, we combine the ids found so
+            // far with the ids found by its query rather than filtering them
+            match filter {
+                Filter::Union(other) => {
+                    let other = self.query_ids(other)?;
+                    pipeline.get_or_insert_with(EntIdSet::new).extend(other);
+                    continue;
+                }
+                Filter::Intersect(other) => {
+                    let other = self.query_ids(other)?;
+                    match pipeline.as_mut() {
+                        Some(ids) => ids.retain(|id| other.contains(id)),
+                        None => pipeline = Some(other),
+                    }
+                    continue;
+                }
+                Filter::Except(other) => {
+                    let other = self.query_ids(other)?;
+                    pipeline
+                        .get_or_insert_with(|| self.ids())
+                        .retain(|id| !other.contains(id));
+                    continue;
+                }
+                _ => {}
+            }
+
+            // If the filter can be served by an index, narrow down the ids
+            // to those found by the index before checking the filter itself
+            if let Some(candidates) = indexed_ids(self, filter)? {
+                match pipeline.as_mut() {
+                    Some(ids) => ids.retain(|id| candidates.contains(id)),
+                    None => pipeline = Some(candidates),
+                }
+            }
+
+            let mut_pipeline = pipeline.get_or_insert_with(|| prefill_ids(self, filter));
 
             // If our filter is the special IntoEdge case, we don't want to
             // actually filter out ids but rather transform them into the ids
@@ -193,7 +417,7 @@ impl Database for SledDatabase {
                                     .map(|maybe_ent| {
                                         maybe_ent
                                             .and_then(|ent| {
-                                                ent.edge(&name).map(|edge| edge.to_ids())
+                                                ent.edge(name).map(|edge| edge.to_ids())
                                             })
                                             .unwrap_or_default()
                                     })
@@ -202,152 +426,464 @@ impl Database for SledDatabase {
                             .collect(),
                     )
                 }
+                // Similarly, IntoInverseEdge transforms ids into the ids of
+                // ents whose edge contains them
+                Filter::IntoInverseEdge(name) => {
+                    let mut ids = EntIdSet::new();
+                    for id in mut_pipeline.iter() {
+                        ids.extend(self.ids_for_inverse_edge(*id, name)?);
+                    }
+                    pipeline = Some(ids);
+                }
                 // Otherwise, the filter is a traditional case where we will
                 // strip out ids by the filter
                 f => {
-                    mut_pipeline.retain(|id| filter_id(self, id, &f));
+                    mut_pipeline.retain(|id| filter_id(self, id, f, None));
                 }
             }
         }
 
-        pipeline
-            .unwrap_or_default()
-            .into_iter()
-            .filter_map(|id| self.get(id).transpose())
-            .collect()
+        Ok(pipeline.unwrap_or_default())
     }
 
-    fn get(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>> {
-        let maybe_ivec = self
-            .0
-            .get(id_to_ivec(id))
-            .map_err(|e| DatabaseError::Connection {
-                source: Box::from(e),
-            })?;
+    /// Returns ids of all ents returned by the query, only retrieving the
+    /// ents if they are needed to order and page them
+    fn query_ids(&self, query: &Query) -> DatabaseResult<EntIdSet> {
+        let ids = self.find_ids(query)?;
+        if !query.is_paged() {
+            return Ok(ids.into_iter().filter(|id| self.has_id(*id)).collect());
+        }
 
-        let result: Result<Option<Box<dyn Ent>>, DatabaseError> = maybe_ivec
-            .map(|ivec| bincode::deserialize(ivec.as_ref()))
-            .transpose()
-            .map_err(|e| DatabaseError::CorruptedEnt {
-                id,
-                source: Box::from(e),
-            });
+        let ents =
+            query.order_and_page(ids.into_iter().filter_map(|id| self.get(id).transpose()))?;
+        Ok(ents.iter().map(|ent| ent.id()).collect())
+    }
 
-        // If we found an ent without a database connection, attempt to fill
-        // it in with the global database if it exists
-        match result {
-            Ok(Some(mut ent)) => {
-                if !ent.is_connected() {
-                    ent.connect(entity::global::db());
+    /// Returns the ids in the order of the query that are positioned after
+    /// its cursor, up to its offset plus limit, or none if the ids cannot be
+    /// ordered without retrieving their ents
+    ///
+    /// Ids can be ordered by themselves, or by a single field that is
+    /// indexed for every type of ent that has it, which is walked from the
+    /// cursor's position rather than ordering every id.
+    fn seek_ids(&self, query: &Query, ids: &EntIdSet) -> DatabaseResult<Option<Vec<Id>>> {
+        let count = match query.get_limit() {
+            Some(limit) => limit.saturating_add(query.get_offset()),
+            None => return Ok(None),
+        };
+        let after = query.get_after();
+
+        match query.order() {
+            [] => Ok(Some(seek_by_id(
+                ids,
+                OrderDirection::Ascending,
+                after,
+                count,
+            ))),
+            [order] => match order.by() {
+                OrderBy::Id => Ok(Some(seek_by_id(ids, order.direction(), after, count))),
+
+                // Staged ents are not part of the indexes yet
+                OrderBy::Field(name) if self.with_pending(|_| ()).is_none() => {
+                    self.seek_field(name, order.direction(), ids, after, count)
                 }
-                Ok(Some(ent))
-            }
-            x => x,
+                _ => Ok(None),
+            },
+            _ => Ok(None),
         }
     }
 
-    fn remove(&self, id: Id) -> DatabaseResult<bool> {
-        if let Some(ent) = self
-            .0
-            .remove(id_to_ivec(id))
-            .map_err(|e| DatabaseError::Connection {
-                source: Box::from(e),
-            })?
-            .map(|ivec| bincode::deserialize::<Box<dyn Ent>>(ivec.as_ref()))
-            .transpose()
-            .map_err(|e| DatabaseError::CorruptedEnt {
-                id,
+    /// Returns the ids ordered by the named field that are positioned after
+    /// the cursor, up to the given count, or none if the field is not
+    /// indexed for every type of ent that has it
+    ///
+    /// Ids are ordered the same way as [`entity::Order::compare`], walking
+    /// the field's index entries from the cursor's position. Ids without a
+    /// key are ordered after all others when ascending and before all
+    /// others when descending.
+    fn seek_field(
+        &self,
+        name: &str,
+        direction: OrderDirection,
+        ids: &EntIdSet,
+        after: Option<&Cursor>,
+        count: usize,
+    ) -> DatabaseResult<Option<Vec<Id>>> {
+        let after = match after {
+            Some(cursor) => match cursor.keys().first() {
+                Some(key) => Some((key.as_ref(), cursor.id())),
+                None => return Ok(None),
+            },
+            None => None,
+        };
+        let ascending = direction == OrderDirection::Ascending;
+        let field_indexes = self.field_index_tree()?;
+
+        let mut prefixes = Vec::new();
+        for r#type in self.ent_type_tree()?.iter().keys() {
+            let r#type = r#type.map_err(|e| DatabaseError::Connection {
                 source: Box::from(e),
-            })?
-        {
-            for edge in ent.edges() {
-                match edge.deletion_policy() {
-                    // If shallow deletion, we only want to remove the connections
-                    // back to this ent from the corresponding ents
-                    EdgeDeletionPolicy::ShallowDelete => {
-                        for edge_id in edge.to_ids() {
-                            self.0
-                                .transaction(|tx_db| {
-                                    let maybe_ivec = tx_db.get(id_to_ivec(id))?;
-                                    let result = maybe_ivec
-                                        .map(|ivec| {
-                                            bincode::deserialize::<Box<dyn Ent>>(ivec.as_ref())
-                                        })
-                                        .transpose()
-                                        .map_err(|e| DatabaseError::CorruptedEnt {
-                                            id,
-                                            source: Box::from(e),
-                                        });
-                                    match result {
-                                        Ok(Some(mut ent)) => {
-                                            for mut edge in ent.edges() {
-                                                let _ = edge.value_mut().remove_ids(Some(edge_id));
-                                                let name = edge.name().to_string();
-                                                let _ = ent.update_edge(&name, edge.into_value());
-                                            }
-                                            match bincode::serialize(&ent) {
-                                                Ok(bytes) => tx_db.insert(id_to_ivec(id), bytes)?,
-                                                Err(x) => sled::transaction::abort(
-                                                    DatabaseError::CorruptedEnt {
-                                                        id: ent.id(),
-                                                        source: x,
-                                                    },
-                                                )?,
-                                            };
-                                        }
-                                        Ok(None) => {}
-                                        Err(x) => {
-                                            sled::transaction::abort(x)?;
-                                        }
-                                    };
-                                    Ok(())
-                                })
-                                .map_err(|e| DatabaseError::Connection {
-                                    source: Box::from(e),
-                                })?;
-                        }
-                    }
-                    // If deep deletion, we want to remove the ents connected
-                    // by the edge
-                    EdgeDeletionPolicy::DeepDelete => {
-                        for id in edge.to_ids() {
-                            let _ = self.remove(id);
-                        }
-                    }
-                    // If deletion policy is nothing, then do nothing
-                    EdgeDeletionPolicy::Nothing => {}
-                }
-            }
-
-            // Remove the id from our type mapping if it is there
-            self.with_ent_type_set(ent.r#type(), |set| {
-                set.remove(&id);
             })?;
+            let mut prefix = field_index_prefix(&String::from_utf8_lossy(&r#type), name);
 
-            // Add the id to the freed ids available in the allocator
-            self.with_id_allocator(|alloc| {
-                alloc.extend(vec![id]);
-                None
-            })?;
+            // If any ent of the type has the field without indexing it, the
+            // ents of the type cannot be ordered by the index
+            prefix.push(UNINDEXED_FIELD);
+            if field_indexes.scan_prefix(&prefix).next().is_some() {
+                return Ok(None);
+            }
 
-            Ok(true)
-        } else {
-            Ok(false)
+            *prefix.last_mut().unwrap() = INDEXED_VALUE;
+            prefixes.push(prefix);
         }
-    }
 
-    fn insert(&self, mut ent: Box<dyn Ent>) -> DatabaseResult<Id> {
-        // Get the id of the ent, swapping out the ephemeral id
-        let id = ent.id();
-        let id = self
-            .with_id_allocator(move |alloc| {
-                if id == EPHEMERAL_ID {
-                    alloc.next()
-                } else {
-                    alloc.mark_external_id(id);
-                    Some(id)
+        // Ids without a key are those missing from every index of the field
+        let unkeyed = |after_id: Option<Id>| -> DatabaseResult<Vec<Id>> {
+            let mut keyed = EntIdSet::new();
+            for prefix in prefixes.iter() {
+                for entry in field_indexes.scan_prefix(prefix).keys() {
+                    keyed.extend(index_entry_to_id(entry.map_err(|e| {
+                        DatabaseError::Connection {
+                            source: Box::from(e),
+                        }
+                    })?));
                 }
-            })?
+            }
+
+            let mut unkeyed: Vec<Id> = ids
+                .iter()
+                .copied()
+                .filter(|id| after_id.map_or(true, |after_id| *id > after_id))
+                .filter(|id| !keyed.contains(id))
+                .collect();
+            unkeyed.sort_unstable();
+            Ok(unkeyed)
+        };
+
+        let mut seeked = Vec::new();
+        let (bounds, tie) = match after {
+            Some((Some(key), id)) if ascending => {
+                ((Included(key.clone()), Unbounded), Some((key, id)))
+            }
+            Some((Some(key), id)) => ((Unbounded, Included(key.clone())), Some((key, id))),
+            Some((None, id)) => {
+                seeked.extend(unkeyed(Some(id))?);
+                if ascending {
+                    seeked.truncate(count);
+                    return Ok(Some(seeked));
+                }
+                ((Unbounded, Unbounded), None)
+            }
+            None => {
+                if !ascending {
+                    seeked.extend(unkeyed(None)?);
+                }
+                ((Unbounded, Unbounded), None)
+            }
+        };
+
+        let mut ranges = Vec::new();
+        for prefix in prefixes.iter() {
+            let with_key = |key: &IndexKey| [prefix.as_slice(), &key.to_ordered_bytes()].concat();
+            let start = match &bounds.0 {
+                Included(key) => with_key(key),
+                _ => prefix.clone(),
+            };
+            let end = match &bounds.1 {
+                Included(key) => prefix_successor(&with_key(key)),
+                _ => prefix_successor(prefix),
+            };
+
+            let range = field_indexes.range(start..end);
+            let range: Box<dyn Iterator<Item = sled::Result<(sled::IVec, sled::IVec)>>> =
+                if ascending {
+                    Box::new(range)
+                } else {
+                    Box::new(range.rev())
+                };
+
+            let at = prefix.len();
+            let entries = range.filter_map(move |entry| match entry {
+                Ok((entry, _)) => {
+                    let (key, _) = IndexKey::from_ordered_bytes(entry.get(at..)?)?;
+                    index_entry_to_id(entry).map(|id| Ok((key, id)))
+                }
+                Err(e) => Some(Err(DatabaseError::Connection {
+                    source: Box::from(e),
+                })),
+            });
+            ranges.push(entries.peekable());
+        }
+
+        // Walks the entries of all indexes together, visiting each key once
+        while seeked.len() < count {
+            for range in ranges.iter_mut() {
+                if let Some(Err(_)) = range.peek() {
+                    if let Some(Err(x)) = range.next() {
+                        return Err(x);
+                    }
+                }
+            }
+
+            let key = ranges.iter_mut().filter_map(|range| match range.peek() {
+                Some(Ok((key, _))) => Some(key.clone()),
+                _ => None,
+            });
+            let key = match if ascending { key.min() } else { key.max() } {
+                Some(key) => key,
+                None => break,
+            };
+
+            // Ids sharing the cursor's key are ordered by id after it
+            let mut at_key = Vec::new();
+            for range in ranges.iter_mut() {
+                while let Some(Ok((next, _))) = range.peek() {
+                    if *next != key {
+                        break;
+                    }
+                    if let Some(Ok((_, id))) = range.next() {
+                        at_key.push(id);
+                    }
+                }
+            }
+            at_key.retain(|id| {
+                ids.contains(id)
+                    && match tie {
+                        Some((tie_key, after_id)) if *tie_key == key => *id > after_id,
+                        _ => true,
+                    }
+            });
+            at_key.sort_unstable();
+            seeked.extend(at_key);
+        }
+
+        if ascending && seeked.len() < count {
+            seeked.extend(unkeyed(None)?);
+        }
+
+        seeked.truncate(count);
+        Ok(Some(seeked))
+    }
+
+    /// Returns true if database contains the provided id
+    pub fn has_id(&self, id: Id) -> bool {
+        match self.with_pending(|pending| pending.transaction.staged(id).map(|x| x.is_some())) {
+            Some(Some(exists)) => exists,
+            _ => self
+                .db
+                .contains_key(id_to_ivec(id))
+                .ok()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns ids of all ents for the given type
+    pub fn ids_for_type(&self, r#type: &str) -> EntIdSet {
+        fn inner(this: &SledDatabase, r#type: &str) -> DatabaseResult<EntIdSet> {
+            match this
+                .db
+                .open_tree(ENTS_OF_TYPE)
+                .map_err(|e| DatabaseError::Connection {
+                    source: Box::from(e),
+                })?
+                .get(r#type)
+                .map_err(|e| DatabaseError::Connection {
+                    source: Box::from(e),
+                })? {
+                Some(ivec) => match bincode::deserialize::<EntIdSet>(&ivec) {
+                    Ok(x) => Ok(x),
+                    Err(x) => Err(DatabaseError::Connection {
+                        source: Box::from(x),
+                    }),
+                },
+                None => Ok(HashSet::new()),
+            }
+        }
+
+        let mut ids = inner(self, r#type).ok().unwrap_or_default();
+
+        self.with_pending(|pending| {
+            for op in pending.transaction.ops() {
+                match op {
+                    TransactionOp::Insert(ent) if ent.r#type() == r#type => {
+                        ids.insert(ent.id());
+                    }
+                    TransactionOp::Insert(ent) => {
+                        ids.remove(&ent.id());
+                    }
+                    TransactionOp::Remove(id) => {
+                        ids.remove(id);
+                    }
+                    TransactionOp::Expect { .. } => {}
+                }
+            }
+        });
+
+        ids
+    }
+
+    /// Returns ids of all ents that could have a value for the named field
+    /// found by the scan, which includes every ent of a type whose field is
+    /// not indexed
+    pub fn ids_for_field(&self, name: &str, scan: &IndexScan) -> DatabaseResult<EntIdSet> {
+        let field_indexes = self.field_index_tree()?;
+        let mut ids = EntIdSet::new();
+
+        for r#type in self.ent_type_tree()?.iter().keys() {
+            let r#type = r#type.map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })?;
+            let r#type = String::from_utf8_lossy(&r#type);
+            let mut prefix = field_index_prefix(&r#type, name);
+
+            // If any ent of the type has the field without indexing it, we
+            // have to check all ents of the type
+            prefix.push(UNINDEXED_FIELD);
+            if field_indexes.scan_prefix(&prefix).next().is_some() {
+                ids.extend(self.ids_for_type(&r#type));
+                continue;
+            }
+
+            *prefix.last_mut().unwrap() = INDEXED_VALUE;
+            ids.extend(scan_field_index(&field_indexes, &prefix, scan)?);
+        }
+
+        // Staged ents may have a value for the field that is not indexed yet
+        self.with_pending(|pending| {
+            ids.extend(pending.transaction.staged_ids().0);
+        });
+
+        Ok(ids)
+    }
+
+    /// Returns ids of all ents whose edge of the given name contains the id
+    pub fn ids_for_inverse_edge(&self, id: Id, name: &str) -> DatabaseResult<EntIdSet> {
+        Ok(self
+            .incoming(id)?
+            .into_iter()
+            .filter(|(_, edge)| edge == name)
+            .map(|(id, _)| id)
+            .collect())
+    }
+
+    /// Returns ids of all ents alongside the names of their edges that
+    /// contain the id
+    fn incoming(&self, id: Id) -> DatabaseResult<HashSet<(Id, String)>> {
+        let mut incoming = HashSet::new();
+
+        for result in self.edge_index_tree()?.scan_prefix(id.to_be_bytes()) {
+            let (entry, name) = result.map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })?;
+            if let Some(source) = index_entry_to_id(entry) {
+                incoming.insert((source, String::from_utf8_lossy(&name).to_string()));
+            }
+        }
+
+        self.with_pending(|pending| {
+            for op in pending.transaction.ops() {
+                match op {
+                    TransactionOp::Insert(ent) => {
+                        incoming.retain(|(source, _)| *source != ent.id());
+                        for edge in ent.edges() {
+                            if edge.to_ids().contains(&id) {
+                                incoming.insert((ent.id(), edge.name().to_string()));
+                            }
+                        }
+                    }
+                    TransactionOp::Remove(removed) => {
+                        incoming.retain(|(source, _)| source != removed);
+                    }
+                    TransactionOp::Expect { .. } => {}
+                }
+            }
+        });
+
+        Ok(incoming)
+    }
+
+    /// Rebuilds the indexes of all ent fields and edges from the ents stored
+    /// in the database, which is needed for ents written before their fields
+    /// were indexed
+    ///
+    /// The indexes are not rebuilt atomically, so this should not be run
+    /// while other threads are writing to the database.
+    pub fn reindex(&self) -> DatabaseResult<()> {
+        let field_indexes = self.field_index_tree()?;
+        let edge_indexes = self.edge_index_tree()?;
+        for tree in &[&field_indexes, &edge_indexes] {
+            tree.clear().map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })?;
+        }
+
+        let mut batch = sled::Batch::default();
+        let mut edge_batch = sled::Batch::default();
+        for result in self.db.iter() {
+            let (key, ivec) = result.map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })?;
+            let ent = bincode::deserialize::<Box<dyn Ent>>(ivec.as_ref()).map_err(|e| {
+                DatabaseError::CorruptedEnt {
+                    id: ivec_to_id(key).unwrap_or_default(),
+                    source: Box::from(e),
+                }
+            })?;
+
+            for entry in field_index_entries(ent.as_ref())
+                .into_iter()
+                .chain(unindexed_field_entries(ent.as_ref()))
+            {
+                batch.insert(entry, Vec::new());
+            }
+
+            for (entry, _) in unique_index_entries(ent.as_ref()) {
+                batch.insert(entry, id_to_ivec(ent.id()));
+            }
+
+            for (entry, name) in edge_index_entries(ent.as_ref()) {
+                edge_batch.insert(entry, name);
+            }
+        }
+
+        field_indexes
+            .apply_batch(batch)
+            .and_then(|_| edge_indexes.apply_batch(edge_batch))
+            .map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })
+    }
+
+    /// Returns true if the current thread has a transaction in progress
+    pub fn in_transaction(&self) -> bool {
+        self.with_pending(|_| ()).is_some()
+    }
+
+    /// Invokes the function with the current thread's transaction if the
+    /// thread has one in progress
+    fn with_pending<R, F: FnOnce(&mut PendingTransaction) -> R>(&self, f: F) -> Option<R> {
+        self.transactions
+            .lock()
+            .unwrap()
+            .get_mut(&thread::current().id())
+            .map(f)
+    }
+
+    /// Inserts the ent, first checking that any stored ent with a matching
+    /// id was last updated at the expected time if one is provided
+    fn insert_ent(&self, mut ent: Box<dyn Ent>, expected: Option<u64>) -> DatabaseResult<Id> {
+        // Get the id of the ent, swapping out the ephemeral id
+        let id = ent.id();
+        let allocated = id == EPHEMERAL_ID;
+        let id = self
+            .with_id_allocator(move |alloc| {
+                if id == EPHEMERAL_ID {
+                    alloc.next()
+                } else {
+                    alloc.mark_external_id(id);
+                    Some(id)
+                }
+            })?
             .ok_or(DatabaseError::EntCapacityReached)?;
 
         // Update the ent's id to match what is actually to be used
@@ -361,23 +897,1049 @@ impl Database for SledDatabase {
             source: Box::from(e),
         })?;
 
-        // Add our ent's id to the set of ids associated with the ent's type
-        self.with_ent_type_set(ent.r#type(), |set| {
-            set.insert(id);
+        // If the current thread has a transaction in progress, stage the
+        // ent to be written when the transaction is committed
+        let mut transactions = self.transactions.lock().unwrap();
+        if let Some(pending) = transactions.get_mut(&thread::current().id()) {
+            if allocated {
+                pending.allocated.push(id);
+            }
+            if let Some(last_updated) = expected {
+                pending.transaction.expect(id, last_updated);
+            }
+            pending.transaction.insert(ent);
+            return Ok(id);
+        }
+        drop(transactions);
+
+        let mut transaction = Transaction::new();
+        if let Some(last_updated) = expected {
+            transaction.expect(id, last_updated);
+        }
+        transaction.insert(ent);
+
+        // Return the id allocated for the ent if it was not written
+        if let Err(x) = self.apply(&transaction) {
+            if allocated {
+                self.with_id_allocator(|alloc| {
+                    alloc.extend(vec![id]);
+                    None
+                })?;
+            }
+            return Err(x);
+        }
+
+        Ok(id)
+    }
+
+    /// Returns true if the current thread has staged changes to the ent
+    /// with the given id
+    fn is_staged(&self, id: Id) -> bool {
+        self.with_pending(|pending| pending.transaction.staged(id).is_some())
+            .unwrap_or_default()
+    }
+
+    /// Removes and returns the current thread's transaction
+    fn take_pending(&self) -> DatabaseResult<PendingTransaction> {
+        self.transactions
+            .lock()
+            .unwrap()
+            .remove(&thread::current().id())
+            .ok_or(DatabaseError::NoTransaction)
+    }
+
+    /// Returns sled tree for id allocator
+    fn id_allocator_tree(&self) -> DatabaseResult<sled::Tree> {
+        self.db
+            .open_tree(ID_ALLOCATOR)
+            .map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })
+    }
+
+    /// Provides a mutable reference to the id allocator, returning an optional
+    /// id in the case that we want to return the next id from the allocator.
+    ///
+    /// Any changes made to the allocator are persisted back to disk.
+    fn with_id_allocator<F: Fn(&mut IdAllocator) -> Option<Id>>(
+        &self,
+        f: F,
+    ) -> DatabaseResult<Option<Id>> {
+        self.id_allocator_tree()?
+            .transaction(move |tx_db| {
+                let mut id_alloc = match tx_db.get([0])? {
+                    Some(ivec) => match bincode::deserialize::<IdAllocator>(&ivec) {
+                        Ok(x) => x,
+                        Err(x) => {
+                            sled::transaction::abort(x)?;
+                            return Ok(None);
+                        }
+                    },
+                    None => IdAllocator::new(),
+                };
+
+                let maybe_id = f(&mut id_alloc);
+
+                let id_alloc_bytes = match bincode::serialize(&id_alloc) {
+                    Ok(x) => x,
+                    Err(x) => {
+                        sled::transaction::abort(x)?;
+                        return Ok(maybe_id);
+                    }
+                };
+
+                tx_db.insert(&[0], id_alloc_bytes)?;
+                Ok(maybe_id)
+            })
+            .map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })
+    }
+
+    /// Returns sled tree for ent types
+    fn ent_type_tree(&self) -> DatabaseResult<sled::Tree> {
+        self.db
+            .open_tree(ENTS_OF_TYPE)
+            .map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })
+    }
+
+    /// Returns sled tree for field indexes
+    fn field_index_tree(&self) -> DatabaseResult<sled::Tree> {
+        self.db
+            .open_tree(FIELD_INDEXES)
+            .map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })
+    }
+
+    /// Returns sled tree for edge indexes
+    fn edge_index_tree(&self) -> DatabaseResult<sled::Tree> {
+        self.db
+            .open_tree(EDGE_INDEXES)
+            .map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })
+    }
+
+    /// Returns sled tree for associations
+    fn assoc_tree(&self) -> DatabaseResult<sled::Tree> {
+        self.db
+            .open_tree(ASSOCS)
+            .map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })
+    }
+
+    /// Returns sled tree for changes made to ents, keyed by sequence number
+    /// and only held until every live subscription has read them
+    fn change_tree(&self) -> DatabaseResult<sled::Tree> {
+        self.db
+            .open_tree(CHANGES)
+            .map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })
+    }
+
+    /// Returns sled tree for subscriptions, shared by every handle on the
+    /// database, holding the sequence number of the last change each
+    /// subscription read and when it was last marked as live
+    fn subscriber_tree(&self) -> DatabaseResult<sled::Tree> {
+        self.db
+            .open_tree(SUBSCRIBERS)
+            .map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })
+    }
+
+    /// Writes all operations of the transaction within a single sled
+    /// transaction, meaning that either all of the operations are persisted
+    /// or none are. This includes the updates to inverse edges of the ents
+    /// connected by those that are written.
+    ///
+    /// Returns the ids of all ents that were removed, including those
+    /// removed as a result of edge deletion policies.
+    fn apply(&self, transaction: &Transaction) -> DatabaseResult<EntIdSet> {
+        self.transact(|trees| {
+            let mut removed = EntIdSet::new();
+
+            for op in transaction.ops() {
+                match op {
+                    TransactionOp::Insert(ent) => {
+                        tx_write_ent(trees, ent.as_ref())?;
+                        removed.remove(&ent.id());
+                    }
+                    TransactionOp::Remove(id) => {
+                        tx_remove_ent(trees, *id, &mut removed)?;
+                    }
+                    TransactionOp::Expect { id, last_updated } => {
+                        if let Some(ent) = tx_get_ent(trees.ents, *id)? {
+                            if ent.last_updated() != *last_updated {
+                                return sled::transaction::abort(DatabaseError::Conflict {
+                                    id: *id,
+                                    expected: *last_updated,
+                                    actual: ent.last_updated(),
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+
+            Ok(removed)
+        })
+    }
+
+    /// Invokes the function within a single sled transaction spanning the
+    /// ent, ent type, id allocator, field index, edge index, assoc, and
+    /// change trees, meaning that either all of its writes are persisted or
+    /// none are
+    ///
+    /// Changes are only recorded while any handle on the database has a
+    /// live subscription to read them.
+    fn transact<R, F: Fn(&TxTrees) -> TxResult<R>>(&self, f: F) -> DatabaseResult<R> {
+        let ents: &sled::Tree = &self.db;
+        let ent_types = self.ent_type_tree()?;
+        let id_alloc = self.id_allocator_tree()?;
+        let field_indexes = self.field_index_tree()?;
+        let edge_indexes = self.edge_index_tree()?;
+        let assocs = self.assoc_tree()?;
+        let changes = self.change_tree()?;
+        let has_subscribers = live_subscribers(&self.subscriber_tree()?)
+            .map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })?
+            .is_some();
+
+        (
+            ents,
+            &ent_types,
+            &id_alloc,
+            &field_indexes,
+            &edge_indexes,
+            &assocs,
+            &changes,
+        )
+            .transaction(
+                |(ents, ent_types, id_alloc, field_indexes, edge_indexes, assocs, changes)| {
+                    let trees = TxTrees {
+                        ents,
+                        ent_types,
+                        id_alloc,
+                        field_indexes,
+                        edge_indexes,
+                        assocs,
+                        changes: if has_subscribers { Some(changes) } else { None },
+                    };
+                    f(&trees)
+                },
+            )
+            .map_err(|e| match e {
+                TransactionError::Abort(x) => x,
+                TransactionError::Storage(x) => DatabaseError::Connection {
+                    source: Box::from(x),
+                },
+            })
+    }
+}
+
+impl Database for SledDatabase {
+    fn get_all(&self, ids: Vec<Id>) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+        ids.into_iter()
+            .filter_map(|id| self.get(id).transpose())
+            .collect()
+    }
+
+    fn find_all(&self, query: Query) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+        let ids = self.find_ids(&query)?;
+        query.order_and_page(ids.into_iter().filter_map(|id| self.get(id).transpose()))
+    }
+
+    fn find_page(&self, query: Query, page_size: usize) -> DatabaseResult<Page> {
+        let order = query.order().to_vec();
+        let query = query.limit(page_size.saturating_add(1));
+        let ids = self.find_ids(&query)?;
+
+        // If the ids can be ordered without the ents, only the ents of the
+        // page itself are retrieved
+        let ents = match self.seek_ids(&query, &ids)? {
+            Some(ids) => ids
+                .into_iter()
+                .filter_map(|id| self.get(id).transpose())
+                .skip(query.get_offset())
+                .collect::<DatabaseResult<Vec<Box<dyn Ent>>>>()?,
+            None => {
+                query.order_and_page(ids.into_iter().filter_map(|id| self.get(id).transpose()))?
+            }
+        };
+
+        Ok(Page::from_results(&order, ents, page_size))
+    }
+
+    fn count(&self, query: Query) -> DatabaseResult<usize> {
+        let ids = self.find_ids(&query)?;
+        match query.count_ids(ids.iter().copied().filter(|id| self.has_id(*id))) {
+            Some(count) => Ok(count),
+
+            // Positions relative to the query's cursor can only be determined
+            // using the ents themselves
+            None => query
+                .order_and_page(ids.into_iter().filter_map(|id| self.get(id).transpose()))
+                .map(|ents| ents.len()),
+        }
+    }
+
+    fn exists(&self, query: Query) -> DatabaseResult<bool> {
+        let limit = query.get_limit().unwrap_or(1).min(1);
+        self.count(query.limit(limit)).map(|count| count > 0)
+    }
+
+    fn aggregate(&self, aggregation: Aggregation) -> DatabaseResult<Vec<AggregateGroup>> {
+        let query = aggregation.query();
+        let ents = self
+            .find_ids(query)?
+            .into_iter()
+            .filter_map(|id| self.get(id).transpose());
+
+        // Unless the query is paged, every ent passing its filters is
+        // aggregated, so they can be aggregated one at a time
+        if query.is_paged() {
+            aggregation.apply(query.order_and_page(ents)?.into_iter().map(Ok))
+        } else {
+            aggregation.apply(ents)
+        }
+    }
+
+    fn incoming_edges(&self, id: Id) -> DatabaseResult<Vec<(Id, String)>> {
+        let mut incoming: Vec<(Id, String)> = self.incoming(id)?.into_iter().collect();
+        incoming.sort();
+        Ok(incoming)
+    }
+
+    fn assocs(&self, id: Id, edge: &str) -> DatabaseResult<Vec<Assoc>> {
+        // Staged ents may have ids on their edges that are not recorded yet
+        let ent = match self.get(id)? {
+            Some(ent) => ent,
+            None => return Ok(Vec::new()),
+        };
+        let ids = ent
+            .edge(edge)
+            .map(|value| value.to_ids())
+            .unwrap_or_default();
+
+        let mut recorded = Vec::new();
+        for result in self
+            .assoc_tree()?
+            .scan_prefix(assoc_prefix(id, edge, ASSOC_BY_ID))
+        {
+            let (_, ivec) = result.map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })?;
+            recorded.push(ivec_to_assoc(id, ivec)?);
+        }
+        Ok(Assoc::for_ids(ids, &recorded, ent.last_updated()))
+    }
+
+    fn assoc_range(&self, id: Id, edge: &str, range: AssocRange) -> DatabaseResult<Vec<Assoc>> {
+        // Staged ents may have ids on their edges that are not recorded yet
+        if self.is_staged(id) {
+            return self.assocs(id, edge).map(|assocs| range.apply(assocs));
+        }
+
+        // Each bound is converted to include the first entry keyed by time
+        // in the range and exclude the first entry after the range
+        let prefix = assoc_prefix(id, edge, ASSOC_BY_TIME);
+        let with_time = |time: &u64| [prefix.as_slice(), &time.to_be_bytes()].concat();
+        let (start, end) = range.created();
+        let start = match start {
+            Included(time) => with_time(&time),
+            Excluded(time) => prefix_successor(&with_time(&time)),
+            Unbounded => prefix.clone(),
+        };
+        let end = match end {
+            Included(time) => prefix_successor(&with_time(&time)),
+            Excluded(time) => with_time(&time),
+            Unbounded => prefix_successor(&prefix),
+        };
+        if start >= end {
+            return Ok(Vec::new());
+        }
+
+        let tree = self.assoc_tree()?;
+        let entries = tree.range(start..end).keys();
+        let entries: Box<dyn Iterator<Item = sled::Result<sled::IVec>>> = match range.direction() {
+            OrderDirection::Ascending => Box::new(entries),
+            OrderDirection::Descending => Box::new(entries.rev()),
+        };
+
+        // Only the associations within the range are loaded, using the
+        // entries keyed by id that hold them
+        let mut assocs = Vec::new();
+        for entry in entries
+            .skip(range.offset())
+            .take(range.limit().unwrap_or(usize::MAX))
+        {
+            let entry = entry.map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })?;
+            if let Some(target) = index_entry_to_id(entry) {
+                let ivec = tree.get(assoc_by_id_entry(id, edge, target)).map_err(|e| {
+                    DatabaseError::Connection {
+                        source: Box::from(e),
+                    }
+                })?;
+                if let Some(ivec) = ivec {
+                    assocs.push(ivec_to_assoc(id, ivec)?);
+                }
+            }
+        }
+        Ok(assocs)
+    }
+
+    fn assoc_count(&self, id: Id, edge: &str) -> DatabaseResult<usize> {
+        // Staged ents may have ids on their edges that are not recorded yet
+        if self.is_staged(id) {
+            return self.assocs(id, edge).map(|assocs| assocs.len());
+        }
+
+        let mut count = 0;
+        for entry in self
+            .assoc_tree()?
+            .scan_prefix(assoc_prefix(id, edge, ASSOC_BY_TIME))
+            .keys()
+        {
+            entry.map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn set_assoc_data(&self, id: Id, edge: &str, target: Id, data: Value) -> DatabaseResult<bool> {
+        let ents: &sled::Tree = &self.db;
+        let assocs = self.assoc_tree()?;
+
+        (ents, &assocs)
+            .transaction(|(ents, assocs)| {
+                let contains = tx_get_ent(ents, id)?.and_then(|ent| {
+                    ent.edge(edge)
+                        .filter(|value| value.to_ids().contains(&target))
+                        .map(|_| ent.last_updated())
+                });
+                let created = match contains {
+                    Some(created) => created,
+                    None => return Ok(false),
+                };
+
+                // Ents stored before their associations were recorded have
+                // the association recorded now
+                let entry = assoc_by_id_entry(id, edge, target);
+                let mut assoc = match tx_remove_assoc(assocs, id, entry.clone())? {
+                    Some(assoc) => assoc,
+                    None => {
+                        let assoc = Assoc::new(target, created);
+                        assocs.insert(assoc_by_time_entry(id, edge, &assoc), Vec::new())?;
+                        assoc
+                    }
+                };
+                *assoc.data_mut() = Some(data.clone());
+                tx_insert_assoc(assocs, id, entry, &assoc)?;
+
+                Ok(true)
+            })
+            .map_err(|e| match e {
+                TransactionError::Abort(x) => x,
+                TransactionError::Storage(x) => DatabaseError::Connection {
+                    source: Box::from(x),
+                },
+            })
+    }
+
+    fn traverse(&self, traversal: Traversal) -> DatabaseResult<Vec<(Path, Box<dyn Ent>)>> {
+        // Ents reached through an edge with a filter are loaded once and
+        // checked against the filter directly rather than finding them with
+        // a query
+        traversal.apply(|id, filter| {
+            self.get(id).map(|maybe_ent| {
+                maybe_ent.filter(|ent| {
+                    filter.map_or(true, |filter| filter_ent(self, ent.as_ref(), filter, None))
+                })
+            })
+        })
+    }
+
+    fn get(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>> {
+        // If the current thread has staged changes to the ent, those take
+        // precedence over what is actually stored
+        let result = match self.with_pending(|pending| {
+            pending
+                .transaction
+                .staged(id)
+                .map(|maybe_ent| maybe_ent.map(dyn_clone::clone_box))
+        }) {
+            Some(Some(staged)) => Ok(staged),
+            _ => {
+                let maybe_ivec =
+                    self.db
+                        .get(id_to_ivec(id))
+                        .map_err(|e| DatabaseError::Connection {
+                            source: Box::from(e),
+                        })?;
+
+                maybe_ivec
+                    .map(|ivec| bincode::deserialize(ivec.as_ref()))
+                    .transpose()
+                    .map_err(|e| DatabaseError::CorruptedEnt {
+                        id,
+                        source: Box::from(e),
+                    })
+            }
+        };
+
+        // If we found an ent without a database connection, attempt to fill
+        // it in with the global database if it exists
+        match result {
+            Ok(Some(mut ent)) => {
+                if !ent.is_connected() {
+                    ent.connect(entity::global::db());
+                }
+                Ok(Some(ent))
+            }
+            x => x,
+        }
+    }
+
+    fn remove(&self, id: Id) -> DatabaseResult<bool> {
+        // If the current thread has a transaction in progress, stage the
+        // removal to be processed when the transaction is committed
+        if self.in_transaction() {
+            let exists = self.has_id(id);
+            self.with_pending(|pending| pending.transaction.remove(id));
+            return Ok(exists);
+        }
+
+        let removed = self.apply(&Transaction::from(vec![TransactionOp::Remove(id)]))?;
+        Ok(removed.contains(&id))
+    }
+
+    fn insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id> {
+        self.insert_ent(ent, None)
+    }
+
+    fn compare_and_insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id> {
+        let expected = ent.last_updated();
+        self.insert_ent(ent, Some(expected))
+    }
+
+    fn compare_and_insert_at(&self, ent: Box<dyn Ent>, expected: u64) -> DatabaseResult<Id> {
+        self.insert_ent(ent, Some(expected))
+    }
+
+    fn update_fields(&self, id: Id, patch: Patch) -> DatabaseResult<Box<dyn Ent>> {
+        // If the current thread has a transaction in progress, stage the
+        // patched ent to be written as long as the ent is unchanged when the
+        // transaction is committed
+        if self.with_pending(|_| ()).is_some() {
+            let mut ent = self.get(id)?.ok_or(DatabaseError::MissingEnt { id })?;
+            let last_updated = ent.last_updated();
+            patch_ent(ent.as_mut(), &patch)?;
+            self.with_pending(|pending| {
+                pending.transaction.expect(id, last_updated);
+                pending.transaction.insert(ent.clone());
+            });
+            return Ok(ent);
+        }
+
+        let mut ent = self.transact(|trees| {
+            let mut ent = match tx_get_ent(trees.ents, id)? {
+                Some(ent) => ent,
+                None => return sled::transaction::abort(DatabaseError::MissingEnt { id }),
+            };
+            if let Err(x) = patch_ent(ent.as_mut(), &patch) {
+                return sled::transaction::abort(x);
+            }
+            tx_write_ent(trees, ent.as_ref())?;
+            Ok(ent)
         })?;
 
-        // Add our ent to the primary database
-        let ent_bytes = bincode::serialize(&ent).map_err(|e| DatabaseError::CorruptedEnt {
-            id,
-            source: Box::from(e),
-        })?;
-        self.0
-            .insert(id_to_ivec(id), ent_bytes)
-            .map_err(|e| DatabaseError::Connection {
-                source: Box::from(e),
-            })?;
+        // Like retrieved ents, fill in the global database if it exists
+        if !ent.is_connected() {
+            ent.connect(entity::global::db());
+        }
+        Ok(ent)
+    }
+
+    fn begin_transaction(&self) -> DatabaseResult<()> {
+        match self
+            .transactions
+            .lock()
+            .unwrap()
+            .entry(thread::current().id())
+        {
+            Entry::Occupied(_) => Err(DatabaseError::TransactionInProgress),
+            Entry::Vacant(entry) => {
+                entry.insert(PendingTransaction::default());
+                Ok(())
+            }
+        }
+    }
+
+    fn commit_transaction(&self) -> DatabaseResult<()> {
+        let pending = self.take_pending()?;
+
+        // Return any ids allocated for the ents if none were written
+        if let Err(x) = self.apply(&pending.transaction) {
+            if !pending.allocated.is_empty() {
+                self.with_id_allocator(|alloc| {
+                    alloc.extend(pending.allocated.iter().copied());
+                    None
+                })?;
+            }
+            return Err(x);
+        }
+
+        Ok(())
+    }
+
+    fn rollback_transaction(&self) -> DatabaseResult<()> {
+        let pending = self.take_pending()?;
+
+        // Return any ids allocated for the discarded ents
+        if !pending.allocated.is_empty() {
+            self.with_id_allocator(|alloc| {
+                alloc.extend(pending.allocated.iter().copied());
+                None
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn subscribe(&self, query: Query) -> DatabaseResult<Subscription> {
+        let changes = self.change_tree()?;
+        let subscribers = self.subscriber_tree()?;
+        let mut events = changes.watch_prefix(vec![]);
+        let (sender, subscription) = Subscription::channel();
+
+        // Changes are recorded by every handle on the database as soon as
+        // the subscription is marked as live, so reading from the latest
+        // change afterwards means it receives every change written from now on
+        let (key, mut cursor) = self
+            .db
+            .generate_id()
+            .and_then(|id| {
+                let key = id.to_be_bytes();
+                mark_subscriber(&subscribers, &key, 0)?;
+                let cursor = latest_change(&changes)?;
+                mark_subscriber(&subscribers, &key, cursor)?;
+                Ok((key, cursor))
+            })
+            .map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })?;
+
+        // Changes are sent from their own thread until the subscription is
+        // dropped, waiting for each change a little at a time so that the
+        // thread stops soon after even if no more changes are written
+        thread::spawn(move || {
+            let mut marked = Instant::now();
+            while !sender.is_closed() {
+                // Events only wake the thread, as changes are read from the
+                // tree in order so that none are missed between events
+                if let Err(RecvTimeoutError::Disconnected) =
+                    events.next_timeout(SUBSCRIPTION_POLL_INTERVAL)
+                {
+                    break;
+                }
+
+                let last_read = cursor;
+                for entry in changes.range((cursor + 1).to_be_bytes()..) {
+                    let (seq, value) = match entry {
+                        Ok(entry) => entry,
+                        Err(_) => break,
+                    };
+                    cursor = ivec_to_u64(&seq);
+
+                    let change = bincode::deserialize::<ChangeRecord>(&value)
+                        .ok()
+                        .and_then(|(before, after)| Change::between(before, after))
+                        .filter(|change| change.matches(&query) != Some(false));
+                    if let Some(change) = change {
+                        if sender.send(change).is_err() {
+                            break;
+                        }
+                    }
+                }
+
+                if cursor != last_read || marked.elapsed() >= SUBSCRIPTION_HEARTBEAT_INTERVAL {
+                    let _ = mark_subscriber(&subscribers, &key, cursor)
+                        .and_then(|_| trim_changes(&changes, &subscribers));
+                    marked = Instant::now();
+                }
+            }
+
+            let _ = subscribers
+                .remove(key)
+                .and_then(|_| trim_changes(&changes, &subscribers));
+        });
+
+        Ok(subscription)
+    }
+}
+
+/// Loads the ent with the given id as part of a sled transaction
+fn tx_get_ent(ents: &TransactionalTree, id: Id) -> TxResult<Option<Box<dyn Ent>>> {
+    match ents.get(id_to_ivec(id))? {
+        Some(ivec) => match bincode::deserialize::<Box<dyn Ent>>(ivec.as_ref()) {
+            Ok(ent) => Ok(Some(ent)),
+            Err(x) => sled::transaction::abort(DatabaseError::CorruptedEnt {
+                id,
+                source: Box::from(x),
+            }),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Writes the ent using its id as part of a sled transaction
+fn tx_insert_ent(ents: &TransactionalTree, ent: &dyn Ent) -> TxResult<()> {
+    match bincode::serialize(ent) {
+        Ok(bytes) => {
+            ents.insert(id_to_ivec(ent.id()), bytes)?;
+            Ok(())
+        }
+        Err(x) => sled::transaction::abort(DatabaseError::CorruptedEnt {
+            id: ent.id(),
+            source: Box::from(x),
+        }),
+    }
+}
+
+/// Represents the trees written as part of a sled transaction
+struct TxTrees<'a> {
+    ents: &'a TransactionalTree,
+    ent_types: &'a TransactionalTree,
+    id_alloc: &'a TransactionalTree,
+    field_indexes: &'a TransactionalTree,
+    edge_indexes: &'a TransactionalTree,
+    assocs: &'a TransactionalTree,
+
+    /// Change tree, only available when there are subscribers to record
+    /// changes for
+    changes: Option<&'a TransactionalTree>,
+}
+
+/// Writes the ent using its id as part of a sled transaction, replacing the
+/// index entries and associations of any ent it overwrites and keeping the
+/// inverse edges of the ents it connects in sync
+fn tx_write_ent(trees: &TxTrees, ent: &dyn Ent) -> TxResult<()> {
+    let existing = tx_get_ent(trees.ents, ent.id())?;
+    tx_reindex_ent(trees, existing.as_deref(), Some(ent))?;
+    tx_insert_ent(trees.ents, ent)?;
+    tx_record_change(trees, existing.as_deref(), Some(ent))?;
+    tx_sync_inverse_edges(
+        trees,
+        InverseEdgeChange::between(existing.as_deref(), Some(ent)),
+    )?;
+
+    // Add our ent's id to the set of ids associated with the ent's type
+    let id = ent.id();
+    tx_with_ent_type_set(trees.ent_types, ent.r#type(), |set| {
+        set.insert(id);
+    })
+}
+
+/// Writes the change between the snapshots of an ent to the change tree as
+/// part of a sled transaction if there are any subscribers to send it to,
+/// keyed by the next sequence number
+///
+/// Every recording transaction updates the latest sequence number, so
+/// concurrent transactions conflict and changes are persisted in the order
+/// of their sequence numbers.
+fn tx_record_change(
+    trees: &TxTrees,
+    before: Option<&dyn Ent>,
+    after: Option<&dyn Ent>,
+) -> TxResult<()> {
+    let changes = match trees.changes {
+        Some(changes) => changes,
+        None => return Ok(()),
+    };
+
+    let seq = match changes.get(LATEST_CHANGE)? {
+        Some(ivec) => ivec_to_u64(&ivec) + 1,
+        None => 1,
+    };
+    match bincode::serialize(&(before, after)) {
+        Ok(bytes) => {
+            changes.insert(LATEST_CHANGE, &seq.to_be_bytes())?;
+            changes.insert(&seq.to_be_bytes(), bytes)?;
+            Ok(())
+        }
+        Err(x) => sled::transaction::abort(DatabaseError::CorruptedEnt {
+            id: before.or(after).map(|ent| ent.id()).unwrap_or_default(),
+            source: Box::from(x),
+        }),
+    }
+}
+
+/// Replaces the field and edge index entries of an old ent with those of a
+/// new ent as part of a sled transaction, also updating the associations of
+/// the ent to match the ents on its edges
+///
+/// Aborts if the new ent has a value for a unique field that is claimed by
+/// another ent.
+fn tx_reindex_ent(trees: &TxTrees, old: Option<&dyn Ent>, new: Option<&dyn Ent>) -> TxResult<()> {
+    for entry in old.map(field_index_entries).unwrap_or_default() {
+        trees.field_indexes.remove(entry)?;
+    }
+
+    for (entry, _) in old.map(unique_index_entries).unwrap_or_default() {
+        trees.field_indexes.remove(entry)?;
+    }
+
+    if let Some(new) = new {
+        for (entry, name) in unique_index_entries(new) {
+            if let Some(id) = trees.field_indexes.get(&entry)?.and_then(ivec_to_id) {
+                if id != new.id() {
+                    return sled::transaction::abort(DatabaseError::NotUnique { name, id });
+                }
+            }
+            trees.field_indexes.insert(entry, id_to_ivec(new.id()))?;
+        }
+    }
+
+    for (entry, _) in old.map(edge_index_entries).unwrap_or_default() {
+        trees.edge_indexes.remove(entry)?;
+    }
+
+    for entry in new.map(field_index_entries).unwrap_or_default() {
+        trees.field_indexes.insert(entry, Vec::new())?;
+    }
+
+    // Unindexed fields are recorded once for the type, only written by the
+    // first ent of the type to have the field
+    for entry in new.map(unindexed_field_entries).unwrap_or_default() {
+        if trees.field_indexes.get(&entry)?.is_none() {
+            trees.field_indexes.insert(entry, Vec::new())?;
+        }
+    }
+
+    for (entry, name) in new.map(edge_index_entries).unwrap_or_default() {
+        trees.edge_indexes.insert(entry, name)?;
+    }
+
+    tx_reassoc_ent(trees.assocs, old, new)
+}
+
+/// Removes the associations of ents no longer on the edges of an old ent and
+/// adds associations for ents newly on the edges of a new ent, created at
+/// the time that the new ent was last updated, as part of a sled transaction
+fn tx_reassoc_ent(
+    assocs: &TransactionalTree,
+    old: Option<&dyn Ent>,
+    new: Option<&dyn Ent>,
+) -> TxResult<()> {
+    if let Some(old) = old {
+        for edge in old.edges() {
+            let kept = new
+                .and_then(|ent| ent.edge(edge.name()))
+                .map(|value| value.to_ids())
+                .unwrap_or_default();
+
+            for target in edge.to_ids() {
+                if kept.contains(&target) {
+                    continue;
+                }
+
+                let entry = assoc_by_id_entry(old.id(), edge.name(), target);
+                if let Some(assoc) = tx_remove_assoc(assocs, old.id(), entry)? {
+                    assocs.remove(assoc_by_time_entry(old.id(), edge.name(), &assoc))?;
+                }
+            }
+        }
+    }
+
+    if let Some(new) = new {
+        for edge in new.edges() {
+            for target in edge.to_ids() {
+                let entry = assoc_by_id_entry(new.id(), edge.name(), target);
+                if assocs.get(&entry)?.is_none() {
+                    let assoc = Assoc::new(target, new.last_updated());
+                    assocs.insert(
+                        assoc_by_time_entry(new.id(), edge.name(), &assoc),
+                        Vec::new(),
+                    )?;
+                    tx_insert_assoc(assocs, new.id(), entry, &assoc)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes the association at the given entry as part of a sled transaction,
+/// returning it if it existed
+fn tx_remove_assoc(assocs: &TransactionalTree, id: Id, entry: Vec<u8>) -> TxResult<Option<Assoc>> {
+    match assocs.remove(entry)? {
+        Some(ivec) => match bincode::deserialize::<Assoc>(&ivec) {
+            Ok(assoc) => Ok(Some(assoc)),
+            Err(x) => sled::transaction::abort(DatabaseError::CorruptedEnt {
+                id,
+                source: Box::from(x),
+            }),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Writes the association at the given entry as part of a sled transaction
+fn tx_insert_assoc(
+    assocs: &TransactionalTree,
+    id: Id,
+    entry: Vec<u8>,
+    assoc: &Assoc,
+) -> TxResult<()> {
+    match bincode::serialize(assoc) {
+        Ok(bytes) => {
+            assocs.insert(entry, bytes)?;
+            Ok(())
+        }
+        Err(x) => sled::transaction::abort(DatabaseError::CorruptedEnt {
+            id,
+            source: Box::from(x),
+        }),
+    }
+}
+
+/// Applies the changes to the inverse edges of stored ents, along with any
+/// further changes caused by updating those edges, as part of a sled
+/// transaction
+fn tx_sync_inverse_edges(trees: &TxTrees, mut changes: Vec<InverseEdgeChange>) -> TxResult<()> {
+    while let Some(change) = changes.pop() {
+        if let Some(mut ent) = tx_get_ent(trees.ents, change.id())? {
+            let existing = dyn_clone::clone_box(ent.as_ref());
+            if change.apply(ent.as_mut()) {
+                let _ = ent.mark_updated();
+                tx_reindex_ent(trees, Some(existing.as_ref()), Some(ent.as_ref()))?;
+                tx_insert_ent(trees.ents, ent.as_ref())?;
+                tx_record_change(trees, Some(existing.as_ref()), Some(ent.as_ref()))?;
+                changes.extend(InverseEdgeChange::between(
+                    Some(existing.as_ref()),
+                    Some(ent.as_ref()),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Provides a mutable reference to the id set associated with an ent type
+/// as part of a sled transaction
+fn tx_with_ent_type_set<F: FnOnce(&mut EntIdSet)>(
+    ent_types: &TransactionalTree,
+    r#type: &str,
+    f: F,
+) -> TxResult<()> {
+    let mut set = match ent_types.get(r#type)? {
+        Some(ivec) => match bincode::deserialize::<EntIdSet>(&ivec) {
+            Ok(x) => x,
+            Err(x) => {
+                return sled::transaction::abort(DatabaseError::Connection {
+                    source: Box::from(x),
+                })
+            }
+        },
+        None => HashSet::new(),
+    };
+
+    f(&mut set);
+
+    match bincode::serialize(&set) {
+        Ok(bytes) => {
+            ent_types.insert(r#type, bytes)?;
+            Ok(())
+        }
+        Err(x) => sled::transaction::abort(DatabaseError::Connection {
+            source: Box::from(x),
+        }),
+    }
+}
+
+/// Removes the ent with the given id as part of a sled transaction,
+/// processing each of its edges based on their deletion policy
+fn tx_remove_ent(trees: &TxTrees, id: Id, removed: &mut EntIdSet) -> TxResult<()> {
+    let ent = match tx_get_ent(trees.ents, id)? {
+        Some(ent) => ent,
+        None => return Ok(()),
+    };
+    trees.ents.remove(id_to_ivec(id))?;
+    tx_reindex_ent(trees, Some(ent.as_ref()), None)?;
+    tx_record_change(trees, Some(ent.as_ref()), None)?;
+    tx_sync_inverse_edges(trees, InverseEdgeChange::between(Some(ent.as_ref()), None))?;
+    removed.insert(id);
+
+    for edge in ent.edges() {
+        match edge.deletion_policy() {
+            // If shallow deletion, we only want to remove the connections
+            // back to this ent from the corresponding ents
+            EdgeDeletionPolicy::ShallowDelete => {
+                for edge_id in edge.to_ids() {
+                    if let Some(mut ent) = tx_get_ent(trees.ents, edge_id)? {
+                        let existing = dyn_clone::clone_box(ent.as_ref());
+                        for mut edge in ent.edges() {
+                            let _ = edge.value_mut().remove_ids(Some(id));
+                            let name = edge.name().to_string();
+                            let _ = ent.update_edge(&name, edge.into_value());
+                        }
+                        tx_reindex_ent(trees, Some(existing.as_ref()), Some(ent.as_ref()))?;
+                        tx_insert_ent(trees.ents, ent.as_ref())?;
+                        tx_record_change(trees, Some(existing.as_ref()), Some(ent.as_ref()))?;
+                    }
+                }
+            }
+            // If deep deletion, we want to remove the ents connected
+            // by the edge
+            EdgeDeletionPolicy::DeepDelete => {
+                for id in edge.to_ids() {
+                    tx_remove_ent(trees, id, removed)?;
+                }
+            }
+            // If deletion policy is nothing, then do nothing
+            EdgeDeletionPolicy::Nothing => {}
+        }
+    }
 
-        Ok(id)
+    // Remove the id from our type mapping if it is there
+    tx_with_ent_type_set(trees.ent_types, ent.r#type(), |set| {
+        set.remove(&id);
+    })?;
+
+    // Add the id to the freed ids available in the allocator
+    let mut alloc = match trees.id_alloc.get([0])? {
+        Some(ivec) => match bincode::deserialize::<IdAllocator>(&ivec) {
+            Ok(x) => x,
+            Err(x) => {
+                return sled::transaction::abort(DatabaseError::Connection {
+                    source: Box::from(x),
+                })
+            }
+        },
+        None => IdAllocator::new(),
+    };
+    alloc.extend(vec![id]);
+    match bincode::serialize(&alloc) {
+        Ok(bytes) => {
+            trees.id_alloc.insert(&[0], bytes)?;
+            Ok(())
+        }
+        Err(x) => sled::transaction::abort(DatabaseError::Connection {
+            source: Box::from(x),
+        }),
     }
 }
 
@@ -388,7 +1950,10 @@ impl Database for SledDatabase {
 ///    the predicate
 /// 2. If lead filter by type equality, will only include those ids that equal
 ///    the type (or many types if wrapped in Or)
-/// 3. Any other variation of id/type filter or other kind of filter will
+/// 3. If lead filter is a combination of filters, will only include those
+///    ids included by every filter (when all must pass) or by any filter
+///    (when any must pass)
+/// 4. Any other variation of id/type filter or other kind of filter will
 ///    result in the more expensive pulling of all ids
 fn prefill_ids(db: &SledDatabase, filter: &Filter) -> EntIdSet {
     fn from_id_predicate(db: &SledDatabase, p: &Predicate, mut ids: EntIdSet) -> Option<EntIdSet> {
@@ -436,31 +2001,251 @@ fn prefill_ids(db: &SledDatabase, filter: &Filter) -> EntIdSet {
             from_type_predicate(db, p.as_untyped(), EntIdSet::new()).unwrap_or_else(|| db.ids())
         }
 
+        Filter::And(filters) => {
+            let mut filters = filters.iter();
+            let mut ids = match filters.next() {
+                Some(filter) => prefill_ids(db, filter),
+                None => db.ids(),
+            };
+            for filter in filters {
+                let other = prefill_ids(db, filter);
+                ids.retain(|id| other.contains(id));
+            }
+            ids
+        }
+
+        Filter::Or(filters) => filters
+            .iter()
+            .flat_map(|filter| prefill_ids(db, filter))
+            .collect(),
+
         // Otherwise, currently no cached/indexed way to look up (yet)
-        // TODO: Support database field indexing so equality of a field can
-        //       be used for faster id lookup; do the same for timestamp fields
+        // TODO: Support indexing timestamp fields for faster id lookup
         _ => db.ids(),
     }
 }
 
-fn filter_id(db: &SledDatabase, id: &Id, filter: &Filter) -> bool {
+/// Looks up the ids of ents that could pass the filter using the field
+/// index tree, returning none if the filter cannot be served by an index
+fn indexed_ids(db: &SledDatabase, filter: &Filter) -> DatabaseResult<Option<EntIdSet>> {
+    match filter {
+        Filter::Field(name, p) => IndexScan::from_predicate(p)
+            .map(|scan| db.ids_for_field(name, &scan))
+            .transpose(),
+
+        // Ents passing all filters must be found by every index that can
+        // serve one of the filters
+        Filter::And(filters) => {
+            let mut found: Option<EntIdSet> = None;
+            for filter in filters {
+                if let Some(other) = indexed_ids(db, filter)? {
+                    match found.as_mut() {
+                        Some(ids) => ids.retain(|id| other.contains(id)),
+                        None => found = Some(other),
+                    }
+                }
+            }
+            Ok(found)
+        }
+
+        // Ents passing any filter can only be found by indexes if every
+        // filter can be served by one
+        Filter::Or(filters) => {
+            let mut found = EntIdSet::new();
+            for filter in filters {
+                match indexed_ids(db, filter)? {
+                    Some(ids) => found.extend(ids),
+                    None => return Ok(None),
+                }
+            }
+            Ok(Some(found))
+        }
+
+        _ => Ok(None),
+    }
+}
+
+/// Returns ids of all ents whose indexed value is found by the scan, where
+/// the prefix is shared by all indexed values of the field
+fn scan_field_index(
+    field_indexes: &sled::Tree,
+    prefix: &[u8],
+    scan: &IndexScan,
+) -> DatabaseResult<EntIdSet> {
+    fn collect_ids(iter: sled::Iter) -> DatabaseResult<EntIdSet> {
+        iter.keys()
+            .filter_map(|entry| match entry {
+                Ok(entry) => index_entry_to_id(entry).map(Ok),
+                Err(e) => Some(Err(DatabaseError::Connection {
+                    source: Box::from(e),
+                })),
+            })
+            .collect()
+    }
+
+    let with_key = |key: &IndexKey| [prefix, &key.to_ordered_bytes()].concat();
+
+    match scan {
+        IndexScan::Keys(keys) => {
+            let mut ids = EntIdSet::new();
+            for key in keys {
+                ids.extend(collect_ids(field_indexes.scan_prefix(with_key(key)))?);
+            }
+            Ok(ids)
+        }
+
+        // Each bound is converted to include the first entry in the range
+        // and exclude the first entry after the range
+        IndexScan::Range(start, end) => {
+            let start = match start {
+                Included(key) => with_key(key),
+                Excluded(key) => prefix_successor(&with_key(key)),
+                Unbounded => prefix.to_vec(),
+            };
+            let end = match end {
+                Included(key) => prefix_successor(&with_key(key)),
+                Excluded(key) => with_key(key),
+                Unbounded => prefix_successor(prefix),
+            };
+
+            if start < end {
+                collect_ids(field_indexes.range(start..end))
+            } else {
+                Ok(EntIdSet::new())
+            }
+        }
+
+        IndexScan::Union(scans) => {
+            let mut ids = EntIdSet::new();
+            for scan in scans {
+                ids.extend(scan_field_index(field_indexes, prefix, scan)?);
+            }
+            Ok(ids)
+        }
+
+        IndexScan::Intersection(scans) => {
+            let mut scans = scans.iter();
+            let mut ids = match scans.next() {
+                Some(scan) => scan_field_index(field_indexes, prefix, scan)?,
+                None => EntIdSet::new(),
+            };
+            for scan in scans {
+                let other = scan_field_index(field_indexes, prefix, scan)?;
+                ids.retain(|id| other.contains(id));
+            }
+            Ok(ids)
+        }
+    }
+}
+
+/// Returns the ids in order of themselves that are positioned after the
+/// cursor, up to the given count
+fn seek_by_id(
+    ids: &EntIdSet,
+    direction: OrderDirection,
+    after: Option<&Cursor>,
+    count: usize,
+) -> Vec<Id> {
+    let mut ids: Vec<Id> = ids
+        .iter()
+        .copied()
+        .filter(|id| match (after, direction) {
+            (Some(cursor), OrderDirection::Ascending) => *id > cursor.id(),
+            (Some(cursor), OrderDirection::Descending) => *id < cursor.id(),
+            (None, _) => true,
+        })
+        .collect();
+
+    ids.sort_unstable();
+    if direction == OrderDirection::Descending {
+        ids.reverse();
+    }
+    ids.truncate(count);
+    ids
+}
+
+/// Applies the patch to the ent, marking the ent as updated once every
+/// change succeeds
+fn patch_ent(ent: &mut dyn Ent, patch: &Patch) -> DatabaseResult<()> {
+    let id = ent.id();
+    patch
+        .apply(ent)
+        .map_err(|source| DatabaseError::EntMutationFailed { id, source })?;
+    ent.clear_cache();
+    ent.mark_updated()
+        .map_err(|source| DatabaseError::EntMutationFailed { id, source })
+}
+
+/// Returns true if the ent with the given id passes the filter, where the
+/// ent is reached through the edge of another ent if filtering that edge
+fn filter_id(db: &SledDatabase, id: &Id, filter: &Filter, via: Option<(Id, &str)>) -> bool {
     match filter {
         Filter::Id(p) => p.check(*id),
-        Filter::Type(p) => with_ent(db, id, |ent| p.check(ent.r#type().to_string())),
-        Filter::Created(p) => with_ent(db, id, |ent| p.check(ent.created())),
-        Filter::LastUpdated(p) => with_ent(db, id, |ent| p.check(ent.last_updated())),
-        Filter::Field(name, p) => with_ent(db, id, |ent| match ent.field(name) {
+        Filter::Type(_)
+        | Filter::Created(_)
+        | Filter::LastUpdated(_)
+        | Filter::Field(_, _)
+        | Filter::Edge(_, _)
+        | Filter::IntoEdge(_) => with_ent(db, id, |ent| filter_ent(db, ent.as_ref(), filter, via)),
+        Filter::AssocCreated(p) => with_assoc(db, id, via, |assoc| p.check(assoc.created())),
+        Filter::AssocData(p) => with_assoc(db, id, via, |assoc| match assoc.data() {
+            Some(value) => p.check(value),
+            None => p.check(&Value::Optional(None)),
+        }),
+        Filter::And(filters) => filters.iter().all(|f| filter_id(db, id, f, via)),
+        Filter::Or(filters) => filters.iter().any(|f| filter_id(db, id, f, via)),
+        Filter::Not(f) => !filter_id(db, id, f, via),
+
+        Filter::IntoInverseEdge(name) => db
+            .ids_for_inverse_edge(*id, name)
+            .map(|ids| !ids.is_empty())
+            .unwrap_or_default(),
+
+        // NOTE: Set operations likewise only combine ids at the top level of
+        //       a query, so when nested the ent passes if it would remain
+        //       after applying the operation to the ent alone
+        Filter::Union(_) => true,
+        Filter::Intersect(other) => db
+            .query_ids(other)
+            .map(|ids| ids.contains(id))
+            .unwrap_or_default(),
+        Filter::Except(other) => db
+            .query_ids(other)
+            .map(|ids| !ids.contains(id))
+            .unwrap_or_default(),
+    }
+}
+
+/// Returns true if the already loaded ent passes the filter, checking the
+/// ent directly rather than loading it again wherever possible
+fn filter_ent(db: &SledDatabase, ent: &dyn Ent, filter: &Filter, via: Option<(Id, &str)>) -> bool {
+    match filter {
+        Filter::Type(p) => p.check(ent.r#type().to_string()),
+        Filter::Created(p) => p.check(ent.created()),
+        Filter::LastUpdated(p) => p.check(ent.last_updated()),
+        Filter::Field(name, p) => match ent.field(name) {
             Some(value) => p.check(&value),
             None => false,
-        }),
-        Filter::Edge(name, f) => with_ent(db, id, |ent| match ent.edge(name) {
-            Some(edge) => edge.to_ids().iter().any(|id| filter_id(db, id, f)),
+        },
+        Filter::Edge(name, f) => match ent.edge(name) {
+            Some(edge) => edge
+                .to_ids()
+                .iter()
+                .any(|edge_id| filter_id(db, edge_id, f, Some((ent.id(), name)))),
             None => false,
-        }),
-
-        // NOTE: Logically, this should be impossible to reach since we only
-        //       call this when we know that the filter is not a transformation
-        Filter::IntoEdge(_) => unreachable!("Bug: Transformation in filter"),
+        },
+        Filter::And(filters) => filters.iter().all(|f| filter_ent(db, ent, f, via)),
+        Filter::Or(filters) => filters.iter().any(|f| filter_ent(db, ent, f, via)),
+        Filter::Not(f) => !filter_ent(db, ent, f, via),
+
+        // NOTE: Transformations only happen at the top level of a query, so
+        //       when nested within another filter, the ent instead passes
+        //       if it has any ents on its edge
+        Filter::IntoEdge(name) => match ent.edge(name) {
+            Some(edge) => !edge.to_ids().is_empty(),
+            None => false,
+        },
+        _ => filter_id(db, &ent.id(), filter, via),
     }
 }
 
@@ -470,6 +2255,26 @@ fn with_ent<F: Fn(Box<dyn Ent>) -> bool>(db: &SledDatabase, id: &Id, f: F) -> bo
         .unwrap_or_default()
 }
 
+fn with_assoc<F: Fn(&Assoc) -> bool>(
+    db: &SledDatabase,
+    id: &Id,
+    via: Option<(Id, &str)>,
+    f: F,
+) -> bool {
+    match via {
+        Some((source, edge)) => db
+            .assocs(source, edge)
+            .map(|assocs| {
+                assocs
+                    .iter()
+                    .find(|assoc| assoc.id() == *id)
+                    .map_or(false, f)
+            })
+            .unwrap_or_default(),
+        None => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -750,6 +2555,537 @@ mod tests {
         );
     }
 
+    #[test]
+    fn remove_should_remove_connections_back_to_ent_for_shallow_delete_edges() {
+        let db = new_db();
+
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                1,
+                vec![],
+                vec![Edge::new_with_deletion_policy(
+                    "a",
+                    vec![2],
+                    EdgeDeletionPolicy::ShallowDelete,
+                )],
+            )))
+            .unwrap();
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                2,
+                vec![],
+                vec![Edge::new("b", vec![1, 3])],
+            )))
+            .unwrap();
+
+        assert!(db.remove(1).expect("Failed to remove ent"));
+
+        let ent = db.get(2).unwrap().expect("Ent missing");
+        assert_eq!(ent.edge("b").expect("Edge missing").to_ids(), vec![3]);
+        assert_eq!(db.incoming_edges(1).unwrap(), vec![]);
+        assert_eq!(db.incoming_edges(3).unwrap(), vec![(2, String::from("b"))]);
+    }
+
+    #[test]
+    fn insert_and_remove_should_keep_inverse_edges_in_sync() {
+        let db = new_db();
+        let insert = |id, edges| {
+            let _ = db
+                .insert(Box::from(UntypedEnt::from_collections(id, vec![], edges)))
+                .unwrap();
+        };
+        let edge_ids = |id, name| {
+            let ent = db.get(id).unwrap().expect("Ent missing");
+            ent.edge(name).expect("Edge missing").to_ids()
+        };
+
+        insert(1, vec![Edge::new("posts", Vec::<Id>::new())]);
+        insert(2, vec![Edge::new("posts", Vec::<Id>::new())]);
+
+        // Writing an edge adds the ent to the inverse edge of its ents
+        insert(3, vec![Edge::new("author", 1).with_inverse("posts")]);
+        assert_eq!(edge_ids(1, "posts"), vec![3]);
+
+        // Changing an edge moves the ent between the inverse edges
+        insert(3, vec![Edge::new("author", 2).with_inverse("posts")]);
+        assert_eq!(edge_ids(1, "posts"), Vec::<Id>::new());
+        assert_eq!(edge_ids(2, "posts"), vec![3]);
+
+        // Removing an ent removes it from the inverse edges
+        assert!(db.remove(3).unwrap());
+        assert_eq!(edge_ids(2, "posts"), Vec::<Id>::new());
+
+        // When both sides are declared, the sync happens in either direction
+        insert(4, vec![Edge::new("author", None).with_inverse("posts")]);
+        insert(1, vec![Edge::new("posts", vec![4]).with_inverse("author")]);
+        assert_eq!(edge_ids(4, "author"), vec![1]);
+
+        insert(4, vec![Edge::new("author", Some(2)).with_inverse("posts")]);
+        assert_eq!(edge_ids(1, "posts"), Vec::<Id>::new());
+        assert_eq!(edge_ids(2, "posts"), vec![4]);
+
+        insert(
+            2,
+            vec![Edge::new("posts", Vec::<Id>::new()).with_inverse("author")],
+        );
+        assert_eq!(edge_ids(4, "author"), Vec::<Id>::new());
+    }
+
+    #[test]
+    fn remove_should_remove_connected_ents_for_deep_delete_edges() {
+        let db = new_db();
+
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(2))).unwrap();
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                1,
+                vec![],
+                vec![Edge::new_with_deletion_policy(
+                    "a",
+                    vec![2],
+                    EdgeDeletionPolicy::DeepDelete,
+                )],
+            )))
+            .unwrap();
+
+        assert!(db.remove(1).expect("Failed to remove ent"));
+        assert!(db.get(2).unwrap().is_none(), "Did not remove connected ent");
+        assert!(db.ids_for_type(UntypedEnt::type_str()).is_empty());
+    }
+
+    #[test]
+    fn compare_and_insert_at_should_fail_if_stored_ent_was_not_updated_at_expected_time() {
+        let db = new_db();
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(1))).unwrap();
+        let stale = db.get(1).unwrap().expect("Ent missing");
+        let expected = stale.last_updated();
+
+        // The ent can be marked as updated after it was loaded
+        let mut ent = dyn_clone::clone_box(stale.as_ref());
+        ent.mark_updated().unwrap();
+        let _ = db.compare_and_insert_at(ent, expected).unwrap();
+        let updated = db.get(1).unwrap().expect("Ent missing");
+        assert!(updated.last_updated() > expected);
+
+        // Writing against the time the ent was loaded again conflicts
+        match db.compare_and_insert_at(dyn_clone::clone_box(stale.as_ref()), expected) {
+            Err(DatabaseError::Conflict {
+                id,
+                expected: x,
+                actual,
+            }) => {
+                assert_eq!(id, 1);
+                assert_eq!(x, expected);
+                assert_eq!(actual, updated.last_updated());
+            }
+            x => panic!("Unexpected result: {:?}", x),
+        }
+
+        // Conflicts within a transaction fail its commit
+        db.begin_transaction().unwrap();
+        let _ = db.compare_and_insert_at(stale, expected).unwrap();
+        assert!(matches!(
+            db.commit_transaction(),
+            Err(DatabaseError::Conflict { id: 1, .. })
+        ));
+        assert_eq!(
+            db.get(1).unwrap().expect("Ent missing").last_updated(),
+            updated.last_updated()
+        );
+    }
+
+    #[test]
+    fn compare_and_insert_should_fail_if_stored_ent_was_updated_since() {
+        let db = new_db();
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(1))).unwrap();
+        let stale = db.get(1).unwrap().expect("Ent missing");
+
+        // Writing the ent as it was loaded succeeds and updates it
+        let _ = db
+            .compare_and_insert(dyn_clone::clone_box(stale.as_ref()))
+            .unwrap();
+        let updated = db.get(1).unwrap().expect("Ent missing");
+        assert!(updated.last_updated() > stale.last_updated());
+
+        // Writing the ent as it was loaded again conflicts
+        match db.compare_and_insert(dyn_clone::clone_box(stale.as_ref())) {
+            Err(DatabaseError::Conflict {
+                id,
+                expected,
+                actual,
+            }) => {
+                assert_eq!(id, 1);
+                assert_eq!(expected, stale.last_updated());
+                assert_eq!(actual, updated.last_updated());
+            }
+            x => panic!("Unexpected result: {:?}", x),
+        }
+
+        // Conflicts within a transaction fail its commit without writing
+        // any of its changes
+        db.begin_transaction().unwrap();
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(2))).unwrap();
+        let _ = db.compare_and_insert(stale).unwrap();
+        assert!(matches!(
+            db.commit_transaction(),
+            Err(DatabaseError::Conflict { id: 1, .. })
+        ));
+        assert!(db.get(2).unwrap().is_none(), "Ent was unexpectedly written");
+        assert_eq!(
+            db.get(1).unwrap().expect("Ent missing").last_updated(),
+            updated.last_updated()
+        );
+
+        // Ents that are not stored are always written
+        let _ = db
+            .compare_and_insert(Box::from(UntypedEnt::empty_with_id(3)))
+            .unwrap();
+        assert!(db.get(3).unwrap().is_some(), "Ent was not written");
+    }
+
+    #[test]
+    fn update_fields_should_apply_all_changes_of_patch_or_none() {
+        let db = std::sync::Arc::new(new_db());
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                1,
+                vec![
+                    Field::new("name", String::from("alice")),
+                    Field::new_with_attributes("code", 7, vec![FieldAttribute::Immutable]),
+                ],
+                vec![Edge::new("friends", vec![2, 3])],
+            )))
+            .unwrap();
+        let name = || db.get(1).unwrap().expect("Ent missing").field("name");
+        let before = db.get(1).unwrap().expect("Ent missing");
+
+        let ent = db
+            .update_fields(
+                1,
+                Patch::new()
+                    .set_field("name", "bob")
+                    .add_ids("friends", vec![4])
+                    .remove_ids("friends", vec![2]),
+            )
+            .unwrap();
+        assert!(ent.last_updated() > before.last_updated());
+
+        let stored = db.get(1).unwrap().expect("Ent missing");
+        assert_eq!(stored.field("name"), Some(Value::from("bob")));
+        assert_eq!(stored.edge("friends").unwrap().to_ids(), vec![3, 4]);
+        assert_eq!(stored.last_updated(), ent.last_updated());
+
+        let q = Query::default().where_field("name", P::equals(String::from("bob")));
+        query_and_assert(&db, q, &[1]);
+
+        // Patches with any change that fails write nothing
+        assert!(matches!(
+            db.update_fields(
+                1,
+                Patch::new().set_field("name", "carol").set_field("code", 8)
+            ),
+            Err(DatabaseError::EntMutationFailed {
+                id: 1,
+                source: EntMutationError::FieldImmutable { .. },
+            })
+        ));
+        assert!(matches!(
+            db.update_fields(1, Patch::new().add_ids("missing", vec![2])),
+            Err(DatabaseError::EntMutationFailed {
+                id: 1,
+                source: EntMutationError::NoEdge { .. },
+            })
+        ));
+        assert!(matches!(
+            db.update_fields(2, Patch::new()),
+            Err(DatabaseError::MissingEnt { id: 2 })
+        ));
+        assert_eq!(name(), Some(Value::from("bob")));
+
+        // Patches within a transaction conflict with changes made to the
+        // ent by others before the transaction is committed
+        db.begin_transaction().unwrap();
+        let _ = db
+            .update_fields(1, Patch::new().set_field("name", "dave"))
+            .unwrap();
+        assert_eq!(name(), Some(Value::from("dave")));
+
+        let other_db = std::sync::Arc::clone(&db);
+        std::thread::spawn(move || {
+            other_db
+                .update_fields(1, Patch::new().set_field("name", "erin"))
+                .unwrap()
+        })
+        .join()
+        .unwrap();
+
+        assert!(matches!(
+            db.commit_transaction(),
+            Err(DatabaseError::Conflict { id: 1, .. })
+        ));
+        assert_eq!(name(), Some(Value::from("erin")));
+    }
+
+    #[test]
+    fn subscribe_should_receive_changes_matching_query_once_written() {
+        let db = new_db();
+        let timeout = std::time::Duration::from_secs(5);
+        let all = db.subscribe(Query::default()).unwrap();
+        let big = db
+            .subscribe(Query::default().where_field("a", P::greater_than(2)))
+            .unwrap();
+
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                1,
+                vec![Field::new("a", 1)],
+                vec![],
+            )))
+            .unwrap();
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                2,
+                vec![Field::new("a", 5)],
+                vec![],
+            )))
+            .unwrap();
+
+        let change = all.recv_timeout(timeout).expect("Missing change");
+        assert_eq!(change.kind(), ChangeKind::Insert);
+        assert_eq!(change.id(), 1);
+        assert_eq!(change.r#type(), UntypedEnt::default().r#type());
+        assert!(change.before().is_none());
+        assert_eq!(change.after().unwrap().field("a"), Some(Value::from(1)));
+        assert_eq!(all.recv_timeout(timeout).map(|c| c.id()), Some(2));
+        assert_eq!(big.recv_timeout(timeout).map(|c| c.id()), Some(2));
+
+        // Updates are received if the ent matches before or after the change
+        let _ = db.update_fields(1, Patch::new().set_field("a", 3)).unwrap();
+        let change = big.recv_timeout(timeout).expect("Missing change");
+        assert_eq!(change.kind(), ChangeKind::Update);
+        assert_eq!(change.before().unwrap().field("a"), Some(Value::from(1)));
+        assert_eq!(change.after().unwrap().field("a"), Some(Value::from(3)));
+        assert_eq!(
+            all.recv_timeout(timeout).map(|c| c.kind()),
+            Some(ChangeKind::Update)
+        );
+
+        // Changes staged by a transaction are only received once committed
+        db.begin_transaction().unwrap();
+        let _ = db.remove(2).unwrap();
+        assert!(big
+            .recv_timeout(std::time::Duration::from_millis(50))
+            .is_none());
+        db.commit_transaction().unwrap();
+
+        let change = big.recv_timeout(timeout).expect("Missing change");
+        assert_eq!(change.kind(), ChangeKind::Remove);
+        assert_eq!(change.id(), 2);
+        assert_eq!(change.before().unwrap().field("a"), Some(Value::from(5)));
+        assert!(change.after().is_none());
+
+        // Changes are only kept until every subscription has read them,
+        // leaving the latest sequence number
+        let start = std::time::Instant::now();
+        while db.change_tree().unwrap().len() > 1 {
+            assert!(start.elapsed() < timeout, "Changes were not trimmed");
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        // Dropped subscriptions stop being sent changes even if no more
+        // changes are written
+        drop(all);
+        let start = std::time::Instant::now();
+        while db.subscriber_tree().unwrap().len() > 1 {
+            assert!(start.elapsed() < timeout, "Subscription was not stopped");
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn subscribe_should_receive_changes_written_through_other_handles() {
+        let db = new_db();
+        let other = SledDatabase::new(db.db.clone());
+        let timeout = std::time::Duration::from_secs(5);
+        let subscription = db.subscribe(Query::default()).unwrap();
+
+        // Changes written in quick succession are all received in order,
+        // even if they are written and trimmed between polls
+        for id in 1..=20 {
+            let _ = other
+                .insert(Box::from(UntypedEnt::from_collections(
+                    id,
+                    vec![Field::new("a", id)],
+                    vec![],
+                )))
+                .unwrap();
+            let _ = other.remove(id).unwrap();
+        }
+
+        for id in 1..=20 {
+            let change = subscription.recv_timeout(timeout).expect("Missing change");
+            assert_eq!((change.id(), change.kind()), (id, ChangeKind::Insert));
+            let change = subscription.recv_timeout(timeout).expect("Missing change");
+            assert_eq!((change.id(), change.kind()), (id, ChangeKind::Remove));
+        }
+
+        // Subscriptions through the other handle receive changes written
+        // through the first
+        let other_subscription = other.subscribe(Query::default()).unwrap();
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                21,
+                vec![Field::new("a", 21)],
+                vec![],
+            )))
+            .unwrap();
+        assert_eq!(
+            other_subscription.recv_timeout(timeout).map(|c| c.id()),
+            Some(21)
+        );
+        assert_eq!(subscription.recv_timeout(timeout).map(|c| c.id()), Some(21));
+
+        // Changes stop being recorded once every subscription is dropped
+        drop(subscription);
+        drop(other_subscription);
+        let start = std::time::Instant::now();
+        while !other.subscriber_tree().unwrap().is_empty() {
+            assert!(start.elapsed() < timeout, "Subscription was not stopped");
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let _ = other.remove(21).unwrap();
+        assert_eq!(other.change_tree().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn begin_transaction_should_fail_if_transaction_already_in_progress() {
+        let db = new_db();
+
+        db.begin_transaction().expect("Failed to begin transaction");
+        assert!(matches!(
+            db.begin_transaction(),
+            Err(DatabaseError::TransactionInProgress)
+        ));
+    }
+
+    #[test]
+    fn commit_transaction_should_fail_if_no_transaction_in_progress() {
+        let db = new_db();
+
+        assert!(matches!(
+            db.commit_transaction(),
+            Err(DatabaseError::NoTransaction)
+        ));
+        assert!(matches!(
+            db.rollback_transaction(),
+            Err(DatabaseError::NoTransaction)
+        ));
+    }
+
+    #[test]
+    fn commit_transaction_should_apply_all_staged_changes() {
+        let db = new_db();
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(1))).unwrap();
+
+        db.begin_transaction().expect("Failed to begin transaction");
+        let id = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                EPHEMERAL_ID,
+                vec![],
+                vec![Edge::new("a", 1)],
+            )))
+            .expect("Failed to stage insert");
+        assert!(db.remove(1).expect("Failed to stage remove"));
+
+        // Staged changes are not written to storage until committed
+        assert!(db.db.contains_key(id_to_ivec(1)).unwrap());
+        assert!(!db.db.contains_key(id_to_ivec(id)).unwrap());
+
+        db.commit_transaction()
+            .expect("Failed to commit transaction");
+
+        assert!(db.get(1).unwrap().is_none(), "Did not remove ent");
+        assert!(db.get(id).unwrap().is_some(), "Did not insert ent");
+        assert!(!db.in_transaction());
+    }
+
+    #[test]
+    fn rollback_transaction_should_discard_all_staged_changes() {
+        let db = new_db();
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(1))).unwrap();
+
+        db.begin_transaction().expect("Failed to begin transaction");
+        let id = db
+            .insert(Box::from(UntypedEnt::empty_with_id(EPHEMERAL_ID)))
+            .expect("Failed to stage insert");
+        assert!(db.remove(1).expect("Failed to stage remove"));
+        db.rollback_transaction()
+            .expect("Failed to roll back transaction");
+
+        assert!(db.get(1).unwrap().is_some(), "Unexpectedly removed ent");
+        assert!(db.get(id).unwrap().is_none(), "Unexpectedly inserted ent");
+
+        // Id allocated within the transaction should have been freed
+        assert_eq!(
+            db.with_id_allocator(|alloc| alloc.freed().first().copied())
+                .unwrap(),
+            Some(id),
+        );
+    }
+
+    #[test]
+    fn transaction_should_make_staged_changes_visible_only_to_current_thread() {
+        let db = new_db();
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(1))).unwrap();
+
+        db.begin_transaction().expect("Failed to begin transaction");
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(2))).unwrap();
+        let _ = db.remove(1).unwrap();
+
+        let ts = <UntypedEnt as EntType>::type_str();
+        let q = Query::default().where_type(TP::equals(ts.to_string()));
+        query_and_assert(&db, q.clone(), &[2]);
+
+        let other_db = db.clone();
+        let ids = std::thread::spawn(move || {
+            let ts = <UntypedEnt as EntType>::type_str();
+            other_db
+                .find_all(Query::default().where_type(TP::equals(ts.to_string())))
+                .unwrap()
+                .iter()
+                .map(|ent| ent.id())
+                .collect::<Vec<Id>>()
+        })
+        .join()
+        .unwrap();
+        assert_eq!(ids, vec![1]);
+
+        db.commit_transaction()
+            .expect("Failed to commit transaction");
+        query_and_assert(&db, q, &[2]);
+    }
+
+    #[test]
+    fn transaction_should_include_ents_committed_while_in_progress() {
+        let db = db_to_rc(new_db());
+
+        let result = db.transaction(|_| {
+            let mut ent = UntypedEnt::empty_with_id(EPHEMERAL_ID);
+            ent.connect(DatabaseRc::downgrade(&db));
+            ent.commit()?;
+            Ok(ent.id())
+        });
+        let id = result.expect("Transaction failed");
+        assert!(db.get(id).unwrap().is_some(), "Ent was not committed");
+
+        let result: DatabaseResult<()> = db.transaction(|db| {
+            let _ = db.insert(Box::from(UntypedEnt::empty_with_id(999)))?;
+            Err(DatabaseError::Disconnected)
+        });
+        assert!(result.is_err());
+        assert!(db.get(999).unwrap().is_none(), "Ent was not rolled back");
+    }
+
     #[test]
     fn get_all_should_return_all_ents_with_associated_ids() {
         let db = new_db();
@@ -915,6 +3251,543 @@ mod tests {
         query_and_assert(&db, q, &[6]);
     }
 
+    /// Creates a new database with ents 1-5 that have an indexed field "n"
+    /// whose values are numbers of different types, none, and text
+    fn new_indexed_test_database() -> SledDatabase {
+        let db = new_db();
+
+        let values = vec![
+            Value::from(1u8),
+            Value::from(2i64),
+            Value::from(3.5f32),
+            Value::from(-4i32),
+            Value::from(None::<u32>),
+        ];
+        for (id, value) in values.into_iter().enumerate() {
+            insert_indexed(&db, id + 1, value);
+        }
+
+        db
+    }
+
+    fn insert_indexed(db: &SledDatabase, id: Id, value: Value) {
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                id,
+                vec![Field::new_with_attributes(
+                    "n",
+                    value,
+                    vec![FieldAttribute::Indexed],
+                )],
+                vec![],
+            )))
+            .unwrap();
+    }
+
+    fn scan_ids(db: &SledDatabase, p: Predicate) -> Vec<Id> {
+        let scan = IndexScan::from_predicate(&p).expect("Predicate not indexable");
+        let mut ids: Vec<Id> = db
+            .ids_for_field("n", &scan)
+            .expect("Failed to scan index")
+            .into_iter()
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    #[test]
+    fn find_all_should_support_filtering_by_indexed_field() {
+        let db = new_indexed_test_database();
+
+        // Equality and set membership only look up matching values
+        assert_eq!(scan_ids(&db, P::equals(2u32)), vec![2]);
+        assert_eq!(scan_ids(&db, P::in_set(vec![1, -4])), vec![1, 4]);
+        assert_eq!(scan_ids(&db, P::IsNone), vec![5]);
+        query_and_assert(
+            &db,
+            Query::default().where_field("n", P::equals(2u32)),
+            &[2],
+        );
+        query_and_assert(
+            &db,
+            Query::default()
+                .where_field("n", P::in_set(vec![Value::from(1u8), Value::from(-4i32)])),
+            &[1, 4],
+        );
+
+        // Ranges only look up values in order within the range
+        assert_eq!(scan_ids(&db, P::greater_than(1)), vec![2, 3]);
+        assert_eq!(scan_ids(&db, P::less_than_or_equals(1)), vec![1, 4]);
+        assert_eq!(
+            scan_ids(&db, P::in_range(Value::from(-10)..=Value::from(2))),
+            vec![1, 2, 4]
+        );
+        query_and_assert(
+            &db,
+            Query::default().where_field("n", P::greater_than(1)),
+            &[2, 3],
+        );
+        query_and_assert(
+            &db,
+            Query::default().where_field("n", P::in_range(Value::from(-10)..=Value::from(2))),
+            &[1, 2, 4],
+        );
+
+        // Indexes narrow down ents already in the pipeline
+        let q = Query::default()
+            .where_id(TP::equals(1) | TP::equals(3))
+            .where_field("n", P::greater_than(0));
+        query_and_assert(&db, q, &[1, 3]);
+    }
+
+    #[test]
+    fn find_all_should_filter_indexed_floats_by_their_exact_value() {
+        let db = new_indexed_test_database();
+        insert_indexed(&db, 6, Value::from(1.2f64));
+        insert_indexed(&db, 7, Value::from(1.7f64));
+        insert_indexed(&db, 8, Value::from(-1.5f64));
+
+        // Floats sharing an integral part are keyed apart from one another
+        assert_eq!(scan_ids(&db, P::equals(1.2f64)), vec![6]);
+        assert_eq!(scan_ids(&db, P::greater_than(1.5f64)), vec![2, 3, 7]);
+        assert_eq!(scan_ids(&db, P::less_than(-1)), vec![4, 8]);
+        query_and_assert(
+            &db,
+            Query::default().where_field("n", P::greater_than(1)),
+            &[2, 3, 6, 7],
+        );
+        query_and_assert(
+            &db,
+            Query::default().where_field("n", P::in_range(Value::from(1.5)..=Value::from(3))),
+            &[2, 7],
+        );
+    }
+
+    #[test]
+    fn indexes_should_stay_in_sync_with_inserted_and_removed_ents() {
+        let db = new_indexed_test_database();
+
+        // Overwriting an ent replaces its indexed value
+        insert_indexed(&db, 2, Value::from(10));
+        assert_eq!(scan_ids(&db, P::equals(2)), Vec::<Id>::new());
+        assert_eq!(scan_ids(&db, P::equals(10)), vec![2]);
+
+        // Removing an ent removes its indexed value
+        assert!(db.remove(1).unwrap());
+        assert_eq!(scan_ids(&db, P::equals(1)), Vec::<Id>::new());
+        query_and_assert(
+            &db,
+            Query::default().where_field("n", P::greater_than(0)),
+            &[2, 3],
+        );
+
+        // Staged ents are included until they are committed to the index
+        db.begin_transaction().unwrap();
+        insert_indexed(&db, 6, Value::from(7));
+        query_and_assert(&db, Query::default().where_field("n", P::equals(7)), &[6]);
+        db.commit_transaction().unwrap();
+        assert_eq!(scan_ids(&db, P::equals(7)), vec![6]);
+    }
+
+    #[test]
+    fn writes_should_fail_if_unique_field_value_is_used_by_another_ent() {
+        let db = new_db();
+        let user = |id: Id, email: &str| -> Box<dyn Ent> {
+            Box::from(UntypedEnt::from_collections(
+                id,
+                vec![Field::new_with_attributes(
+                    "email",
+                    String::from(email),
+                    vec![FieldAttribute::Unique],
+                )],
+                vec![],
+            ))
+        };
+
+        // Rewriting an ent with the value it already holds is allowed
+        db.insert(user(1, "a@example.com")).unwrap();
+        db.insert(user(1, "a@example.com")).unwrap();
+        assert!(matches!(
+            db.insert(user(2, "a@example.com")),
+            Err(DatabaseError::NotUnique { ref name, id: 1 }) if name == "email"
+        ));
+        assert!(!db.has_id(2));
+
+        // Values are released once the ent holding them changes or is removed
+        db.insert(user(1, "b@example.com")).unwrap();
+        db.insert(user(2, "a@example.com")).unwrap();
+        let patch = || Patch::new().set_field("email", "b@example.com");
+        assert!(matches!(
+            db.update_fields(2, patch()),
+            Err(DatabaseError::NotUnique { id: 1, .. })
+        ));
+        db.remove(1).unwrap();
+        db.update_fields(2, patch()).unwrap();
+
+        // Transactions are checked as a whole once committed
+        db.begin_transaction().unwrap();
+        db.insert(user(3, "c@example.com")).unwrap();
+        db.insert(user(4, "c@example.com")).unwrap();
+        assert!(matches!(
+            db.commit_transaction(),
+            Err(DatabaseError::NotUnique { id: 3, .. })
+        ));
+        assert!(!db.has_id(3));
+        assert!(!db.has_id(4));
+
+        db.begin_transaction().unwrap();
+        db.insert(user(2, "d@example.com")).unwrap();
+        db.insert(user(3, "b@example.com")).unwrap();
+        db.commit_transaction().unwrap();
+
+        // Claimed values are rebuilt alongside the other indexes
+        db.field_index_tree().unwrap().clear().unwrap();
+        db.reindex().unwrap();
+        assert!(matches!(
+            db.insert(user(4, "b@example.com")),
+            Err(DatabaseError::NotUnique { id: 3, .. })
+        ));
+
+        // Values are compared exactly, and values without a key such as
+        // lists are never considered the same
+        let score = |id: Id, value: Value| -> Box<dyn Ent> {
+            Box::from(UntypedEnt::from_collections(
+                id,
+                vec![Field::new_with_attributes(
+                    "score",
+                    value,
+                    vec![FieldAttribute::Unique],
+                )],
+                vec![],
+            ))
+        };
+        db.insert(score(5, Value::from(1.2))).unwrap();
+        db.insert(score(6, Value::from(1.7))).unwrap();
+        assert!(matches!(
+            db.insert(score(7, Value::from(1.2))),
+            Err(DatabaseError::NotUnique { ref name, id: 5 }) if name == "score"
+        ));
+        db.insert(score(8, Value::from(vec![1, 2]))).unwrap();
+        db.insert(score(9, Value::from(vec![1, 2]))).unwrap();
+    }
+
+    #[test]
+    fn find_all_should_check_all_ents_of_type_if_field_is_not_indexed() {
+        let db = new_indexed_test_database();
+
+        // Ent of the same type with the field not marked as indexed
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                6,
+                vec![Field::new("n", 2)],
+                vec![],
+            )))
+            .unwrap();
+
+        assert_eq!(scan_ids(&db, P::equals(2)), vec![1, 2, 3, 4, 5, 6]);
+        query_and_assert(
+            &db,
+            Query::default().where_field("n", P::equals(2)),
+            &[2, 6],
+        );
+
+        // The field is recorded as unindexed once for the type rather than
+        // for every ent with the field
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                7,
+                vec![Field::new("n", 3)],
+                vec![],
+            )))
+            .unwrap();
+        let mut prefix = field_index_prefix(UntypedEnt::default().r#type(), "n");
+        prefix.push(UNINDEXED_FIELD);
+        assert_eq!(
+            db.field_index_tree().unwrap().scan_prefix(&prefix).count(),
+            1
+        );
+        assert!(db.remove(7).unwrap());
+
+        // The field stays unindexed for the type until the indexes are
+        // rebuilt without any ents of the type having it unindexed
+        assert!(db.remove(6).unwrap());
+        assert_eq!(scan_ids(&db, P::equals(2)), vec![1, 2, 3, 4, 5]);
+
+        db.reindex().unwrap();
+        assert_eq!(scan_ids(&db, P::equals(2)), vec![2]);
+    }
+
+    #[test]
+    fn reindex_should_rebuild_indexes_from_stored_ents() {
+        let db = new_indexed_test_database();
+
+        db.field_index_tree().unwrap().clear().unwrap();
+        assert_eq!(scan_ids(&db, P::greater_than(0)), Vec::<Id>::new());
+
+        db.reindex().unwrap();
+        assert_eq!(scan_ids(&db, P::greater_than(0)), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn reindex_should_rebuild_edge_indexes_from_stored_ents() {
+        let db = new_test_database();
+
+        db.edge_index_tree().unwrap().clear().unwrap();
+        assert_eq!(db.incoming_edges(8).unwrap(), vec![]);
+
+        db.reindex().unwrap();
+        assert_eq!(db.incoming_edges(8).unwrap(), vec![(12, String::from("c"))]);
+    }
+
+    #[test]
+    fn find_all_should_support_ordering_and_paging() {
+        let db = new_test_database();
+        let find_ids = |query: Query| {
+            db.find_all(query)
+                .expect("Failed to retrieve ents")
+                .iter()
+                .map(|ent| ent.id())
+                .collect::<Vec<Id>>()
+        };
+
+        // Ents are ordered by each clause in turn, followed by their ids
+        let q = Query::default()
+            .where_id(TP::always())
+            .order_by_id(OrderDirection::Descending);
+        assert_eq!(find_ids(q), vec![12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1]);
+
+        let q = Query::default()
+            .where_id(TP::less_than(7))
+            .order_by_field("a", OrderDirection::Descending);
+        assert_eq!(find_ids(q), vec![1, 2, 3, 6, 5, 4]);
+
+        let q = Query::default()
+            .where_id(TP::less_than(7))
+            .order_by_field("a", OrderDirection::Ascending)
+            .order_by_id(OrderDirection::Descending);
+        assert_eq!(find_ids(q), vec![4, 5, 6, 3, 2, 1]);
+
+        // Offset and limit are applied after ordering
+        let q = Query::default()
+            .where_id(TP::less_than(7))
+            .order_by_field("a", OrderDirection::Ascending)
+            .offset(1)
+            .limit(3);
+        assert_eq!(find_ids(q), vec![5, 6, 1]);
+
+        // Without any ordering clauses, ents are ordered by id when paging
+        let q = Query::default().where_id(TP::always()).offset(10);
+        assert_eq!(find_ids(q), vec![11, 12]);
+
+        let q = Query::default().where_id(TP::always()).limit(0);
+        assert_eq!(find_ids(q), Vec::<Id>::new());
+    }
+
+    #[test]
+    fn find_page_should_resume_after_cursor_of_previous_page() {
+        let db = new_test_database();
+        let query = || {
+            Query::default()
+                .where_id(TP::less_than(7))
+                .order_by_field("a", OrderDirection::Ascending)
+        };
+        let find_page = |query: Query| {
+            let (ents, cursor) = db
+                .find_page(query, 2)
+                .expect("Failed to retrieve page")
+                .into_parts();
+            (ents.iter().map(|ent| ent.id()).collect::<Vec<Id>>(), cursor)
+        };
+
+        let (ids, cursor) = find_page(query());
+        assert_eq!(ids, vec![4, 5]);
+        let cursor = cursor.expect("Missing cursor for next page");
+
+        // Cursors survive being passed around as strings
+        let cursor: Cursor = cursor.to_string().parse().unwrap();
+
+        // Ents removed after the first page are not part of later pages
+        // and do not shift where the query resumes
+        assert!(db.remove(1).expect("Failed to remove ent"));
+
+        let (ids, cursor) = find_page(query().after(cursor));
+        assert_eq!(ids, vec![6, 2]);
+        let cursor = cursor.expect("Missing cursor for next page");
+
+        let (ids, cursor) = find_page(query().after(cursor));
+        assert_eq!(ids, vec![3]);
+        assert!(cursor.is_none(), "Unexpected cursor for last page");
+
+        // A page that exactly fits the remaining ents has no next page
+        let (ids, cursor) = find_page(query().where_id(TP::greater_than(4)));
+        assert_eq!(ids, vec![5, 6]);
+        assert!(cursor.is_none(), "Unexpected cursor for last page");
+    }
+
+    #[test]
+    fn find_page_should_seek_in_the_same_order_as_find_all() {
+        let db = new_indexed_test_database();
+        insert_indexed(&db, 6, Value::from(2u8));
+        for id in vec![7, 8] {
+            let _ = db.insert(Box::from(UntypedEnt::empty_with_id(id))).unwrap();
+        }
+
+        // Pages are sought by id or by the indexed field, including ents
+        // without the field, and must line up with the ents found at once
+        for direction in vec![OrderDirection::Ascending, OrderDirection::Descending] {
+            let queries = vec![
+                Query::default()
+                    .where_id(TP::always())
+                    .order_by_id(direction),
+                Query::default()
+                    .where_id(TP::always())
+                    .order_by_field("n", direction),
+            ];
+
+            for query in queries {
+                let expected: Vec<Id> = db
+                    .find_all(query.clone())
+                    .expect("Failed to retrieve ents")
+                    .iter()
+                    .map(|ent| ent.id())
+                    .collect();
+
+                for page_size in 1..=3 {
+                    let mut ids = Vec::new();
+                    let mut page = db.find_page(query.clone(), page_size).unwrap();
+                    loop {
+                        ids.extend(page.items().iter().map(|ent| ent.id()));
+                        match page.next_cursor() {
+                            Some(cursor) => {
+                                let query = query.clone().after(cursor.clone());
+                                page = db.find_page(query, page_size).unwrap();
+                            }
+                            None => break,
+                        }
+                    }
+                    assert_eq!(ids, expected, "{:?} in pages of {}", query, page_size);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn count_should_return_total_ents_that_would_be_found() {
+        let db = new_test_database();
+
+        let q = Query::default();
+        assert_eq!(db.count(q).expect("Failed to count ents"), 0);
+
+        let q = Query::default().where_id(TP::always());
+        assert_eq!(db.count(q).expect("Failed to count ents"), 12);
+
+        let q = Query::default().where_field("a", P::greater_than(1));
+        assert_eq!(db.count(q).expect("Failed to count ents"), 2);
+
+        // Dangling edge ids are not counted, as no ent would be found
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                13,
+                vec![],
+                vec![Edge::new("b", vec![1, 999])],
+            )))
+            .unwrap();
+        let q = Query::default()
+            .where_id(TP::equals(13))
+            .where_into_edge("b");
+        assert_eq!(db.count(q).expect("Failed to count ents"), 1);
+
+        // Offset, limit, and cursor are honored
+        let q = Query::default().where_id(TP::always()).offset(10).limit(2);
+        assert_eq!(db.count(q).expect("Failed to count ents"), 2);
+
+        let cursor = Cursor::new(&[], db.get(8).unwrap().unwrap().as_ref());
+        let q = Query::default().where_id(TP::always()).after(cursor);
+        assert_eq!(db.count(q).expect("Failed to count ents"), 5);
+
+        let order = [Order::ascending(OrderBy::Field(String::from("a")))];
+        let cursor = Cursor::new(&order, db.get(5).unwrap().unwrap().as_ref());
+        let q = Query::default()
+            .where_id(TP::less_than(7))
+            .order_by_field("a", OrderDirection::Ascending)
+            .after(cursor);
+        assert_eq!(db.count(q).expect("Failed to count ents"), 4);
+    }
+
+    #[test]
+    fn exists_should_return_true_if_any_ent_would_be_found() {
+        let db = new_test_database();
+
+        let q = Query::default().where_id(TP::equals(5));
+        assert!(db.exists(q).expect("Failed to check for ents"));
+
+        let q = Query::default().where_id(TP::equals(999));
+        assert!(!db.exists(q).expect("Failed to check for ents"));
+
+        let q = Query::default().where_id(TP::equals(5)).offset(1);
+        assert!(!db.exists(q).expect("Failed to check for ents"));
+
+        let q = Query::default().where_id(TP::equals(5)).limit(0);
+        assert!(!db.exists(q).expect("Failed to check for ents"));
+    }
+
+    #[test]
+    fn aggregate_should_calculate_aggregates_over_ents_found_by_query() {
+        let db = new_test_database();
+
+        let aggregation = Aggregation::new(Query::default().where_id(TP::less_than(7)))
+            .count()
+            .sum("a")
+            .avg("a")
+            .min("a")
+            .max("a")
+            .distinct("b");
+        let groups = db.aggregate(aggregation).expect("Failed to aggregate");
+        assert_eq!(groups.len(), 1);
+        assert_eq!(
+            groups[0].values(),
+            &[
+                Value::from(6usize),
+                Value::from(Some(9)),
+                Value::from(Some(3.0)),
+                Value::from(Some(1)),
+                Value::from(Some(5)),
+                Value::from(vec![2, 4, 6]),
+            ]
+        );
+
+        // Groups are ordered by value, followed by ents without the field
+        let aggregation = Aggregation::new(Query::default().where_id(TP::always()))
+            .count()
+            .group_by("a");
+        let groups: Vec<(Option<Value>, Vec<Value>)> = db
+            .aggregate(aggregation)
+            .expect("Failed to aggregate")
+            .into_iter()
+            .map(AggregateGroup::into_parts)
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (Some(Value::from(1)), vec![Value::from(1usize)]),
+                (Some(Value::from(3)), vec![Value::from(1usize)]),
+                (Some(Value::from(5)), vec![Value::from(1usize)]),
+                (None, vec![Value::from(9usize)]),
+            ]
+        );
+
+        // Only the ents within the page of a paged query are aggregated
+        let aggregation = Aggregation::new(
+            Query::default()
+                .where_id(TP::less_than(7))
+                .order_by_field("a", OrderDirection::Ascending)
+                .limit(2),
+        )
+        .sum("a");
+        let groups = db.aggregate(aggregation).expect("Failed to aggregate");
+        assert_eq!(groups[0].values(), &[Value::from(Some(4))]);
+    }
+
     #[test]
     fn find_all_should_support_filtering_by_edge() {
         let db = new_test_database();
@@ -949,6 +3822,358 @@ mod tests {
         query_and_assert(&db, q, &[3, 4, 5]);
     }
 
+    #[test]
+    fn find_all_should_support_combining_filters() {
+        let db = new_test_database();
+
+        // Ents passing any of the filters are included
+        let q = Query::default().where_or(vec![
+            Filter::where_id(TP::equals(1)),
+            Filter::where_field("a", P::greater_than(2)),
+        ]);
+        query_and_assert(&db, q, &[1, 5, 6]);
+
+        // Ents passing all of the filters are included, where nested edges
+        // are checked for ents rather than transformed into them
+        let q = Query::default().where_and(vec![
+            Filter::where_into_edge("c"),
+            Filter::where_edge("a", Filter::where_id(TP::greater_than(1))),
+        ]);
+        query_and_assert(&db, q, &[12]);
+
+        // Ents not passing the filter are included
+        let q = Query::default()
+            .where_id(TP::less_than(7))
+            .where_not(Filter::where_field("a", P::less_than(5)));
+        query_and_assert(&db, q, &[1, 2, 3, 6]);
+    }
+
+    #[test]
+    fn find_all_should_support_filtering_into_inverse_edges() {
+        let db = new_test_database();
+
+        // Ents are transformed into the ents whose edge contains them
+        let q = Query::default()
+            .where_id(TP::equals(3))
+            .where_into_inverse_edge("b");
+        query_and_assert(&db, q, &[10, 11]);
+
+        let q = Query::default()
+            .where_id(TP::equals(1) | TP::equals(2))
+            .where_into_inverse_edge("a");
+        query_and_assert(&db, q, &[10, 11]);
+
+        // When nested, ents pass if any edge contains them
+        let q = Query::default()
+            .where_id(TP::less_than(10))
+            .where_not(Filter::where_into_inverse_edge("b"));
+        query_and_assert(&db, q, &[7, 8, 9]);
+    }
+
+    #[test]
+    fn incoming_edges_should_return_ents_with_edges_containing_id() {
+        let db = new_test_database();
+        let incoming = |id| db.incoming_edges(id).unwrap();
+
+        assert_eq!(
+            incoming(3),
+            vec![
+                (10, String::from("b")),
+                (11, String::from("b")),
+                (12, String::from("a")),
+            ]
+        );
+        assert_eq!(incoming(7), vec![]);
+
+        // Overwriting or removing an ent updates the edges containing ids
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(12, vec![], vec![])))
+            .unwrap();
+        let _ = db.remove(10).unwrap();
+        assert_eq!(incoming(3), vec![(11, String::from("b"))]);
+
+        // Staged changes are only reflected until rolled back
+        db.begin_transaction().unwrap();
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                13,
+                vec![],
+                vec![Edge::new("c", 3)],
+            )))
+            .unwrap();
+        let _ = db.remove(11).unwrap();
+        assert_eq!(incoming(3), vec![(13, String::from("c"))]);
+        db.rollback_transaction().unwrap();
+        assert_eq!(incoming(3), vec![(11, String::from("b"))]);
+    }
+
+    #[test]
+    fn assocs_should_record_when_ents_were_added_to_edges_and_their_data() {
+        let db = new_db();
+        for id in 1..=3 {
+            let _ = db.insert(Box::from(UntypedEnt::empty_with_id(id))).unwrap();
+        }
+        let insert = |ids: Vec<Id>| {
+            let _ = db
+                .insert(Box::from(UntypedEnt::from_collections(
+                    10,
+                    vec![],
+                    vec![Edge::new("friends", ids)],
+                )))
+                .unwrap();
+            db.get(10).unwrap().expect("Ent missing").last_updated()
+        };
+        let assocs = || db.assocs(10, "friends").unwrap();
+
+        let first = insert(vec![1, 2]);
+        assert_eq!(assocs(), vec![Assoc::new(1, first), Assoc::new(2, first)]);
+
+        // Ents kept on the edge keep their association while new ents are
+        // associated when written
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = insert(vec![3, 2]);
+        assert!(second > first);
+        assert_eq!(assocs(), vec![Assoc::new(2, first), Assoc::new(3, second)]);
+
+        assert!(db
+            .set_assoc_data(10, "friends", 3, Value::from("best"))
+            .unwrap());
+        assert!(!db
+            .set_assoc_data(10, "friends", 1, Value::from("old"))
+            .unwrap());
+        insert(vec![3, 2]);
+        assert_eq!(
+            assocs(),
+            vec![
+                Assoc::new(2, first),
+                Assoc::new(3, second).with_data("best")
+            ]
+        );
+
+        // Associations can be filtered when filtering by edge
+        let q = Query::default().where_edge(
+            "friends",
+            Filter::where_assoc_data(P::equals(String::from("best"))),
+        );
+        query_and_assert(&db, q, &[10]);
+
+        let q = Query::default()
+            .where_edge("friends", Filter::where_assoc_created(TP::less_than(first)));
+        query_and_assert(&db, q, &[]);
+
+        let q = Query::default()
+            .where_id(TP::always())
+            .where_and(vec![Filter::where_assoc_created(TP::always())]);
+        query_and_assert(&db, q, &[]);
+
+        assert!(db.remove(10).unwrap());
+        assert_eq!(assocs(), vec![]);
+    }
+
+    #[test]
+    fn assoc_range_should_read_associations_ordered_by_creation_time() {
+        let db = new_db();
+        let insert = |ids: Vec<Id>| {
+            let _ = db
+                .insert(Box::from(UntypedEnt::from_collections(
+                    10,
+                    vec![],
+                    vec![Edge::new("likes", ids)],
+                )))
+                .unwrap();
+            db.get(10).unwrap().expect("Ent missing").last_updated()
+        };
+        let range = |range: AssocRange| {
+            db.assoc_range(10, "likes", range)
+                .unwrap()
+                .into_iter()
+                .map(|assoc| assoc.id())
+                .collect::<Vec<Id>>()
+        };
+
+        let mut times = Vec::new();
+        for id in 1..=4 {
+            times.push(insert((1..=id).collect()));
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        assert_eq!(range(AssocRange::default()), vec![1, 2, 3, 4]);
+        assert_eq!(
+            range(AssocRange::default().newest_first().with_limit(2)),
+            vec![4, 3]
+        );
+        assert_eq!(
+            range(AssocRange::default().with_offset(1).with_limit(2)),
+            vec![2, 3]
+        );
+        assert_eq!(
+            range(AssocRange::default().with_created(times[1]..=times[2])),
+            vec![2, 3]
+        );
+        assert_eq!(
+            range(
+                AssocRange::default()
+                    .with_created(..times[2])
+                    .newest_first()
+            ),
+            vec![2, 1]
+        );
+        assert_eq!(db.assoc_count(10, "likes").unwrap(), 4);
+        assert_eq!(db.assoc_count(10, "missing").unwrap(), 0);
+
+        // Staged changes are only reflected until rolled back
+        db.begin_transaction().unwrap();
+        insert(vec![5, 2]);
+        assert_eq!(range(AssocRange::default()), vec![2, 5]);
+        assert_eq!(db.assoc_count(10, "likes").unwrap(), 2);
+        db.rollback_transaction().unwrap();
+        assert_eq!(range(AssocRange::default()), vec![1, 2, 3, 4]);
+        assert_eq!(db.assoc_count(10, "likes").unwrap(), 4);
+    }
+
+    #[test]
+    fn find_all_should_support_set_operations_on_queries() {
+        let db = new_test_database();
+
+        // Ents found by either query are included, where later filters apply
+        // to the combined ents
+        let q = Query::default()
+            .where_id(TP::equals(1))
+            .union(Query::default().where_field("a", P::greater_than(2)));
+        query_and_assert(&db, q.clone(), &[1, 5, 6]);
+        query_and_assert(&db, q.where_id(TP::less_than(6)), &[1, 5]);
+
+        // Ents found by both queries are included, where edges can be
+        // traversed by either query
+        let q = Query::default().where_id(TP::less_than(7)).intersect(
+            Query::default()
+                .where_id(TP::equals(12))
+                .where_into_edge("a"),
+        );
+        query_and_assert(&db, q, &[3]);
+
+        // Ents found by the first query and not the second are included
+        let q = Query::default()
+            .where_id(TP::equals(11))
+            .where_into_edge("b")
+            .except(
+                Query::default()
+                    .where_id(TP::equals(10))
+                    .where_into_edge("b"),
+            );
+        query_and_assert(&db, q, &[1, 2, 6]);
+
+        // Queries that are paged only contribute the ents they return
+        let q = Query::default().where_id(TP::equals(1)).union(
+            Query::default()
+                .where_id(TP::always())
+                .order_by_id(OrderDirection::Descending)
+                .limit(2),
+        );
+        query_and_assert(&db, q, &[1, 11, 12]);
+
+        // Leading set operations start from the ents found by the query, or
+        // from all ents if removing them
+        let q = Query::default().union(Query::default().where_id(TP::equals(999)));
+        query_and_assert(&db, q, &[]);
+        let q = Query::default().except(Query::default().where_id(TP::greater_than(2)));
+        query_and_assert(&db, q, &[1, 2]);
+
+        // Nested set operations check whether the ent would remain
+        let q = Query::default()
+            .where_id(TP::less_than(7))
+            .where_not(Filter::intersect(
+                Query::default().where_field("a", P::greater_than(2)),
+            ));
+        query_and_assert(&db, q, &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn traverse_should_follow_edges_of_ents() {
+        let db = new_db();
+        for (id, friends) in vec![(1, vec![2, 3]), (2, vec![1, 3]), (3, vec![4]), (4, vec![])] {
+            let _ = db
+                .insert(Box::from(UntypedEnt::from_collections(
+                    id,
+                    vec![Field::new("n", id as u32)],
+                    vec![Edge::new("friends", friends)],
+                )))
+                .unwrap();
+        }
+
+        // Ents within the max depth are reached once each
+        let t = Traversal::new(vec![1]).follow("friends").max_depth(2);
+        assert_eq!(db.traverse_ids(t).unwrap(), vec![2, 3, 4]);
+
+        // Ents are only reached through an edge if they pass its filter
+        let t = Traversal::new(vec![1])
+            .follow_where("friends", Filter::where_field("n", P::greater_than(2u32)));
+        assert_eq!(db.traverse_ids(t).unwrap(), vec![3, 4]);
+
+        let t = Traversal::new(vec![1]).follow_where(
+            "friends",
+            Filter::where_field("n", P::greater_than(1u32))
+                & Filter::where_field("n", P::less_than(4u32)),
+        );
+        assert_eq!(db.traverse_ids(t).unwrap(), vec![2, 3]);
+
+        let ents = db
+            .traverse_ents(Traversal::new(vec![2]).follow("friends").min_depth(2))
+            .unwrap();
+        assert_eq!(
+            ents.iter().map(|ent| ent.id()).collect::<Vec<Id>>(),
+            vec![4]
+        );
+
+        // Shortest paths are found breadth first, if any exist
+        let t = Traversal::new(vec![2]).follow("friends").depth_first();
+        let path = db.shortest_path(t, 4).unwrap().map(Path::into_ids);
+        assert_eq!(path, Some(vec![2, 3, 4]));
+
+        let t = Traversal::new(vec![4]).follow("friends");
+        assert_eq!(db.shortest_path(t, 1).unwrap(), None);
+    }
+
+    #[test]
+    fn find_all_should_use_indexes_for_combined_filters() {
+        let db = new_indexed_test_database();
+
+        // Any filter can only be served by indexes if each filter can be
+        let filter = Filter::or(vec![
+            Filter::where_field("n", P::equals(2u32)),
+            Filter::where_field("n", P::IsNone),
+        ]);
+        let mut ids: Vec<Id> = indexed_ids(&db, &filter)
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![2, 5]);
+        query_and_assert(&db, Query::default().chain(filter), &[2, 5]);
+
+        let filter = Filter::or(vec![
+            Filter::where_field("n", P::equals(2u32)),
+            Filter::where_id(TP::equals(3)),
+        ]);
+        assert_eq!(indexed_ids(&db, &filter).unwrap(), None);
+        query_and_assert(&db, Query::default().chain(filter), &[2, 3]);
+
+        // All filters are served by any indexes that can serve one of them
+        let filter = Filter::and(vec![
+            Filter::where_field("n", P::greater_than(0)),
+            Filter::where_id(TP::not_equals(2)),
+        ]);
+        let mut ids: Vec<Id> = indexed_ids(&db, &filter)
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![1, 2, 3]);
+        query_and_assert(&db, Query::default().chain(filter), &[1, 3]);
+    }
+
     #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
     struct TestEnt(Id);
 
@@ -85,6 +85,7 @@ struct EntDerive {
     ent_builder: bool,
     ent_loader: bool,
     ent_query: bool,
+    ent_schema: bool,
     ent_typed_fields: bool,
     ent_typed_edges: bool,
     ent_wrapper: bool,
@@ -111,6 +112,7 @@ impl From<PathList> for EntDerive {
                 "entbuilder" => d.ent_builder = true,
                 "entloader" => d.ent_loader = true,
                 "entquery" => d.ent_query = true,
+                "entschema" => d.ent_schema = true,
                 "enttypedfields" => d.ent_typed_fields = true,
                 "enttypededges" => d.ent_typed_edges = true,
                 "entwrapper" => d.ent_wrapper = true,
@@ -209,6 +211,10 @@ pub fn do_simple_ent(
                 derive_paths.push(parse_quote!(#root::EntLoader));
             }
 
+            if !ent_derive.ent_schema {
+                derive_paths.push(parse_quote!(#root::EntSchema));
+            }
+
             if !ent_derive.ent_typed_fields {
                 derive_paths.push(parse_quote!(#root::EntTypedFields));
             }
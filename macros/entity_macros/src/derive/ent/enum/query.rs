@@ -123,6 +123,90 @@ pub fn do_derive_ent_query(root: Path, ent: EnumEnt) -> TokenStream {
             pub fn where_field(self, name: &::std::primitive::str, p: #root::Predicate) -> Self {
                 Self(self.0.where_field(name, p), #(#default_phantoms),*)
             }
+
+            #[doc = "Orders the returned ents by id in the given direction"]
+            pub fn order_by_id(self, direction: #root::OrderDirection) -> Self {
+                Self(self.0.order_by_id(direction), #(#default_phantoms),*)
+            }
+
+            #[doc = "Orders the returned ents by created timestamp in the given direction"]
+            pub fn order_by_created(self, direction: #root::OrderDirection) -> Self {
+                Self(self.0.order_by_created(direction), #(#default_phantoms),*)
+            }
+
+            #[doc = "Orders the returned ents by last updated timestamp in the given direction"]
+            pub fn order_by_last_updated(self, direction: #root::OrderDirection) -> Self {
+                Self(self.0.order_by_last_updated(direction), #(#default_phantoms),*)
+            }
+
+            #[doc = "Orders the returned ents by field in the given direction"]
+            pub fn order_by_field(
+                self,
+                name: &::std::primitive::str,
+                direction: #root::OrderDirection,
+            ) -> Self {
+                Self(self.0.order_by_field(name, direction), #(#default_phantoms),*)
+            }
+
+            #[doc = "Limits the query to return at most `limit` ents"]
+            pub fn limit(self, limit: ::std::primitive::usize) -> Self {
+                Self(self.0.limit(limit), #(#default_phantoms),*)
+            }
+
+            #[doc = "Skips the first `offset` ents that would be returned by the query"]
+            pub fn offset(self, offset: ::std::primitive::usize) -> Self {
+                Self(self.0.offset(offset), #(#default_phantoms),*)
+            }
+
+            #[doc = "Begins the results of the query after the ent at the cursor's position"]
+            pub fn after(self, cursor: #root::Cursor) -> Self {
+                Self(self.0.after(cursor), #(#default_phantoms),*)
+            }
+
+            #[doc = "Filters to return all ents that pass the filters of all of the given queries"]
+            pub fn where_and(self, queries: ::std::vec::Vec<Self>) -> Self {
+                Self(
+                    self.0.where_and(::std::iter::Iterator::map(
+                        ::std::iter::IntoIterator::into_iter(queries),
+                        |q| #root::Filter::And(#root::Query::into_filters(q.0)),
+                    )),
+                    #(#default_phantoms),*
+                )
+            }
+
+            #[doc = "Filters to return all ents that pass the filters of any of the given queries"]
+            pub fn where_or(self, queries: ::std::vec::Vec<Self>) -> Self {
+                Self(
+                    self.0.where_or(::std::iter::Iterator::map(
+                        ::std::iter::IntoIterator::into_iter(queries),
+                        |q| #root::Filter::And(#root::Query::into_filters(q.0)),
+                    )),
+                    #(#default_phantoms),*
+                )
+            }
+
+            #[doc = "Filters to return all ents that do not pass the filters of the given query"]
+            pub fn where_not(self, query: Self) -> Self {
+                Self(
+                    self.0.where_not(#root::Filter::And(#root::Query::into_filters(query.0))),
+                    #(#default_phantoms),*
+                )
+            }
+
+            #[doc = "Combines with all ents returned by the given query"]
+            pub fn union(self, query: Self) -> Self {
+                Self(self.0.union(query.0), #(#default_phantoms),*)
+            }
+
+            #[doc = "Filters to return only ents also returned by the given query"]
+            pub fn intersect(self, query: Self) -> Self {
+                Self(self.0.intersect(query.0), #(#default_phantoms),*)
+            }
+
+            #[doc = "Filters to return only ents not returned by the given query"]
+            pub fn except(self, query: Self) -> Self {
+                Self(self.0.except(query.0), #(#default_phantoms),*)
+            }
         }
 
         #[automatically_derived]
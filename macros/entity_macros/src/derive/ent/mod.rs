@@ -76,6 +76,16 @@ pub fn do_derive_ent_query(root: Path, input: DeriveInput) -> darling::Result<To
     }
 }
 
+pub fn do_derive_ent_schema(root: Path, input: DeriveInput) -> darling::Result<TokenStream> {
+    match &input.data {
+        Data::Struct(_) => {
+            r#struct::do_derive_ent_schema(root, StructEnt::from_derive_input(&input)?)
+        }
+        Data::Enum(_) => Err(darling::Error::custom("Enums are not supported").with_span(&input)),
+        Data::Union(_) => Err(darling::Error::custom("Unions are not supported").with_span(&input)),
+    }
+}
+
 pub fn do_derive_ent_type(root: Path, input: DeriveInput) -> darling::Result<TokenStream> {
     match &input.data {
         Data::Struct(_) => Ok(r#struct::do_derive_ent_type(
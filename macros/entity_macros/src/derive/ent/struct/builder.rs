@@ -1,3 +1,4 @@
+use crate::utils;
 use entity_macros_data::StructEnt;
 use heck::CamelCase;
 use proc_macro2::TokenStream;
@@ -22,6 +23,7 @@ pub fn do_derive_ent_builder(root: Path, ent: StructEnt) -> TokenStream {
     let mut error_variant_field_names = Vec::new();
     let mut build_assignments = Vec::new();
     let mut has_normal_struct_field = false;
+    let mut has_validated_field = false;
 
     push_id_field(
         &root,
@@ -64,11 +66,11 @@ pub fn do_derive_ent_builder(root: Path, ent: StructEnt) -> TokenStream {
         &mut struct_setters,
     );
 
-    for (name, ty, is_computed) in ent
+    for (name, ty, is_computed, validation) in ent
         .fields
         .iter()
-        .map(|f| (&f.name, &f.ty, f.computed.is_some()))
-        .chain(ent.edges.iter().map(|e| (&e.name, &e.ty, false)))
+        .map(|f| (&f.name, &f.ty, f.computed.is_some(), f.validation.as_ref()))
+        .chain(ent.edges.iter().map(|e| (&e.name, &e.ty, false, None)))
     {
         if !is_computed {
             has_normal_struct_field = true;
@@ -77,9 +79,22 @@ pub fn do_derive_ent_builder(root: Path, ent: StructEnt) -> TokenStream {
             struct_field_defaults.push(quote!(::std::option::Option::None));
 
             let error_variant = format_ident!("Missing{}", name.to_string().to_camel_case());
-            build_assignments.push(quote! {
-                #name: self.#name.ok_or(#builder_error_name::#error_variant)?
-            });
+            if let Some(validation) = validation {
+                has_validated_field = true;
+                let check =
+                    utils::validate_field_value(&root, name, ty, validation, quote!(&value));
+                build_assignments.push(quote! {
+                    #name: {
+                        let value = self.#name.ok_or(#builder_error_name::#error_variant)?;
+                        #check.map_err(#builder_error_name::Invalid)?;
+                        value
+                    }
+                });
+            } else {
+                build_assignments.push(quote! {
+                    #name: self.#name.ok_or(#builder_error_name::#error_variant)?
+                });
+            }
             error_variants.push(error_variant);
             error_variant_field_names.push(name);
 
@@ -96,6 +111,29 @@ pub fn do_derive_ent_builder(root: Path, ent: StructEnt) -> TokenStream {
         }
     }
 
+    // Validation failures carry the mutation error describing them, which
+    // cannot be copied or compared, so those traits are only derived when
+    // no field is validated
+    let (error_derives, invalid_variant, invalid_display) = if has_validated_field {
+        (
+            quote!(::std::fmt::Debug),
+            quote!(Invalid(#root::EntMutationError),),
+            quote!(Self::Invalid(x) => ::std::write!(f, "{}", x),),
+        )
+    } else {
+        (
+            quote! {
+                ::std::marker::Copy,
+                ::std::clone::Clone,
+                ::std::fmt::Debug,
+                ::std::cmp::PartialEq,
+                ::std::cmp::Eq,
+            },
+            quote!(),
+            quote!(),
+        )
+    };
+
     let display_fmt_inner = if has_normal_struct_field {
         quote! {
             match self {
@@ -105,6 +143,7 @@ pub fn do_derive_ent_builder(root: Path, ent: StructEnt) -> TokenStream {
                         concat!("Missing ", ::std::stringify!(#error_variant_field_names)),
                     ),
                 )*
+                #invalid_display
             }
         }
     } else {
@@ -112,17 +151,12 @@ pub fn do_derive_ent_builder(root: Path, ent: StructEnt) -> TokenStream {
     };
 
     quote! {
-        #[derive(
-            ::std::marker::Copy,
-            ::std::clone::Clone,
-            ::std::fmt::Debug,
-            ::std::cmp::PartialEq,
-            ::std::cmp::Eq,
-        )]
+        #[derive(#error_derives)]
         #[automatically_derived]
         #[allow(clippy::enum_variant_names)]
         #vis enum #builder_error_name {
-            #(#error_variants),*
+            #(#error_variants,)*
+            #invalid_variant
         }
 
         #[automatically_derived]
@@ -9,10 +9,20 @@ pub fn do_derive_ent_typed_edges(root: Path, ent: StructEnt) -> TokenStream {
     let mut edge_methods: Vec<TokenStream> = Vec::new();
     let (impl_generics, ty_generics, where_clause) = ent.generics.split_for_impl();
 
-    for edge in ent.edges {
-        edge_methods.push(fn_typed_id_getter(&edge));
-        edge_methods.push(fn_typed_id_setter(&edge));
-        edge_methods.push(fn_typed_load_edge(&root, &edge));
+    for edge in &ent.edges {
+        edge_methods.push(fn_typed_id_getter(edge));
+        edge_methods.push(fn_typed_id_setter(edge));
+        edge_methods.push(fn_typed_load_edge(&root, edge, false));
+        edge_methods.push(fn_typed_load_edge(&root, edge, true));
+
+        if edge.inverse {
+            edge_methods.push(fn_typed_load_inverse_edge(&root, edge));
+        }
+
+        if let Some(data_ty) = &edge.data_ty {
+            edge_methods.push(fn_typed_assoc_data_getter(&root, &ent, edge, data_ty));
+            edge_methods.push(fn_typed_assoc_data_setter(&root, &ent, edge, data_ty));
+        }
     }
 
     quote! {
@@ -68,39 +78,69 @@ fn fn_typed_id_setter(edge: &StructEntEdge) -> TokenStream {
     }
 }
 
-fn fn_typed_load_edge(root: &Path, edge: &StructEntEdge) -> TokenStream {
+fn fn_typed_load_edge(root: &Path, edge: &StructEntEdge, is_async: bool) -> TokenStream {
+    let method_name = if is_async {
+        format_ident!("load_{}_async", edge.name)
+    } else {
+        format_ident!("load_{}", edge.name)
+    };
+    let load = LoadEdge::new(root, &edge.name, is_async);
+
     match edge.kind {
-        StructEntEdgeKind::Maybe => fn_typed_load_edge_of_maybe(
-            root,
-            &format_ident!("load_{}", edge.name),
-            &edge.name,
-            &edge.ent_ty,
-            edge.wrap,
-        ),
-        StructEntEdgeKind::One => fn_typed_load_edge_of_one(
-            root,
-            &format_ident!("load_{}", edge.name),
-            &edge.name,
-            &edge.ent_ty,
-            edge.wrap,
-        ),
-        StructEntEdgeKind::Many => fn_typed_load_edge_of_many(
-            root,
-            &format_ident!("load_{}", edge.name),
-            &edge.name,
-            &edge.ent_ty,
-            edge.wrap,
-        ),
+        StructEntEdgeKind::Maybe => {
+            fn_typed_load_edge_of_maybe(root, &method_name, &load, &edge.ent_ty, edge.wrap)
+        }
+        StructEntEdgeKind::One => {
+            fn_typed_load_edge_of_one(root, &method_name, &load, &edge.ent_ty, edge.wrap)
+        }
+        StructEntEdgeKind::Many => {
+            fn_typed_load_edge_of_many(root, &method_name, &load, &edge.ent_ty, edge.wrap)
+        }
+    }
+}
+
+/// Pieces of a typed edge loading method that differ between its blocking
+/// and asynchronous forms
+struct LoadEdge {
+    edge_name: Ident,
+    asyncness: TokenStream,
+    load_ents: TokenStream,
+}
+
+impl LoadEdge {
+    fn new(root: &Path, edge_name: &Ident, is_async: bool) -> Self {
+        let (asyncness, load_ents) = if is_async {
+            (
+                quote!(async),
+                quote!(#root::Ent::load_edge_async(self, ::std::stringify!(#edge_name)).await?),
+            )
+        } else {
+            (
+                quote!(),
+                quote!(#root::Ent::load_edge(self, ::std::stringify!(#edge_name))?),
+            )
+        };
+
+        Self {
+            edge_name: edge_name.clone(),
+            asyncness,
+            load_ents,
+        }
     }
 }
 
 fn fn_typed_load_edge_of_maybe(
     root: &Path,
     method_name: &Ident,
-    edge_name: &Ident,
+    load: &LoadEdge,
     edge_type: &Type,
     wrap: bool,
 ) -> TokenStream {
+    let LoadEdge {
+        edge_name,
+        asyncness,
+        load_ents,
+    } = load;
     let filter_map = if wrap {
         quote!(<#edge_type as #root::EntWrapper>::wrap_ent(ent))
     } else {
@@ -108,8 +148,8 @@ fn fn_typed_load_edge_of_maybe(
     };
 
     quote! {
-        pub fn #method_name(&self) -> #root::DatabaseResult<::std::option::Option<#edge_type>> {
-            let ents = #root::Ent::load_edge(self, ::std::stringify!(#edge_name))?;
+        pub #asyncness fn #method_name(&self) -> #root::DatabaseResult<::std::option::Option<#edge_type>> {
+            let ents = #load_ents;
             let typed_ents: ::std::vec::Vec<#edge_type> = ::std::iter::Iterator::collect(
                 ::std::iter::Iterator::filter_map(
                     ::std::iter::IntoIterator::into_iter(ents),
@@ -134,10 +174,15 @@ fn fn_typed_load_edge_of_maybe(
 fn fn_typed_load_edge_of_one(
     root: &Path,
     method_name: &Ident,
-    edge_name: &Ident,
+    load: &LoadEdge,
     edge_type: &Type,
     wrap: bool,
 ) -> TokenStream {
+    let LoadEdge {
+        edge_name,
+        asyncness,
+        load_ents,
+    } = load;
     let filter_map = if wrap {
         quote!(<#edge_type as #root::EntWrapper>::wrap_ent(ent))
     } else {
@@ -145,8 +190,8 @@ fn fn_typed_load_edge_of_one(
     };
 
     quote! {
-        pub fn #method_name(&self) -> #root::DatabaseResult<#edge_type> {
-            let ents = #root::Ent::load_edge(self, ::std::stringify!(#edge_name))?;
+        pub #asyncness fn #method_name(&self) -> #root::DatabaseResult<#edge_type> {
+            let ents = #load_ents;
             let typed_ents: ::std::vec::Vec<#edge_type> =
                 ::std::iter::Iterator::collect(
                     ::std::iter::Iterator::filter_map(
@@ -172,10 +217,15 @@ fn fn_typed_load_edge_of_one(
 fn fn_typed_load_edge_of_many(
     root: &Path,
     method_name: &Ident,
-    edge_name: &Ident,
+    load: &LoadEdge,
     edge_type: &Type,
     wrap: bool,
 ) -> TokenStream {
+    let LoadEdge {
+        asyncness,
+        load_ents,
+        ..
+    } = load;
     let filter_map = if wrap {
         quote!(<#edge_type as #root::EntWrapper>::wrap_ent(ent))
     } else {
@@ -183,8 +233,8 @@ fn fn_typed_load_edge_of_many(
     };
 
     quote! {
-        pub fn #method_name(&self) -> #root::DatabaseResult<::std::vec::Vec<#edge_type>> {
-            let ents = #root::Ent::load_edge(self, ::std::stringify!(#edge_name))?;
+        pub #asyncness fn #method_name(&self) -> #root::DatabaseResult<::std::vec::Vec<#edge_type>> {
+            let ents = #load_ents;
             let typed_ents: ::std::vec::Vec<#edge_type> =
                 ::std::iter::Iterator::collect(
                     ::std::iter::Iterator::filter_map(
@@ -196,3 +246,138 @@ fn fn_typed_load_edge_of_many(
         }
     }
 }
+
+fn fn_typed_load_inverse_edge(root: &Path, edge: &StructEntEdge) -> TokenStream {
+    let edge_name = &edge.name;
+    let method_name = format_ident!("load_{}_inverse", edge_name);
+    let method_with_db_name = format_ident!("load_{}_inverse_with_db", edge_name);
+    let doc_string = format!(
+        "Loads all ents of this type whose {} edge contains the id",
+        edge_name,
+    );
+    let doc_with_db_string = format!(
+        "Loads all ents of this type from the database whose {} edge contains the id",
+        edge_name,
+    );
+
+    quote! {
+        #[doc = #doc_string]
+        pub fn #method_name(id: #root::Id) -> #root::DatabaseResult<::std::vec::Vec<Self>> {
+            Self::#method_with_db_name(#root::global::db(), id)
+        }
+
+        #[doc = #doc_with_db_string]
+        pub fn #method_with_db_name(
+            db: #root::WeakDatabaseRc,
+            id: #root::Id,
+        ) -> #root::DatabaseResult<::std::vec::Vec<Self>> {
+            let database = #root::WeakDatabaseRc::upgrade(&db)
+                .ok_or(#root::DatabaseError::Disconnected)?;
+
+            #root::DatabaseExt::find_all_typed::<Self>(
+                ::std::convert::AsRef::<dyn #root::Database>::as_ref(
+                    ::std::convert::AsRef::<
+                        ::std::boxed::Box<dyn #root::Database>
+                    >::as_ref(&database),
+                ),
+                #root::Query::default()
+                    .where_id(#root::TypedPredicate::equals(id))
+                    .where_into_inverse_edge(::std::stringify!(#edge_name)),
+            )
+        }
+    }
+}
+
+fn fn_typed_assoc_data_getter(
+    root: &Path,
+    ent: &StructEnt,
+    edge: &StructEntEdge,
+    data_ty: &Type,
+) -> TokenStream {
+    let ident_database = &ent.database;
+    let ident_id = &ent.id;
+    let edge_name = &edge.name;
+    let method_name = format_ident!("{}_data", edge_name);
+    let doc_string = format!(
+        "Loads the data associated with the ent with the id on the {} edge",
+        edge_name,
+    );
+
+    quote! {
+        #[doc = #doc_string]
+        pub fn #method_name(
+            &self,
+            id: #root::Id,
+        ) -> #root::DatabaseResult<::std::option::Option<#data_ty>> {
+            let database = #root::WeakDatabaseRc::upgrade(
+                &self.#ident_database
+            ).ok_or(#root::DatabaseError::Disconnected)?;
+            let assocs = #root::Database::assocs(
+                ::std::convert::AsRef::<dyn #root::Database>::as_ref(
+                    ::std::convert::AsRef::<
+                        ::std::boxed::Box<dyn #root::Database>
+                    >::as_ref(&database),
+                ),
+                self.#ident_id,
+                ::std::stringify!(#edge_name),
+            )?;
+
+            match ::std::iter::Iterator::find(
+                &mut ::std::iter::IntoIterator::into_iter(assocs),
+                |assoc| assoc.id() == id,
+            ).and_then(#root::Assoc::into_data) {
+                ::std::option::Option::Some(value) =>
+                    <#data_ty as #root::ValueLike>::try_from_value(value)
+                        .map(::std::option::Option::Some)
+                        .map_err(|_| #root::DatabaseError::BrokenEdge {
+                            name: ::std::string::ToString::to_string(
+                                ::std::stringify!(#edge_name),
+                            ),
+                        }),
+                ::std::option::Option::None => ::std::result::Result::Ok(
+                    ::std::option::Option::None,
+                ),
+            }
+        }
+    }
+}
+
+fn fn_typed_assoc_data_setter(
+    root: &Path,
+    ent: &StructEnt,
+    edge: &StructEntEdge,
+    data_ty: &Type,
+) -> TokenStream {
+    let ident_database = &ent.database;
+    let ident_id = &ent.id;
+    let edge_name = &edge.name;
+    let method_name = format_ident!("set_{}_data", edge_name);
+    let doc_string = format!(
+        "Associates data with the ent with the id on the stored {} edge, returning false if the edge does not contain the id",
+        edge_name,
+    );
+
+    quote! {
+        #[doc = #doc_string]
+        pub fn #method_name(
+            &self,
+            id: #root::Id,
+            data: #data_ty,
+        ) -> #root::DatabaseResult<::std::primitive::bool> {
+            let database = #root::WeakDatabaseRc::upgrade(
+                &self.#ident_database
+            ).ok_or(#root::DatabaseError::Disconnected)?;
+            #root::Database::set_assoc_data(
+                ::std::convert::AsRef::<dyn #root::Database>::as_ref(
+                    ::std::convert::AsRef::<
+                        ::std::boxed::Box<dyn #root::Database>
+                    >::as_ref(&database),
+                ),
+                self.#ident_id,
+                ::std::stringify!(#edge_name),
+                id,
+                #root::ValueLike::into_value(data),
+            )
+        }
+    }
+}
@@ -62,13 +62,23 @@ pub fn do_derive_ent(root: Path, ent: StructEnt) -> darling::Result<TokenStream>
                     }
                 ))
             } else {
+                let validate = f.validation.as_ref().map(|validation| {
+                    let check = utils::validate_field_value(
+                        &root,
+                        field_name,
+                        field_ty,
+                        validation,
+                        quote!(&converted),
+                    );
+                    quote!(#check?;)
+                });
                 quote!({
                     let old_value = ::std::clone::Clone::clone(&self.#field_name);
                     let converted: ::std::result::Result<
                         #field_ty,
                         #root::Value,
                     > = #v_to_tf;
-                    self.#field_name = converted.map_err(
+                    let converted = converted.map_err(
                         |_| #root::EntMutationError::WrongValueType {
                             description: ::std::string::ToString::to_string(
                                 ::std::concat!(
@@ -78,6 +88,8 @@ pub fn do_derive_ent(root: Path, ent: StructEnt) -> darling::Result<TokenStream>
                             )
                         }
                     )?;
+                    #validate
+                    self.#field_name = converted;
                     ::std::result::Result::Ok(#root::ValueLike::into_value(old_value))
                 })
             };
@@ -139,10 +151,14 @@ pub fn do_derive_ent(root: Path, ent: StructEnt) -> darling::Result<TokenStream>
             }
 
             fn mark_updated(&mut self) -> ::std::result::Result<(), #root::EntMutationError> {
-                self.#ident_last_updated = ::std::time::SystemTime::now()
+                let now = ::std::time::SystemTime::now()
                     .duration_since(::std::time::UNIX_EPOCH)
                     .map_err(|e| #root::EntMutationError::MarkUpdatedFailed { source: e })?
                     .as_millis() as ::std::primitive::u64;
+                self.#ident_last_updated = ::std::cmp::max(
+                    now,
+                    ::std::primitive::u64::saturating_add(self.#ident_last_updated, 1),
+                );
                 ::std::result::Result::Ok(())
             }
 
@@ -307,12 +323,13 @@ pub fn do_derive_ent(root: Path, ent: StructEnt) -> darling::Result<TokenStream>
                 let database = #root::WeakDatabaseRc::upgrade(
                     &self.#ident_database
                 ).ok_or(#root::DatabaseError::Disconnected)?;
-                match #root::Database::insert(
-                    ::std::convert::AsRef::<dyn #root::Database>::as_ref(
-                        ::std::convert::AsRef::<
-                            ::std::boxed::Box<dyn #root::Database>
-                        >::as_ref(&database),
-                    ),
+                let database = ::std::convert::AsRef::<dyn #root::Database>::as_ref(
+                    ::std::convert::AsRef::<
+                        ::std::boxed::Box<dyn #root::Database>
+                    >::as_ref(&database),
+                );
+                match #root::Database::compare_and_insert(
+                    database,
                     ::std::boxed::Box::new(
                         ::std::clone::Clone::clone(
                             ::std::ops::Deref::deref(&self)
@@ -321,6 +338,13 @@ pub fn do_derive_ent(root: Path, ent: StructEnt) -> darling::Result<TokenStream>
                 ) {
                     ::std::result::Result::Ok(id) => {
                         #root::Ent::set_id(self, id);
+                        if let ::std::option::Option::Some(ent) =
+                            #root::Database::get(database, id)?
+                        {
+                            self.#ident_last_updated = #root::Ent::last_updated(
+                                ::std::convert::AsRef::<dyn #root::Ent>::as_ref(&ent),
+                            );
+                        }
                         ::std::result::Result::Ok(())
                     }
                     ::std::result::Result::Err(x) => ::std::result::Result::Err(x),
@@ -344,7 +368,7 @@ pub fn do_derive_ent(root: Path, ent: StructEnt) -> darling::Result<TokenStream>
     })
 }
 
-fn make_field_definitions(
+pub(super) fn make_field_definitions(
     root: &Path,
     fields: &[StructEntField],
 ) -> darling::Result<Vec<TokenStream>> {
@@ -375,6 +399,10 @@ fn make_field_definitions(
             attrs.push(quote! { #root::FieldAttribute::Computed });
         }
 
+        if f.unique {
+            attrs.push(quote! { #root::FieldAttribute::Unique });
+        }
+
         token_streams.push(quote! {
             #root::FieldDefinition::new_with_attributes(
                 ::std::stringify!(#name),
@@ -518,7 +546,7 @@ fn make_field_value_type(root: &Path, r#type: &Type) -> darling::Result<TokenStr
     })
 }
 
-fn make_edge_definitions(root: &Path, edges: &[StructEntEdge]) -> Vec<TokenStream> {
+pub(super) fn make_edge_definitions(root: &Path, edges: &[StructEntEdge]) -> Vec<TokenStream> {
     let mut token_streams = Vec::new();
 
     for e in edges {
@@ -536,12 +564,17 @@ fn make_edge_definitions(root: &Path, edges: &[StructEntEdge]) -> Vec<TokenStrea
             StructEntEdgeDeletionPolicy::Nothing => quote! { #root::EdgeDeletionPolicy::Nothing },
         };
 
+        let inverse = e
+            .inverse_edge
+            .as_ref()
+            .map(|inverse| quote! { .with_inverse(#inverse) });
+
         token_streams.push(quote! {
             #root::EdgeDefinition::new_with_deletion_policy(
                 ::std::stringify!(#name),
                 #ty,
                 #deletion_policy,
-            )
+            )#inverse
         });
     }
 
@@ -1,9 +1,10 @@
+use crate::utils;
 use entity_macros_data::StructEnt;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 use syn::Path;
 
-pub fn do_derive_ent_typed_fields(_root: Path, ent: StructEnt) -> TokenStream {
+pub fn do_derive_ent_typed_fields(root: Path, ent: StructEnt) -> TokenStream {
     let name = &ent.ident;
     let mut field_methods: Vec<TokenStream> = Vec::new();
     let (impl_generics, ty_generics, where_clause) = ent.generics.split_for_impl();
@@ -57,9 +58,31 @@ pub fn do_derive_ent_typed_fields(_root: Path, ent: StructEnt) -> TokenStream {
 
             if field.mutable {
                 let setter_name = format_ident!("set_{}", field_name);
-                let setter = quote! {
-                    pub fn #setter_name(&mut self, x: #field_type) -> #field_type {
-                        ::std::mem::replace(&mut self.#field_name, x)
+
+                // If the field is validated, the setter reports whether the
+                // new value is invalid instead of always replacing the old
+                let setter = if let Some(validation) = field.validation.as_ref() {
+                    let check = utils::validate_field_value(
+                        &root,
+                        field_name,
+                        field_type,
+                        validation,
+                        quote!(&x),
+                    );
+                    quote! {
+                        pub fn #setter_name(
+                            &mut self,
+                            x: #field_type,
+                        ) -> ::std::result::Result<#field_type, #root::EntMutationError> {
+                            #check?;
+                            ::std::result::Result::Ok(::std::mem::replace(&mut self.#field_name, x))
+                        }
+                    }
+                } else {
+                    quote! {
+                        pub fn #setter_name(&mut self, x: #field_type) -> #field_type {
+                            ::std::mem::replace(&mut self.#field_name, x)
+                        }
                     }
                 };
                 field_methods.push(setter);
@@ -5,6 +5,7 @@ mod ent;
 mod field;
 mod loader;
 mod query;
+mod schema;
 mod r#type;
 
 pub use builder::do_derive_ent_builder;
@@ -15,3 +16,4 @@ pub use field::do_derive_ent_typed_fields;
 pub use loader::do_derive_ent_loader;
 pub use query::do_derive_ent_query;
 pub use r#type::do_derive_ent_type;
+pub use schema::do_derive_ent_schema;
@@ -48,10 +48,49 @@ pub fn do_derive_ent_query(root: Path, ent: StructEnt) -> darling::Result<TokenS
         }
     });
 
+    let method_name = format_ident!("order_by_{}", ent.id);
+    methods.push(quote! {
+        #[doc = "Orders the returned ents by id in the given direction"]
+        pub fn #method_name(self, direction: #root::OrderDirection) -> Self {
+            Self(self.0.order_by_id(direction), #(#default_phantoms),*)
+        }
+    });
+
+    let method_name = format_ident!("order_by_{}", ent.created);
+    methods.push(quote! {
+        #[doc = "Orders the returned ents by created timestamp in the given direction"]
+        pub fn #method_name(self, direction: #root::OrderDirection) -> Self {
+            Self(self.0.order_by_created(direction), #(#default_phantoms),*)
+        }
+    });
+
+    let method_name = format_ident!("order_by_{}", ent.last_updated);
+    methods.push(quote! {
+        #[doc = "Orders the returned ents by last updated timestamp in the given direction"]
+        pub fn #method_name(self, direction: #root::OrderDirection) -> Self {
+            Self(self.0.order_by_last_updated(direction), #(#default_phantoms),*)
+        }
+    });
+
     for f in &ent.fields {
         let name = &f.name;
         let ty = &f.ty;
 
+        let method_name = format_ident!("order_by_{}", name);
+        let doc_string = format!(
+            "Orders the returned ents by the field \"{}\" in the given direction",
+            name
+        );
+        methods.push(quote! {
+            #[doc = #doc_string]
+            pub fn #method_name(self, direction: #root::OrderDirection) -> Self {
+                Self(
+                    self.0.order_by_field(::std::stringify!(#name), direction),
+                    #(#default_phantoms),*
+                )
+            }
+        });
+
         let method_name = format_ident!("where_{}", name);
         let predicate_type = if utils::is_map_type(ty) {
             let value_ty = match ty {
@@ -205,6 +244,66 @@ pub fn do_derive_ent_query(root: Path, ent: StructEnt) -> darling::Result<TokenS
                 <Self as ::std::default::Default>::default()
             }
 
+            /// Limits the query to return at most `limit` ents
+            pub fn limit(self, limit: ::std::primitive::usize) -> Self {
+                Self(self.0.limit(limit), #(#default_phantoms),*)
+            }
+
+            /// Skips the first `offset` ents that would be returned by the query
+            pub fn offset(self, offset: ::std::primitive::usize) -> Self {
+                Self(self.0.offset(offset), #(#default_phantoms),*)
+            }
+
+            /// Begins the results of the query after the ent at the cursor's position
+            pub fn after(self, cursor: #root::Cursor) -> Self {
+                Self(self.0.after(cursor), #(#default_phantoms),*)
+            }
+
+            /// Filters to return all ents that pass the filters of all of the given queries
+            pub fn where_and(self, queries: ::std::vec::Vec<Self>) -> Self {
+                Self(
+                    self.0.where_and(::std::iter::Iterator::map(
+                        ::std::iter::IntoIterator::into_iter(queries),
+                        |q| #root::Filter::And(#root::Query::into_filters(q.0)),
+                    )),
+                    #(#default_phantoms),*
+                )
+            }
+
+            /// Filters to return all ents that pass the filters of any of the given queries
+            pub fn where_or(self, queries: ::std::vec::Vec<Self>) -> Self {
+                Self(
+                    self.0.where_or(::std::iter::Iterator::map(
+                        ::std::iter::IntoIterator::into_iter(queries),
+                        |q| #root::Filter::And(#root::Query::into_filters(q.0)),
+                    )),
+                    #(#default_phantoms),*
+                )
+            }
+
+            /// Filters to return all ents that do not pass the filters of the given query
+            pub fn where_not(self, query: Self) -> Self {
+                Self(
+                    self.0.where_not(#root::Filter::And(#root::Query::into_filters(query.0))),
+                    #(#default_phantoms),*
+                )
+            }
+
+            /// Combines with all ents returned by the given query
+            pub fn union(self, query: Self) -> Self {
+                Self(self.0.union(query.0), #(#default_phantoms),*)
+            }
+
+            /// Filters to return only ents also returned by the given query
+            pub fn intersect(self, query: Self) -> Self {
+                Self(self.0.intersect(query.0), #(#default_phantoms),*)
+            }
+
+            /// Filters to return only ents not returned by the given query
+            pub fn except(self, query: Self) -> Self {
+                Self(self.0.except(query.0), #(#default_phantoms),*)
+            }
+
             #(#methods)*
         }
 
@@ -0,0 +1,38 @@
+use super::ent::{make_edge_definitions, make_field_definitions};
+use entity_macros_data::StructEnt;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Path;
+
+pub fn do_derive_ent_schema(root: Path, ent: StructEnt) -> darling::Result<TokenStream> {
+    let name = &ent.ident;
+    let (impl_generics, ty_generics, where_clause) = ent.generics.split_for_impl();
+
+    let field_definitions = make_field_definitions(&root, &ent.fields)?;
+    let edge_schemas: Vec<TokenStream> = make_edge_definitions(&root, &ent.edges)
+        .into_iter()
+        .zip(ent.edges.iter())
+        .map(|(definition, e)| {
+            let ent_ty = &e.ent_ty;
+            quote! {
+                #root::EdgeSchema::new(
+                    #definition,
+                    <#ent_ty as #root::EntType>::type_str(),
+                )
+            }
+        })
+        .collect();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #root::EntSchema for #name #ty_generics #where_clause {
+            fn schema() -> #root::TypeSchema {
+                #root::TypeSchema::new(
+                    <Self as #root::EntType>::type_str(),
+                    ::std::vec![#(#field_definitions),*],
+                    ::std::vec![#(#edge_schemas),*],
+                )
+            }
+        }
+    })
+}
@@ -9,6 +9,15 @@ pub fn do_derive_ent_type(root: Path, ent: StructEnt) -> TokenStream {
     let (impl_generics, ty_generics, where_clause) = ent.generics.split_for_impl();
     let type_str_t = utils::make_type_str(name);
 
+    let policy_t = ent.policy.as_ref().map(|policy| {
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics #root::EntPolicy for #name #ty_generics #where_clause {
+                type Policy = #policy;
+            }
+        }
+    });
+
     quote! {
         #[automatically_derived]
         impl #impl_generics #root::EntType for #name #ty_generics #where_clause {
@@ -16,5 +25,7 @@ pub fn do_derive_ent_type(root: Path, ent: StructEnt) -> TokenStream {
                 #root::EntTypeData::Concrete { ty: #type_str_t }
             }
         }
+
+        #policy_t
     }
 }
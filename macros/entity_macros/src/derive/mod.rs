@@ -6,6 +6,7 @@ pub use ent::do_derive_ent_builder;
 pub use ent::do_derive_ent_debug;
 pub use ent::do_derive_ent_loader;
 pub use ent::do_derive_ent_query;
+pub use ent::do_derive_ent_schema;
 pub use ent::do_derive_ent_type;
 pub use ent::do_derive_ent_typed_edges;
 pub use ent::do_derive_ent_typed_fields;
@@ -120,6 +120,11 @@ pub fn derive_ent_query(input: proc_macro::TokenStream) -> proc_macro::TokenStre
     utils::do_derive(derive::do_derive_ent_query)(input)
 }
 
+#[proc_macro_derive(EntSchema, attributes(ent))]
+pub fn derive_ent_schema(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    utils::do_derive(derive::do_derive_ent_schema)(input)
+}
+
 #[proc_macro_derive(EntTypedFields, attributes(ent))]
 pub fn derive_ent_typed_fields(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     utils::do_derive(derive::do_derive_ent_typed_fields)(input)
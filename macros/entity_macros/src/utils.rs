@@ -1,8 +1,10 @@
+use entity_macros_data::StructEntFieldValidation;
 use proc_macro2::{Span, TokenStream};
 use proc_macro_crate::{crate_name, FoundCrate};
+use quote::{quote, ToTokens};
 use syn::{
-    parse_macro_input, parse_quote, DeriveInput, Expr, GenericArgument, Ident, Macro, Path,
-    PathArguments, PathSegment, Type,
+    parse_macro_input, parse_quote, DeriveInput, Expr, ExprUnary, GenericArgument, Ident, Macro,
+    Path, PathArguments, PathSegment, Type, UnOp,
 };
 
 /// Produces a token stream in the form of `::entity` or renamed version
@@ -72,6 +74,149 @@ pub fn convert_from_value(root: &Path, name: &Ident, ty: &Type) -> Expr {
     })
 }
 
+/// Checks the value referenced by the given expression against the
+/// validation of the field with the given name and type, producing an
+/// expression that results in `EntMutationError::InvalidField` with the
+/// reason of the first check that fails
+///
+/// If the field is an Option<T>, only the inner value is checked when present
+pub fn validate_field_value(
+    root: &Path,
+    name: &Ident,
+    ty: &Type,
+    validation: &StructEntFieldValidation,
+    value: TokenStream,
+) -> TokenStream {
+    let mut checks = Vec::new();
+
+    if let Some(min) = validation.min.as_ref() {
+        let reason = format!("must be at least {}", expr_to_string(min));
+        checks.push(quote! {
+            if *value < #min {
+                ::std::option::Option::Some(::std::string::ToString::to_string(#reason))
+            } else {
+                ::std::option::Option::None
+            }
+        });
+    }
+
+    if let Some(max) = validation.max.as_ref() {
+        let reason = format!("must be at most {}", expr_to_string(max));
+        checks.push(quote! {
+            if *value > #max {
+                ::std::option::Option::Some(::std::string::ToString::to_string(#reason))
+            } else {
+                ::std::option::Option::None
+            }
+        });
+    }
+
+    if let Some(min_len) = validation.min_len {
+        let reason = format!("length must be at least {}", min_len);
+        checks.push(quote! {
+            if #root::vendor::macros::Length::length(value) < #min_len {
+                ::std::option::Option::Some(::std::string::ToString::to_string(#reason))
+            } else {
+                ::std::option::Option::None
+            }
+        });
+    }
+
+    if let Some(max_len) = validation.max_len {
+        let reason = format!("length must be at most {}", max_len);
+        checks.push(quote! {
+            if #root::vendor::macros::Length::length(value) > #max_len {
+                ::std::option::Option::Some(::std::string::ToString::to_string(#reason))
+            } else {
+                ::std::option::Option::None
+            }
+        });
+    }
+
+    if let Some(pattern) = validation.regex.as_ref() {
+        let reason = format!("must match {}", pattern);
+        checks.push(quote! {
+            if #root::vendor::macros::regex::is_match!(
+                #pattern,
+                ::std::convert::AsRef::<::std::primitive::str>::as_ref(value)
+            ) {
+                ::std::option::Option::None
+            } else {
+                ::std::option::Option::Some(::std::string::ToString::to_string(#reason))
+            }
+        });
+    }
+
+    if !validation.one_of.is_empty() {
+        let options = &validation.one_of;
+        let reason = format!(
+            "must be one of {}",
+            options
+                .iter()
+                .map(expr_to_string)
+                .collect::<Vec<String>>()
+                .join(", ")
+        );
+        checks.push(quote! {
+            if #(*value == #options)||* {
+                ::std::option::Option::None
+            } else {
+                ::std::option::Option::Some(::std::string::ToString::to_string(#reason))
+            }
+        });
+    }
+
+    if let Some(custom) = validation.custom.as_ref() {
+        checks.push(quote! {
+            ::std::option::Option::map(
+                ::std::result::Result::err(#custom(value)),
+                |x| ::std::string::ToString::to_string(&x),
+            )
+        });
+    }
+
+    let first_failure = quote! {
+        ::std::option::Option::<::std::string::String>::None
+            #(.or_else(|| #checks))*
+    };
+    let reason = if strip_option(ty).is_ok() {
+        quote! {
+            ::std::option::Option::and_then(
+                ::std::option::Option::as_ref(value),
+                |value| #first_failure,
+            )
+        }
+    } else {
+        first_failure
+    };
+
+    quote!({
+        let value: &#ty = #value;
+        match #reason {
+            ::std::option::Option::Some(reason) => ::std::result::Result::Err(
+                #root::EntMutationError::InvalidField {
+                    name: ::std::string::ToString::to_string(::std::stringify!(#name)),
+                    reason,
+                },
+            ),
+            ::std::option::Option::None => ::std::result::Result::Ok(()),
+        }
+    })
+}
+
+/// Renders an expression the way it would be written, which unlike its
+/// tokens keeps negative numbers like `-10` together
+fn expr_to_string(expr: &Expr) -> String {
+    match expr {
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => format!("-{}", expr_to_string(expr)),
+        expr => expr.to_token_stream().to_string(),
+    }
+}
+
 /// Returns true if given type appears to be any of the following:
 /// * [`std::collections::HashMap`]
 /// * [`std::collections::BTreeMap`]
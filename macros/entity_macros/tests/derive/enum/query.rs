@@ -231,6 +231,188 @@ fn produces_method_to_filter_by_field() {
     assert!(results.contains(&2));
 }
 
+#[test]
+fn produces_methods_to_order_and_page_results() {
+    let database = db_to_rc(InmemoryDatabase::default());
+
+    database
+        .insert(Box::from(TestEnt1 {
+            id: 1,
+            database: WeakDatabaseRc::new(),
+            created: 0,
+            last_updated: 0,
+            field1: 1000,
+            other: 2,
+        }))
+        .expect("Failed to insert a test ent");
+
+    database
+        .insert(Box::from(TestEnt2 {
+            id: 2,
+            database: WeakDatabaseRc::new(),
+            created: 0,
+            last_updated: 0,
+            field1: 999,
+            field2: String::from("test"),
+            maybe_other: Some(1),
+            dups: Vec::new(),
+        }))
+        .expect("Failed to insert a test ent");
+
+    let results: Vec<Id> = TestEntQuery::default()
+        .order_by_field("field1", OrderDirection::Ascending)
+        .execute_with_db(DatabaseRc::downgrade(&database))
+        .expect("Failed to query for ents")
+        .iter()
+        .map(Ent::id)
+        .collect();
+    assert_eq!(results, vec![2, 1]);
+
+    let results: Vec<Id> = TestEntQuery::default()
+        .order_by_id(OrderDirection::Descending)
+        .limit(1)
+        .execute_with_db(DatabaseRc::downgrade(&database))
+        .expect("Failed to query for ents")
+        .iter()
+        .map(Ent::id)
+        .collect();
+    assert_eq!(results, vec![2]);
+
+    let cursor = Cursor::new(
+        &[Order::descending(OrderBy::Id)],
+        database
+            .get(2)
+            .expect("Failed to get ent")
+            .expect("Missing ent")
+            .as_ref(),
+    );
+    let results: Vec<Id> = TestEntQuery::default()
+        .order_by_id(OrderDirection::Descending)
+        .after(cursor)
+        .execute_with_db(DatabaseRc::downgrade(&database))
+        .expect("Failed to query for ents")
+        .iter()
+        .map(Ent::id)
+        .collect();
+    assert_eq!(results, vec![1]);
+}
+
+#[test]
+fn produces_methods_to_count_and_check_existence_of_results() {
+    let database = db_to_rc(InmemoryDatabase::default());
+
+    database
+        .insert(Box::from(TestEnt1 {
+            id: 1,
+            database: WeakDatabaseRc::new(),
+            created: 0,
+            last_updated: 0,
+            field1: 1000,
+            other: 2,
+        }))
+        .expect("Failed to insert a test ent");
+
+    database
+        .insert(Box::from(TestEnt2 {
+            id: 2,
+            database: WeakDatabaseRc::new(),
+            created: 0,
+            last_updated: 0,
+            field1: 999,
+            field2: String::from("test"),
+            maybe_other: Some(1),
+            dups: Vec::new(),
+        }))
+        .expect("Failed to insert a test ent");
+
+    let count = TestEntQuery::default()
+        .count_with_db(DatabaseRc::downgrade(&database))
+        .expect("Failed to count ents");
+    assert_eq!(count, 2);
+
+    let count = TestEntQuery::default()
+        .where_id(P::equals(2))
+        .count_with_db(DatabaseRc::downgrade(&database))
+        .expect("Failed to count ents");
+    assert_eq!(count, 1);
+
+    let exists = TestEntQuery::default()
+        .where_id(P::equals(3))
+        .exists_with_db(DatabaseRc::downgrade(&database))
+        .expect("Failed to check for ents");
+    assert!(!exists);
+}
+
+#[test]
+fn produces_methods_to_combine_filters_of_queries() {
+    let database = db_to_rc(InmemoryDatabase::default());
+
+    database
+        .insert(Box::from(TestEnt1 {
+            id: 1,
+            database: WeakDatabaseRc::new(),
+            created: 0,
+            last_updated: 0,
+            field1: 1000,
+            other: 2,
+        }))
+        .expect("Failed to insert a test ent");
+
+    database
+        .insert(Box::from(TestEnt2 {
+            id: 2,
+            database: WeakDatabaseRc::new(),
+            created: 0,
+            last_updated: 0,
+            field1: 999,
+            field2: String::from("test"),
+            maybe_other: Some(1),
+            dups: Vec::new(),
+        }))
+        .expect("Failed to insert a test ent");
+
+    let ids = |q: TestEntQuery| {
+        let mut ids: Vec<Id> = q
+            .execute_with_db(DatabaseRc::downgrade(&database))
+            .expect("Failed to query for ents")
+            .iter()
+            .map(Ent::id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    };
+
+    let q = TestEntQuery::default().where_or(vec![
+        TestEntQuery::default().where_id(P::equals(1)),
+        TestEntQuery::default().where_field("field2", Predicate::equals(String::from("test"))),
+    ]);
+    assert_eq!(ids(q), vec![1, 2]);
+
+    let q = TestEntQuery::default().where_and(vec![
+        TestEntQuery::default().where_created(P::equals(0)),
+        TestEntQuery::default().where_id(P::equals(2)),
+    ]);
+    assert_eq!(ids(q), vec![2]);
+
+    let q = TestEntQuery::default().where_not(TestEntQuery::default().where_id(P::equals(1)));
+    assert_eq!(ids(q), vec![2]);
+
+    let q = TestEntQuery::default()
+        .where_id(P::equals(1))
+        .union(TestEntQuery::default().where_id(P::equals(2)));
+    assert_eq!(ids(q), vec![1, 2]);
+
+    let q = TestEntQuery::default()
+        .where_created(P::equals(0))
+        .intersect(TestEntQuery::default().where_id(P::equals(2)));
+    assert_eq!(ids(q), vec![2]);
+
+    let q = TestEntQuery::default()
+        .where_created(P::equals(0))
+        .except(TestEntQuery::default().where_id(P::equals(2)));
+    assert_eq!(ids(q), vec![1]);
+}
+
 #[test]
 fn supports_generic_fields() {
     #[derive(Clone, Ent, EntQuery, EntType)]
@@ -313,6 +313,146 @@ fn build_succeeds_when_all_struct_fields_are_set() {
     assert_eq!(ent.edge3, vec![7, 8]);
 }
 
+#[test]
+fn build_fails_when_struct_field_is_invalid() {
+    fn not_admin(value: &str) -> Result<(), &'static str> {
+        if value == "admin" {
+            Err("is reserved")
+        } else {
+            Ok(())
+        }
+    }
+
+    #[derive(Clone, Derivative, Ent, EntBuilder)]
+    #[derivative(Debug)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[derivative(Debug = "ignore")]
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field(validate(min = 0, max = 150)))]
+        age: i32,
+
+        #[ent(field(validate(len(min = 1, max = 8), regex = "^[a-z]+$", custom = "not_admin")))]
+        name: String,
+
+        #[ent(field(validate(one_of("red", "blue"))))]
+        color: Option<String>,
+    }
+
+    fn builder() -> TestEntBuilder {
+        TestEntBuilder::default()
+            .age(30)
+            .name(String::from("alice"))
+            .color(None)
+    }
+
+    let ent = builder().finish().expect("Failed to build ent!");
+    assert_eq!(ent.age, 30);
+    assert_eq!(ent.name, "alice");
+    assert_eq!(ent.color, None);
+
+    let reason = |result: Result<TestEnt, TestEntBuilderError>| match result.unwrap_err() {
+        TestEntBuilderError::Invalid(EntMutationError::InvalidField { name, reason }) => {
+            (name, reason)
+        }
+        x => panic!("Unexpected error: {}", x),
+    };
+
+    assert_eq!(
+        reason(builder().age(-1).finish()),
+        (String::from("age"), String::from("must be at least 0"))
+    );
+    assert_eq!(
+        reason(builder().age(151).finish()),
+        (String::from("age"), String::from("must be at most 150"))
+    );
+    assert_eq!(
+        reason(builder().name(String::new()).finish()),
+        (
+            String::from("name"),
+            String::from("length must be at least 1")
+        )
+    );
+    assert_eq!(
+        reason(builder().name(String::from("alexandria")).finish()),
+        (
+            String::from("name"),
+            String::from("length must be at most 8")
+        )
+    );
+    assert_eq!(
+        reason(builder().name(String::from("Alice")).finish()),
+        (String::from("name"), String::from("must match ^[a-z]+$"))
+    );
+    assert_eq!(
+        reason(builder().name(String::from("admin")).finish()),
+        (String::from("name"), String::from("is reserved"))
+    );
+    assert_eq!(
+        reason(builder().color(Some(String::from("green"))).finish()),
+        (
+            String::from("color"),
+            String::from("must be one of \"red\", \"blue\"")
+        )
+    );
+    assert!(builder().color(Some(String::from("red"))).finish().is_ok());
+
+    assert!(matches!(
+        TestEntBuilder::default()
+            .name(String::from("alice"))
+            .color(None)
+            .finish(),
+        Err(TestEntBuilderError::MissingAge)
+    ));
+}
+
+#[test]
+fn finish_and_commit_participates_in_open_transaction() {
+    #[derive(Clone, Ent, EntBuilder)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field)]
+        field1: u32,
+    }
+
+    let db = DatabaseRc::new(Box::new(InmemoryDatabase::default()));
+    db.begin_transaction().expect("Failed to begin transaction");
+
+    let ent = TestEnt::build()
+        .database(DatabaseRc::downgrade(&db))
+        .field1(123)
+        .finish_and_commit()
+        .expect("Failed to build ent")
+        .expect("Failed to commit ent");
+
+    // Ent is visible within the transaction, but discarded by a rollback
+    assert!(db.get(ent.id()).unwrap().is_some(), "Ent not staged");
+    db.rollback_transaction()
+        .expect("Failed to roll back transaction");
+    assert!(db.get(ent.id()).unwrap().is_none(), "Ent not discarded");
+}
+
 #[test]
 fn builder_sets_computed_field_caches_to_none() {
     #[derive(Clone, Derivative, Ent, EntBuilder)]
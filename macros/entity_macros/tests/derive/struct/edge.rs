@@ -540,6 +540,207 @@ fn produces_load_method_for_edge_of_kind_many_that_returns_zero_or_more_ents() {
     );
 }
 
+#[test]
+fn produces_inverse_load_methods_for_edge_with_inverse_attr() {
+    #[derive(Clone, Ent, EntTypedEdges)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(edge(type = "TestEnt", inverse))]
+        my_edge: Vec<Id>,
+    }
+
+    assert!(matches!(
+        TestEnt::load_my_edge_inverse_with_db(WeakDatabaseRc::new(), 999),
+        Err(DatabaseError::Disconnected)
+    ));
+
+    let database = DatabaseRc::new(Box::new(InmemoryDatabase::default()));
+    for (id, my_edge) in vec![(999, vec![1000]), (1000, vec![999, 1000]), (1001, vec![])] {
+        let mut ent = TestEnt {
+            id,
+            database: WeakDatabaseRc::new(),
+            created: 0,
+            last_updated: 0,
+            my_edge,
+        };
+        ent.connect(DatabaseRc::downgrade(&database));
+        ent.commit().expect("Failed to save ent");
+    }
+
+    let load_inverse = |id| {
+        let mut ids: Vec<Id> =
+            TestEnt::load_my_edge_inverse_with_db(DatabaseRc::downgrade(&database), id)
+                .expect("Unexpected database failure loading inverse edge")
+                .into_iter()
+                .map(|ent| ent.id)
+                .collect();
+        ids.sort_unstable();
+        ids
+    };
+
+    assert_eq!(load_inverse(999), vec![1000]);
+    assert_eq!(load_inverse(1000), vec![999, 1000]);
+    assert_eq!(load_inverse(1001), Vec::<Id>::new());
+}
+
+#[test]
+fn keeps_named_inverse_edge_in_sync_when_committed() {
+    #[derive(Clone, Ent, EntLoader, EntTypedEdges)]
+    struct User {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(edge(type = "Post"))]
+        posts: Vec<Id>,
+    }
+
+    #[derive(Clone, Ent, EntTypedEdges)]
+    struct Post {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(edge(type = "User", inverse = "posts"))]
+        author: Id,
+    }
+
+    let database = DatabaseRc::new(Box::new(InmemoryDatabase::default()));
+    let db = DatabaseRc::downgrade(&database);
+    let load_user = |id| {
+        User::load_from_db(WeakDatabaseRc::clone(&db), id)
+            .unwrap()
+            .unwrap()
+    };
+
+    for id in vec![1, 2] {
+        let mut user = User {
+            id,
+            database: WeakDatabaseRc::clone(&db),
+            created: 0,
+            last_updated: 0,
+            posts: vec![],
+        };
+        user.commit().expect("Failed to save user");
+    }
+
+    let mut post = Post {
+        id: 3,
+        database: WeakDatabaseRc::clone(&db),
+        created: 0,
+        last_updated: 0,
+        author: 1,
+    };
+    assert_eq!(
+        post.edge_definition("author").unwrap().inverse(),
+        Some("posts")
+    );
+
+    post.commit().expect("Failed to save post");
+    assert_eq!(load_user(1).posts_ids(), &[3]);
+
+    post.set_author_id(2);
+    post.commit().expect("Failed to save post");
+    assert_eq!(load_user(1).posts_ids(), &[] as &[Id]);
+    assert_eq!(load_user(2).posts_ids(), &[3]);
+
+    assert!(post.remove().expect("Failed to remove post"));
+    assert_eq!(load_user(2).posts_ids(), &[] as &[Id]);
+}
+
+#[test]
+fn produces_async_load_method_for_each_edge_kind() {
+    #[derive(Clone, Ent, EntTypedEdges)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(edge(type = "TestEnt"))]
+        maybe_edge: Option<Id>,
+
+        #[ent(edge(type = "TestEnt"))]
+        one_edge: Id,
+
+        #[ent(edge(type = "TestEnt"))]
+        many_edge: Vec<Id>,
+    }
+
+    fn assert_send<T: Send>(x: T) -> T {
+        x
+    }
+
+    let mut ent = TestEnt {
+        id: 999,
+        database: WeakDatabaseRc::new(),
+        created: 0,
+        last_updated: 0,
+        maybe_edge: Some(999),
+        one_edge: 999,
+        many_edge: vec![999, 1000],
+    };
+
+    assert!(matches!(
+        futures::executor::block_on(ent.load_one_edge_async()),
+        Err(DatabaseError::Disconnected)
+    ));
+
+    let database = DatabaseRc::new(Box::new(InmemoryDatabase::default()));
+    ent.connect(DatabaseRc::downgrade(&database));
+    futures::executor::block_on(ent.clone().commit_async()).expect("Failed to save ent");
+
+    let maybe_ent = futures::executor::block_on(assert_send(ent.load_maybe_edge_async()))
+        .expect("Unexpected database failure loading maybe edge")
+        .expect("Missing ent for maybe edge");
+    assert_eq!(maybe_ent.id, 999);
+
+    let one_ent = futures::executor::block_on(assert_send(ent.load_one_edge_async()))
+        .expect("Unexpected database failure loading one edge");
+    assert_eq!(one_ent.id, 999);
+
+    let many_ents = futures::executor::block_on(assert_send(ent.load_many_edge_async()))
+        .expect("Unexpected database failure loading many edge");
+    assert_eq!(
+        many_ents.into_iter().map(|ent| ent.id).collect::<Vec<Id>>(),
+        vec![999]
+    );
+}
+
 #[test]
 fn produces_load_method_for_edge_with_ent_wrapper_type_if_wrapper_attr_specified() {
     #[derive(Clone, Ent, EntTypedEdges)]
@@ -638,3 +839,46 @@ fn produces_load_method_for_edge_with_ent_wrapper_type_if_wrapper_attr_specified
     ));
     assert!(matches!(ent3.load_my_edge(), Ok(TestEntEnum::One(_))));
 }
+
+#[test]
+fn produces_data_methods_for_edge_with_data_attr() {
+    #[derive(Clone, Ent, EntTypedEdges)]
+    struct Group {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(edge(type = "Group", data = "String"))]
+        members: Vec<Id>,
+    }
+
+    let database = DatabaseRc::new(Box::new(InmemoryDatabase::default()));
+    let db = DatabaseRc::downgrade(&database);
+
+    let mut group = Group {
+        id: 1,
+        database: WeakDatabaseRc::clone(&db),
+        created: 0,
+        last_updated: 0,
+        members: vec![1],
+    };
+    group.commit().expect("Failed to save group");
+
+    assert_eq!(group.members_data(1).unwrap(), None);
+    assert!(group
+        .set_members_data(1, String::from("admin"))
+        .expect("Failed to set data"));
+    assert!(!group
+        .set_members_data(2, String::from("member"))
+        .expect("Failed to set data"));
+    assert_eq!(group.members_data(1).unwrap(), Some(String::from("admin")));
+    assert_eq!(group.members_data(2).unwrap(), None);
+}
@@ -174,6 +174,9 @@ fn field_definitions_should_return_list_of_definitions_for_ent_fields() {
 
         #[ent(field(computed = "123"))]
         f: Option<u32>,
+
+        #[ent(field(unique, mutable))]
+        g: String,
     }
 
     let ent = TestEnt {
@@ -187,6 +190,7 @@ fn field_definitions_should_return_list_of_definitions_for_ent_fields() {
         d: true,
         e: CustomValue,
         f: None,
+        g: String::from("unique"),
     };
 
     assert_eq!(
@@ -220,6 +224,11 @@ fn field_definitions_should_return_list_of_definitions_for_ent_fields() {
                 )))),
                 vec![FieldAttribute::Computed, FieldAttribute::Immutable],
             ),
+            FieldDefinition::new_with_attributes(
+                "g",
+                ValueType::Text,
+                vec![FieldAttribute::Unique]
+            ),
         ]
     );
 }
@@ -503,6 +512,48 @@ fn update_field_should_fail_if_field_marked_computed() {
     assert_eq!(ent.field("computed_field"), Some(Value::from(123u32)));
 }
 
+#[test]
+fn update_field_should_fail_if_value_is_invalid() {
+    #[derive(Clone, Ent)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field(mutable, validate(min = "-10", max = 10)))]
+        validated_field: i32,
+    }
+
+    let mut ent = TestEnt {
+        id: EPHEMERAL_ID,
+        database: WeakDatabaseRc::new(),
+        created: 0,
+        last_updated: 0,
+        validated_field: 0,
+    };
+
+    match ent.update_field("validated_field", Value::from(-11i32)) {
+        Err(EntMutationError::InvalidField { name, reason }) => {
+            assert_eq!(name, "validated_field");
+            assert_eq!(reason, "must be at least -10");
+        }
+        x => panic!("Unexpected result: {:?}", x),
+    }
+    assert_eq!(ent.field("validated_field"), Some(Value::from(0i32)));
+
+    ent.update_field("validated_field", Value::from(-10i32))
+        .expect("Failed to update field");
+    assert_eq!(ent.field("validated_field"), Some(Value::from(-10i32)));
+}
+
 #[test]
 fn edge_definitions_should_return_list_of_definitions_for_ent_edges() {
     #[derive(Clone, Ent)]
@@ -1142,6 +1193,52 @@ fn commit_should_save_ent_to_database_and_update_id_if_it_was_changed() {
     );
 }
 
+#[test]
+fn commit_should_fail_if_ent_was_updated_since_it_was_last_committed() {
+    #[derive(Clone, Ent)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+    }
+
+    let database = DatabaseRc::new(Box::new(InmemoryDatabase::default()));
+    let mut ent = TestEnt {
+        id: 999,
+        database: DatabaseRc::downgrade(&database),
+        created: 0,
+        last_updated: 0,
+    };
+
+    ent.commit().expect("Failed to commit ent");
+    assert_eq!(
+        ent.last_updated,
+        database
+            .get(999)
+            .expect("Failed to get ent")
+            .expect("Ent missing")
+            .last_updated()
+    );
+
+    let mut other = ent.clone();
+    ent.commit().expect("Failed to commit ent again");
+    assert!(matches!(
+        other.commit(),
+        Err(DatabaseError::Conflict { id: 999, .. })
+    ));
+
+    other.refresh().expect("Failed to refresh ent");
+    other.commit().expect("Failed to commit refreshed ent");
+}
+
 #[test]
 fn remove_should_delete_ent_from_database() {
     #[derive(Clone, Derivative, Ent)]
@@ -1197,6 +1294,116 @@ fn remove_should_delete_ent_from_database() {
     );
 }
 
+#[test]
+fn commit_and_remove_should_run_hooks_of_hooked_database() {
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Ent, EntType, EntBuilder)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field)]
+        name: String,
+    }
+
+    #[derive(Debug)]
+    struct EmptyName;
+
+    impl std::fmt::Display for EmptyName {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Name cannot be empty")
+        }
+    }
+
+    impl std::error::Error for EmptyName {}
+
+    #[derive(Default)]
+    struct TestHook(Arc<Mutex<Vec<String>>>);
+
+    impl Hook for TestHook {
+        fn before_insert(&self, ent: &mut dyn Ent) -> HookResult {
+            let ent = ent.as_mut_any().downcast_mut::<TestEnt>().unwrap();
+            ent.name = ent.name.to_lowercase();
+            Ok(())
+        }
+
+        fn after_insert(&self, ent: &dyn Ent) {
+            self.0.lock().unwrap().push(format!("insert {}", ent.id()));
+        }
+
+        fn before_update(&self, _existing: &dyn Ent, ent: &mut dyn Ent) -> HookResult {
+            if ent.field("name") == Some(Value::from("")) {
+                return Err(Box::new(EmptyName));
+            }
+            Ok(())
+        }
+
+        fn after_update(&self, _existing: &dyn Ent, ent: &dyn Ent) {
+            self.0.lock().unwrap().push(format!("update {}", ent.id()));
+        }
+
+        fn after_remove(&self, ent: &dyn Ent) {
+            self.0.lock().unwrap().push(format!("remove {}", ent.id()));
+        }
+    }
+
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut hooks = Hooks::new();
+    hooks.register_for::<TestEnt, _>(TestHook(Arc::clone(&log)));
+    let database = DatabaseRc::new(Box::new(HookedDatabase::new(
+        InmemoryDatabase::default(),
+        hooks,
+    )));
+
+    let mut ent = TestEnt::build()
+        .database(DatabaseRc::downgrade(&database))
+        .name(String::from("Alice"))
+        .finish_and_commit()
+        .expect("Failed to build ent")
+        .expect("Failed to commit ent");
+    assert_eq!(
+        database
+            .get(ent.id)
+            .expect("Failed to get ent")
+            .expect("Ent missing")
+            .field("name"),
+        Some(Value::from("alice"))
+    );
+
+    ent.refresh().expect("Failed to refresh ent");
+    ent.name = String::from("bob");
+    ent.commit().expect("Failed to commit ent");
+
+    ent.name = String::new();
+    match ent.commit() {
+        Err(DatabaseError::Vetoed { id, source }) => {
+            assert_eq!(id, ent.id);
+            assert!(source.downcast_ref::<EmptyName>().is_some());
+        }
+        x => panic!("Unexpected result: {:?}", x.map(|_| ())),
+    }
+
+    assert!(ent.remove().expect("Failed to remove ent"));
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![
+            format!("insert {}", ent.id),
+            format!("update {}", ent.id),
+            format!("remove {}", ent.id),
+        ]
+    );
+}
+
 #[test]
 fn supports_all_std_collection_list_types_for_edge_ids() {
     use std::collections::*;
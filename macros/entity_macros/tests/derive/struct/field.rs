@@ -116,6 +116,53 @@ fn produces_setters_for_fields_marked_as_mutable() {
     assert_eq!(ent.my_field2, String::from("something"));
 }
 
+#[test]
+fn produces_setters_that_fail_for_invalid_values_of_validated_fields() {
+    #[derive(Clone, Ent, EntTypedFields)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field(mutable, validate(len(max = 4))))]
+        my_field: String,
+    }
+
+    let mut ent = TestEnt {
+        id: 999,
+        database: WeakDatabaseRc::new(),
+        created: 0,
+        last_updated: 0,
+        my_field: String::from("test"),
+    };
+
+    assert!(matches!(
+        ent.set_my_field(String::from("too long")),
+        Err(EntMutationError::InvalidField { .. })
+    ));
+    assert_eq!(ent.my_field, String::from("test"));
+
+    assert_eq!(
+        ent.set_my_field(String::from("ok")).unwrap(),
+        String::from("test")
+    );
+    assert_eq!(ent.my_field, String::from("ok"));
+
+    // Lengths of text are counted in characters rather than bytes
+    assert_eq!(
+        ent.set_my_field(String::from("café")).unwrap(),
+        String::from("ok")
+    );
+}
+
 #[test]
 fn supports_generic_ent_fields() {
     #![allow(clippy::float_cmp)]
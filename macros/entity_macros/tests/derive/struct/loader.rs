@@ -44,3 +44,62 @@ fn produces_load_methods_that_pull_an_ent_out_of_a_database() {
         assert!(TestEnt::load_from_db_strict(entity::global::db(), 999).is_err());
     });
 }
+
+#[test]
+fn produces_load_methods_that_respect_the_policy_of_the_ent() {
+    struct OwnerPolicy;
+
+    impl PrivacyPolicy for OwnerPolicy {
+        fn policy() -> Policy {
+            Policy::new().read(PolicyRule::allow_if_field_is_viewer("owner"))
+        }
+    }
+
+    #[derive(Clone, Ent, EntType, EntLoader)]
+    #[ent(policy = "OwnerPolicy")]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        owner: Id,
+    }
+
+    let db = InmemoryDatabase::default();
+    for (id, owner) in vec![(1, 10), (2, 20)] {
+        db.insert(Box::new(TestEnt {
+            id,
+            database: WeakDatabaseRc::new(),
+            created: 0,
+            last_updated: 0,
+            owner,
+        }))
+        .unwrap();
+    }
+
+    let mut policies = Policies::new();
+    policies.register_ent::<TestEnt>();
+    let db = DatabaseRc::new(Box::new(PrivacyDatabase::new(
+        db,
+        policies,
+        Viewer::Ent(10),
+    )));
+
+    assert_eq!(
+        TestEnt::load_from_db(DatabaseRc::downgrade(&db), 1)
+            .unwrap()
+            .map(|ent| ent.owner),
+        Some(10)
+    );
+    assert!(TestEnt::load_from_db(DatabaseRc::downgrade(&db), 2)
+        .unwrap()
+        .is_none());
+}
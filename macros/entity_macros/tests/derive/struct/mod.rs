@@ -6,4 +6,5 @@ mod ent_type;
 mod field;
 mod loader;
 mod query;
+mod schema;
 mod value;
@@ -228,6 +228,219 @@ fn produces_method_to_filter_by_field() {
     assert!(results.contains(&1));
 }
 
+#[test]
+fn produces_methods_to_order_and_page_results() {
+    #[derive(Clone, Ent, EntQuery, EntType)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field)]
+        value: u32,
+    }
+
+    let database = db_to_rc(InmemoryDatabase::default());
+
+    for (id, value) in vec![(1, 300), (2, 100), (3, 200), (4, 100)] {
+        database
+            .insert(Box::from(TestEnt {
+                id,
+                database: WeakDatabaseRc::new(),
+                created: 0,
+                last_updated: 0,
+                value,
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    let results: Vec<Id> = TestEntQuery::default()
+        .order_by_value(OrderDirection::Ascending)
+        .order_by_id(OrderDirection::Descending)
+        .execute_with_db(DatabaseRc::downgrade(&database))
+        .expect("Failed to query for ents")
+        .iter()
+        .map(Ent::id)
+        .collect();
+    assert_eq!(results, vec![4, 2, 3, 1]);
+
+    let results: Vec<Id> = TestEntQuery::default()
+        .order_by_value(OrderDirection::Descending)
+        .offset(1)
+        .limit(2)
+        .execute_with_db(DatabaseRc::downgrade(&database))
+        .expect("Failed to query for ents")
+        .iter()
+        .map(Ent::id)
+        .collect();
+    assert_eq!(results, vec![3, 2]);
+
+    let cursor = Cursor::new(
+        &[
+            Order::ascending(OrderBy::Field(String::from("value"))),
+            Order::descending(OrderBy::Id),
+        ],
+        database
+            .get(2)
+            .expect("Failed to get ent")
+            .expect("Missing ent")
+            .as_ref(),
+    );
+    let results: Vec<Id> = TestEntQuery::default()
+        .order_by_value(OrderDirection::Ascending)
+        .order_by_id(OrderDirection::Descending)
+        .after(cursor)
+        .execute_with_db(DatabaseRc::downgrade(&database))
+        .expect("Failed to query for ents")
+        .iter()
+        .map(Ent::id)
+        .collect();
+    assert_eq!(results, vec![3, 1]);
+}
+
+#[test]
+fn produces_methods_to_count_and_check_existence_of_results() {
+    #[derive(Clone, Ent, EntQuery, EntType)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field)]
+        value: u32,
+    }
+
+    let database = db_to_rc(InmemoryDatabase::default());
+
+    for (id, value) in vec![(1, 300), (2, 100), (3, 200)] {
+        database
+            .insert(Box::from(TestEnt {
+                id,
+                database: WeakDatabaseRc::new(),
+                created: 0,
+                last_updated: 0,
+                value,
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    let count = TestEntQuery::default()
+        .count_with_db(DatabaseRc::downgrade(&database))
+        .expect("Failed to count ents");
+    assert_eq!(count, 3);
+
+    let count = TestEntQuery::default()
+        .where_value(P::greater_than(150))
+        .count_with_db(DatabaseRc::downgrade(&database))
+        .expect("Failed to count ents");
+    assert_eq!(count, 2);
+
+    let exists = TestEntQuery::default()
+        .where_value(P::greater_than(150))
+        .exists_with_db(DatabaseRc::downgrade(&database))
+        .expect("Failed to check for ents");
+    assert!(exists);
+
+    let exists = TestEntQuery::default()
+        .where_value(P::greater_than(300))
+        .exists_with_db(DatabaseRc::downgrade(&database))
+        .expect("Failed to check for ents");
+    assert!(!exists);
+}
+
+#[test]
+fn produces_methods_to_combine_filters_of_queries() {
+    #[derive(Clone, Ent, EntQuery, EntType)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field)]
+        value: u32,
+    }
+
+    let database = db_to_rc(InmemoryDatabase::default());
+
+    for (id, value) in vec![(1, 300), (2, 100), (3, 200)] {
+        database
+            .insert(Box::from(TestEnt {
+                id,
+                database: WeakDatabaseRc::new(),
+                created: 0,
+                last_updated: 0,
+                value,
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    let ids = |q: TestEntQuery| {
+        let mut ids: Vec<Id> = q
+            .execute_with_db(DatabaseRc::downgrade(&database))
+            .expect("Failed to query for ents")
+            .iter()
+            .map(Ent::id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    };
+
+    let q = TestEntQuery::default().where_or(vec![
+        TestEntQuery::default().where_value(P::equals(100)),
+        TestEntQuery::default().where_id(P::equals(3)),
+    ]);
+    assert_eq!(ids(q), vec![2, 3]);
+
+    let q = TestEntQuery::default().where_and(vec![
+        TestEntQuery::default().where_value(P::greater_than(150)),
+        TestEntQuery::default().where_id(P::less_than(3)),
+    ]);
+    assert_eq!(ids(q), vec![1]);
+
+    let q = TestEntQuery::default()
+        .where_not(TestEntQuery::default().where_value(P::greater_than(150)));
+    assert_eq!(ids(q), vec![2]);
+
+    let q = TestEntQuery::default()
+        .where_id(P::equals(1))
+        .union(TestEntQuery::default().where_value(P::less_than(150)));
+    assert_eq!(ids(q), vec![1, 2]);
+
+    let q = TestEntQuery::default()
+        .where_value(P::greater_than(150))
+        .intersect(TestEntQuery::default().where_id(P::greater_than(1)));
+    assert_eq!(ids(q), vec![3]);
+
+    let q = TestEntQuery::default()
+        .where_value(P::greater_than(150))
+        .except(TestEntQuery::default().where_id(P::equals(1)));
+    assert_eq!(ids(q), vec![3]);
+}
+
 #[test]
 fn supports_generic_fields() {
     #[derive(Clone, Ent, EntQuery, EntType)]
@@ -0,0 +1,128 @@
+use entity::*;
+
+#[test]
+fn describes_the_fields_and_edges_of_the_ent_type() {
+    #[derive(Clone, EntType)]
+    struct OtherEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+    }
+
+    #[derive(Clone, EntType, EntSchema)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field(indexed, unique))]
+        name: String,
+
+        #[ent(field(mutable))]
+        age: Option<u8>,
+
+        #[ent(edge(type = "OtherEnt", policy = "deep"))]
+        others: Vec<Id>,
+
+        #[ent(edge(type = "TestEnt"))]
+        parent: Option<Id>,
+    }
+
+    let schema = TestEnt::schema();
+    assert_eq!(schema.r#type(), TestEnt::type_str());
+    assert_eq!(
+        schema.fields(),
+        &[
+            FieldDefinition::new_with_attributes(
+                "name",
+                ValueType::Text,
+                vec![
+                    FieldAttribute::Indexed,
+                    FieldAttribute::Immutable,
+                    FieldAttribute::Unique
+                ],
+            ),
+            FieldDefinition::new(
+                "age",
+                ValueType::Optional(Box::new(ValueType::from(NumberType::U8))),
+            ),
+        ]
+    );
+    assert_eq!(
+        schema.edges(),
+        &[
+            EdgeSchema::new(
+                EdgeDefinition::new_with_deletion_policy(
+                    "others",
+                    EdgeValueType::Many,
+                    EdgeDeletionPolicy::DeepDelete,
+                ),
+                OtherEnt::type_str(),
+            ),
+            EdgeSchema::new(
+                EdgeDefinition::new("parent", EdgeValueType::MaybeOne),
+                TestEnt::type_str(),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn can_be_collected_into_a_schema() {
+    #[simple_ent]
+    struct User {
+        name: String,
+
+        #[ent(edge(type = "Post"))]
+        posts: Vec<Id>,
+    }
+
+    #[simple_ent]
+    struct Post {
+        title: String,
+
+        #[ent(edge(type = "User"))]
+        author: Id,
+    }
+
+    let mut schema = Schema::new();
+    schema.register::<User>().register::<Post>();
+
+    let mut types: Vec<&str> = vec![User::type_str(), Post::type_str()];
+    types.sort_unstable();
+    assert_eq!(schema.types().collect::<Vec<_>>(), types);
+    assert_eq!(
+        schema
+            .get(User::type_str())
+            .unwrap()
+            .edge("posts")
+            .unwrap()
+            .target(),
+        Post::type_str()
+    );
+    assert_eq!(
+        schema
+            .get(Post::type_str())
+            .unwrap()
+            .edge("author")
+            .unwrap()
+            .target(),
+        User::type_str()
+    );
+}
@@ -0,0 +1,21 @@
+use entity::{Ent, Id, WeakDatabaseRc};
+
+#[derive(Clone, Ent)]
+struct TestEnt {
+    #[ent(id)]
+    id: Id,
+
+    #[ent(database)]
+    database: WeakDatabaseRc,
+
+    #[ent(created)]
+    created: u64,
+
+    #[ent(last_updated)]
+    last_updated: u64,
+
+    #[ent(field(validate(regex = "^[a-z+$")))]
+    name: String,
+}
+
+fn main() {}
@@ -4,7 +4,7 @@ use darling::{
     util::{Flag, Override, SpannedValue},
     FromDeriveInput, FromField, FromMeta,
 };
-use syn::{Generics, Ident, Type, Visibility};
+use syn::{Generics, Ident, Lit, NestedMeta, Type, Visibility};
 
 /// Information about a struct deriving ent
 #[derive(Debug, FromDeriveInput)]
@@ -14,6 +14,9 @@ pub struct Ent {
     pub vis: Visibility,
     pub generics: Generics,
     pub data: ast::Data<(), EntField>,
+    /// Path to the type providing the privacy policy of the ent
+    #[darling(default)]
+    pub policy: Option<String>,
 }
 
 /// Information for a field of a struct deriving ent
@@ -113,10 +116,75 @@ pub struct FieldAttr {
     #[darling(default)]
     pub mutable: Flag,
 
+    /// If provided, indicates that no two ents can have the same value for
+    /// the field, which is only enforced for values that can be keyed and
+    /// so not for lists or maps
+    #[darling(default)]
+    pub unique: Flag,
+
     /// If provided, cannot be mutable -- will be expression to invoke as a
     /// method instead of a data field
     #[darling(default)]
     pub computed: Option<String>,
+
+    /// If provided, indicates that the value of the field must pass each of
+    /// the validations whenever it is set
+    #[darling(default)]
+    pub validate: Option<ValidateAttr>,
+}
+
+/// Information for a validate attribute on a field of a struct deriving ent
+#[derive(Debug, Clone, Default, FromMeta)]
+#[darling(default)]
+pub struct ValidateAttr {
+    /// Smallest value allowed, where a string is parsed as an expression
+    /// to support values like negative numbers
+    pub min: Option<Lit>,
+
+    /// Largest value allowed, where a string is parsed as an expression
+    /// to support values like negative numbers
+    pub max: Option<Lit>,
+
+    /// Bounds of the length of the value, counting the characters of text
+    /// and the items of collections
+    pub len: Option<LenAttr>,
+
+    /// Pattern that the value must match
+    pub regex: Option<String>,
+
+    /// Literals where the value must equal one of them
+    pub one_of: Option<LitList>,
+
+    /// Path to a function taking a reference to the value and returning a
+    /// result whose error is the reason the value is invalid
+    pub custom: Option<String>,
+}
+
+/// Information for a len attribute within a validate attribute
+#[derive(Debug, Clone, Default, FromMeta)]
+#[darling(default)]
+pub struct LenAttr {
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+}
+
+/// List of literals such as `one_of(1, 2, 3)`
+#[derive(Debug, Clone, Default)]
+pub struct LitList(pub Vec<Lit>);
+
+impl FromMeta for LitList {
+    fn from_list(items: &[NestedMeta]) -> darling::Result<Self> {
+        items
+            .iter()
+            .map(|item| match item {
+                NestedMeta::Lit(x) => Ok(x.clone()),
+                NestedMeta::Meta(x) => {
+                    Err(darling::Error::unexpected_type("non-literal").with_span(x))
+                }
+            })
+            .collect::<darling::Result<Vec<Lit>>>()
+            .map(Self)
+    }
 }
 
 /// Information for an edge attribute on a field of a struct deriving ent
@@ -131,6 +199,10 @@ pub struct EdgeAttr {
     pub wrap: Flag,
     #[darling(default)]
     pub use_id_slice: Flag,
+    #[darling(default)]
+    pub inverse: Option<Override<String>>,
+    #[darling(default)]
+    pub data: Option<String>,
     #[darling(default, rename = "policy")]
     pub deletion_policy: EntEdgeDeletionPolicy,
 }
@@ -1,7 +1,7 @@
 mod internal;
 
-use darling::{FromDeriveInput, FromMeta};
-use syn::{parse_str, DeriveInput, Expr, Generics, Ident, Type, Visibility};
+use darling::{util::Override, FromDeriveInput, FromMeta};
+use syn::{parse_str, DeriveInput, Expr, ExprLit, Generics, Ident, Lit, Path, Type, Visibility};
 
 /// Information about attributes on a struct that will represent an ent
 #[derive(Debug)]
@@ -24,6 +24,10 @@ pub struct Ent {
 
     pub fields: Vec<EntField>,
     pub edges: Vec<EntEdge>,
+
+    /// If policy = "..." provided, signifies the type that provides the
+    /// privacy policy of the ent
+    pub policy: Option<Type>,
 }
 
 /// Information about a specific field for an ent
@@ -41,12 +45,23 @@ pub struct EntField {
     /// be included when generating typed methods
     pub mutable: bool,
 
+    /// If field(unique) provided, signifies that no two ents of the same
+    /// type should have the same value for this field in the database
+    /// where they are stored
+    pub unique: bool,
+
     /// If field(computed(...)) provided, signifies that this field should
     /// be computed based on provided expression instead of treated as data
     /// stored in the struct (and database)
     ///
     /// Cannot be used with mutable
     pub computed: Option<EntFieldComputed>,
+
+    /// If field(validate(...)) provided, signifies that the value of this
+    /// field should be checked whenever it is built or updated
+    ///
+    /// Cannot be used with computed
+    pub validation: Option<EntFieldValidation>,
 }
 
 #[derive(Debug)]
@@ -58,6 +73,30 @@ pub struct EntFieldComputed {
     pub return_ty: Type,
 }
 
+#[derive(Debug, Default)]
+pub struct EntFieldValidation {
+    /// The expression of the smallest value allowed
+    pub min: Option<Expr>,
+
+    /// The expression of the largest value allowed
+    pub max: Option<Expr>,
+
+    /// The smallest length allowed
+    pub min_len: Option<usize>,
+
+    /// The largest length allowed
+    pub max_len: Option<usize>,
+
+    /// The pattern that the value must match
+    pub regex: Option<String>,
+
+    /// The expressions where the value must equal one of them
+    pub one_of: Vec<Expr>,
+
+    /// The path to the function used to validate the value
+    pub custom: Option<Path>,
+}
+
 /// Information about a specific edge for an ent
 #[derive(Debug)]
 pub struct EntEdge {
@@ -67,6 +106,9 @@ pub struct EntEdge {
     pub ent_query_ty: Option<Type>,
     pub wrap: bool,
     pub use_id_slice: bool,
+    pub inverse: bool,
+    pub inverse_edge: Option<String>,
+    pub data_ty: Option<Type>,
     pub kind: EntEdgeKind,
     pub deletion_policy: EntEdgeDeletionPolicy,
 }
@@ -200,6 +242,14 @@ impl FromDeriveInput for Ent {
                     );
                 }
 
+                // Computed fields are not stored, so there is nothing to keep unique
+                if attr.unique.is_some() && attr.computed.is_some() {
+                    errors.push(
+                        darling::Error::custom("Cannot have field be unique and computed")
+                            .with_span(&name),
+                    );
+                }
+
                 let computed = if let Some(expr) = attr.computed {
                     let res_expr: darling::Result<Expr> = parse_str(&expr)
                         .map_err(|x| darling::Error::custom(x.to_string()).with_span(&name));
@@ -229,12 +279,31 @@ impl FromDeriveInput for Ent {
                     None
                 };
 
+                // Computed fields are never set, so there is nothing to validate
+                if computed.is_some() && attr.validate.is_some() {
+                    errors.push(
+                        darling::Error::custom("Cannot have field be validated and computed")
+                            .with_span(&name),
+                    );
+                }
+
+                let validation = match attr.validate.map(|x| make_validation(x, name)) {
+                    Some(Ok(validation)) => Some(validation),
+                    Some(Err(x)) => {
+                        errors.push(x);
+                        None
+                    }
+                    None => None,
+                };
+
                 fields.push(EntField {
                     name: name.clone(),
                     ty: f.ty.clone(),
                     indexed: attr.indexed.is_some(),
                     mutable: attr.mutable.is_some(),
+                    unique: attr.unique.is_some(),
                     computed,
+                    validation,
                 });
             }
 
@@ -258,6 +327,12 @@ impl FromDeriveInput for Ent {
                         .and_then(|type_str| syn::parse_str(&type_str).ok()),
                     wrap: attr.wrap.is_some(),
                     use_id_slice: attr.use_id_slice.is_some(),
+                    inverse: attr.inverse.is_some(),
+                    inverse_edge: attr.inverse.and_then(Override::explicit),
+                    data_ty: match attr.data {
+                        Some(type_str) => Some(syn::parse_str(&type_str)?),
+                        None => None,
+                    },
                     kind,
                     deletion_policy: attr.deletion_policy,
                 });
@@ -271,11 +346,22 @@ impl FromDeriveInput for Ent {
                     ty: f.ty.clone(),
                     indexed: false,
                     mutable: false,
+                    unique: false,
                     computed: None,
+                    validation: None,
                 });
             }
         }
 
+        let policy = match ent.policy.as_deref().map(parse_str::<Type>) {
+            Some(Ok(ty)) => Some(ty),
+            Some(Err(x)) => {
+                errors.push(darling::Error::custom(x).with_span(input));
+                None
+            }
+            None => None,
+        };
+
         if id.is_none() {
             errors.push(darling::Error::custom("No id field provided").with_span(input));
         }
@@ -312,10 +398,61 @@ impl FromDeriveInput for Ent {
             last_updated_ty: last_updated_ty.cloned().unwrap(),
             fields,
             edges,
+            policy,
         })
     }
 }
 
+fn make_validation(
+    attr: internal::ValidateAttr,
+    name: &Ident,
+) -> darling::Result<EntFieldValidation> {
+    let lit_to_expr = |lit: Lit| -> darling::Result<Expr> {
+        match lit {
+            Lit::Str(x) => {
+                parse_str(&x.value()).map_err(|e| darling::Error::custom(e).with_span(&x))
+            }
+            lit => Ok(Expr::Lit(ExprLit {
+                attrs: Vec::new(),
+                lit,
+            })),
+        }
+    };
+
+    // Patterns are checked here so that an invalid pattern fails to compile
+    // rather than failing every time a value is validated
+    if let Some(pattern) = attr.regex.as_deref() {
+        if let Err(x) = regex::Regex::new(pattern) {
+            return Err(darling::Error::custom(format!("Invalid regex: {}", x)).with_span(name));
+        }
+    }
+
+    let len = attr.len.unwrap_or_default();
+    Ok(EntFieldValidation {
+        min: attr.min.map(lit_to_expr).transpose()?,
+        max: attr.max.map(lit_to_expr).transpose()?,
+        min_len: len.min,
+        max_len: len.max,
+        regex: attr.regex,
+        one_of: attr
+            .one_of
+            .map(|x| x.0)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|lit| {
+                Expr::Lit(ExprLit {
+                    attrs: Vec::new(),
+                    lit,
+                })
+            })
+            .collect(),
+        custom: attr
+            .custom
+            .map(|x| parse_str(&x).map_err(|e| darling::Error::custom(e).with_span(name)))
+            .transpose()?,
+    })
+}
+
 fn infer_edge_kind_from_ty(ty: &Type) -> darling::Result<EntEdgeKind> {
     match &ty {
         Type::Path(x) => {
@@ -0,0 +1,385 @@
+use super::{db_to_rc, Database, DatabaseRc, DatabaseResult};
+use crate::{
+    ent::{AggregateGroup, Aggregation, Page, Query},
+    AsAny, Ent, Id,
+};
+use std::{
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex, RwLock,
+    },
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+/// Maximum number of threads kept by the default pool that performs
+/// blocking operations when no executor has been set
+const MAX_BLOCKING_WORKERS: usize = 32;
+
+lazy_static::lazy_static! {
+    static ref BLOCKING_EXECUTOR: RwLock<Option<BlockingExecutor>> = RwLock::new(None);
+    static ref BLOCKING_POOL: WorkerPool = WorkerPool::new(MAX_BLOCKING_WORKERS);
+}
+
+/// Represents a boxed future returned by asynchronous database operations
+pub type DatabaseFuture<'a, T> = Pin<Box<dyn Future<Output = DatabaseResult<T>> + Send + 'a>>;
+
+/// Represents a blocking job handed to a [`BlockingExecutor`]
+pub type BlockingJob = Box<dyn FnOnce() + Send>;
+
+/// Represents an executor that runs blocking jobs away from the tasks
+/// awaiting them, such as a runtime's pool of blocking threads
+pub type BlockingExecutor = Arc<dyn Fn(BlockingJob) + Send + Sync>;
+
+/// Sets the executor used by [`spawn_blocking`] to run blocking operations,
+/// replacing the default pool of threads shared by the process
+///
+/// ## Examples
+///
+/// ```
+/// use entity::{set_blocking_executor, spawn_blocking};
+///
+/// set_blocking_executor(|job| {
+///     std::thread::spawn(job);
+/// });
+///
+/// let x = futures::executor::block_on(spawn_blocking(|| Ok(5))).unwrap();
+/// assert_eq!(x, 5);
+/// ```
+pub fn set_blocking_executor<F: Fn(BlockingJob) + Send + Sync + 'static>(f: F) {
+    *BLOCKING_EXECUTOR.write().unwrap() = Some(Arc::new(f));
+}
+
+/// Clears any executor set by [`set_blocking_executor`], returning to the
+/// default pool of threads shared by the process
+pub fn clear_blocking_executor() {
+    *BLOCKING_EXECUTOR.write().unwrap() = None;
+}
+
+/// Runs the job using the executor that has been set, or the default pool
+/// of threads otherwise
+fn execute_blocking(job: BlockingJob) {
+    let executor = BLOCKING_EXECUTOR.read().unwrap().clone();
+    match executor {
+        Some(executor) => executor(job),
+        None => BLOCKING_POOL.execute(job),
+    }
+}
+
+/// Represents a bounded pool of threads, which are started as jobs arrive
+/// while none are idle and are then kept for later jobs
+struct WorkerPool {
+    sender: Mutex<Sender<BlockingJob>>,
+    receiver: Arc<Mutex<Receiver<BlockingJob>>>,
+    max_workers: usize,
+    workers: AtomicUsize,
+    idle: Arc<AtomicUsize>,
+}
+
+impl WorkerPool {
+    fn new(max_workers: usize) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            sender: Mutex::new(sender),
+            receiver: Arc::new(Mutex::new(receiver)),
+            max_workers,
+            workers: AtomicUsize::new(0),
+            idle: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Queues the job, starting another thread to run it if none are idle
+    /// and the pool has not reached its maximum size
+    fn execute(&self, job: BlockingJob) {
+        self.sender
+            .lock()
+            .unwrap()
+            .send(job)
+            .expect("Blocking pool stopped receiving jobs");
+
+        let can_start = self.idle.load(Ordering::SeqCst) == 0
+            && self
+                .workers
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                    if n < self.max_workers {
+                        Some(n + 1)
+                    } else {
+                        None
+                    }
+                })
+                .is_ok();
+        if can_start {
+            let receiver = Arc::clone(&self.receiver);
+            let idle = Arc::clone(&self.idle);
+            thread::Builder::new()
+                .name(String::from("entity-blocking"))
+                .spawn(move || loop {
+                    idle.fetch_add(1, Ordering::SeqCst);
+                    let job = receiver.lock().unwrap().recv();
+                    idle.fetch_sub(1, Ordering::SeqCst);
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => break,
+                    }
+                })
+                .expect("Failed to start blocking thread");
+        }
+    }
+}
+
+/// Produces a future that performs the blocking operation away from the
+/// task awaiting it once first polled, completing with the result of the
+/// operation
+///
+/// Operations are run by the executor given to [`set_blocking_executor`],
+/// or otherwise by a bounded pool of threads shared by the process, so this
+/// does not depend on any particular runtime. If the operation panics, the
+/// panic is resumed by the task awaiting the future.
+pub fn spawn_blocking<T, F>(f: F) -> DatabaseFuture<'static, T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> DatabaseResult<T> + Send + 'static,
+{
+    Box::pin(Blocking {
+        task: Some(Box::new(f)),
+        state: Arc::new(Mutex::new(BlockingState {
+            result: None,
+            waker: None,
+        })),
+    })
+}
+
+type BlockingTask<T> = Box<dyn FnOnce() -> DatabaseResult<T> + Send>;
+
+/// Future returned by [`spawn_blocking`]
+struct Blocking<T> {
+    task: Option<BlockingTask<T>>,
+    state: Arc<Mutex<BlockingState<T>>>,
+}
+
+/// State shared between a [`Blocking`] future and the job performing its
+/// operation
+struct BlockingState<T> {
+    result: Option<thread::Result<DatabaseResult<T>>>,
+    waker: Option<Waker>,
+}
+
+impl<T: Send + 'static> Future for Blocking<T> {
+    type Output = DatabaseResult<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        {
+            let mut state = this.state.lock().unwrap();
+            match state.result.take() {
+                Some(Ok(result)) => return Poll::Ready(result),
+                Some(Err(x)) => panic::resume_unwind(x),
+                None => state.waker = Some(cx.waker().clone()),
+            }
+        }
+
+        if let Some(task) = this.task.take() {
+            let state = Arc::clone(&this.state);
+            execute_blocking(Box::new(move || {
+                let result = panic::catch_unwind(AssertUnwindSafe(task));
+                let waker = {
+                    let mut state = state.lock().unwrap();
+                    state.result = Some(result);
+                    state.waker.take()
+                };
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            }));
+        }
+
+        Poll::Pending
+    }
+}
+
+/// Represents an asynchronous database, which performs non-blocking CRUD
+/// operations using ents. Mirrors [`Database`], but each operation returns
+/// a future to be awaited rather than blocking the caller.
+pub trait AsyncDatabase: AsAny + Send + Sync {
+    /// Retrieves a copy of a single, generic ent with the corresponding id
+    ///
+    /// This should not connect the ent back to the database upon return as
+    /// that decision should be made outside of the database itself.
+    fn get(&self, id: Id) -> DatabaseFuture<'_, Option<Box<dyn Ent>>>;
+
+    /// Removes the ent with the corresponding id, triggering edge
+    /// processing for all disconnected ents. Returns a boolean indicating
+    /// if an ent was removed.
+    fn remove(&self, id: Id) -> DatabaseFuture<'_, bool>;
+
+    /// Inserts a new ent using its id as the primary index, overwriting
+    /// any ent with a matching id. If the ent's id is set to the ephemeral
+    /// id (of 0), a unique id will be assigned to the ent prior to being
+    /// inserted.
+    ///
+    /// The ent's id is returned after being inserted.
+    fn insert(&self, ent: Box<dyn Ent>) -> DatabaseFuture<'_, Id>;
+
+    /// Performs a retrieval of multiple ents of any type
+    fn get_all(&self, ids: Vec<Id>) -> DatabaseFuture<'_, Vec<Box<dyn Ent>>>;
+
+    /// Finds all generic ents that match the query
+    fn find_all(&self, query: Query) -> DatabaseFuture<'_, Vec<Box<dyn Ent>>>;
+
+    /// Finds a single page of at most `page_size` generic ents that match
+    /// the query, alongside a cursor to resume the query after the page if
+    /// more ents follow it. The page size replaces any limit of the query.
+    fn find_page(&self, query: Query, page_size: usize) -> DatabaseFuture<'_, Page> {
+        let order = query.order().to_vec();
+        let ents = self.find_all(query.limit(page_size.saturating_add(1)));
+        Box::pin(async move { Ok(Page::from_results(&order, ents.await?, page_size)) })
+    }
+
+    /// Counts the generic ents that match the query, honoring its cursor,
+    /// offset, and limit
+    fn count(&self, query: Query) -> DatabaseFuture<'_, usize> {
+        let ents = self.find_all(query);
+        Box::pin(async move { Ok(ents.await?.len()) })
+    }
+
+    /// Returns true if at least one generic ent matches the query, honoring
+    /// its cursor, offset, and limit
+    fn exists(&self, query: Query) -> DatabaseFuture<'_, bool> {
+        let limit = query.get_limit().unwrap_or(1).min(1);
+        let ents = self.find_all(query.limit(limit));
+        Box::pin(async move { Ok(!ents.await?.is_empty()) })
+    }
+
+    /// Calculates the aggregation's aggregates over the generic ents that
+    /// match its query, producing the results for each group of ents
+    fn aggregate(&self, aggregation: Aggregation) -> DatabaseFuture<'_, Vec<AggregateGroup>> {
+        let ents = self.find_all(aggregation.query().clone());
+        Box::pin(async move {
+            let ents = ents.await?;
+            aggregation.apply(ents.into_iter().map(Ok))
+        })
+    }
+}
+
+/// Implementation for a generic trait object of [`AsyncDatabase`] that
+/// provides methods to downcast into a concrete type
+impl dyn AsyncDatabase {
+    /// Attempts to convert this dynamic AsyncDatabase ref into a concrete
+    /// AsyncDatabase ref by downcasting
+    pub fn as_async_database<D: AsyncDatabase>(&self) -> Option<&D> {
+        self.as_any().downcast_ref::<D>()
+    }
+
+    /// Attempts to convert this dynamic AsyncDatabase mutable ref into a
+    /// concrete AsyncDatabase mutable ref by downcasting
+    pub fn as_mut_async_database<D: AsyncDatabase>(&mut self) -> Option<&mut D> {
+        self.as_mut_any().downcast_mut::<D>()
+    }
+}
+
+/// Represents an adapter that exposes any synchronous [`Database`] as an
+/// [`AsyncDatabase`]
+///
+/// Each operation is performed by the wrapped database away from the task
+/// awaiting it once its future is first polled (see [`spawn_blocking`]), so
+/// a blocking database does not block the task. This means that operations
+/// do not participate in any transaction of the thread that awaits them.
+///
+/// ## Examples
+///
+/// ```
+/// use entity::{AsyncDatabase, AsyncDatabaseAdapter, Database, UntypedEnt};
+/// # use entity::{DatabaseResult, Ent, Id, Query};
+/// # struct MyDatabase;
+/// # impl Database for MyDatabase {
+/// #     fn get(&self, _: Id) -> DatabaseResult<Option<Box<dyn Ent>>> { Ok(None) }
+/// #     fn remove(&self, _: Id) -> DatabaseResult<bool> { Ok(false) }
+/// #     fn insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id> { Ok(ent.id()) }
+/// #     fn get_all(&self, _: Vec<Id>) -> DatabaseResult<Vec<Box<dyn Ent>>> { Ok(Vec::new()) }
+/// #     fn find_all(&self, _: Query) -> DatabaseResult<Vec<Box<dyn Ent>>> { Ok(Vec::new()) }
+/// # }
+///
+/// let db = AsyncDatabaseAdapter::new(MyDatabase);
+/// let id = futures::executor::block_on(async {
+///     db.insert(Box::from(UntypedEnt::empty_with_id(999))).await
+/// }).unwrap();
+/// assert_eq!(id, 999);
+/// ```
+#[derive(Clone)]
+pub struct AsyncDatabaseAdapter(DatabaseRc);
+
+impl AsyncDatabaseAdapter {
+    /// Creates a new adapter around the given database
+    pub fn new<D: Database>(db: D) -> Self {
+        Self(db_to_rc(db))
+    }
+
+    /// Returns a reference to the wrapped database
+    pub fn as_database(&self) -> &dyn Database {
+        self.0.as_ref().as_ref()
+    }
+
+    /// Returns a new reference to the wrapped database
+    pub fn to_database_rc(&self) -> DatabaseRc {
+        DatabaseRc::clone(&self.0)
+    }
+}
+
+impl From<DatabaseRc> for AsyncDatabaseAdapter {
+    /// Creates a new adapter around a shared database
+    fn from(db: DatabaseRc) -> Self {
+        Self(db)
+    }
+}
+
+impl AsyncDatabase for AsyncDatabaseAdapter {
+    fn get(&self, id: Id) -> DatabaseFuture<'_, Option<Box<dyn Ent>>> {
+        let db = DatabaseRc::clone(&self.0);
+        spawn_blocking(move || db.get(id))
+    }
+
+    fn remove(&self, id: Id) -> DatabaseFuture<'_, bool> {
+        let db = DatabaseRc::clone(&self.0);
+        spawn_blocking(move || db.remove(id))
+    }
+
+    fn insert(&self, ent: Box<dyn Ent>) -> DatabaseFuture<'_, Id> {
+        let db = DatabaseRc::clone(&self.0);
+        spawn_blocking(move || db.insert(ent))
+    }
+
+    fn get_all(&self, ids: Vec<Id>) -> DatabaseFuture<'_, Vec<Box<dyn Ent>>> {
+        let db = DatabaseRc::clone(&self.0);
+        spawn_blocking(move || db.get_all(ids))
+    }
+
+    fn find_all(&self, query: Query) -> DatabaseFuture<'_, Vec<Box<dyn Ent>>> {
+        let db = DatabaseRc::clone(&self.0);
+        spawn_blocking(move || db.find_all(query))
+    }
+
+    fn find_page(&self, query: Query, page_size: usize) -> DatabaseFuture<'_, Page> {
+        let db = DatabaseRc::clone(&self.0);
+        spawn_blocking(move || db.find_page(query, page_size))
+    }
+
+    fn count(&self, query: Query) -> DatabaseFuture<'_, usize> {
+        let db = DatabaseRc::clone(&self.0);
+        spawn_blocking(move || db.count(query))
+    }
+
+    fn exists(&self, query: Query) -> DatabaseFuture<'_, bool> {
+        let db = DatabaseRc::clone(&self.0);
+        spawn_blocking(move || db.exists(query))
+    }
+
+    fn aggregate(&self, aggregation: Aggregation) -> DatabaseFuture<'_, Vec<AggregateGroup>> {
+        let db = DatabaseRc::clone(&self.0);
+        spawn_blocking(move || db.aggregate(aggregation))
+    }
+}
@@ -0,0 +1,362 @@
+use crate::{
+    AggregateGroup, Aggregation, Assoc, AssocRange, Database, DatabaseError, DatabaseResult, Ent,
+    EntType, Id, Page, Patch, Path, Query, Subscription, Traversal, Value, EPHEMERAL_ID,
+};
+use std::{collections::HashMap, error::Error, fmt, sync::Arc};
+
+/// Alias to the result of a hook run before an operation, where an error
+/// vetoes the operation
+pub type HookResult = Result<(), Box<dyn Error + Send + Sync>>;
+
+/// Represents logic run whenever an ent is inserted, updated, or removed
+/// through a [`HookedDatabase`]
+///
+/// Hooks run before an operation can veto it by returning an error, which
+/// fails the operation with [`DatabaseError::Vetoed`] holding that error.
+/// Hooks run before an insert or update can also modify the ent being
+/// written. Every method does nothing by default.
+pub trait Hook: Send + Sync {
+    /// Invoked before a new ent is inserted
+    fn before_insert(&self, _ent: &mut dyn Ent) -> HookResult {
+        Ok(())
+    }
+
+    /// Invoked once a new ent is inserted, given the ent as it was stored
+    fn after_insert(&self, _ent: &dyn Ent) {}
+
+    /// Invoked before the existing ent is overwritten by the ent
+    fn before_update(&self, _existing: &dyn Ent, _ent: &mut dyn Ent) -> HookResult {
+        Ok(())
+    }
+
+    /// Invoked once the existing ent is overwritten, given the ent as it
+    /// was stored
+    fn after_update(&self, _existing: &dyn Ent, _ent: &dyn Ent) {}
+
+    /// Invoked before the ent is removed
+    fn before_remove(&self, _ent: &dyn Ent) -> HookResult {
+        Ok(())
+    }
+
+    /// Invoked once the ent is removed
+    fn after_remove(&self, _ent: &dyn Ent) {}
+}
+
+/// Represents a registry of hooks organized by the type of ent they run for
+#[derive(Clone, Default)]
+pub struct Hooks {
+    hooks: HashMap<String, Vec<Arc<dyn Hook>>>,
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.hooks.iter().map(|(ty, hooks)| (ty, hooks.len())))
+            .finish()
+    }
+}
+
+impl Hooks {
+    /// Creates a new, empty registry of hooks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the hook to run for ents of the given type, after any hooks
+    /// already registered for the type
+    pub fn register<T: Into<String>, H: Hook + 'static>(
+        &mut self,
+        r#type: T,
+        hook: H,
+    ) -> &mut Self {
+        self.hooks
+            .entry(r#type.into())
+            .or_default()
+            .push(Arc::new(hook));
+        self
+    }
+
+    /// Registers the hook to run for ents of the type given by
+    /// [`EntType::type_str`], after any hooks already registered for the type
+    pub fn register_for<E: EntType, H: Hook + 'static>(&mut self, hook: H) -> &mut Self {
+        self.register(E::type_str(), hook)
+    }
+
+    /// Returns the hooks registered for ents of the given type, in the order
+    /// they run
+    pub fn for_type(&self, r#type: &str) -> &[Arc<dyn Hook>] {
+        self.hooks
+            .get(r#type)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// Represents a database that wraps another database, running the hooks
+/// registered for each type of ent whenever an ent of that type is
+/// inserted, updated, or removed through it
+///
+/// Whether a write is an insert or an update depends on whether an ent with
+/// the same id already exists. Hooks run as soon as an operation is called,
+/// meaning that they run as writes are staged within a transaction, and only
+/// run for the ent being written or removed rather than any other ents
+/// changed as a result, such as those removed by edge deletion policies.
+///
+/// ## Examples
+///
+/// ```
+/// use entity::{Database, DatabaseError, Ent, Hook, HookResult, HookedDatabase, Hooks, Id};
+/// # use entity::{DatabaseResult, Query};
+/// # use std::{collections::HashMap, sync::Mutex};
+/// # #[derive(Default)]
+/// # struct MyDatabase(Mutex<HashMap<Id, Box<dyn Ent>>>);
+/// # impl Database for MyDatabase {
+/// #     fn get(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>> {
+/// #         Ok(self.0.lock().unwrap().get(&id).cloned())
+/// #     }
+/// #     fn remove(&self, id: Id) -> DatabaseResult<bool> {
+/// #         Ok(self.0.lock().unwrap().remove(&id).is_some())
+/// #     }
+/// #     fn insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id> {
+/// #         let id = ent.id();
+/// #         self.0.lock().unwrap().insert(id, ent);
+/// #         Ok(id)
+/// #     }
+/// #     fn get_all(&self, _: Vec<Id>) -> DatabaseResult<Vec<Box<dyn Ent>>> { Ok(Vec::new()) }
+/// #     fn find_all(&self, _: Query) -> DatabaseResult<Vec<Box<dyn Ent>>> { Ok(Vec::new()) }
+/// # }
+/// use entity::UntypedEnt;
+///
+/// struct KeepFirst;
+///
+/// impl Hook for KeepFirst {
+///     fn before_remove(&self, ent: &dyn Ent) -> HookResult {
+///         if ent.id() == 1 {
+///             Err("The first ent is kept".into())
+///         } else {
+///             Ok(())
+///         }
+///     }
+/// }
+///
+/// let mut hooks = Hooks::new();
+/// hooks.register_for::<UntypedEnt, _>(KeepFirst);
+/// let db = HookedDatabase::new(MyDatabase::default(), hooks);
+///
+/// db.insert(Box::from(UntypedEnt::empty_with_id(1))).unwrap();
+/// db.insert(Box::from(UntypedEnt::empty_with_id(2))).unwrap();
+///
+/// assert!(matches!(db.remove(1), Err(DatabaseError::Vetoed { id: 1, .. })));
+/// assert_eq!(db.remove(2).unwrap(), true);
+/// assert!(db.get(1).unwrap().is_some());
+/// ```
+pub struct HookedDatabase<D: Database> {
+    db: D,
+    hooks: Hooks,
+}
+
+impl<D: Database> HookedDatabase<D> {
+    /// Creates a new database that runs the hooks for writes to the database
+    /// that it wraps
+    pub fn new(db: D, hooks: Hooks) -> Self {
+        Self { db, hooks }
+    }
+
+    /// Returns a reference to the wrapped database, which does not run hooks
+    pub fn inner(&self) -> &D {
+        &self.db
+    }
+
+    /// Consumes the database, returning the wrapped database
+    pub fn into_inner(self) -> D {
+        self.db
+    }
+
+    /// Returns the hooks run by the database
+    pub fn hooks(&self) -> &Hooks {
+        &self.hooks
+    }
+
+    /// Writes the ent using the wrapped database, running hooks for an
+    /// update if an existing ent is provided and for an insert otherwise
+    ///
+    /// If the time that the ent was expected to be last updated is provided,
+    /// the ent is only written if the stored ent was last updated at that
+    /// time, even if the ent has since been marked as updated.
+    fn write(
+        &self,
+        mut ent: Box<dyn Ent>,
+        existing: Option<Box<dyn Ent>>,
+        expected: Option<u64>,
+    ) -> DatabaseResult<Id> {
+        let hooks = self.hooks.for_type(ent.r#type());
+
+        for hook in hooks {
+            match existing.as_deref() {
+                Some(existing) => hook.before_update(existing, ent.as_mut()),
+                None => hook.before_insert(ent.as_mut()),
+            }
+            .map_err(|source| DatabaseError::Vetoed {
+                id: ent.id(),
+                source,
+            })?;
+        }
+
+        let id = match expected {
+            Some(expected) => self.db.compare_and_insert_at(ent, expected)?,
+            None => self.db.insert(ent)?,
+        };
+
+        if let Some(ent) = self.db.get(id)? {
+            for hook in hooks {
+                match existing.as_deref() {
+                    Some(existing) => hook.after_update(existing, ent.as_ref()),
+                    None => hook.after_insert(ent.as_ref()),
+                }
+            }
+        }
+
+        Ok(id)
+    }
+
+    /// Writes the ent using the wrapped database, running any hooks for its
+    /// type
+    fn write_ent(&self, ent: Box<dyn Ent>, expected: Option<u64>) -> DatabaseResult<Id> {
+        if self.hooks.for_type(ent.r#type()).is_empty() {
+            return match expected {
+                Some(expected) => self.db.compare_and_insert_at(ent, expected),
+                None => self.db.insert(ent),
+            };
+        }
+
+        let existing = if ent.id() == EPHEMERAL_ID {
+            None
+        } else {
+            self.db.get(ent.id())?
+        };
+        self.write(ent, existing, expected)
+    }
+}
+
+impl<D: Database> Database for HookedDatabase<D> {
+    fn get(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>> {
+        self.db.get(id)
+    }
+
+    fn remove(&self, id: Id) -> DatabaseResult<bool> {
+        let ent = match self.db.get(id)? {
+            Some(ent) if !self.hooks.for_type(ent.r#type()).is_empty() => ent,
+            _ => return self.db.remove(id),
+        };
+        let hooks = self.hooks.for_type(ent.r#type());
+
+        for hook in hooks {
+            hook.before_remove(ent.as_ref())
+                .map_err(|source| DatabaseError::Vetoed { id, source })?;
+        }
+
+        let removed = self.db.remove(id)?;
+        if removed {
+            for hook in hooks {
+                hook.after_remove(ent.as_ref());
+            }
+        }
+
+        Ok(removed)
+    }
+
+    fn insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id> {
+        self.write_ent(ent, None)
+    }
+
+    fn compare_and_insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id> {
+        let expected = ent.last_updated();
+        self.write_ent(ent, Some(expected))
+    }
+
+    fn compare_and_insert_at(&self, ent: Box<dyn Ent>, expected: u64) -> DatabaseResult<Id> {
+        self.write_ent(ent, Some(expected))
+    }
+
+    fn update_fields(&self, id: Id, patch: Patch) -> DatabaseResult<Box<dyn Ent>> {
+        let existing = self.db.get(id)?.ok_or(DatabaseError::MissingEnt { id })?;
+        if self.hooks.for_type(existing.r#type()).is_empty() {
+            return self.db.update_fields(id, patch);
+        }
+
+        // Hooks need to see the patched ent before it is written, so the
+        // patch is applied here and written only if the stored ent is still
+        // the one that was patched
+        let last_updated = existing.last_updated();
+        let mut ent = existing.clone();
+        patch
+            .apply(ent.as_mut())
+            .map_err(|source| DatabaseError::EntMutationFailed { id, source })?;
+        self.write(ent, Some(existing), Some(last_updated))?;
+        self.db.get(id)?.ok_or(DatabaseError::MissingEnt { id })
+    }
+
+    fn get_all(&self, ids: Vec<Id>) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+        self.db.get_all(ids)
+    }
+
+    fn find_all(&self, query: Query) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+        self.db.find_all(query)
+    }
+
+    fn find_page(&self, query: Query, page_size: usize) -> DatabaseResult<Page> {
+        self.db.find_page(query, page_size)
+    }
+
+    fn count(&self, query: Query) -> DatabaseResult<usize> {
+        self.db.count(query)
+    }
+
+    fn exists(&self, query: Query) -> DatabaseResult<bool> {
+        self.db.exists(query)
+    }
+
+    fn aggregate(&self, aggregation: Aggregation) -> DatabaseResult<Vec<AggregateGroup>> {
+        self.db.aggregate(aggregation)
+    }
+
+    fn incoming_edges(&self, id: Id) -> DatabaseResult<Vec<(Id, String)>> {
+        self.db.incoming_edges(id)
+    }
+
+    fn assocs(&self, id: Id, edge: &str) -> DatabaseResult<Vec<Assoc>> {
+        self.db.assocs(id, edge)
+    }
+
+    fn assoc_range(&self, id: Id, edge: &str, range: AssocRange) -> DatabaseResult<Vec<Assoc>> {
+        self.db.assoc_range(id, edge, range)
+    }
+
+    fn assoc_count(&self, id: Id, edge: &str) -> DatabaseResult<usize> {
+        self.db.assoc_count(id, edge)
+    }
+
+    fn set_assoc_data(&self, id: Id, edge: &str, target: Id, data: Value) -> DatabaseResult<bool> {
+        self.db.set_assoc_data(id, edge, target, data)
+    }
+
+    fn subscribe(&self, query: Query) -> DatabaseResult<Subscription> {
+        self.db.subscribe(query)
+    }
+
+    fn traverse(&self, traversal: Traversal) -> DatabaseResult<Vec<(Path, Box<dyn Ent>)>> {
+        self.db.traverse(traversal)
+    }
+
+    fn begin_transaction(&self) -> DatabaseResult<()> {
+        self.db.begin_transaction()
+    }
+
+    fn commit_transaction(&self) -> DatabaseResult<()> {
+        self.db.commit_transaction()
+    }
+
+    fn rollback_transaction(&self) -> DatabaseResult<()> {
+        self.db.rollback_transaction()
+    }
+}
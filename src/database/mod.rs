@@ -0,0 +1,481 @@
+use crate::{
+    ent::{
+        AggregateGroup, Aggregation, Assoc, AssocRange, Ent, EntMutationError, Page, Path, Query,
+        Traversal, TypedPredicate, Value, ValueType,
+    },
+    AsAny, Id,
+};
+use derive_more::Display;
+use std::sync::{Arc, Weak};
+
+mod asynchronous;
+mod hooks;
+mod patch;
+mod privacy;
+mod subscription;
+mod transaction;
+pub use asynchronous::*;
+pub use hooks::*;
+pub use patch::*;
+pub use privacy::*;
+pub use subscription::*;
+pub use transaction::*;
+
+/// Represents a thread-safe reference to a boxed database trait object
+pub type DatabaseRc = Arc<Box<dyn Database>>;
+
+/// Converts a typed database into a `DatabaseRc`
+#[inline]
+pub fn db_to_rc<D: Database>(db: D) -> DatabaseRc {
+    Arc::new(Box::new(db))
+}
+
+/// Represents a weak thread-safe reference to a boxed database trait object
+pub type WeakDatabaseRc = Weak<Box<dyn Database>>;
+
+/// Alias to a result that can contain a database error
+pub type DatabaseResult<T> = Result<T, DatabaseError>;
+
+/// Represents some error the can occur when accessing the database
+#[derive(Debug, Display)]
+pub enum DatabaseError {
+    #[display(fmt = "Connection Issue: {}", source)]
+    Connection {
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[display(fmt = "Disconnected")]
+    Disconnected,
+
+    #[display(fmt = "Missing Field: {}", name)]
+    MissingField { name: String },
+
+    #[display(fmt = "Missing Edge: {}", name)]
+    MissingEdge { name: String },
+
+    #[display(fmt = "Missing Ent: {}", id)]
+    MissingEnt { id: Id },
+
+    #[display(fmt = "Mutation Failed (Ent = {}): {}", id, source)]
+    EntMutationFailed { id: Id, source: EntMutationError },
+
+    #[display(fmt = "Expected type {}, but got type {}", expected, actual)]
+    WrongType {
+        expected: ValueType,
+        actual: ValueType,
+    },
+
+    #[display(fmt = "Corrupted Ent {}: {}", id, source)]
+    CorruptedEnt {
+        id: Id,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[display(fmt = "Broken Edge {}", name)]
+    BrokenEdge { name: String },
+
+    #[display(fmt = "Ent Capacity Reached")]
+    EntCapacityReached,
+
+    #[display(fmt = "Transaction already in progress")]
+    TransactionInProgress,
+
+    #[display(fmt = "No transaction in progress")]
+    NoTransaction,
+
+    #[display(
+        fmt = "Conflict (Ent = {}): expected last updated {}, but was {}",
+        id,
+        expected,
+        actual
+    )]
+    Conflict { id: Id, expected: u64, actual: u64 },
+
+    #[display(
+        fmt = "Not Unique (Ent = {}): value of field {} already used",
+        id,
+        name
+    )]
+    NotUnique { name: String, id: Id },
+
+    #[display(fmt = "Vetoed (Ent = {}): {}", id, source)]
+    Vetoed {
+        id: Id,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[display(fmt = "Denied (Ent = {})", id)]
+    Denied { id: Id },
+
+    #[display(fmt = "{}", source)]
+    Other {
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+impl std::error::Error for DatabaseError {}
+
+/// Represents a synchronous database, which performs blocking CRUD
+/// operations using ents. Given that many database implementations handle
+/// interior mutability themselves, the API of this trait does not provide
+/// any mut guarantees itself.
+pub trait Database: AsAny + Send + Sync {
+    /// Retrieves a copy of a single, generic ent with the corresponding id
+    ///
+    /// This should not connect the ent back to the database upon return as
+    /// that decision should be made outside of the database itself.
+    fn get(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>>;
+
+    /// Removes the ent with the corresponding id, triggering edge
+    /// processing for all disconnected ents. Returns a boolean indicating
+    /// if an ent was removed.
+    fn remove(&self, id: Id) -> DatabaseResult<bool>;
+
+    /// Inserts a new ent using its id as the primary index, overwriting
+    /// any ent with a matching id. If the ent's id is set to the ephemeral
+    /// id (of 0), a unique id will be assigned to the ent prior to being
+    /// inserted.
+    ///
+    /// The ent's id is returned after being inserted.
+    fn insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id>;
+
+    /// Inserts the ent like [`Database::insert`], but only if any stored ent
+    /// with a matching id was last updated at the same time as the ent being
+    /// inserted, serving as the version of the ent. Otherwise, this fails
+    /// with [`DatabaseError::Conflict`] and nothing is written.
+    ///
+    /// By default, this compares against the ent's last updated time using
+    /// [`Database::compare_and_insert_at`].
+    fn compare_and_insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id> {
+        let expected = ent.last_updated();
+        self.compare_and_insert_at(ent, expected)
+    }
+
+    /// Inserts the ent like [`Database::compare_and_insert`], but compares
+    /// any stored ent against the expected last updated time rather than
+    /// that of the ent, which may have been marked as updated after it was
+    /// loaded, such as by a hook or while being patched
+    ///
+    /// By default, the stored ent is checked and then the ent is inserted,
+    /// which is not done atomically.
+    fn compare_and_insert_at(&self, ent: Box<dyn Ent>, expected: u64) -> DatabaseResult<Id> {
+        if let Some(existing) = self.get(ent.id())? {
+            if existing.last_updated() != expected {
+                return Err(DatabaseError::Conflict {
+                    id: ent.id(),
+                    expected,
+                    actual: existing.last_updated(),
+                });
+            }
+        }
+
+        self.insert(ent)
+    }
+
+    /// Applies the patch to the fields and edges of the ent with the given id
+    /// and writes the result, returning the updated ent. If the ent does not
+    /// exist or any change in the patch fails, nothing is written.
+    ///
+    /// By default, the ent is retrieved, patched, and then inserted, which is
+    /// not done atomically.
+    fn update_fields(&self, id: Id, patch: Patch) -> DatabaseResult<Box<dyn Ent>> {
+        let mut ent = self.get(id)?.ok_or(DatabaseError::MissingEnt { id })?;
+        patch
+            .apply(ent.as_mut())
+            .map_err(|source| DatabaseError::EntMutationFailed { id, source })?;
+        self.insert(ent)?;
+        self.get(id)?.ok_or(DatabaseError::MissingEnt { id })
+    }
+
+    /// Performs a retrieval of multiple ents of any type
+    fn get_all(&self, ids: Vec<Id>) -> DatabaseResult<Vec<Box<dyn Ent>>>;
+
+    /// Finds all generic ents that match the query
+    fn find_all(&self, query: Query) -> DatabaseResult<Vec<Box<dyn Ent>>>;
+
+    /// Finds a single page of at most `page_size` generic ents that match
+    /// the query, alongside a cursor to resume the query after the page if
+    /// more ents follow it. The page size replaces any limit of the query.
+    ///
+    /// To retrieve the next page, the cursor is provided to the query using
+    /// [`Query::after`] while keeping the query's ordering clauses the same.
+    fn find_page(&self, query: Query, page_size: usize) -> DatabaseResult<Page> {
+        let order = query.order().to_vec();
+        let ents = self.find_all(query.limit(page_size.saturating_add(1)))?;
+        Ok(Page::from_results(&order, ents, page_size))
+    }
+
+    /// Counts the generic ents that match the query, honoring its cursor,
+    /// offset, and limit
+    ///
+    /// By default, this finds all of the matching ents, so databases are
+    /// encouraged to count the ids of matching ents instead.
+    fn count(&self, query: Query) -> DatabaseResult<usize> {
+        self.find_all(query).map(|ents| ents.len())
+    }
+
+    /// Returns true if at least one generic ent matches the query, honoring
+    /// its cursor, offset, and limit
+    ///
+    /// By default, this finds at most one of the matching ents, so databases
+    /// are encouraged to check the ids of matching ents instead.
+    fn exists(&self, query: Query) -> DatabaseResult<bool> {
+        let limit = query.get_limit().unwrap_or(1).min(1);
+        self.find_all(query.limit(limit))
+            .map(|ents| !ents.is_empty())
+    }
+
+    /// Calculates the aggregation's aggregates over the generic ents that
+    /// match its query, producing the results for each group of ents
+    ///
+    /// By default, this finds all of the matching ents before aggregating
+    /// them, so databases are encouraged to aggregate the ents one at a time.
+    fn aggregate(&self, aggregation: Aggregation) -> DatabaseResult<Vec<AggregateGroup>> {
+        let ents = self.find_all(aggregation.query().clone())?;
+        aggregation.apply(ents.into_iter().map(Ok))
+    }
+
+    /// Finds the edges of other ents that contain the ent with the given id,
+    /// producing the id of each ent alongside the name of its edge ordered
+    /// by id and then name
+    ///
+    /// By default, this checks the edges of every ent, so databases are
+    /// encouraged to maintain an index of the ents contained by edges.
+    fn incoming_edges(&self, id: Id) -> DatabaseResult<Vec<(Id, String)>> {
+        let ents = self.find_all(Query::default().where_id(TypedPredicate::always()))?;
+        let mut incoming: Vec<(Id, String)> = ents
+            .iter()
+            .flat_map(|ent| {
+                ent.edges()
+                    .into_iter()
+                    .filter(|edge| edge.to_ids().contains(&id))
+                    .map(move |edge| (ent.id(), edge.name().to_string()))
+            })
+            .collect();
+        incoming.sort();
+        Ok(incoming)
+    }
+
+    /// Returns the associations between the ent with the given id and the
+    /// ents on its edge of the given name, ordered by when each association
+    /// was created and then by id
+    ///
+    /// By default, no associations are recorded, so every association is
+    /// treated as created when the ent was last updated and has no data.
+    fn assocs(&self, id: Id, edge: &str) -> DatabaseResult<Vec<Assoc>> {
+        Ok(match self.get(id)? {
+            Some(ent) => match ent.edge(edge) {
+                Some(value) => Assoc::for_ids(value.to_ids(), &[], ent.last_updated()),
+                None => Vec::new(),
+            },
+            None => Vec::new(),
+        })
+    }
+
+    /// Returns the associations between the ent with the given id and the
+    /// ents on its edge of the given name that are covered by the range
+    ///
+    /// By default, this selects the range from all associations of the edge.
+    fn assoc_range(&self, id: Id, edge: &str, range: AssocRange) -> DatabaseResult<Vec<Assoc>> {
+        self.assocs(id, edge).map(|assocs| range.apply(assocs))
+    }
+
+    /// Returns the total number of associations between the ent with the
+    /// given id and the ents on its edge of the given name
+    fn assoc_count(&self, id: Id, edge: &str) -> DatabaseResult<usize> {
+        self.assocs(id, edge).map(|assocs| assocs.len())
+    }
+
+    /// Attaches data to the association between the ent with the given id
+    /// and the target ent on its edge of the given name, returning false if
+    /// the edge of the stored ent does not contain the target
+    ///
+    /// The data is written immediately, even if the current thread has a
+    /// transaction in progress. By default, associations cannot carry data,
+    /// so this always fails.
+    fn set_assoc_data(
+        &self,
+        _id: Id,
+        _edge: &str,
+        _target: Id,
+        _data: Value,
+    ) -> DatabaseResult<bool> {
+        Err(DatabaseError::Other {
+            source: Box::from("Associations cannot carry data in this database"),
+        })
+    }
+
+    /// Subscribes to changes made to ents once they are written, receiving
+    /// only the changes where the ent before or after the change passes the
+    /// filters of the query as described by [`Change::matches`]
+    ///
+    /// Changes where whether the ent passes depends on other ents, such as
+    /// through [`crate::Filter::Edge`], are also received so that none are
+    /// missed.
+    ///
+    /// Changes staged by a transaction are received once the transaction is
+    /// committed. By default, changes cannot be subscribed to, so this
+    /// always fails.
+    fn subscribe(&self, _query: Query) -> DatabaseResult<Subscription> {
+        Err(DatabaseError::Other {
+            source: Box::from("Changes cannot be subscribed to in this database"),
+        })
+    }
+
+    /// Walks through generic ents by following the edges of the traversal,
+    /// producing the path taken to reach each ent visited alongside the ent
+    ///
+    /// By default, this finds each ent reached through an edge with a filter
+    /// using a query, so databases are encouraged to check the filter
+    /// against the ent directly.
+    fn traverse(&self, traversal: Traversal) -> DatabaseResult<Vec<(Path, Box<dyn Ent>)>> {
+        traversal.apply(|id, filter| match filter {
+            Some(filter) => {
+                let query = Query::default()
+                    .where_id(TypedPredicate::equals(id))
+                    .where_and(vec![filter.clone()]);
+                self.find_all(query).map(|mut ents| ents.pop())
+            }
+            None => self.get(id),
+        })
+    }
+
+    /// Begins a new transaction for the current thread. Until the transaction
+    /// is committed or rolled back, all inserts and removals made on this
+    /// thread are staged rather than written, and retrievals made on this
+    /// thread will reflect the staged changes.
+    ///
+    /// Fails if the current thread already has a transaction in progress.
+    /// By default, transactions are not supported, so this always fails.
+    ///
+    /// As transactions are tied to threads, an asynchronous task that can
+    /// resume on another thread after an `.await` must not keep a
+    /// transaction in progress across it. Operations performed through an
+    /// [`AsyncDatabase`] or the asynchronous methods of [`Ent`] run on
+    /// separate threads and never participate in the caller's transaction.
+    fn begin_transaction(&self) -> DatabaseResult<()> {
+        Err(DatabaseError::Other {
+            source: Box::from("Transactions are not supported in this database"),
+        })
+    }
+
+    /// Applies all changes staged by the current thread's transaction
+    /// atomically, meaning that either all of them are written or none are.
+    ///
+    /// Fails if the current thread has no transaction in progress, which is
+    /// always the case by default.
+    fn commit_transaction(&self) -> DatabaseResult<()> {
+        Err(DatabaseError::NoTransaction)
+    }
+
+    /// Discards all changes staged by the current thread's transaction
+    ///
+    /// Fails if the current thread has no transaction in progress, which is
+    /// always the case by default.
+    fn rollback_transaction(&self) -> DatabaseResult<()> {
+        Err(DatabaseError::NoTransaction)
+    }
+}
+
+/// Implementation for a generic trait object of [`Database`] that provides
+/// methods to downcast into a concrete type
+impl dyn Database {
+    /// Attempts to convert this dynamic Database ref into a concrete Database
+    /// ref by downcasting
+    pub fn as_database<D: Database>(&self) -> Option<&D> {
+        self.as_any().downcast_ref::<D>()
+    }
+
+    /// Attempts to convert this dynamic Database mutable ref into a concrete
+    /// Database mutable ref by downcasting
+    pub fn as_mut_database<D: Database>(&mut self) -> Option<&mut D> {
+        self.as_mut_any().downcast_mut::<D>()
+    }
+}
+
+pub trait DatabaseExt: Database {
+    /// Inserts an ent of a specific type
+    fn insert_typed<E: Ent>(&self, ent: E) -> DatabaseResult<Id>;
+
+    /// Retrieves an ent by id with a specific type
+    fn get_typed<E: Ent>(&self, id: Id) -> DatabaseResult<Option<E>>;
+
+    /// Retrieves ents by id with a specific type
+    fn get_all_typed<E: Ent>(&self, ids: Vec<Id>) -> DatabaseResult<Vec<E>>;
+
+    /// Finds ents that match the specified query and are of the specified type
+    fn find_all_typed<E: Ent>(&self, query: Query) -> DatabaseResult<Vec<E>>;
+
+    /// Finds a single page of ents that match the specified query and are of
+    /// the specified type
+    fn find_page_typed<E: Ent>(&self, query: Query, page_size: usize) -> DatabaseResult<Page<E>>;
+
+    /// Walks through ents by following the edges of the traversal,
+    /// producing the ids of the ents visited
+    fn traverse_ids(&self, traversal: Traversal) -> DatabaseResult<Vec<Id>>;
+
+    /// Walks through ents by following the edges of the traversal,
+    /// producing the ents visited
+    fn traverse_ents(&self, traversal: Traversal) -> DatabaseResult<Vec<Box<dyn Ent>>>;
+
+    /// Finds a shortest path from the ents of the traversal to the ent with
+    /// the given id by following the edges of the traversal breadth first
+    fn shortest_path(&self, traversal: Traversal, to: Id) -> DatabaseResult<Option<Path>>;
+
+    /// Runs the function within a new transaction, committing the staged
+    /// changes if the function succeeds and rolling them back otherwise
+    fn transaction<R, F: FnOnce(&Self) -> DatabaseResult<R>>(&self, f: F) -> DatabaseResult<R>;
+}
+
+impl<T: Database + ?Sized> DatabaseExt for T {
+    fn insert_typed<E: Ent>(&self, ent: E) -> DatabaseResult<Id> {
+        self.insert(Box::from(ent))
+    }
+
+    fn get_typed<E: Ent>(&self, id: Id) -> DatabaseResult<Option<E>> {
+        self.get(id).map(|x| x.and_then(|ent| ent.to_ent::<E>()))
+    }
+
+    fn get_all_typed<E: Ent>(&self, ids: Vec<Id>) -> DatabaseResult<Vec<E>> {
+        self.get_all(ids)
+            .map(|x| x.into_iter().filter_map(|ent| ent.to_ent::<E>()).collect())
+    }
+
+    fn find_all_typed<E: Ent>(&self, query: Query) -> DatabaseResult<Vec<E>> {
+        self.find_all(query)
+            .map(|x| x.into_iter().filter_map(|ent| ent.to_ent::<E>()).collect())
+    }
+
+    fn find_page_typed<E: Ent>(&self, query: Query, page_size: usize) -> DatabaseResult<Page<E>> {
+        self.find_page(query, page_size).map(Page::into_typed)
+    }
+
+    fn traverse_ids(&self, traversal: Traversal) -> DatabaseResult<Vec<Id>> {
+        self.traverse(traversal)
+            .map(|visited| visited.iter().map(|(path, _)| path.end()).collect())
+    }
+
+    fn traverse_ents(&self, traversal: Traversal) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+        self.traverse(traversal)
+            .map(|visited| visited.into_iter().map(|(_, ent)| ent).collect())
+    }
+
+    fn shortest_path(&self, traversal: Traversal, to: Id) -> DatabaseResult<Option<Path>> {
+        self.traverse(traversal.breadth_first().to(to))
+            .map(|mut visited| visited.pop().map(|(path, _)| path))
+    }
+
+    fn transaction<R, F: FnOnce(&Self) -> DatabaseResult<R>>(&self, f: F) -> DatabaseResult<R> {
+        self.begin_transaction()?;
+        match f(self) {
+            Ok(x) => {
+                self.commit_transaction()?;
+                Ok(x)
+            }
+            Err(x) => {
+                self.rollback_transaction()?;
+                Err(x)
+            }
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{EdgeValue, Ent, EntMutationError, Id, Value};
+
+/// Represents a single change made by a [`Patch`] to a field or edge
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub enum PatchOp {
+    /// Set the field with the given name to the value
+    SetField(String, Value),
+
+    /// Set the edge with the given name to the value
+    SetEdge(String, EdgeValue),
+
+    /// Add the ids to the edge with the given name
+    AddIds(String, Vec<Id>),
+
+    /// Remove the ids from the edge with the given name
+    RemoveIds(String, Vec<Id>),
+}
+
+impl PatchOp {
+    /// Applies the change to the ent, failing if the field being set is
+    /// immutable or computed according to its definition
+    pub fn apply(&self, ent: &mut dyn Ent) -> Result<(), EntMutationError> {
+        match self {
+            Self::SetField(name, value) => {
+                match ent.field_definition(name) {
+                    Some(def) if def.is_computed() => {
+                        return Err(EntMutationError::FieldComputed { name: name.clone() })
+                    }
+                    Some(def) if def.is_immutable() => {
+                        return Err(EntMutationError::FieldImmutable { name: name.clone() })
+                    }
+                    Some(_) => {}
+                    None => return Err(EntMutationError::NoField { name: name.clone() }),
+                }
+                ent.update_field(name, value.clone()).map(|_| ())
+            }
+            Self::SetEdge(name, value) => ent.update_edge(name, value.clone()).map(|_| ()),
+            Self::AddIds(name, ids) => {
+                let mut value = edge_value(ent, name)?;
+                value
+                    .add_ids(ids.iter().copied())
+                    .map_err(|source| EntMutationError::BadEdgeValueMutation { source })?;
+                ent.update_edge(name, value).map(|_| ())
+            }
+            Self::RemoveIds(name, ids) => {
+                let mut value = edge_value(ent, name)?;
+                value
+                    .remove_ids(ids.iter().copied())
+                    .map_err(|source| EntMutationError::BadEdgeValueMutation { source })?;
+                ent.update_edge(name, value).map(|_| ())
+            }
+        }
+    }
+}
+
+fn edge_value(ent: &dyn Ent, name: &str) -> Result<EdgeValue, EntMutationError> {
+    ent.edge(name).ok_or_else(|| EntMutationError::NoEdge {
+        name: name.to_string(),
+    })
+}
+
+/// Represents a set of changes to the fields and edges of an ent, which a
+/// database applies all at once, or not at all
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Patch {
+    ops: Vec<PatchOp>,
+}
+
+impl Patch {
+    /// Creates a new, empty patch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates the patch to set the field with the given name to the value
+    pub fn set_field<N: Into<String>, V: Into<Value>>(mut self, name: N, value: V) -> Self {
+        self.ops.push(PatchOp::SetField(name.into(), value.into()));
+        self
+    }
+
+    /// Updates the patch to set the edge with the given name to the value
+    pub fn set_edge<N: Into<String>, V: Into<EdgeValue>>(mut self, name: N, value: V) -> Self {
+        self.ops.push(PatchOp::SetEdge(name.into(), value.into()));
+        self
+    }
+
+    /// Updates the patch to add the ids to the edge with the given name
+    pub fn add_ids<N: Into<String>, I: IntoIterator<Item = Id>>(mut self, name: N, ids: I) -> Self {
+        self.ops
+            .push(PatchOp::AddIds(name.into(), ids.into_iter().collect()));
+        self
+    }
+
+    /// Updates the patch to remove the ids from the edge with the given name
+    pub fn remove_ids<N: Into<String>, I: IntoIterator<Item = Id>>(
+        mut self,
+        name: N,
+        ids: I,
+    ) -> Self {
+        self.ops
+            .push(PatchOp::RemoveIds(name.into(), ids.into_iter().collect()));
+        self
+    }
+
+    /// Returns the changes in the order they were added
+    pub fn ops(&self) -> &[PatchOp] {
+        &self.ops
+    }
+
+    /// Returns the total changes in the patch
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Returns true if the patch has no changes
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Applies each change to the ent in order, stopping at the first
+    /// change that fails, which leaves the ent partially changed
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::{Edge, Ent, Field, FieldAttribute, Patch, UntypedEnt, Value};
+    ///
+    /// let mut ent = UntypedEnt::from_collections(
+    ///     1,
+    ///     vec![
+    ///         Field::new("name", String::from("alice")),
+    ///         Field::new_with_attributes("code", 7, vec![FieldAttribute::Immutable]),
+    ///     ],
+    ///     vec![Edge::new("friends", vec![2, 3])],
+    /// );
+    ///
+    /// let patch = Patch::new()
+    ///     .set_field("name", "bob")
+    ///     .add_ids("friends", vec![4])
+    ///     .remove_ids("friends", vec![2]);
+    /// patch.apply(&mut ent).unwrap();
+    /// assert_eq!(ent.field("name"), Some(Value::from("bob")));
+    /// assert_eq!(ent.edge("friends").unwrap().to_ids(), vec![3, 4]);
+    ///
+    /// assert!(Patch::new().set_field("code", 8).apply(&mut ent).is_err());
+    /// assert_eq!(ent.field("code"), Some(Value::from(7)));
+    /// ```
+    pub fn apply(&self, ent: &mut dyn Ent) -> Result<(), EntMutationError> {
+        for op in self.ops.iter() {
+            op.apply(ent)?;
+        }
+        Ok(())
+    }
+}
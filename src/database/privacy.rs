@@ -0,0 +1,649 @@
+use crate::{
+    AggregateGroup, Aggregation, Assoc, AssocRange, Change, Database, DatabaseError,
+    DatabaseResult, Ent, EntType, Id, IndexKey, Page, Patch, Path, Query, Subscription, Traversal,
+    TypedPredicate, Value,
+};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{mpsc::RecvTimeoutError, Arc},
+    thread,
+    time::Duration,
+};
+
+/// How long a subscription's forwarding thread waits for a change before
+/// checking if the subscription has been dropped
+const SUBSCRIPTION_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Represents who is reading or writing ents through a [`PrivacyDatabase`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub enum Viewer {
+    /// Viewer that is not represented by any ent, such as a visitor who has
+    /// not logged in
+    Anonymous,
+
+    /// Viewer represented by the ent with the given id
+    Ent(Id),
+
+    /// Viewer that bypasses every policy, such as the system itself
+    Omniscient,
+}
+
+impl Default for Viewer {
+    /// Returns an anonymous viewer
+    fn default() -> Self {
+        Self::Anonymous
+    }
+}
+
+impl Viewer {
+    /// The id of the ent representing the viewer, if there is one
+    pub fn id(&self) -> Option<Id> {
+        match self {
+            Self::Ent(id) => Some(*id),
+            Self::Anonymous | Self::Omniscient => None,
+        }
+    }
+
+    /// Returns true if the viewer bypasses every policy
+    pub fn is_omniscient(&self) -> bool {
+        matches!(self, Self::Omniscient)
+    }
+}
+
+/// Alias to the function used by a [`PolicyRule`] to check a viewer against
+/// an ent
+pub type PolicyCheck = Arc<dyn Fn(&Viewer, &dyn Ent) -> bool + Send + Sync>;
+
+/// Represents a single rule of a [`Policy`], which decides whether a viewer
+/// can access an ent only if its check passes
+#[derive(Clone)]
+pub enum PolicyRule {
+    /// Allows access if the check passes
+    AllowIf(PolicyCheck),
+
+    /// Denies access if the check passes
+    DenyIf(PolicyCheck),
+}
+
+impl fmt::Debug for PolicyRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AllowIf(_) => write!(f, "AllowIf"),
+            Self::DenyIf(_) => write!(f, "DenyIf"),
+        }
+    }
+}
+
+impl PolicyRule {
+    /// Creates a rule that allows access if the check passes
+    pub fn allow_if<F: Fn(&Viewer, &dyn Ent) -> bool + Send + Sync + 'static>(f: F) -> Self {
+        Self::AllowIf(Arc::new(f))
+    }
+
+    /// Creates a rule that denies access if the check passes
+    pub fn deny_if<F: Fn(&Viewer, &dyn Ent) -> bool + Send + Sync + 'static>(f: F) -> Self {
+        Self::DenyIf(Arc::new(f))
+    }
+
+    /// Creates a rule that always allows access
+    pub fn allow_all() -> Self {
+        Self::allow_if(|_, _| true)
+    }
+
+    /// Creates a rule that always denies access
+    pub fn deny_all() -> Self {
+        Self::deny_if(|_, _| true)
+    }
+
+    /// Creates a rule that allows access if the viewer is the ent itself
+    pub fn allow_if_viewer_is_ent() -> Self {
+        Self::allow_if(|viewer, ent| viewer.id() == Some(ent.id()))
+    }
+
+    /// Creates a rule that allows access if the field with the given name
+    /// holds the id of the viewer
+    pub fn allow_if_field_is_viewer<N: Into<String>>(name: N) -> Self {
+        let name = name.into();
+        Self::allow_if(move |viewer, ent| match (viewer.id(), ent.field(&name)) {
+            (Some(id), Some(value)) => {
+                IndexKey::from_value(&value) == IndexKey::from_value(&Value::from(id))
+            }
+            _ => false,
+        })
+    }
+
+    /// Creates a rule that allows access if the edge with the given name
+    /// contains the id of the viewer
+    pub fn allow_if_edge_has_viewer<N: Into<String>>(name: N) -> Self {
+        let name = name.into();
+        Self::allow_if(move |viewer, ent| match (viewer.id(), ent.edge(&name)) {
+            (Some(id), Some(value)) => value.to_ids().contains(&id),
+            _ => false,
+        })
+    }
+}
+
+/// Represents the rules that decide whether a viewer can read or write an
+/// ent of some type
+///
+/// Rules are checked in the order they were added, where the first rule
+/// whose check passes decides whether access is allowed or denied. Access
+/// is denied if no rule's check passes.
+///
+/// ## Examples
+///
+/// ```
+/// use entity::{Field, Policy, PolicyRule, UntypedEnt, Viewer};
+///
+/// let policy = Policy::new()
+///     .read(PolicyRule::deny_if(|_, ent| ent.field("hidden").is_some()))
+///     .read(PolicyRule::allow_all())
+///     .write(PolicyRule::allow_if_field_is_viewer("owner"));
+///
+/// let ent = UntypedEnt::from_collections(1, vec![Field::new("owner", 2usize)], vec![]);
+/// assert!(policy.can_read(&Viewer::Anonymous, &ent));
+/// assert!(!policy.can_write(&Viewer::Anonymous, &ent));
+/// assert!(!policy.can_write(&Viewer::Ent(3), &ent));
+/// assert!(policy.can_write(&Viewer::Ent(2), &ent));
+///
+/// let ent = UntypedEnt::from_collections(1, vec![Field::new("hidden", true)], vec![]);
+/// assert!(!policy.can_read(&Viewer::Anonymous, &ent));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Policy {
+    read: Vec<PolicyRule>,
+    write: Vec<PolicyRule>,
+}
+
+impl Policy {
+    /// Creates a new policy without any rules, which denies all access
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates the policy to check the rule when reading an ent, after any
+    /// read rules already added
+    pub fn read(mut self, rule: PolicyRule) -> Self {
+        self.read.push(rule);
+        self
+    }
+
+    /// Updates the policy to check the rule when writing an ent, after any
+    /// write rules already added
+    pub fn write(mut self, rule: PolicyRule) -> Self {
+        self.write.push(rule);
+        self
+    }
+
+    /// Returns true if the viewer can read the ent
+    pub fn can_read(&self, viewer: &Viewer, ent: &dyn Ent) -> bool {
+        viewer.is_omniscient() || check_rules(&self.read, viewer, ent)
+    }
+
+    /// Returns true if the viewer can insert, update, or remove the ent
+    pub fn can_write(&self, viewer: &Viewer, ent: &dyn Ent) -> bool {
+        viewer.is_omniscient() || check_rules(&self.write, viewer, ent)
+    }
+}
+
+fn check_rules(rules: &[PolicyRule], viewer: &Viewer, ent: &dyn Ent) -> bool {
+    rules
+        .iter()
+        .find_map(|rule| match rule {
+            PolicyRule::AllowIf(check) if check(viewer, ent) => Some(true),
+            PolicyRule::DenyIf(check) if check(viewer, ent) => Some(false),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Represents a type that declares the policy of one or more types of ents
+pub trait PrivacyPolicy {
+    /// Returns the policy to register
+    fn policy() -> Policy;
+}
+
+/// Represents an ent that declares the type providing its policy
+pub trait EntPolicy: EntType {
+    /// The type providing the policy of the ent
+    type Policy: PrivacyPolicy;
+}
+
+/// Represents a registry of policies organized by the type of ent they apply
+/// to, where ents of types without a policy are always allowed
+#[derive(Clone, Debug, Default)]
+pub struct Policies {
+    policies: HashMap<String, Policy>,
+}
+
+impl Policies {
+    /// Creates a new, empty registry of policies
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the policy for ents of the given type, replacing any policy
+    /// already registered for the type
+    pub fn register<T: Into<String>>(&mut self, r#type: T, policy: Policy) -> &mut Self {
+        self.policies.insert(r#type.into(), policy);
+        self
+    }
+
+    /// Registers the policy for ents of the type given by
+    /// [`EntType::type_str`], replacing any policy already registered for
+    /// the type
+    pub fn register_for<E: EntType>(&mut self, policy: Policy) -> &mut Self {
+        self.register(E::type_str(), policy)
+    }
+
+    /// Registers the policy declared by the ent for its type, replacing any
+    /// policy already registered for the type
+    pub fn register_ent<E: EntPolicy>(&mut self) -> &mut Self {
+        self.register_for::<E>(E::Policy::policy())
+    }
+
+    /// Returns the policy registered for ents of the given type
+    pub fn for_type(&self, r#type: &str) -> Option<&Policy> {
+        self.policies.get(r#type)
+    }
+
+    /// Returns true if the viewer can read the ent
+    pub fn can_read(&self, viewer: &Viewer, ent: &dyn Ent) -> bool {
+        self.for_type(ent.r#type())
+            .map_or(true, |policy| policy.can_read(viewer, ent))
+    }
+
+    /// Returns true if the viewer can insert, update, or remove the ent
+    pub fn can_write(&self, viewer: &Viewer, ent: &dyn Ent) -> bool {
+        self.for_type(ent.r#type())
+            .map_or(true, |policy| policy.can_write(viewer, ent))
+    }
+
+    /// Returns true if the viewer can read the change, which requires being
+    /// able to read each snapshot of the ent that it has
+    pub fn can_read_change(&self, viewer: &Viewer, change: &Change) -> bool {
+        match (change.before(), change.after()) {
+            (None, None) => viewer.is_omniscient() || self.for_type(change.r#type()).is_none(),
+            (before, after) => before
+                .into_iter()
+                .chain(after)
+                .all(|ent| self.can_read(viewer, ent)),
+        }
+    }
+
+    /// Returns true if the viewer is never restricted by the policies
+    fn is_unrestricted(&self, viewer: &Viewer) -> bool {
+        viewer.is_omniscient() || self.policies.is_empty()
+    }
+}
+
+/// Represents a database that wraps another database, checking the policies
+/// registered for each type of ent against a viewer whenever an ent of that
+/// type is read or written through it
+///
+/// Ents that the viewer cannot read are left out of results as if they did
+/// not exist, and writes of ents that the viewer cannot write fail with
+/// [`DatabaseError::Denied`]. Updates and removals check the stored ent,
+/// while inserts and updates also check the ent being written. Queries find
+/// ents before those that cannot be read are left out, meaning that pages
+/// and results of limited queries may hold fewer ents than requested.
+///
+/// Wrapping a database used by ents, such as through a [`crate::DatabaseRc`],
+/// also checks the policies when ents load other ents.
+///
+/// ## Examples
+///
+/// ```
+/// use entity::{
+///     Database, DatabaseError, Field, Policies, Policy, PolicyRule, PrivacyDatabase,
+///     UntypedEnt, Viewer,
+/// };
+/// # use entity::{DatabaseResult, Ent, Id, Query};
+/// # use std::{collections::HashMap, sync::Mutex};
+/// # #[derive(Default)]
+/// # struct MyDatabase(Mutex<HashMap<Id, Box<dyn Ent>>>);
+/// # impl Database for MyDatabase {
+/// #     fn get(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>> {
+/// #         Ok(self.0.lock().unwrap().get(&id).cloned())
+/// #     }
+/// #     fn remove(&self, id: Id) -> DatabaseResult<bool> {
+/// #         Ok(self.0.lock().unwrap().remove(&id).is_some())
+/// #     }
+/// #     fn insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id> {
+/// #         let id = ent.id();
+/// #         self.0.lock().unwrap().insert(id, ent);
+/// #         Ok(id)
+/// #     }
+/// #     fn get_all(&self, ids: Vec<Id>) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+/// #         Ok(ids.into_iter().filter_map(|id| self.get(id).ok().flatten()).collect())
+/// #     }
+/// #     fn find_all(&self, _: Query) -> DatabaseResult<Vec<Box<dyn Ent>>> { Ok(Vec::new()) }
+/// # }
+///
+/// let mut policies = Policies::new();
+/// policies.register_for::<UntypedEnt>(
+///     Policy::new()
+///         .read(PolicyRule::allow_if_field_is_viewer("owner"))
+///         .write(PolicyRule::allow_if_field_is_viewer("owner")),
+/// );
+///
+/// let ent = |id, owner: usize| UntypedEnt::from_collections(id, vec![Field::new("owner", owner)], vec![]);
+/// let db = PrivacyDatabase::new(MyDatabase::default(), policies, Viewer::Ent(10));
+///
+/// db.insert(Box::from(ent(1, 10))).unwrap();
+/// assert!(matches!(db.insert(Box::from(ent(2, 20))), Err(DatabaseError::Denied { id: 2 })));
+///
+/// db.inner().insert(Box::from(ent(2, 20))).unwrap();
+/// assert!(db.get(1).unwrap().is_some());
+/// assert!(db.get(2).unwrap().is_none());
+/// assert_eq!(db.get_all(vec![1, 2]).unwrap().len(), 1);
+/// assert!(matches!(db.remove(2), Err(DatabaseError::Denied { id: 2 })));
+/// ```
+pub struct PrivacyDatabase<D: Database> {
+    db: D,
+    policies: Arc<Policies>,
+    viewer: Viewer,
+}
+
+impl<D: Database> PrivacyDatabase<D> {
+    /// Creates a new database that checks the policies against the viewer
+    /// for reads and writes of the database that it wraps
+    pub fn new(db: D, policies: Policies, viewer: Viewer) -> Self {
+        Self {
+            db,
+            policies: Arc::new(policies),
+            viewer,
+        }
+    }
+
+    /// Returns a reference to the wrapped database, which does not check
+    /// policies
+    pub fn inner(&self) -> &D {
+        &self.db
+    }
+
+    /// Consumes the database, returning the wrapped database
+    pub fn into_inner(self) -> D {
+        self.db
+    }
+
+    /// Returns the policies checked by the database
+    pub fn policies(&self) -> &Policies {
+        &self.policies
+    }
+
+    /// Returns the viewer that the policies are checked against
+    pub fn viewer(&self) -> &Viewer {
+        &self.viewer
+    }
+
+    /// Updates the database to check the policies against the viewer
+    pub fn with_viewer(mut self, viewer: Viewer) -> Self {
+        self.viewer = viewer;
+        self
+    }
+
+    /// Returns true if the viewer can read the ent
+    fn can_read(&self, ent: &dyn Ent) -> bool {
+        self.policies.can_read(&self.viewer, ent)
+    }
+
+    /// Returns true if the viewer can read the ent with the given id
+    fn can_read_id(&self, id: Id) -> DatabaseResult<bool> {
+        Ok(self
+            .db
+            .get(id)?
+            .map_or(false, |ent| self.can_read(ent.as_ref())))
+    }
+
+    /// Fails if the viewer cannot write the ent
+    fn check_write(&self, ent: &dyn Ent) -> DatabaseResult<()> {
+        if self.policies.can_write(&self.viewer, ent) {
+            Ok(())
+        } else {
+            Err(DatabaseError::Denied { id: ent.id() })
+        }
+    }
+
+    /// Leaves out the ents that the viewer cannot read
+    fn filter(&self, ents: Vec<Box<dyn Ent>>) -> Vec<Box<dyn Ent>> {
+        if self.policies.is_unrestricted(&self.viewer) {
+            return ents;
+        }
+
+        ents.into_iter()
+            .filter(|ent| self.can_read(ent.as_ref()))
+            .collect()
+    }
+
+    /// Leaves out the associations with ents that the viewer cannot read,
+    /// returning none if the viewer cannot read the ent with the given id
+    fn filter_assocs(&self, id: Id, assocs: Vec<Assoc>) -> DatabaseResult<Vec<Assoc>> {
+        if self.policies.is_unrestricted(&self.viewer) {
+            return Ok(assocs);
+        }
+
+        if !self.can_read_id(id)? {
+            return Ok(Vec::new());
+        }
+
+        let mut readable = Vec::new();
+        for assoc in assocs {
+            if self.can_read_id(assoc.id())? {
+                readable.push(assoc);
+            }
+        }
+        Ok(readable)
+    }
+
+    /// Writes the ent using the wrapped database, checking both the stored
+    /// ent it overwrites and the ent itself
+    fn write_ent(&self, ent: Box<dyn Ent>, expected: Option<u64>) -> DatabaseResult<Id> {
+        if !self.policies.is_unrestricted(&self.viewer) {
+            if let Some(existing) = self.db.get(ent.id())? {
+                self.check_write(existing.as_ref())?;
+            }
+            self.check_write(ent.as_ref())?;
+        }
+
+        match expected {
+            Some(expected) => self.db.compare_and_insert_at(ent, expected),
+            None => self.db.insert(ent),
+        }
+    }
+}
+
+impl<D: Database> Database for PrivacyDatabase<D> {
+    fn get(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>> {
+        Ok(self.db.get(id)?.filter(|ent| self.can_read(ent.as_ref())))
+    }
+
+    fn remove(&self, id: Id) -> DatabaseResult<bool> {
+        if !self.policies.is_unrestricted(&self.viewer) {
+            if let Some(ent) = self.db.get(id)? {
+                self.check_write(ent.as_ref())?;
+            }
+        }
+
+        self.db.remove(id)
+    }
+
+    fn insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id> {
+        self.write_ent(ent, None)
+    }
+
+    fn compare_and_insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id> {
+        let expected = ent.last_updated();
+        self.write_ent(ent, Some(expected))
+    }
+
+    fn compare_and_insert_at(&self, ent: Box<dyn Ent>, expected: u64) -> DatabaseResult<Id> {
+        self.write_ent(ent, Some(expected))
+    }
+
+    fn update_fields(&self, id: Id, patch: Patch) -> DatabaseResult<Box<dyn Ent>> {
+        if self.policies.is_unrestricted(&self.viewer) {
+            return self.db.update_fields(id, patch);
+        }
+
+        let existing = self.db.get(id)?.ok_or(DatabaseError::MissingEnt { id })?;
+        // The patched ent needs to be checked before it is written, so the
+        // patch is applied here and written only if the stored ent has not
+        // been updated since it was loaded, even if patching marked the ent
+        // as updated
+        self.check_write(existing.as_ref())?;
+        let last_updated = existing.last_updated();
+        let mut ent = existing;
+        patch
+            .apply(ent.as_mut())
+            .map_err(|source| DatabaseError::EntMutationFailed { id, source })?;
+        self.check_write(ent.as_ref())?;
+        self.db.compare_and_insert_at(ent, last_updated)?;
+        self.db.get(id)?.ok_or(DatabaseError::MissingEnt { id })
+    }
+
+    fn get_all(&self, ids: Vec<Id>) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+        self.db.get_all(ids).map(|ents| self.filter(ents))
+    }
+
+    fn find_all(&self, query: Query) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+        self.db.find_all(query).map(|ents| self.filter(ents))
+    }
+
+    fn find_page(&self, query: Query, page_size: usize) -> DatabaseResult<Page> {
+        let page = self.db.find_page(query, page_size)?;
+        if self.policies.is_unrestricted(&self.viewer) {
+            return Ok(page);
+        }
+
+        let next_cursor = page.next_cursor().cloned();
+        Ok(Page::new(self.filter(page.into_items()), next_cursor))
+    }
+
+    fn count(&self, query: Query) -> DatabaseResult<usize> {
+        if self.policies.is_unrestricted(&self.viewer) {
+            return self.db.count(query);
+        }
+
+        self.find_all(query).map(|ents| ents.len())
+    }
+
+    fn exists(&self, query: Query) -> DatabaseResult<bool> {
+        if self.policies.is_unrestricted(&self.viewer) {
+            return self.db.exists(query);
+        }
+
+        self.find_all(query).map(|ents| !ents.is_empty())
+    }
+
+    fn aggregate(&self, aggregation: Aggregation) -> DatabaseResult<Vec<AggregateGroup>> {
+        if self.policies.is_unrestricted(&self.viewer) {
+            return self.db.aggregate(aggregation);
+        }
+
+        let ents = self.find_all(aggregation.query().clone())?;
+        aggregation.apply(ents.into_iter().map(Ok))
+    }
+
+    fn incoming_edges(&self, id: Id) -> DatabaseResult<Vec<(Id, String)>> {
+        let incoming = self.db.incoming_edges(id)?;
+        if self.policies.is_unrestricted(&self.viewer) {
+            return Ok(incoming);
+        }
+
+        let mut readable = Vec::new();
+        for (id, name) in incoming {
+            if self.can_read_id(id)? {
+                readable.push((id, name));
+            }
+        }
+        Ok(readable)
+    }
+
+    fn assocs(&self, id: Id, edge: &str) -> DatabaseResult<Vec<Assoc>> {
+        let assocs = self.db.assocs(id, edge)?;
+        self.filter_assocs(id, assocs)
+    }
+
+    fn assoc_range(&self, id: Id, edge: &str, range: AssocRange) -> DatabaseResult<Vec<Assoc>> {
+        let assocs = self.db.assoc_range(id, edge, range)?;
+        self.filter_assocs(id, assocs)
+    }
+
+    fn assoc_count(&self, id: Id, edge: &str) -> DatabaseResult<usize> {
+        if self.policies.is_unrestricted(&self.viewer) {
+            return self.db.assoc_count(id, edge);
+        }
+
+        self.assocs(id, edge).map(|assocs| assocs.len())
+    }
+
+    fn set_assoc_data(&self, id: Id, edge: &str, target: Id, data: Value) -> DatabaseResult<bool> {
+        if !self.policies.is_unrestricted(&self.viewer) {
+            let ent = self.db.get(id)?.ok_or(DatabaseError::MissingEnt { id })?;
+            self.check_write(ent.as_ref())?;
+        }
+
+        self.db.set_assoc_data(id, edge, target, data)
+    }
+
+    fn subscribe(&self, query: Query) -> DatabaseResult<Subscription> {
+        let changes = self.db.subscribe(query)?;
+        if self.policies.is_unrestricted(&self.viewer) {
+            return Ok(changes);
+        }
+
+        // Changes are forwarded until either subscription is dropped, where
+        // the thread waits for changes in intervals so that it stops soon
+        // after even if no more changes are written
+        let (sender, subscription) = Subscription::channel();
+        let policies = Arc::clone(&self.policies);
+        let viewer = self.viewer.clone();
+        thread::spawn(move || {
+            while !sender.is_closed() {
+                let change = match changes.poll(SUBSCRIPTION_POLL_INTERVAL) {
+                    Ok(change) => change,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
+
+                if policies.can_read_change(&viewer, &change) && sender.send(change).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(subscription)
+    }
+
+    fn traverse(&self, traversal: Traversal) -> DatabaseResult<Vec<(Path, Box<dyn Ent>)>> {
+        if self.policies.is_unrestricted(&self.viewer) {
+            return self.db.traverse(traversal);
+        }
+
+        // Ents that cannot be read are never visited, as if they did not exist
+        traversal.apply(|id, filter| match filter {
+            Some(filter) => {
+                let query = Query::default()
+                    .where_id(TypedPredicate::equals(id))
+                    .where_and(vec![filter.clone()]);
+                self.find_all(query).map(|mut ents| ents.pop())
+            }
+            None => self.get(id),
+        })
+    }
+
+    fn begin_transaction(&self) -> DatabaseResult<()> {
+        self.db.begin_transaction()
+    }
+
+    fn commit_transaction(&self) -> DatabaseResult<()> {
+        self.db.commit_transaction()
+    }
+
+    fn rollback_transaction(&self) -> DatabaseResult<()> {
+        self.db.rollback_transaction()
+    }
+}
@@ -0,0 +1,260 @@
+use crate::{ent::any_match, Ent, Id, Query};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Represents the kind of write that changed an ent
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChangeKind {
+    /// The ent was written for the first time
+    Insert,
+
+    /// The ent was overwritten, either directly or because of a write to
+    /// another ent such as keeping inverse edges in sync
+    Update,
+
+    /// The ent was removed
+    Remove,
+}
+
+/// Represents a change made to an ent once written to a database, along
+/// with snapshots of the ent before and after the change when available
+#[derive(Clone)]
+pub struct Change {
+    kind: ChangeKind,
+    id: Id,
+    r#type: String,
+    before: Option<Box<dyn Ent>>,
+    after: Option<Box<dyn Ent>>,
+}
+
+impl Change {
+    /// Creates a change of the given kind to the ent with the given id and
+    /// type, without any snapshots of the ent
+    pub fn new<T: Into<String>>(kind: ChangeKind, id: Id, r#type: T) -> Self {
+        Self {
+            kind,
+            id,
+            r#type: r#type.into(),
+            before: None,
+            after: None,
+        }
+    }
+
+    /// Creates a change from the snapshots of an ent before and after being
+    /// written, where a missing snapshot before means the ent was inserted
+    /// and a missing snapshot after means the ent was removed, returning
+    /// none if both snapshots are missing
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::{Change, ChangeKind, Ent, Query, UntypedEnt};
+    ///
+    /// let ent: Box<dyn Ent> = Box::from(UntypedEnt::empty_with_id(3));
+    ///
+    /// let change = Change::between(None, Some(ent.clone())).unwrap();
+    /// assert_eq!(change.kind(), ChangeKind::Insert);
+    /// assert_eq!(change.id(), 3);
+    /// assert_eq!(change.matches(&Query::default()), Some(true));
+    ///
+    /// let change = Change::between(Some(ent), None).unwrap();
+    /// assert_eq!(change.kind(), ChangeKind::Remove);
+    ///
+    /// assert!(Change::between(None, None).is_none());
+    /// ```
+    pub fn between(before: Option<Box<dyn Ent>>, after: Option<Box<dyn Ent>>) -> Option<Self> {
+        let (kind, ent) = match (before.as_deref(), after.as_deref()) {
+            (None, Some(ent)) => (ChangeKind::Insert, ent),
+            (Some(_), Some(ent)) => (ChangeKind::Update, ent),
+            (Some(ent), None) => (ChangeKind::Remove, ent),
+            (None, None) => return None,
+        };
+
+        let mut change = Self::new(kind, ent.id(), ent.r#type());
+        change.before = before;
+        change.after = after;
+        Some(change)
+    }
+
+    /// Updates the change with the snapshot of the ent before the change
+    pub fn with_before(mut self, ent: Box<dyn Ent>) -> Self {
+        self.before = Some(ent);
+        self
+    }
+
+    /// Updates the change with the snapshot of the ent after the change
+    pub fn with_after(mut self, ent: Box<dyn Ent>) -> Self {
+        self.after = Some(ent);
+        self
+    }
+
+    /// The kind of write that changed the ent
+    #[inline]
+    pub fn kind(&self) -> ChangeKind {
+        self.kind
+    }
+
+    /// The id of the changed ent
+    #[inline]
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// The type of the changed ent
+    #[inline]
+    pub fn r#type(&self) -> &str {
+        &self.r#type
+    }
+
+    /// The snapshot of the ent before the change, if available
+    #[inline]
+    pub fn before(&self) -> Option<&dyn Ent> {
+        self.before.as_deref()
+    }
+
+    /// The snapshot of the ent after the change, if available
+    #[inline]
+    pub fn after(&self) -> Option<&dyn Ent> {
+        self.after.as_deref()
+    }
+
+    /// Checks if either snapshot of the ent passes the filters of the query
+    /// as described by [`Query::matches`], returning none if that depends on
+    /// other ents, and passing if the change has no snapshots to check
+    pub fn matches(&self, query: &Query) -> Option<bool> {
+        if self.before.is_none() && self.after.is_none() {
+            return Some(true);
+        }
+
+        let mut matched = Some(false);
+        for ent in self.before().into_iter().chain(self.after()) {
+            matched = any_match(matched, query.matches(ent));
+        }
+        matched
+    }
+}
+
+impl fmt::Debug for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Change")
+            .field("kind", &self.kind)
+            .field("id", &self.id)
+            .field("type", &self.r#type)
+            .field(
+                "before",
+                &self.before.as_ref().map(|ent| ent.last_updated()),
+            )
+            .field("after", &self.after.as_ref().map(|ent| ent.last_updated()))
+            .finish()
+    }
+}
+
+/// Represents a stream of changes made to ents within a database, in the
+/// order they were written, which ends once the database stops sending
+/// changes
+#[derive(Debug)]
+pub struct Subscription {
+    receiver: Receiver<Change>,
+    closed: Arc<AtomicBool>,
+}
+
+impl Subscription {
+    /// Creates a new subscription alongside the sender used to deliver
+    /// changes to it
+    pub fn channel() -> (ChangeSender, Self) {
+        let (sender, receiver) = mpsc::channel();
+        let closed = Arc::new(AtomicBool::new(false));
+        (
+            ChangeSender {
+                sender,
+                closed: Arc::clone(&closed),
+            },
+            Self { receiver, closed },
+        )
+    }
+
+    /// Waits for the next change, returning none if no more changes will
+    /// be sent
+    pub fn recv(&self) -> Option<Change> {
+        self.receiver.recv().ok()
+    }
+
+    /// Returns the next change if one is available without waiting
+    pub fn try_recv(&self) -> Option<Change> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Waits up to the given duration for the next change
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Change> {
+        self.poll(timeout).ok()
+    }
+
+    /// Waits up to the given duration for the next change, failing with
+    /// whether the wait timed out or no more changes will be sent
+    pub(crate) fn poll(&self, timeout: Duration) -> Result<Change, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+}
+
+impl Drop for Subscription {
+    /// Closes the subscription so that senders waiting for changes can stop
+    /// without needing to send another change first
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+}
+
+impl Iterator for Subscription {
+    type Item = Change;
+
+    /// Waits for the next change, ending once no more changes will be sent
+    fn next(&mut self) -> Option<Self::Item> {
+        self.recv()
+    }
+}
+
+/// Represents the sending half of a [`Subscription`], used by databases to
+/// deliver changes to it
+#[derive(Clone, Debug)]
+pub struct ChangeSender {
+    sender: Sender<Change>,
+    closed: Arc<AtomicBool>,
+}
+
+impl ChangeSender {
+    /// Sends the change to the subscription, returning the change back if
+    /// the subscription has been dropped
+    pub fn send(&self, change: Change) -> Result<(), Change> {
+        if self.is_closed() {
+            return Err(change);
+        }
+
+        self.sender.send(change).map_err(|x| x.0)
+    }
+
+    /// Returns true if the subscription has been dropped, meaning that no
+    /// more changes need to be sent to it
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::Subscription;
+    ///
+    /// let (sender, subscription) = Subscription::channel();
+    /// assert!(!sender.is_closed());
+    ///
+    /// drop(subscription);
+    /// assert!(sender.is_closed());
+    /// ```
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{Ent, Id};
+use std::{collections::HashSet, fmt};
+
+/// Represents a single write staged within a [`Transaction`]
+#[derive(Clone)]
+pub enum TransactionOp {
+    /// Insert (or overwrite) an ent using its id
+    Insert(Box<dyn Ent>),
+
+    /// Remove the ent with the given id, triggering edge processing
+    Remove(Id),
+
+    /// Fail the transaction with a conflict if the ent with the given id
+    /// exists and was last updated at a different time
+    Expect { id: Id, last_updated: u64 },
+}
+
+impl TransactionOp {
+    /// Returns the id of the ent targeted by this operation
+    pub fn id(&self) -> Id {
+        match self {
+            Self::Insert(ent) => ent.id(),
+            Self::Remove(id) => *id,
+            Self::Expect { id, .. } => *id,
+        }
+    }
+}
+
+impl fmt::Debug for TransactionOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Insert(ent) => f
+                .debug_struct("Insert")
+                .field("id", &ent.id())
+                .field("type", &ent.r#type())
+                .finish(),
+            Self::Remove(id) => f.debug_tuple("Remove").field(id).finish(),
+            Self::Expect { id, last_updated } => f
+                .debug_struct("Expect")
+                .field("id", id)
+                .field("last_updated", last_updated)
+                .finish(),
+        }
+    }
+}
+
+/// Represents an ordered collection of writes that a database applies
+/// all at once, or not at all
+#[derive(Clone, Debug, Default)]
+pub struct Transaction {
+    ops: Vec<TransactionOp>,
+}
+
+impl Transaction {
+    /// Creates a new, empty transaction
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stages the insertion of an ent, which is expected to already have
+    /// been assigned its final id
+    pub fn insert(&mut self, ent: Box<dyn Ent>) {
+        self.ops.push(TransactionOp::Insert(ent));
+    }
+
+    /// Stages the removal of the ent with the given id
+    pub fn remove(&mut self, id: Id) {
+        self.ops.push(TransactionOp::Remove(id));
+    }
+
+    /// Stages a check that the ent with the given id, if it exists once the
+    /// earlier operations are applied, was last updated at the given time
+    pub fn expect(&mut self, id: Id, last_updated: u64) {
+        self.ops.push(TransactionOp::Expect { id, last_updated });
+    }
+
+    /// Returns the staged operations in the order they were added
+    pub fn ops(&self) -> &[TransactionOp] {
+        &self.ops
+    }
+
+    /// Returns the total operations staged in the transaction
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Returns true if no operations have been staged
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Returns the staged state of the ent with the given id
+    ///
+    /// * `None` - transaction has not touched the ent
+    /// * `Some(None)` - transaction will remove the ent
+    /// * `Some(Some(ent))` - transaction will insert the ent
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::{Transaction, UntypedEnt};
+    ///
+    /// let mut tx = Transaction::new();
+    /// tx.insert(Box::from(UntypedEnt::empty_with_id(1)));
+    /// tx.remove(2);
+    ///
+    /// assert_eq!(tx.staged(1).unwrap().unwrap().id(), 1);
+    /// assert!(tx.staged(2).unwrap().is_none());
+    /// assert!(tx.staged(3).is_none());
+    /// ```
+    pub fn staged(&self, id: Id) -> Option<Option<&dyn Ent>> {
+        self.ops.iter().rev().find_map(|op| match op {
+            TransactionOp::Insert(ent) if ent.id() == id => Some(Some(ent.as_ref())),
+            TransactionOp::Remove(x) if *x == id => Some(None),
+            _ => None,
+        })
+    }
+
+    /// Returns the ids of ents that will exist once the transaction is
+    /// applied, paired with the ids of ents that will no longer exist
+    pub fn staged_ids(&self) -> (HashSet<Id>, HashSet<Id>) {
+        let mut inserted = HashSet::new();
+        let mut removed = HashSet::new();
+
+        for op in self.ops.iter() {
+            match op {
+                TransactionOp::Insert(ent) => {
+                    removed.remove(&ent.id());
+                    inserted.insert(ent.id());
+                }
+                TransactionOp::Remove(id) => {
+                    inserted.remove(id);
+                    removed.insert(*id);
+                }
+                TransactionOp::Expect { .. } => {}
+            }
+        }
+
+        (inserted, removed)
+    }
+}
+
+impl IntoIterator for Transaction {
+    type Item = TransactionOp;
+    type IntoIter = std::vec::IntoIter<TransactionOp>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ops.into_iter()
+    }
+}
+
+impl From<Vec<TransactionOp>> for Transaction {
+    fn from(ops: Vec<TransactionOp>) -> Self {
+        Self { ops }
+    }
+}
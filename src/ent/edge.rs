@@ -1,19 +1,23 @@
-use crate::Id;
+use crate::{Ent, Id, OrderDirection, Value};
 use derive_more::{From, TryInto};
 use std::{
     collections::{BTreeSet, BinaryHeap, HashSet, LinkedList, VecDeque},
     convert::TryFrom,
+    ops::{Bound, RangeBounds},
 };
 use strum::{Display, EnumDiscriminants, EnumString};
 
 /// Represents a definition of an edge, which is comprised of its name, type
-/// of edge value, and the edge's deletion policy
+/// of edge value, the edge's deletion policy, and the name of the edge on the
+/// other ents that is kept as its inverse
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
 pub struct EdgeDefinition {
     pub(super) name: String,
     r#type: EdgeValueType,
     deletion_policy: EdgeDeletionPolicy,
+    #[cfg_attr(feature = "serde-1", serde(default))]
+    inverse: Option<String>,
 }
 
 impl EdgeDefinition {
@@ -34,6 +38,16 @@ impl EdgeDefinition {
             name: name.into(),
             r#type: r#type.into(),
             deletion_policy,
+            inverse: None,
+        }
+    }
+
+    /// Updates the definition to keep the edge with the given name on the
+    /// ents connected by this edge in sync as its inverse
+    pub fn with_inverse<N: Into<String>>(self, inverse: N) -> Self {
+        Self {
+            inverse: Some(inverse.into()),
+            ..self
         }
     }
 
@@ -72,17 +86,30 @@ impl EdgeDefinition {
     pub fn has_deep_deletion_policy(&self) -> bool {
         matches!(self.deletion_policy(), EdgeDeletionPolicy::DeepDelete)
     }
+
+    /// Returns the name of the edge on the connected ents that is kept in
+    /// sync as the inverse of the edge tied to the definition
+    #[inline]
+    pub fn inverse(&self) -> Option<&str> {
+        self.inverse.as_deref()
+    }
 }
 
 impl From<Edge> for EdgeDefinition {
     fn from(edge: Edge) -> Self {
-        Self::new_with_deletion_policy(edge.name, edge.value, edge.deletion_policy)
+        Self {
+            inverse: edge.inverse,
+            ..Self::new_with_deletion_policy(edge.name, edge.value, edge.deletion_policy)
+        }
     }
 }
 
 impl<'a> From<&'a Edge> for EdgeDefinition {
     fn from(edge: &'a Edge) -> Self {
-        Self::new_with_deletion_policy(edge.name(), edge.value(), edge.deletion_policy())
+        Self {
+            inverse: edge.inverse.clone(),
+            ..Self::new_with_deletion_policy(edge.name(), edge.value(), edge.deletion_policy())
+        }
     }
 }
 
@@ -93,6 +120,8 @@ pub struct Edge {
     name: String,
     value: EdgeValue,
     deletion_policy: EdgeDeletionPolicy,
+    #[cfg_attr(feature = "serde-1", serde(default))]
+    inverse: Option<String>,
 }
 
 impl Edge {
@@ -138,6 +167,25 @@ impl Edge {
             name: name.into(),
             value: value.into(),
             deletion_policy,
+            inverse: None,
+        }
+    }
+
+    /// Updates the edge to keep the edge with the given name on the ents it
+    /// connects in sync as its inverse
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::Edge;
+    ///
+    /// let edge = Edge::new("author", 999).with_inverse("posts");
+    /// assert_eq!(edge.inverse(), Some("posts"));
+    /// ```
+    pub fn with_inverse<N: Into<String>>(self, inverse: N) -> Self {
+        Self {
+            inverse: Some(inverse.into()),
+            ..self
         }
     }
 
@@ -198,6 +246,394 @@ impl Edge {
     pub fn has_deep_deletion_policy(&self) -> bool {
         matches!(self.deletion_policy(), EdgeDeletionPolicy::DeepDelete)
     }
+
+    /// Returns the name of the edge on the connected ents that is kept in
+    /// sync as the inverse of this edge
+    #[inline]
+    pub fn inverse(&self) -> Option<&str> {
+        self.inverse.as_deref()
+    }
+}
+
+/// Represents a change to the inverse edge of some ent that keeps it in sync
+/// with the edge of another ent, the source, when that edge is written
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InverseEdgeChange {
+    /// The source is now connected to the ent, so its id is added to the
+    /// ent's inverse edge
+    Add { id: Id, edge: String, source: Id },
+
+    /// The source is no longer connected to the ent, so its id is removed
+    /// from the ent's inverse edge
+    Remove { id: Id, edge: String, source: Id },
+}
+
+impl InverseEdgeChange {
+    /// Returns the changes needed to keep inverse edges in sync when an ent
+    /// is written, going from the old version of the ent (if it existed) to
+    /// the new version of the ent (if it still exists)
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::{Edge, InverseEdgeChange, UntypedEnt};
+    ///
+    /// let old = UntypedEnt::from_collections(1, vec![], vec![
+    ///     Edge::new("author", 2).with_inverse("posts"),
+    /// ]);
+    /// let new = UntypedEnt::from_collections(1, vec![], vec![
+    ///     Edge::new("author", 3).with_inverse("posts"),
+    /// ]);
+    ///
+    /// assert_eq!(
+    ///     InverseEdgeChange::between(Some(&old), Some(&new)),
+    ///     vec![
+    ///         InverseEdgeChange::Remove { id: 2, edge: String::from("posts"), source: 1 },
+    ///         InverseEdgeChange::Add { id: 3, edge: String::from("posts"), source: 1 },
+    ///     ],
+    /// );
+    /// ```
+    pub fn between(old: Option<&dyn Ent>, new: Option<&dyn Ent>) -> Vec<Self> {
+        let ids_of = |ent: Option<&dyn Ent>, name: &str| {
+            ent.and_then(|ent| ent.edge(name))
+                .map(|value| value.to_ids())
+                .unwrap_or_default()
+        };
+        let mut changes = Vec::new();
+
+        // The old version of an ent may predate its edge having an inverse,
+        // so the inverse of the new version is used in its place
+        let inverse_of = |edge: &Edge| {
+            edge.inverse().map(ToString::to_string).or_else(|| {
+                new.and_then(|ent| ent.edge_definition(edge.name()))
+                    .and_then(|def| def.inverse().map(ToString::to_string))
+            })
+        };
+
+        if let Some(ent) = old {
+            for edge in ent.edges() {
+                if let Some(inverse) = inverse_of(&edge) {
+                    let kept = ids_of(new, edge.name());
+                    for id in edge.to_ids().into_iter().filter(|id| !kept.contains(id)) {
+                        changes.push(Self::Remove {
+                            id,
+                            edge: inverse.clone(),
+                            source: ent.id(),
+                        });
+                    }
+                }
+            }
+        }
+
+        if let Some(ent) = new {
+            for edge in ent.edges() {
+                if let Some(inverse) = edge.inverse() {
+                    let existing = ids_of(old, edge.name());
+                    for id in edge
+                        .to_ids()
+                        .into_iter()
+                        .filter(|id| !existing.contains(id))
+                    {
+                        changes.push(Self::Add {
+                            id,
+                            edge: inverse.to_string(),
+                            source: ent.id(),
+                        });
+                    }
+                }
+            }
+        }
+
+        changes
+    }
+
+    /// Returns the id of the ent whose inverse edge is changed
+    pub fn id(&self) -> Id {
+        match self {
+            Self::Add { id, .. } | Self::Remove { id, .. } => *id,
+        }
+    }
+
+    /// Applies the change to the inverse edge of the ent, returning true if
+    /// the edge was updated
+    ///
+    /// Adding to an edge that can only have one id replaces that id, while
+    /// removing the only id of an edge that must have one leaves the edge
+    /// untouched. Ents without the inverse edge are never updated.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::{Edge, EdgeValue, Ent, InverseEdgeChange, UntypedEnt};
+    ///
+    /// let mut ent = UntypedEnt::from_collections(2, vec![], vec![
+    ///     Edge::new("posts", vec![4]),
+    /// ]);
+    ///
+    /// let change = InverseEdgeChange::Add { id: 2, edge: String::from("posts"), source: 1 };
+    /// assert!(change.apply(&mut ent));
+    /// assert!(!change.apply(&mut ent));
+    /// assert_eq!(ent.edge("posts"), Some(EdgeValue::Many(vec![4, 1])));
+    ///
+    /// let change = InverseEdgeChange::Remove { id: 2, edge: String::from("posts"), source: 4 };
+    /// assert!(change.apply(&mut ent));
+    /// assert_eq!(ent.edge("posts"), Some(EdgeValue::Many(vec![1])));
+    /// ```
+    pub fn apply(&self, ent: &mut dyn Ent) -> bool {
+        let (name, source) = match self {
+            Self::Add { edge, source, .. } | Self::Remove { edge, source, .. } => (edge, *source),
+        };
+        let mut value = match ent.edge(name) {
+            Some(value) => value,
+            None => return false,
+        };
+        let connected = value.to_ids().contains(&source);
+
+        let changed = match self {
+            Self::Add { .. } if connected => false,
+            Self::Add { .. } => match value {
+                EdgeValue::MaybeOne(_) => {
+                    value = EdgeValue::MaybeOne(Some(source));
+                    true
+                }
+                EdgeValue::One(_) => {
+                    value = EdgeValue::One(source);
+                    true
+                }
+                EdgeValue::Many(_) => value.add_ids(Some(source)).is_ok(),
+            },
+            Self::Remove { .. } => connected && value.remove_ids(Some(source)).is_ok(),
+        };
+
+        changed && ent.update_edge(name, value).is_ok()
+    }
+}
+
+/// Represents the association between an ent and one of the ents on its
+/// edge, which records when the ent was added to the edge alongside any data
+/// attached to the connection
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Assoc {
+    id: Id,
+    created: u64,
+    data: Option<Value>,
+}
+
+impl Assoc {
+    /// Creates a new association with the ent of the given id, created at
+    /// the given time and without any data
+    pub fn new(id: Id, created: u64) -> Self {
+        Self {
+            id,
+            created,
+            data: None,
+        }
+    }
+
+    /// Updates the association to carry the given data
+    pub fn with_data<V: Into<Value>>(self, data: V) -> Self {
+        Self {
+            data: Some(data.into()),
+            ..self
+        }
+    }
+
+    /// Returns the associations for the ids of an edge, reusing the
+    /// recorded association of each id where there is one and otherwise
+    /// creating an association at the given time, ordered by when they were
+    /// created and then by id
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::Assoc;
+    ///
+    /// let recorded = vec![Assoc::new(3, 100), Assoc::new(4, 200)];
+    /// assert_eq!(
+    ///     Assoc::for_ids(vec![5, 4, 1], &recorded, 150),
+    ///     vec![Assoc::new(1, 150), Assoc::new(5, 150), Assoc::new(4, 200)],
+    /// );
+    /// ```
+    pub fn for_ids(
+        ids: impl IntoIterator<Item = Id>,
+        recorded: &[Assoc],
+        created: u64,
+    ) -> Vec<Self> {
+        let mut assocs: Vec<Self> = ids
+            .into_iter()
+            .map(|id| match recorded.iter().find(|assoc| assoc.id == id) {
+                Some(assoc) => assoc.clone(),
+                None => Self::new(id, created),
+            })
+            .collect();
+        assocs.sort_by_key(|assoc| (assoc.created, assoc.id));
+        assocs.dedup_by_key(|assoc| assoc.id);
+        assocs
+    }
+
+    /// The id of the ent on the other end of the association
+    #[inline]
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// The time when the association was created as the milliseconds since
+    /// epoch (1970-01-01 00:00:00 UTC)
+    #[inline]
+    pub fn created(&self) -> u64 {
+        self.created
+    }
+
+    /// The data attached to the association, if any
+    #[inline]
+    pub fn data(&self) -> Option<&Value> {
+        self.data.as_ref()
+    }
+
+    /// The mutable data attached to the association
+    #[inline]
+    pub fn data_mut(&mut self) -> &mut Option<Value> {
+        &mut self.data
+    }
+
+    /// Converts the association into its data, if any
+    #[inline]
+    pub fn into_data(self) -> Option<Value> {
+        self.data
+    }
+}
+
+/// Represents a slice of the associations of an edge, which are ordered by
+/// when each association was created and then by id
+///
+/// By default, the range covers every association from oldest to newest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub struct AssocRange {
+    start: Bound<u64>,
+    end: Bound<u64>,
+    direction: OrderDirection,
+    offset: usize,
+    limit: Option<usize>,
+}
+
+/// Copies the time out of a borrowed bound
+fn copy_bound(bound: Bound<&u64>) -> Bound<u64> {
+    match bound {
+        Bound::Included(x) => Bound::Included(*x),
+        Bound::Excluded(x) => Bound::Excluded(*x),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+impl Default for AssocRange {
+    fn default() -> Self {
+        Self {
+            start: Bound::Unbounded,
+            end: Bound::Unbounded,
+            direction: OrderDirection::Ascending,
+            offset: 0,
+            limit: None,
+        }
+    }
+}
+
+impl AssocRange {
+    /// Updates the range to only cover associations created within the
+    /// given times
+    pub fn with_created<R: RangeBounds<u64>>(self, created: R) -> Self {
+        Self {
+            start: copy_bound(created.start_bound()),
+            end: copy_bound(created.end_bound()),
+            ..self
+        }
+    }
+
+    /// Updates the range to order associations from newest to oldest
+    pub fn newest_first(self) -> Self {
+        Self {
+            direction: OrderDirection::Descending,
+            ..self
+        }
+    }
+
+    /// Updates the range to skip the given number of associations
+    pub fn with_offset(self, offset: usize) -> Self {
+        Self { offset, ..self }
+    }
+
+    /// Updates the range to cover at most the given number of associations
+    pub fn with_limit(self, limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// The bounds on the creation time of associations within the range
+    #[inline]
+    pub fn created(&self) -> (Bound<u64>, Bound<u64>) {
+        (self.start, self.end)
+    }
+
+    /// The direction in which associations are ordered by creation time
+    #[inline]
+    pub fn direction(&self) -> OrderDirection {
+        self.direction
+    }
+
+    /// The number of associations skipped
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The maximum number of associations covered, if any
+    #[inline]
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// Returns true if an association created at the given time falls
+    /// within the bounds of the range
+    pub fn contains(&self, created: u64) -> bool {
+        (self.start, self.end).contains(&created)
+    }
+
+    /// Selects the associations covered by the range from all associations
+    /// of an edge, which are expected to be ordered by when each association
+    /// was created and then by id
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::{Assoc, AssocRange};
+    ///
+    /// let assocs = vec![Assoc::new(1, 100), Assoc::new(2, 200), Assoc::new(3, 300)];
+    /// assert_eq!(
+    ///     AssocRange::default().newest_first().with_limit(2).apply(assocs.clone()),
+    ///     vec![Assoc::new(3, 300), Assoc::new(2, 200)],
+    /// );
+    /// assert_eq!(
+    ///     AssocRange::default().with_created(150..).with_offset(1).apply(assocs),
+    ///     vec![Assoc::new(3, 300)],
+    /// );
+    /// ```
+    pub fn apply(&self, assocs: Vec<Assoc>) -> Vec<Assoc> {
+        let assocs = assocs
+            .into_iter()
+            .filter(|assoc| self.contains(assoc.created()));
+        let assocs: Box<dyn Iterator<Item = Assoc>> = match self.direction {
+            OrderDirection::Ascending => Box::new(assocs),
+            OrderDirection::Descending => Box::new(assocs.rev()),
+        };
+
+        assocs
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
 }
 
 /// Represents the policy to apply to an edge when its ent is deleted
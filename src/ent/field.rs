@@ -76,6 +76,13 @@ impl FieldDefinition {
     pub fn is_computed(&self) -> bool {
         self.attributes().contains(&FieldAttribute::Computed)
     }
+
+    /// Returns true if this field is marked as unique, meaning that no two
+    /// ents of the same type can have the same value for it
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        self.attributes().contains(&FieldAttribute::Unique)
+    }
 }
 
 impl From<Field> for FieldDefinition {
@@ -179,6 +186,13 @@ impl Field {
     pub fn is_computed(&self) -> bool {
         self.attributes().contains(&FieldAttribute::Computed)
     }
+
+    /// Returns true if this field is marked as unique, meaning that no two
+    /// ents of the same type can have the same value for it
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        self.attributes().contains(&FieldAttribute::Unique)
+    }
 }
 
 /// Represents an attribute associated with a field for an ent
@@ -196,4 +210,13 @@ pub enum FieldAttribute {
     /// Indicates that this field is computed and does not have a standard
     /// data representation within the ent (although it may have a cached value)
     Computed,
+
+    /// Indicates that no two ents of the same type can have the same value
+    /// for this field, which databases enforce when ents are written
+    ///
+    /// Values are compared by their [`IndexKey`](crate::IndexKey), so only
+    /// values that can be keyed are enforced. Values without a key, such as
+    /// lists and maps, along with the absence of a value, are never
+    /// considered the same as another ent's value.
+    Unique,
 }
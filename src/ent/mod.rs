@@ -1,15 +1,19 @@
 mod edge;
 mod field;
 pub mod query;
+mod schema;
 mod value;
 
 use crate::AsAny;
 pub use edge::*;
 pub use field::*;
 pub use query::*;
+pub use schema::*;
 pub use value::*;
 
-use crate::{DatabaseError, DatabaseResult, Id, WeakDatabaseRc, EPHEMERAL_ID};
+use crate::{
+    spawn_blocking, DatabaseError, DatabaseFuture, DatabaseResult, Id, WeakDatabaseRc, EPHEMERAL_ID,
+};
 use derive_more::{Display, Error};
 use dyn_clone::DynClone;
 use std::{
@@ -44,6 +48,9 @@ pub enum EntMutationError {
 
     #[display(fmt = "Failed to mark ent as updated: {}", source)]
     MarkUpdatedFailed { source: SystemTimeError },
+
+    #[display(fmt = "Field {} is invalid: {}", name, reason)]
+    InvalidField { name: String, reason: String },
 }
 
 /// Represents some error that can occur when converting an ent to another type
@@ -204,7 +211,9 @@ pub trait Ent: AsAny + DynClone + Send + Sync {
     fn last_updated(&self) -> u64;
 
     /// Updates the time when the instance of the ent was last updated to
-    /// the current time in milliseconds since epoch (1970-01-01 00:00:00 UTC)
+    /// the current time in milliseconds since epoch (1970-01-01 00:00:00 UTC),
+    /// or to a millisecond after the previous time if that is later so that
+    /// the time changes with every update
     fn mark_updated(&mut self) -> Result<(), EntMutationError>;
 
     /// Returns a list of definitions for fields contained by the ent
@@ -245,8 +254,9 @@ pub trait Ent: AsAny + DynClone + Send + Sync {
     }
 
     /// Updates the local value of a field with the specified name, returning
-    /// the old field value if updated. This will also update the last updated
-    /// time for the ent.
+    /// the old field value if updated. The last updated time of the ent is
+    /// left unchanged until the ent is written to a database, as it is the
+    /// version of the ent used by [`Ent::commit`].
     fn update_field(&mut self, name: &str, value: Value) -> Result<Value, EntMutationError>;
 
     /// Returns a list of definitions for edges contained by the ent
@@ -284,17 +294,22 @@ pub trait Ent: AsAny + DynClone + Send + Sync {
     /// Returns a copy of all edges contained by the ent and their associated values
     fn edges(&self) -> Vec<Edge> {
         let mut edges = Vec::new();
-        for name in self.edge_names() {
-            if let Some(value) = self.edge(&name) {
-                edges.push(Edge::new(name, value));
+        for def in self.edge_definitions() {
+            if let Some(value) = self.edge(def.name()) {
+                let edge = Edge::new_with_deletion_policy(def.name(), value, def.deletion_policy());
+                edges.push(match def.inverse() {
+                    Some(inverse) => edge.with_inverse(inverse),
+                    None => edge,
+                });
             }
         }
         edges
     }
 
     /// Updates the local value of an edge with the specified name, returning
-    /// the old edge value if updated. This will also update the last updated
-    /// time for the ent.
+    /// the old edge value if updated. The last updated time of the ent is
+    /// left unchanged until the ent is written to a database, as it is the
+    /// version of the ent used by [`Ent::commit`].
     fn update_edge(&mut self, name: &str, value: EdgeValue) -> Result<EdgeValue, EntMutationError>;
 
     /// Connects ent to the given database so all future
@@ -316,13 +331,22 @@ pub trait Ent: AsAny + DynClone + Send + Sync {
     /// Clears out any locally-cached data for the ent
     fn clear_cache(&mut self);
 
-    /// Refreshes ent by checking database for latest version and returning it
+    /// Refreshes ent by checking database for latest version and returning it,
+    /// which includes the time that the ent was last updated used by
+    /// [`Ent::commit`] to detect conflicting changes
     ///
     /// Requires ent to be connected to a database
     fn refresh(&mut self) -> DatabaseResult<()>;
 
     /// Saves the ent to the database, updating this local instance's id
-    /// if the database has reported a new id
+    /// if the database has reported a new id. If the database has a
+    /// transaction in progress for the current thread, the save is staged
+    /// as part of that transaction.
+    ///
+    /// The ent is only saved if the stored ent has not been updated since
+    /// this instance was loaded, refreshed, or last committed, failing with
+    /// [`DatabaseError::Conflict`] otherwise. Once saved, this instance is
+    /// updated with the time that it was last updated in the database.
     ///
     /// Requires ent to be connected to a database
     fn commit(&mut self) -> DatabaseResult<()>;
@@ -331,6 +355,59 @@ pub trait Ent: AsAny + DynClone + Send + Sync {
     ///
     /// Requires ent to be connected to a database
     fn remove(&self) -> DatabaseResult<bool>;
+
+    /// Asynchronous variant of [`Ent::load_edge`]
+    ///
+    /// By default, this performs [`Ent::load_edge`] using a clone of the ent
+    /// on a separate thread once first polled
+    fn load_edge_async(&self, name: &str) -> DatabaseFuture<'_, Vec<Box<dyn Ent>>> {
+        let ent = dyn_clone::clone_box(self);
+        let name = name.to_string();
+        spawn_blocking(move || ent.load_edge(&name))
+    }
+
+    /// Asynchronous variant of [`Ent::refresh`]
+    ///
+    /// By default, this performs [`Ent::refresh`] using a clone of the ent
+    /// on a separate thread once first polled, replacing the ent with the
+    /// refreshed clone upon success
+    fn refresh_async(&mut self) -> DatabaseFuture<'_, ()>
+    where
+        Self: Sized,
+    {
+        let mut ent = dyn_clone::clone(self);
+        let refreshed = spawn_blocking(move || ent.refresh().map(|_| ent));
+        Box::pin(async move {
+            *self = refreshed.await?;
+            Ok(())
+        })
+    }
+
+    /// Asynchronous variant of [`Ent::commit`]
+    ///
+    /// By default, this performs [`Ent::commit`] using a clone of the ent
+    /// on a separate thread once first polled, replacing the ent with the
+    /// committed clone upon success
+    fn commit_async(&mut self) -> DatabaseFuture<'_, ()>
+    where
+        Self: Sized,
+    {
+        let mut ent = dyn_clone::clone(self);
+        let committed = spawn_blocking(move || ent.commit().map(|_| ent));
+        Box::pin(async move {
+            *self = committed.await?;
+            Ok(())
+        })
+    }
+
+    /// Asynchronous variant of [`Ent::remove`]
+    ///
+    /// By default, this performs [`Ent::remove`] using a clone of the ent
+    /// on a separate thread once first polled
+    fn remove_async(&self) -> DatabaseFuture<'_, bool> {
+        let ent = dyn_clone::clone_box(self);
+        spawn_blocking(move || ent.remove())
+    }
 }
 
 dyn_clone::clone_trait_object!(Ent);
@@ -540,10 +617,11 @@ impl Ent for UntypedEnt {
 
     /// Updates the local, internal timestamp of this ent instance
     fn mark_updated(&mut self) -> Result<(), EntMutationError> {
-        self.last_updated = SystemTime::now()
+        let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .map_err(|e| EntMutationError::MarkUpdatedFailed { source: e })?
             .as_millis() as u64;
+        self.last_updated = std::cmp::max(now, self.last_updated.saturating_add(1));
         Ok(())
     }
 
@@ -636,11 +714,10 @@ impl Ent for UntypedEnt {
     /// assert_eq!(ent.field("field1"), Some(Value::from(5u8)));
     /// ```
     fn update_field(&mut self, name: &str, value: Value) -> Result<Value, EntMutationError> {
-        self.mark_updated()?;
-
         match self.fields.entry(name.to_string()) {
             Entry::Occupied(mut x) => {
-                let field = Field::new(name.to_string(), value);
+                let attributes = x.get().attributes().to_vec();
+                let field = Field::new_with_attributes(name.to_string(), value, attributes);
                 Ok(x.insert(field).into_value())
             }
             Entry::Vacant(_) => Err(EntMutationError::NoField {
@@ -739,13 +816,8 @@ impl Ent for UntypedEnt {
     /// assert_eq!(ent.edge("edge1"), Some(EdgeValue::One(123)));
     /// ```
     fn update_edge(&mut self, name: &str, value: EdgeValue) -> Result<EdgeValue, EntMutationError> {
-        self.mark_updated()?;
-
         match self.edges.entry(name.to_string()) {
-            Entry::Occupied(mut x) => {
-                let edge = Edge::new(name.to_string(), value);
-                Ok(x.insert(edge).into_value())
-            }
+            Entry::Occupied(mut x) => Ok(std::mem::replace(x.get_mut().value_mut(), value)),
             Entry::Vacant(_) => Err(EntMutationError::NoEdge {
                 name: name.to_string(),
             }),
@@ -831,16 +903,20 @@ impl Ent for UntypedEnt {
         }
     }
 
-    /// Saves the ent to the database, updating this local instance's id
-    /// if the database has reported a new id
+    /// Saves the ent to the database if the stored ent has not been updated
+    /// since this instance was loaded, updating this local instance's id
+    /// if the database has reported a new id alongside its last updated time
     ///
     /// Requires ent to be connected to a database
     fn commit(&mut self) -> DatabaseResult<()> {
         let database =
             WeakDatabaseRc::upgrade(&self.database).ok_or(DatabaseError::Disconnected)?;
-        match database.insert(Box::new(Self::clone(&self))) {
+        match database.compare_and_insert(Box::new(Self::clone(self))) {
             Ok(id) => {
                 self.set_id(id);
+                if let Some(ent) = database.get(id)? {
+                    self.last_updated = ent.last_updated();
+                }
                 Ok(())
             }
             Err(x) => Err(x),
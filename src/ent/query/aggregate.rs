@@ -0,0 +1,485 @@
+use crate::{DatabaseResult, Ent, IndexKey, Number, Primitive, Query, Value};
+use std::{cmp::Ordering, collections::BTreeMap};
+
+/// Represents a single calculation performed over the ents found by a query
+///
+/// Calculations over a field ignore ents that are missing the field or whose
+/// value is `None`, and those that are numeric also ignore ents whose value
+/// is not a number.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub enum Aggregate {
+    /// Counts the ents
+    Count,
+
+    /// Adds together the numbers of a field, producing `None` if there are
+    /// no numbers
+    Sum(String),
+
+    /// Averages the numbers of a field as an `f64`, producing `None` if
+    /// there are no numbers
+    Avg(String),
+
+    /// Finds the smallest value of a field, producing `None` if there are no
+    /// values
+    Min(String),
+
+    /// Finds the largest value of a field, producing `None` if there are no
+    /// values
+    Max(String),
+
+    /// Collects the distinct values of a field into a list
+    Distinct(String),
+}
+
+/// Represents a query whose ents are reduced into the results of one or more
+/// aggregates, optionally grouped by the value of a field
+///
+/// ## Examples
+///
+/// ```
+/// use entity::{Aggregation, Ent, Field, Query, UntypedEnt, Value};
+///
+/// let ents = vec![("a", 1), ("b", 2), ("a", 3)].into_iter().enumerate().map(|(id, (g, x))| {
+///     let fields = vec![Field::new("g", String::from(g)), Field::new("x", x)];
+///     Ok(Box::from(UntypedEnt::from_collections(id + 1, fields, vec![])) as Box<dyn Ent>)
+/// });
+///
+/// let groups = Aggregation::new(Query::default())
+///     .count()
+///     .sum("x")
+///     .group_by("g")
+///     .apply(ents)
+///     .unwrap();
+///
+/// assert_eq!(groups.len(), 2);
+/// assert_eq!(groups[0].key(), Some(&Value::from("a")));
+/// assert_eq!(groups[0].values(), &[Value::from(2usize), Value::from(4)]);
+/// assert_eq!(groups[1].key(), Some(&Value::from("b")));
+/// assert_eq!(groups[1].values(), &[Value::from(1usize), Value::from(2)]);
+/// ```
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Aggregation {
+    query: Query,
+    aggregates: Vec<Aggregate>,
+    group_by: Option<String>,
+}
+
+impl Aggregation {
+    /// Creates a new aggregation over the ents found by the query, which
+    /// has no aggregates and is not grouped
+    pub fn new(query: Query) -> Self {
+        Self {
+            query,
+            ..Default::default()
+        }
+    }
+
+    /// The query used to find the ents being aggregated
+    #[inline]
+    pub fn query(&self) -> &Query {
+        &self.query
+    }
+
+    /// The aggregates calculated for each group, in order
+    #[inline]
+    pub fn aggregates(&self) -> &[Aggregate] {
+        &self.aggregates
+    }
+
+    /// The name of the field whose values are used to group ents, if any
+    #[inline]
+    pub fn get_group_by(&self) -> Option<&str> {
+        self.group_by.as_deref()
+    }
+
+    /// Consumes aggregation, producing a new aggregation with the additional
+    /// aggregate added to the end
+    pub fn aggregate(mut self, aggregate: Aggregate) -> Self {
+        self.aggregates.push(aggregate);
+        self
+    }
+
+    /// Consumes aggregation, producing a new aggregation that also counts
+    /// the ents
+    pub fn count(self) -> Self {
+        self.aggregate(Aggregate::Count)
+    }
+
+    /// Consumes aggregation, producing a new aggregation that also adds
+    /// together the numbers of the named field
+    pub fn sum<S: Into<String>>(self, name: S) -> Self {
+        self.aggregate(Aggregate::Sum(name.into()))
+    }
+
+    /// Consumes aggregation, producing a new aggregation that also averages
+    /// the numbers of the named field
+    pub fn avg<S: Into<String>>(self, name: S) -> Self {
+        self.aggregate(Aggregate::Avg(name.into()))
+    }
+
+    /// Consumes aggregation, producing a new aggregation that also finds the
+    /// smallest value of the named field
+    pub fn min<S: Into<String>>(self, name: S) -> Self {
+        self.aggregate(Aggregate::Min(name.into()))
+    }
+
+    /// Consumes aggregation, producing a new aggregation that also finds the
+    /// largest value of the named field
+    pub fn max<S: Into<String>>(self, name: S) -> Self {
+        self.aggregate(Aggregate::Max(name.into()))
+    }
+
+    /// Consumes aggregation, producing a new aggregation that also collects
+    /// the distinct values of the named field
+    pub fn distinct<S: Into<String>>(self, name: S) -> Self {
+        self.aggregate(Aggregate::Distinct(name.into()))
+    }
+
+    /// Consumes aggregation, producing a new aggregation that calculates its
+    /// aggregates separately for each value of the named field
+    pub fn group_by<S: Into<String>>(mut self, name: S) -> Self {
+        self.group_by = Some(name.into());
+        self
+    }
+
+    /// Calculates the aggregates over the ents found by the query, which are
+    /// consumed one at a time
+    ///
+    /// Without grouping, a single group without a key is always produced.
+    /// Otherwise, a group is produced for each distinct value of the field
+    /// being grouped by, ordered by those values, followed by a group
+    /// without a key for any ents missing the field or whose value cannot be
+    /// ordered (such as lists and maps).
+    pub fn apply<I: IntoIterator<Item = DatabaseResult<Box<dyn Ent>>>>(
+        &self,
+        ents: I,
+    ) -> DatabaseResult<Vec<AggregateGroup>> {
+        let new_accumulators = || {
+            self.aggregates
+                .iter()
+                .map(Accumulator::new)
+                .collect::<Vec<Accumulator>>()
+        };
+
+        let mut groups: BTreeMap<IndexKey, (Value, Vec<Accumulator>)> = BTreeMap::new();
+        let mut ungrouped = match self.group_by {
+            Some(_) => None,
+            None => Some(new_accumulators()),
+        };
+
+        for ent in ents {
+            let ent = ent?;
+
+            let accumulators = match self.group_by.as_deref().and_then(|name| ent.field(name)) {
+                Some(value) => match IndexKey::from_value(&value) {
+                    Some(key) => {
+                        &mut groups
+                            .entry(key)
+                            .or_insert_with(|| (value, new_accumulators()))
+                            .1
+                    }
+                    None => ungrouped.get_or_insert_with(new_accumulators),
+                },
+                None => ungrouped.get_or_insert_with(new_accumulators),
+            };
+
+            for (aggregate, accumulator) in self.aggregates.iter().zip(accumulators.iter_mut()) {
+                accumulator.add(aggregate, ent.as_ref());
+            }
+        }
+
+        let to_values = |accumulators: Vec<Accumulator>| {
+            accumulators
+                .into_iter()
+                .map(Accumulator::into_value)
+                .collect()
+        };
+
+        Ok(groups
+            .into_iter()
+            .map(|(_, (key, accumulators))| AggregateGroup::new(Some(key), to_values(accumulators)))
+            .chain(ungrouped.map(|accumulators| AggregateGroup::new(None, to_values(accumulators))))
+            .collect())
+    }
+}
+
+/// Represents the results of an aggregation's aggregates for a single group
+/// of ents
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub struct AggregateGroup {
+    key: Option<Value>,
+    values: Vec<Value>,
+}
+
+impl AggregateGroup {
+    /// Creates a new group with the given key and results
+    pub fn new(key: Option<Value>, values: Vec<Value>) -> Self {
+        Self { key, values }
+    }
+
+    /// The value of the field shared by all ents in the group, or none if
+    /// the ents are not grouped by a value
+    #[inline]
+    pub fn key(&self) -> Option<&Value> {
+        self.key.as_ref()
+    }
+
+    /// The results of each aggregate, in the same order as the aggregates
+    #[inline]
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    /// Consumes the group, returning its key and results
+    pub fn into_parts(self) -> (Option<Value>, Vec<Value>) {
+        (self.key, self.values)
+    }
+}
+
+/// Represents the running state of a single aggregate
+enum Accumulator {
+    Count(usize),
+    Sum(Option<Number>),
+    Avg(Option<Number>, usize),
+    Min(Option<Value>),
+    Max(Option<Value>),
+    Distinct(BTreeMap<IndexKey, Value>, Vec<Value>),
+}
+
+impl Accumulator {
+    fn new(aggregate: &Aggregate) -> Self {
+        match aggregate {
+            Aggregate::Count => Self::Count(0),
+            Aggregate::Sum(_) => Self::Sum(None),
+            Aggregate::Avg(_) => Self::Avg(None, 0),
+            Aggregate::Min(_) => Self::Min(None),
+            Aggregate::Max(_) => Self::Max(None),
+            Aggregate::Distinct(_) => Self::Distinct(BTreeMap::new(), Vec::new()),
+        }
+    }
+
+    fn add(&mut self, aggregate: &Aggregate, ent: &dyn Ent) {
+        let value = match aggregate {
+            Aggregate::Count => None,
+            Aggregate::Sum(name)
+            | Aggregate::Avg(name)
+            | Aggregate::Min(name)
+            | Aggregate::Max(name)
+            | Aggregate::Distinct(name) => match ent.field(name).map(unwrap_optional) {
+                Some(Some(value)) => Some(value),
+                _ => return,
+            },
+        };
+        let number = match &value {
+            Some(Value::Primitive(Primitive::Number(x))) => Some(*x),
+            _ => None,
+        };
+
+        match (self, value, number) {
+            (Self::Count(count), _, _) => *count += 1,
+            (Self::Sum(sum), _, Some(x)) => *sum = Some(sum.map_or(x, |sum| sum + x)),
+            (Self::Avg(sum, count), _, Some(x)) => {
+                *sum = Some(sum.map_or(x, |sum| sum + x));
+                *count += 1;
+            }
+            (Self::Min(min), Some(x), _) => replace_if(min, x, Ordering::Less),
+            (Self::Max(max), Some(x), _) => replace_if(max, x, Ordering::Greater),
+            (Self::Distinct(keyed, unkeyed), Some(x), _) => match IndexKey::from_value(&x) {
+                Some(key) => {
+                    keyed.entry(key).or_insert(x);
+                }
+                None if !unkeyed.contains(&x) => unkeyed.push(x),
+                None => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn into_value(self) -> Value {
+        match self {
+            Self::Count(count) => Value::from(count),
+            Self::Sum(sum) => Value::from(sum),
+            Self::Avg(sum, count) => {
+                Value::from(sum.map(|sum| Number::from(sum.to_f64() / count as f64)))
+            }
+            Self::Min(x) | Self::Max(x) => Value::Optional(x.map(Box::from)),
+            Self::Distinct(keyed, unkeyed) => Value::from(
+                keyed
+                    .into_iter()
+                    .map(|(_, x)| x)
+                    .chain(unkeyed)
+                    .collect::<Vec<Value>>(),
+            ),
+        }
+    }
+}
+
+/// Strips any optional wrappers from a value, returning none if the value
+/// is missing
+fn unwrap_optional(value: Value) -> Option<Value> {
+    match value {
+        Value::Optional(x) => x.and_then(|x| unwrap_optional(*x)),
+        x => Some(x),
+    }
+}
+
+/// Replaces the current value with the new value if there is no current
+/// value or the new value compares with the given ordering against it
+fn replace_if(current: &mut Option<Value>, value: Value, ordering: Ordering) {
+    let replace = match current.as_ref() {
+        Some(current) => value.partial_cmp(current) == Some(ordering),
+        None => value.partial_cmp(&value).is_some(),
+    };
+
+    if replace {
+        *current = Some(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Field, UntypedEnt};
+
+    fn ents(fields: Vec<Vec<Field>>) -> impl Iterator<Item = DatabaseResult<Box<dyn Ent>>> {
+        fields.into_iter().enumerate().map(|(i, fields)| {
+            Ok(Box::from(UntypedEnt::from_collections(i + 1, fields, vec![])) as Box<dyn Ent>)
+        })
+    }
+
+    #[test]
+    fn apply_should_produce_single_group_without_key_if_not_grouped() {
+        let aggregation = Aggregation::new(Query::default())
+            .count()
+            .sum("x")
+            .avg("x")
+            .min("x")
+            .max("x")
+            .distinct("x");
+
+        let groups = aggregation
+            .apply(ents(vec![
+                vec![Field::new("x", 5u8)],
+                vec![Field::new("x", -3i64)],
+                vec![Field::new("x", Some(5u32))],
+                vec![Field::new("x", Value::from("text"))],
+                vec![Field::new("x", None::<u8>)],
+                vec![],
+            ]))
+            .unwrap();
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].key(), None);
+        assert_eq!(
+            groups[0].values(),
+            &[
+                Value::from(6usize),
+                Value::from(Some(7)),
+                Value::from(Some(7.0 / 3.0)),
+                Value::from(Some(-3)),
+                Value::from(Some(5)),
+                Value::from(vec![Value::from(-3), Value::from(5), Value::from("text")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn apply_should_produce_empty_results_if_no_ents() {
+        let groups = Aggregation::new(Query::default())
+            .count()
+            .sum("x")
+            .avg("x")
+            .min("x")
+            .distinct("x")
+            .apply(ents(vec![]))
+            .unwrap();
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(
+            groups[0].values(),
+            &[
+                Value::from(0usize),
+                Value::from(None::<u8>),
+                Value::from(None::<f64>),
+                Value::from(None::<u8>),
+                Value::from(Vec::<Value>::new()),
+            ]
+        );
+
+        let groups = Aggregation::new(Query::default())
+            .count()
+            .group_by("g")
+            .apply(ents(vec![]))
+            .unwrap();
+        assert!(groups.is_empty());
+    }
+
+    #[test]
+    fn apply_should_produce_ordered_groups_followed_by_ents_without_key() {
+        let groups = Aggregation::new(Query::default())
+            .count()
+            .max("x")
+            .group_by("g")
+            .apply(ents(vec![
+                vec![Field::new("g", 2u8), Field::new("x", 1)],
+                vec![Field::new("g", 1i64), Field::new("x", 2)],
+                vec![Field::new("g", 2i32), Field::new("x", 3)],
+                vec![Field::new("x", 4)],
+                vec![Field::new("g", vec![1, 2]), Field::new("x", 5)],
+            ]))
+            .unwrap();
+
+        let groups: Vec<(Option<Value>, Vec<Value>)> =
+            groups.into_iter().map(AggregateGroup::into_parts).collect();
+        assert_eq!(
+            groups,
+            vec![
+                (
+                    Some(Value::from(1)),
+                    vec![Value::from(1usize), Value::from(Some(2))]
+                ),
+                (
+                    Some(Value::from(2)),
+                    vec![Value::from(2usize), Value::from(Some(3))]
+                ),
+                (None, vec![Value::from(2usize), Value::from(Some(5))]),
+            ]
+        );
+    }
+
+    #[test]
+    fn apply_should_keep_floats_with_the_same_integral_part_apart() {
+        let groups = Aggregation::new(Query::default())
+            .count()
+            .distinct("x")
+            .group_by("g")
+            .apply(ents(vec![
+                vec![Field::new("g", 1.5), Field::new("x", 1.2)],
+                vec![Field::new("g", 1.7), Field::new("x", 1.7)],
+                vec![Field::new("g", 1.5), Field::new("x", 1.7)],
+            ]))
+            .unwrap();
+
+        let groups: Vec<(Option<Value>, Vec<Value>)> =
+            groups.into_iter().map(AggregateGroup::into_parts).collect();
+        assert_eq!(
+            groups,
+            vec![
+                (
+                    Some(Value::from(1.5)),
+                    vec![
+                        Value::from(2usize),
+                        Value::from(vec![Value::from(1.2), Value::from(1.7)])
+                    ]
+                ),
+                (
+                    Some(Value::from(1.7)),
+                    vec![Value::from(1usize), Value::from(vec![Value::from(1.7)])]
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use crate::{Ent, Id, IndexKey, Order};
+use std::{cmp::Ordering, convert::TryInto, fmt, str::FromStr};
+
+/// Represents the position of an ent within the ordered results of a query,
+/// used to resume the query after that ent
+///
+/// A cursor captures the ent's key for each of the query's ordering clauses
+/// alongside the ent's id, so a query resumes in the same place even if ents
+/// are inserted or removed in between pages. Cursors can be converted to and
+/// from an opaque string that can be handed out to clients.
+///
+/// ## Examples
+///
+/// ```
+/// use entity::{Cursor, Field, Order, OrderBy, UntypedEnt};
+///
+/// let order = vec![Order::descending(OrderBy::Field(String::from("x")))];
+/// let ent = UntypedEnt::from_collections(3, vec![Field::new("x", 5)], vec![]);
+///
+/// let cursor = Cursor::new(&order, &ent);
+/// let s = cursor.to_string();
+/// assert_eq!(s.parse::<Cursor>().unwrap(), cursor);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Cursor {
+    keys: Vec<Option<IndexKey>>,
+    id: Id,
+}
+
+impl Cursor {
+    /// Creates a new cursor positioned at the ent within results ordered by
+    /// the given clauses
+    pub fn new(order: &[Order], ent: &dyn Ent) -> Self {
+        Self {
+            keys: order.iter().map(|order| order.key(ent)).collect(),
+            id: ent.id(),
+        }
+    }
+
+    /// The id of the ent at the cursor's position
+    #[inline]
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// The keys of the ent at the cursor's position for each ordering
+    /// clause, where an ent without a comparable value for a clause has no
+    /// key for it
+    #[inline]
+    pub fn keys(&self) -> &[Option<IndexKey>] {
+        &self.keys
+    }
+
+    /// Compares the position of this cursor to another cursor, both of which
+    /// are expected to have been created with the given ordering clauses
+    ///
+    /// Positions are compared by each ordering clause in turn, followed by
+    /// their ids.
+    pub fn compare(&self, other: &Self, order: &[Order]) -> Ordering {
+        order
+            .iter()
+            .zip(self.keys.iter().zip(other.keys.iter()))
+            .map(|(order, (a, b))| order.compare_keys(a.as_ref(), b.as_ref()))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or_else(|| self.id.cmp(&other.id))
+    }
+
+    /// Encodes the cursor as bytes, where each key is preceded by a marker
+    /// indicating if it exists and the id comes last
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        for key in self.keys.iter() {
+            match key {
+                Some(key) => {
+                    bytes.push(1);
+                    bytes.extend(key.to_ordered_bytes());
+                }
+                None => bytes.push(0),
+            }
+        }
+
+        bytes.extend_from_slice(&(self.id as u64).to_be_bytes());
+        bytes
+    }
+
+    /// Decodes a cursor from bytes produced by [`Cursor::to_bytes`]
+    fn from_bytes(mut bytes: &[u8]) -> Option<Self> {
+        let mut keys = Vec::new();
+
+        while bytes.len() > std::mem::size_of::<u64>() {
+            let (marker, rest) = bytes.split_first()?;
+            bytes = match marker {
+                0 => {
+                    keys.push(None);
+                    rest
+                }
+                1 => {
+                    let (key, rest) = IndexKey::from_ordered_bytes(rest)?;
+                    keys.push(Some(key));
+                    rest
+                }
+                _ => return None,
+            };
+        }
+
+        let id = u64::from_be_bytes(bytes.try_into().ok()?);
+        Some(Self {
+            keys,
+            id: id.try_into().ok()?,
+        })
+    }
+}
+
+impl fmt::Display for Cursor {
+    /// Writes the cursor as an opaque string of hex characters
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for b in self.to_bytes() {
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+/// Represents an error encountered when parsing a string that is not a
+/// valid [`Cursor`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, derive_more::Display, derive_more::Error)]
+#[display(fmt = "Invalid cursor")]
+pub struct InvalidCursor;
+
+impl FromStr for Cursor {
+    type Err = InvalidCursor;
+
+    /// Parses a cursor from a string produced by displaying a cursor
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = s
+            .as_bytes()
+            .chunks(2)
+            .map(|pair| match std::str::from_utf8(pair) {
+                Ok(hex) if hex.len() == 2 => u8::from_str_radix(hex, 16).ok(),
+                _ => None,
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(InvalidCursor)?;
+
+        Self::from_bytes(&bytes).ok_or(InvalidCursor)
+    }
+}
+
+#[cfg(feature = "serde-1")]
+impl serde::Serialize for Cursor {
+    /// Serializes the cursor as its opaque string
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde-1")]
+impl<'de> serde::Deserialize<'de> for Cursor {
+    /// Deserializes the cursor from its opaque string
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Represents a single page of results from a query alongside the cursor
+/// used to resume the query after the page
+#[derive(Clone)]
+pub struct Page<T = Box<dyn Ent>> {
+    items: Vec<T>,
+    next_cursor: Option<Cursor>,
+}
+
+impl<T> Page<T> {
+    /// Creates a new page of items, which is followed by more items if a
+    /// cursor is provided
+    pub fn new(items: Vec<T>, next_cursor: Option<Cursor>) -> Self {
+        Self { items, next_cursor }
+    }
+
+    /// The items contained in the page
+    #[inline]
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Consumes the page, returning its items
+    #[inline]
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+
+    /// The cursor positioned at the last item of the page, which is only
+    /// available if more items follow the page
+    #[inline]
+    pub fn next_cursor(&self) -> Option<&Cursor> {
+        self.next_cursor.as_ref()
+    }
+
+    /// Returns true if more items follow the page
+    #[inline]
+    pub fn has_next(&self) -> bool {
+        self.next_cursor.is_some()
+    }
+
+    /// Consumes the page, returning its items and next cursor
+    pub fn into_parts(self) -> (Vec<T>, Option<Cursor>) {
+        (self.items, self.next_cursor)
+    }
+}
+
+impl Page {
+    /// Creates a page of at most `page_size` ents from the results of a
+    /// query ordered by the given clauses and limited to one more than the
+    /// page size, where the extra ent indicates that more ents follow
+    pub fn from_results(order: &[Order], mut ents: Vec<Box<dyn Ent>>, page_size: usize) -> Self {
+        let next_cursor = if ents.len() > page_size {
+            ents.truncate(page_size);
+            ents.last().map(|ent| Cursor::new(order, ent.as_ref()))
+        } else {
+            None
+        };
+
+        Self::new(ents, next_cursor)
+    }
+
+    /// Consumes the page, converting its ents to the specified type and
+    /// discarding any ents of a different type
+    pub fn into_typed<E: Ent>(self) -> Page<E> {
+        Page::new(
+            self.items
+                .into_iter()
+                .filter_map(|ent| ent.to_ent::<E>())
+                .collect(),
+            self.next_cursor,
+        )
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Page<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Page")
+            .field("items", &self.items)
+            .field("next_cursor", &self.next_cursor)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Field, OrderBy, UntypedEnt};
+
+    fn ent(id: Id, x: Option<i32>) -> UntypedEnt {
+        UntypedEnt::from_collections(id, x.map(|x| Field::new("x", x)), vec![])
+    }
+
+    #[test]
+    fn cursor_should_convert_to_and_from_string() {
+        let order = vec![
+            Order::ascending(OrderBy::Field(String::from("x"))),
+            Order::descending(OrderBy::Created),
+        ];
+
+        for ent in &[ent(1, Some(-5)), ent(2, None), ent(999, Some(0))] {
+            let cursor = Cursor::new(&order, ent);
+            assert_eq!(cursor.to_string().parse::<Cursor>(), Ok(cursor));
+        }
+
+        let cursor = Cursor::new(&[], &ent(123, None));
+        assert_eq!(cursor.to_string().parse::<Cursor>(), Ok(cursor));
+    }
+
+    #[test]
+    fn cursor_should_fail_to_parse_invalid_strings() {
+        assert_eq!("".parse::<Cursor>(), Err(InvalidCursor));
+        assert_eq!("abc".parse::<Cursor>(), Err(InvalidCursor));
+        assert_eq!("zz".parse::<Cursor>(), Err(InvalidCursor));
+        assert_eq!(
+            "0201000000000000000001".parse::<Cursor>(),
+            Err(InvalidCursor)
+        );
+    }
+
+    #[test]
+    fn cursor_should_compare_by_each_ordering_clause_then_id() {
+        let order = vec![Order::descending(OrderBy::Field(String::from("x")))];
+        let cursor = |id, x| Cursor::new(&order, &ent(id, x));
+
+        assert_eq!(
+            cursor(1, Some(5)).compare(&cursor(2, Some(3)), &order),
+            Ordering::Less
+        );
+        assert_eq!(
+            cursor(1, Some(5)).compare(&cursor(2, Some(5)), &order),
+            Ordering::Less
+        );
+        assert_eq!(
+            cursor(3, Some(5)).compare(&cursor(2, Some(5)), &order),
+            Ordering::Greater
+        );
+        assert_eq!(
+            cursor(1, None).compare(&cursor(2, Some(5)), &order),
+            Ordering::Less
+        );
+    }
+}
@@ -1,8 +1,9 @@
-use crate::{Id, Predicate, TypedPredicate};
+use crate::{Ent, Id, Predicate, Query, TypedPredicate};
 
 /// Represents some filter to apply against an ent when searching through
 /// a database
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
 pub enum Filter {
     /// Filters by the ent's id
     Id(TypedPredicate<Id>),
@@ -23,8 +24,63 @@ pub enum Filter {
     /// [`Filter::IntoEdge`], which converts an ent to its edge's ents
     Edge(String, Box<Filter>),
 
+    /// **(Special case)** Filters by the creation timestamp of the
+    /// association with the ent whose edge is being filtered
+    ///
+    /// Only applies within [`Filter::Edge`]; otherwise, no ent passes
+    AssocCreated(TypedPredicate<u64>),
+
+    /// **(Special case)** Filters by the data attached to the association
+    /// with the ent whose edge is being filtered, where an association
+    /// without data is checked as [`crate::Value::Optional`] of none
+    ///
+    /// Only applies within [`Filter::Edge`]; otherwise, no ent passes
+    AssocData(Predicate),
+
     /// **(Special case)** Filters by converting an ent into the ents on its edge
+    ///
+    /// When within [`Filter::And`], [`Filter::Or`], [`Filter::Not`], or
+    /// [`Filter::Edge`], no conversion happens and instead filters by whether
+    /// the ent has any ents on its edge
     IntoEdge(String),
+
+    /// **(Special case)** Filters by converting an ent into the ents that
+    /// contain it on their edge of the given name
+    ///
+    /// When within [`Filter::And`], [`Filter::Or`], [`Filter::Not`], or
+    /// [`Filter::Edge`], no conversion happens and instead filters by whether
+    /// any ents contain the ent on their edge
+    IntoInverseEdge(String),
+
+    /// **(Special case)** Adds the ents found by the query to the ents found
+    /// so far, or only includes the ents found by the query if first
+    ///
+    /// When nested within another filter, every ent passes
+    Union(Box<Query>),
+
+    /// **(Special case)** Keeps only the ents found so far that are also
+    /// found by the query, or only includes the ents found by the query if
+    /// first
+    ///
+    /// When nested within another filter, filters by whether the ent is
+    /// found by the query
+    Intersect(Box<Query>),
+
+    /// **(Special case)** Removes the ents found by the query from the ents
+    /// found so far, or from all ents if first
+    ///
+    /// When nested within another filter, filters by whether the ent is not
+    /// found by the query
+    Except(Box<Query>),
+
+    /// Filters by an ent passing all of the filters
+    And(Vec<Filter>),
+
+    /// Filters by an ent passing any of the filters
+    Or(Vec<Filter>),
+
+    /// Filters by an ent not passing the filter
+    Not(Box<Filter>),
 }
 
 impl Filter {
@@ -52,7 +108,186 @@ impl Filter {
         Self::Edge(name.into(), Box::new(filter.into()))
     }
 
+    pub fn where_assoc_created<P: Into<TypedPredicate<u64>>>(p: P) -> Self {
+        Self::AssocCreated(p.into())
+    }
+
+    pub fn where_assoc_data<P: Into<Predicate>>(p: P) -> Self {
+        Self::AssocData(p.into())
+    }
+
     pub fn where_into_edge<S: Into<String>>(name: S) -> Self {
         Self::IntoEdge(name.into())
     }
+
+    pub fn where_into_inverse_edge<S: Into<String>>(name: S) -> Self {
+        Self::IntoInverseEdge(name.into())
+    }
+
+    pub fn union<Q: Into<Query>>(query: Q) -> Self {
+        Self::Union(Box::new(query.into()))
+    }
+
+    pub fn intersect<Q: Into<Query>>(query: Q) -> Self {
+        Self::Intersect(Box::new(query.into()))
+    }
+
+    pub fn except<Q: Into<Query>>(query: Q) -> Self {
+        Self::Except(Box::new(query.into()))
+    }
+
+    pub fn and<I: IntoIterator<Item = Filter>>(filters: I) -> Self {
+        Self::And(filters.into_iter().collect())
+    }
+
+    pub fn or<I: IntoIterator<Item = Filter>>(filters: I) -> Self {
+        Self::Or(filters.into_iter().collect())
+    }
+
+    pub fn not<F: Into<Filter>>(filter: F) -> Self {
+        Self::Not(Box::new(filter.into()))
+    }
+
+    /// Checks if the ent passes the filter using the ent alone rather than
+    /// looking up any other ents in a database, returning none if whether
+    /// it passes depends on other ents
+    ///
+    /// Filters are checked as they are when nested within another filter.
+    /// [`Filter::Edge`] only fails for ents without any ents on the edge and
+    /// [`Filter::IntoInverseEdge`] is never decided, while
+    /// [`Filter::IntoEdge`] passes if the ent has any ents on the edge,
+    /// [`Filter::Union`] always passes, [`Filter::Intersect`] and
+    /// [`Filter::Except`] check the ent against their query, and association
+    /// filters never pass.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::{Edge, Filter, TypedPredicate as TP, UntypedEnt};
+    ///
+    /// let ent = UntypedEnt::from_collections(3, vec![], vec![Edge::new("friends", 1)]);
+    ///
+    /// assert_eq!(Filter::where_id(TP::equals(3)).matches(&ent), Some(true));
+    /// assert_eq!(Filter::where_edge("friends", Filter::where_id(TP::equals(1))).matches(&ent), None);
+    /// assert_eq!(Filter::where_edge("enemies", Filter::where_id(TP::equals(1))).matches(&ent), Some(false));
+    /// ```
+    pub fn matches(&self, ent: &dyn Ent) -> Option<bool> {
+        match self {
+            Self::Id(p) => Some(p.check(ent.id())),
+            Self::Type(p) => Some(p.check(ent.r#type().to_string())),
+            Self::Created(p) => Some(p.check(ent.created())),
+            Self::LastUpdated(p) => Some(p.check(ent.last_updated())),
+            Self::Field(name, p) => Some(match ent.field(name) {
+                Some(value) => p.check(&value),
+                None => false,
+            }),
+            Self::Edge(name, _) => match ent.edge(name) {
+                Some(edge) if !edge.to_ids().is_empty() => None,
+                _ => Some(false),
+            },
+            Self::IntoEdge(name) => Some(match ent.edge(name) {
+                Some(edge) => !edge.to_ids().is_empty(),
+                None => false,
+            }),
+            Self::AssocCreated(_) | Self::AssocData(_) => Some(false),
+            Self::IntoInverseEdge(_) => None,
+            Self::Union(_) => Some(true),
+            Self::Intersect(query) => query.matches(ent),
+            Self::Except(query) => query.matches(ent).map(|matched| !matched),
+            Self::And(filters) => {
+                let mut matched = Some(true);
+                for f in filters {
+                    matched = all_match(matched, f.matches(ent));
+                }
+                matched
+            }
+            Self::Or(filters) => {
+                let mut matched = Some(false);
+                for f in filters {
+                    matched = any_match(matched, f.matches(ent));
+                }
+                matched
+            }
+            Self::Not(f) => f.matches(ent).map(|matched| !matched),
+        }
+    }
+}
+
+/// Combines whether an ent passes two checks into whether it passes both,
+/// which is decided if either check fails even if the other is undecided
+pub(crate) fn all_match(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+    match (a, b) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None,
+    }
+}
+
+/// Combines whether an ent passes two checks into whether it passes either,
+/// which is decided if either check passes even if the other is undecided
+pub(crate) fn any_match(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+    match (a, b) {
+        (Some(true), _) | (_, Some(true)) => Some(true),
+        (Some(false), Some(false)) => Some(false),
+        _ => None,
+    }
+}
+
+impl std::ops::BitAnd for Filter {
+    type Output = Self;
+
+    /// Shorthand to produce [`Filter::And`], merging with either side that
+    /// is already a [`Filter::And`]
+    fn bitand(self, rhs: Self) -> Self {
+        let inner = match (self, rhs) {
+            (Self::And(mut list1), Self::And(mut list2)) => {
+                list1.append(&mut list2);
+                list1
+            }
+            (x, Self::And(mut list)) => {
+                list.insert(0, x);
+                list
+            }
+            (Self::And(mut list), x) => {
+                list.push(x);
+                list
+            }
+            (x1, x2) => vec![x1, x2],
+        };
+        Self::And(inner)
+    }
+}
+
+impl std::ops::BitOr for Filter {
+    type Output = Self;
+
+    /// Shorthand to produce [`Filter::Or`], merging with either side that
+    /// is already a [`Filter::Or`]
+    fn bitor(self, rhs: Self) -> Self {
+        let inner = match (self, rhs) {
+            (Self::Or(mut list1), Self::Or(mut list2)) => {
+                list1.append(&mut list2);
+                list1
+            }
+            (x, Self::Or(mut list)) => {
+                list.insert(0, x);
+                list
+            }
+            (Self::Or(mut list), x) => {
+                list.push(x);
+                list
+            }
+            (x1, x2) => vec![x1, x2],
+        };
+        Self::Or(inner)
+    }
+}
+
+impl std::ops::Not for Filter {
+    type Output = Self;
+
+    /// Shorthand to produce [`Filter::Not`]
+    fn not(self) -> Self::Output {
+        Self::Not(Box::new(self))
+    }
 }
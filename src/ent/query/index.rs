@@ -0,0 +1,588 @@
+use crate::{NumberSign, Predicate, Primitive, Value};
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    ops::Bound::{self, Excluded, Included, Unbounded},
+};
+
+/// Represents the normalized form of a [`Value`] used as a key within a
+/// secondary index of field values
+///
+/// Keys are built such that values equal to one another produce the same
+/// key, and values ordered relative to one another produce keys with the
+/// same ordering. Only values that can be compared this way have a key,
+/// meaning lists, maps, and numbers that are not comparable (like NaN)
+/// are never indexed.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IndexKey {
+    /// Key for a value representing the absence of a value
+    None,
+
+    /// Key for a unit primitive
+    Unit,
+
+    /// Key for a bool primitive
+    Bool(bool),
+
+    /// Key for a char primitive
+    Char(char),
+
+    /// Key for a negative number, ordered by its magnitude in reverse to
+    /// align with how [`crate::Number`] compares numbers
+    ///
+    /// The magnitude is made up of the number's integral part and the bits
+    /// of its fractional part, so floats are keyed exactly.
+    Negative(Reverse<(u128, u64)>),
+
+    /// Key for a number that is zero
+    Zero,
+
+    /// Key for a positive number, ordered by its magnitude to align with how
+    /// [`crate::Number`] compares numbers
+    ///
+    /// The magnitude is made up of the number's integral part and the bits
+    /// of its fractional part, so floats are keyed exactly.
+    Positive(u128, u64),
+
+    /// Key for a text value
+    Text(String),
+}
+
+impl IndexKey {
+    /// Produces the key for the given value if it has one
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::{IndexKey, Value};
+    ///
+    /// // Numbers of different types that are equal produce the same key
+    /// assert_eq!(
+    ///     IndexKey::from_value(&Value::from(3u8)),
+    ///     IndexKey::from_value(&Value::from(3i64)),
+    /// );
+    ///
+    /// // Optional values are keyed by their inner value
+    /// assert_eq!(
+    ///     IndexKey::from_value(&Value::from(Some(3))),
+    ///     IndexKey::from_value(&Value::from(3)),
+    /// );
+    ///
+    /// // Floats are keyed exactly rather than by their integral part
+    /// assert_ne!(
+    ///     IndexKey::from_value(&Value::from(1.2)),
+    ///     IndexKey::from_value(&Value::from(1.7)),
+    /// );
+    ///
+    /// // Collections have no key
+    /// assert_eq!(IndexKey::from_value(&Value::from(vec![1, 2, 3])), None);
+    /// ```
+    pub fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Optional(None) => Some(Self::None),
+            Value::Optional(Some(x)) => Self::from_value(x),
+            Value::Primitive(Primitive::Unit) => Some(Self::Unit),
+            Value::Primitive(Primitive::Bool(x)) => Some(Self::Bool(*x)),
+            Value::Primitive(Primitive::Char(x)) => Some(Self::Char(*x)),
+            Value::Primitive(Primitive::Number(x)) => {
+                if !x.is_normal() && !x.is_zero() {
+                    return None;
+                }
+
+                Some(match x.sign() {
+                    NumberSign::Negative => Self::Negative(Reverse(x.magnitude())),
+                    NumberSign::Zero => Self::Zero,
+                    NumberSign::Positive => {
+                        let (integral, fraction) = x.magnitude();
+                        Self::Positive(integral, fraction)
+                    }
+                })
+            }
+            Value::Text(x) => Some(Self::Text(x.to_string())),
+            Value::List(_) | Value::Map(_) => None,
+        }
+    }
+
+    /// Encodes the key as bytes that sort in the same order as the key
+    ///
+    /// No encoded key is a prefix of another encoded key, so additional
+    /// bytes can be appended to the encoding (such as an id) without
+    /// changing the order.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::{IndexKey, Value};
+    ///
+    /// let bytes = |v: Value| IndexKey::from_value(&v).unwrap().to_ordered_bytes();
+    ///
+    /// assert!(bytes(Value::from(-5)) < bytes(Value::from(-1)));
+    /// assert!(bytes(Value::from(-1)) < bytes(Value::from(0)));
+    /// assert!(bytes(Value::from(0)) < bytes(Value::from(1u8)));
+    /// assert!(bytes(Value::from(1u8)) < bytes(Value::from(1.5)));
+    /// assert!(bytes(Value::from(1u8)) < bytes(Value::from(300u64)));
+    /// assert!(bytes(Value::from("a")) < bytes(Value::from("ab")));
+    /// ```
+    pub fn to_ordered_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        match self {
+            Self::None => bytes.push(0),
+            Self::Unit => bytes.push(1),
+            Self::Bool(x) => bytes.extend_from_slice(&[2, *x as u8]),
+            Self::Char(x) => {
+                bytes.push(3);
+                bytes.extend_from_slice(&(*x as u32).to_be_bytes());
+            }
+            Self::Negative(Reverse((integral, fraction))) => {
+                bytes.push(4);
+                bytes.extend_from_slice(&(!*integral).to_be_bytes());
+                bytes.extend_from_slice(&(!*fraction).to_be_bytes());
+            }
+            Self::Zero => bytes.push(5),
+            Self::Positive(integral, fraction) => {
+                bytes.push(6);
+                bytes.extend_from_slice(&integral.to_be_bytes());
+                bytes.extend_from_slice(&fraction.to_be_bytes());
+            }
+
+            // Zero bytes within text are escaped so that two zero bytes can
+            // mark the end of the text while still sorting shorter text first
+            Self::Text(x) => {
+                bytes.push(7);
+                for b in x.bytes() {
+                    if b == 0 {
+                        bytes.extend_from_slice(&[0, 0xFF]);
+                    } else {
+                        bytes.push(b);
+                    }
+                }
+                bytes.extend_from_slice(&[0, 0]);
+            }
+        }
+
+        bytes
+    }
+
+    /// Decodes a key from the front of bytes produced by
+    /// [`IndexKey::to_ordered_bytes`], returning the key alongside the
+    /// remaining bytes, or none if the bytes do not start with a valid key
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::IndexKey;
+    ///
+    /// let mut bytes = IndexKey::Text(String::from("abc")).to_ordered_bytes();
+    /// bytes.push(123);
+    ///
+    /// let (key, rest) = IndexKey::from_ordered_bytes(&bytes).unwrap();
+    /// assert_eq!(key, IndexKey::Text(String::from("abc")));
+    /// assert_eq!(rest, &[123]);
+    /// ```
+    pub fn from_ordered_bytes(bytes: &[u8]) -> Option<(Self, &[u8])> {
+        use std::convert::TryInto;
+
+        let (tag, bytes) = bytes.split_first()?;
+        match tag {
+            0 => Some((Self::None, bytes)),
+            1 => Some((Self::Unit, bytes)),
+            2 => match bytes.split_first()? {
+                (0, rest) => Some((Self::Bool(false), rest)),
+                (1, rest) => Some((Self::Bool(true), rest)),
+                _ => None,
+            },
+            3 if bytes.len() >= 4 => {
+                let (x, rest) = bytes.split_at(4);
+                let x = u32::from_be_bytes(x.try_into().ok()?);
+                Some((Self::Char(std::char::from_u32(x)?), rest))
+            }
+            4 if bytes.len() >= 24 => {
+                let (integral, rest) = bytes.split_at(16);
+                let (fraction, rest) = rest.split_at(8);
+                let integral = u128::from_be_bytes(integral.try_into().ok()?);
+                let fraction = u64::from_be_bytes(fraction.try_into().ok()?);
+                Some((Self::Negative(Reverse((!integral, !fraction))), rest))
+            }
+            5 => Some((Self::Zero, bytes)),
+            6 if bytes.len() >= 24 => {
+                let (integral, rest) = bytes.split_at(16);
+                let (fraction, rest) = rest.split_at(8);
+                let integral = u128::from_be_bytes(integral.try_into().ok()?);
+                let fraction = u64::from_be_bytes(fraction.try_into().ok()?);
+                Some((Self::Positive(integral, fraction), rest))
+            }
+            7 => {
+                let mut text = Vec::new();
+                let mut i = 0;
+                loop {
+                    match (*bytes.get(i)?, bytes.get(i + 1).copied()) {
+                        (0, Some(0)) => break,
+                        (0, Some(0xFF)) => {
+                            text.push(0);
+                            i += 2;
+                        }
+                        (0, _) => return None,
+                        (b, _) => {
+                            text.push(b);
+                            i += 1;
+                        }
+                    }
+                }
+                let text = String::from_utf8(text).ok()?;
+                Some((Self::Text(text), &bytes[i + 2..]))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the smallest and largest keys of the same kind as this key,
+    /// where the largest is unbounded for text
+    fn kind_bounds(&self) -> (Bound<Self>, Bound<Self>) {
+        match self {
+            Self::None => (Included(Self::None), Included(Self::None)),
+            Self::Unit => (Included(Self::Unit), Included(Self::Unit)),
+            Self::Bool(_) => (Included(Self::Bool(false)), Included(Self::Bool(true))),
+            Self::Char(_) => (
+                Included(Self::Char('\0')),
+                Included(Self::Char(std::char::MAX)),
+            ),
+            Self::Negative(_) | Self::Zero | Self::Positive(..) => (
+                Included(Self::Negative(Reverse((u128::MAX, u64::MAX)))),
+                Included(Self::Positive(u128::MAX, u64::MAX)),
+            ),
+            Self::Text(_) => (Included(Self::Text(String::new())), Unbounded),
+        }
+    }
+}
+
+/// Represents the portion of a secondary index that contains every value
+/// able to satisfy some predicate
+///
+/// A scan can include values that do not satisfy the predicate, so the
+/// predicate should still be checked against everything found by the scan.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IndexScan {
+    /// Values whose key is any of the given keys
+    Keys(HashSet<IndexKey>),
+
+    /// Values whose key falls within the given bounds
+    Range(Bound<IndexKey>, Bound<IndexKey>),
+
+    /// Values found by any of the given scans
+    Union(Vec<IndexScan>),
+
+    /// Values found by all of the given scans
+    Intersection(Vec<IndexScan>),
+}
+
+impl IndexScan {
+    /// Plans a scan that covers every value satisfying the predicate,
+    /// returning none if the predicate cannot be served by an index
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::{IndexKey, IndexScan, Predicate as P, Value};
+    /// use std::ops::Bound;
+    ///
+    /// let scan = IndexScan::from_predicate(&P::equals(3)).unwrap();
+    /// assert_eq!(scan, IndexScan::Keys(
+    ///     vec![IndexKey::from_value(&Value::from(3)).unwrap()].into_iter().collect()
+    /// ));
+    ///
+    /// let scan = IndexScan::from_predicate(&P::greater_than(3)).unwrap();
+    /// assert!(matches!(scan, IndexScan::Range(Bound::Excluded(_), Bound::Included(_))));
+    ///
+    /// assert_eq!(IndexScan::from_predicate(&P::text_ends_with("abc")), None);
+    /// ```
+    pub fn from_predicate(predicate: &Predicate) -> Option<Self> {
+        match predicate {
+            Predicate::Never => Some(Self::Keys(HashSet::new())),
+            Predicate::Equals(v) => {
+                IndexKey::from_value(v).map(|k| Self::Keys(Some(k).into_iter().collect()))
+            }
+            Predicate::InSet(set) => set
+                .iter()
+                .map(IndexKey::from_value)
+                .collect::<Option<HashSet<IndexKey>>>()
+                .map(Self::Keys),
+            Predicate::IsNone => Some(Self::Keys(Some(IndexKey::None).into_iter().collect())),
+            Predicate::GreaterThan(v) => Self::above(v, false),
+            Predicate::GreaterThanOrEquals(v) => Self::above(v, true),
+            Predicate::LessThan(v) => Self::below(v, false),
+            Predicate::LessThanOrEquals(v) => Self::below(v, true),
+            Predicate::InRange(r) => Some(Self::Intersection(vec![
+                Self::above(r.start(), true)?,
+                Self::below(r.end(), true)?,
+            ])),
+            Predicate::NotNoneAnd(p) => Self::from_predicate(p),
+            Predicate::NoneOr(p) => Some(Self::Union(vec![
+                Self::Keys(Some(IndexKey::None).into_iter().collect()),
+                Self::from_predicate(p)?,
+            ])),
+
+            // Every value must pass each predicate, so any of them that can
+            // be served by an index narrows down the values
+            Predicate::And(list) => {
+                let scans: Vec<Self> = list.iter().filter_map(Self::from_predicate).collect();
+                if scans.is_empty() {
+                    None
+                } else {
+                    Some(Self::Intersection(scans))
+                }
+            }
+
+            // A value can pass any of the predicates, so all of them must
+            // be served by an index
+            Predicate::Or(list) => list
+                .iter()
+                .map(Self::from_predicate)
+                .collect::<Option<Vec<Self>>>()
+                .map(Self::Union),
+
+            _ => None,
+        }
+    }
+
+    /// Returns true if the given key is part of the scan
+    pub fn contains(&self, key: &IndexKey) -> bool {
+        match self {
+            Self::Keys(keys) => keys.contains(key),
+            Self::Range(start, end) => {
+                (match start {
+                    Included(x) => key >= x,
+                    Excluded(x) => key > x,
+                    Unbounded => true,
+                }) && (match end {
+                    Included(x) => key <= x,
+                    Excluded(x) => key < x,
+                    Unbounded => true,
+                })
+            }
+            Self::Union(list) => list.iter().any(|s| s.contains(key)),
+            Self::Intersection(list) => list.iter().all(|s| s.contains(key)),
+        }
+    }
+
+    /// Scan of all values greater than (or equal to) the given value
+    fn above(value: &Value, inclusive: bool) -> Option<Self> {
+        let key = IndexKey::from_value(value)?;
+        let (_, end) = key.kind_bounds();
+        let range = |key| {
+            if inclusive {
+                Included(key)
+            } else {
+                Excluded(key)
+            }
+        };
+
+        Some(match key {
+            // Nothing is ordered relative to the absence of a value
+            IndexKey::None => Self::Keys(HashSet::new()),
+
+            // Chars and text are compared against one another as text
+            IndexKey::Char(c) => Self::Union(vec![
+                Self::Range(range(IndexKey::Char(c)), end),
+                Self::Range(range(IndexKey::Text(c.to_string())), Unbounded),
+            ]),
+            IndexKey::Text(t) => Self::Union(vec![
+                Self::Range(range(IndexKey::Text(t)), end),
+                Self::Range(
+                    Included(IndexKey::Char('\0')),
+                    Included(IndexKey::Char(std::char::MAX)),
+                ),
+            ]),
+
+            key => Self::Range(range(key), end),
+        })
+    }
+
+    /// Scan of all values less than (or equal to) the given value
+    fn below(value: &Value, inclusive: bool) -> Option<Self> {
+        let key = IndexKey::from_value(value)?;
+        let (start, _) = key.kind_bounds();
+        let range = |key| {
+            if inclusive {
+                Included(key)
+            } else {
+                Excluded(key)
+            }
+        };
+
+        Some(match key {
+            // Nothing is ordered relative to the absence of a value
+            IndexKey::None => Self::Keys(HashSet::new()),
+
+            // Chars and text are compared against one another as text
+            IndexKey::Char(c) => Self::Union(vec![
+                Self::Range(start, range(IndexKey::Char(c))),
+                Self::Range(
+                    Included(IndexKey::Text(String::new())),
+                    range(IndexKey::Text(c.to_string())),
+                ),
+            ]),
+            IndexKey::Text(t) => Self::Union(vec![
+                Self::Range(start, range(IndexKey::Text(t))),
+                Self::Range(
+                    Included(IndexKey::Char('\0')),
+                    Included(IndexKey::Char(std::char::MAX)),
+                ),
+            ]),
+
+            key => Self::Range(start, range(key)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Predicate as P;
+
+    /// Verifies that every value passing the predicate is part of the scan
+    fn assert_covers(predicate: Predicate, values: &[Value]) {
+        let scan = IndexScan::from_predicate(&predicate).expect("Predicate not indexable");
+        for value in values {
+            if predicate.check(value) {
+                let key = IndexKey::from_value(value).expect("Value not indexable");
+                assert!(
+                    scan.contains(&key),
+                    "{:?} passes {:?}, but is not covered by {:?}",
+                    value,
+                    predicate,
+                    scan
+                );
+            }
+        }
+    }
+
+    fn values() -> Vec<Value> {
+        vec![
+            Value::from(-100i64),
+            Value::from(-1.5f32),
+            Value::from(-1.2f64),
+            Value::from(-1i8),
+            Value::from(0u8),
+            Value::from(0.5f64),
+            Value::from(1u32),
+            Value::from(1.2f64),
+            Value::from(1.7f32),
+            Value::from(1.9f64),
+            Value::from(100i128),
+            Value::from(1e39f64),
+            Value::from(Some(3)),
+            Value::from(None::<u32>),
+            Value::from(true),
+            Value::from(false),
+            Value::from('a'),
+            Value::from('z'),
+            Value::from("a"),
+            Value::from("abc"),
+            Value::from("zz"),
+        ]
+    }
+
+    #[test]
+    fn index_key_should_order_like_value_comparisons() {
+        let values = values();
+        for a in values.iter() {
+            for b in values.iter() {
+                let (ka, kb) = (
+                    IndexKey::from_value(a).unwrap(),
+                    IndexKey::from_value(b).unwrap(),
+                );
+                if let Some(ordering) = a.partial_cmp(b) {
+                    if matches!(
+                        (&ka, &kb),
+                        (IndexKey::Text(_), IndexKey::Char(_))
+                            | (IndexKey::Char(_), IndexKey::Text(_))
+                    ) {
+                        continue;
+                    }
+                    assert_eq!(ka.cmp(&kb), ordering, "{:?} vs {:?}", a, b);
+                }
+                if a == b {
+                    assert_eq!(ka, kb, "{:?} == {:?}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn index_key_should_encode_to_and_decode_from_bytes_that_sort_like_keys() {
+        let mut keys: Vec<IndexKey> = values()
+            .iter()
+            .filter_map(IndexKey::from_value)
+            .chain(vec![
+                IndexKey::Unit,
+                IndexKey::Text(String::from("a\0")),
+                IndexKey::Text(String::from("a\0b")),
+                IndexKey::Text(String::new()),
+            ])
+            .collect();
+        keys.sort();
+        keys.dedup();
+
+        for pair in keys.windows(2) {
+            let (a, b) = (pair[0].to_ordered_bytes(), pair[1].to_ordered_bytes());
+            assert!(a < b, "{:?} !< {:?}", pair[0], pair[1]);
+            assert!(
+                !b.starts_with(&a),
+                "{:?} is prefix of {:?}",
+                pair[0],
+                pair[1]
+            );
+        }
+
+        for key in keys {
+            let mut bytes = key.to_ordered_bytes();
+            bytes.push(0);
+            assert_eq!(
+                IndexKey::from_ordered_bytes(&bytes),
+                Some((key.clone(), &[0][..])),
+                "{:?} failed to decode",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn index_key_should_not_exist_for_collections_or_incomparable_numbers() {
+        assert_eq!(IndexKey::from_value(&Value::from(vec![1, 2])), None);
+        assert_eq!(IndexKey::from_value(&Value::from(f64::NAN)), None);
+        assert_eq!(IndexKey::from_value(&Value::from(f32::INFINITY)), None);
+    }
+
+    #[test]
+    fn index_scan_should_cover_all_values_passing_predicate() {
+        let values = values();
+        for v in values.iter() {
+            assert_covers(P::equals(v.clone()), &values);
+            assert_covers(P::greater_than(v.clone()), &values);
+            assert_covers(P::greater_than_or_equals(v.clone()), &values);
+            assert_covers(P::less_than(v.clone()), &values);
+            assert_covers(P::less_than_or_equals(v.clone()), &values);
+        }
+
+        assert_covers(P::in_range(Value::from(-1)..=Value::from(1)), &values);
+        assert_covers(P::in_range(Value::from('b')..=Value::from("zzz")), &values);
+        assert_covers(P::in_set(vec![Value::from(1), Value::from("abc")]), &values);
+        assert_covers(P::IsNone, &values);
+        assert_covers(P::NoneOr(Box::new(P::equals(3))), &values);
+        assert_covers(P::NotNoneAnd(Box::new(P::equals(3))), &values);
+        assert_covers(P::equals(1) | P::equals(Value::from("a")), &values);
+        assert_covers(P::greater_than(0) & P::text_ends_with("c"), &values);
+    }
+
+    #[test]
+    fn index_scan_should_not_exist_for_predicates_that_cannot_use_an_index() {
+        assert_eq!(IndexScan::from_predicate(&P::Always), None);
+        assert_eq!(IndexScan::from_predicate(&P::not(P::equals(3))), None);
+        assert_eq!(IndexScan::from_predicate(&P::equals(vec![1, 2])), None);
+        assert_eq!(
+            IndexScan::from_predicate(&(P::equals(3) | P::text_ends_with("c"))),
+            None
+        );
+    }
+}
@@ -1,13 +1,30 @@
 use crate::{DatabaseError, DatabaseResult, Ent, Id, WeakDatabaseRc};
-use derive_more::{Constructor, IntoIterator};
-use std::{fmt::Debug, iter::Extend};
+use std::{cmp::Ordering, fmt::Debug, iter::Extend};
+
+mod aggregate;
+pub use aggregate::*;
+
+mod cursor;
+pub use cursor::*;
 
 mod filter;
 pub use filter::*;
 
+mod index;
+pub use index::*;
+
+mod order;
+pub use order::*;
+
 mod predicate;
 pub use predicate::*;
 
+mod text;
+pub use text::*;
+
+mod traversal;
+pub use traversal::*;
+
 /// Represents a query interface for some ent
 pub trait EntQuery: Sized {
     type Output;
@@ -21,11 +38,64 @@ pub trait EntQuery: Sized {
     fn execute(self) -> DatabaseResult<Self::Output> {
         Self::execute_with_db(self, crate::global::db())
     }
+
+    /// Counts the ents matching the query within the provided database
+    /// without retrieving them
+    fn count_with_db(self, db: WeakDatabaseRc) -> DatabaseResult<usize>
+    where
+        Self: Into<Query>,
+    {
+        let database = WeakDatabaseRc::upgrade(&db).ok_or(DatabaseError::Disconnected)?;
+        database.count(self.into())
+    }
+
+    /// Counts the ents matching the query within the global database
+    /// without retrieving them
+    fn count(self) -> DatabaseResult<usize>
+    where
+        Self: Into<Query>,
+    {
+        Self::count_with_db(self, crate::global::db())
+    }
+
+    /// Returns true if any ent matches the query within the provided
+    /// database without retrieving it
+    fn exists_with_db(self, db: WeakDatabaseRc) -> DatabaseResult<bool>
+    where
+        Self: Into<Query>,
+    {
+        let database = WeakDatabaseRc::upgrade(&db).ok_or(DatabaseError::Disconnected)?;
+        database.exists(self.into())
+    }
+
+    /// Returns true if any ent matches the query within the global database
+    /// without retrieving it
+    fn exists(self) -> DatabaseResult<bool>
+    where
+        Self: Into<Query>,
+    {
+        Self::exists_with_db(self, crate::global::db())
+    }
 }
 
 /// Represents a generic query to find ents within some database
-#[derive(Constructor, IntoIterator, Clone, Debug, Default)]
-pub struct Query(Vec<Filter>);
+///
+/// Ents are found by applying each filter in order, where set operations
+/// such as [`Query::union`] combine the ents found so far with all of the
+/// ents returned by another query. If the query has any ordering clauses, a
+/// cursor, an offset, or a limit, the ents found are then ordered by each
+/// clause (followed by their ids) before skipping all ents up to and
+/// including the cursor's position, skipping the first `offset` ents, and
+/// returning at most `limit` of the remaining ents.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Query {
+    filters: Vec<Filter>,
+    order: Vec<Order>,
+    limit: Option<usize>,
+    offset: usize,
+    after: Option<Cursor>,
+}
 
 impl EntQuery for Query {
     type Output = Vec<Box<dyn Ent>>;
@@ -40,26 +110,298 @@ impl EntQuery for Query {
 impl Extend<Filter> for Query {
     /// Extends the query's filters with the contents of the iterator
     fn extend<T: IntoIterator<Item = Filter>>(&mut self, iter: T) {
-        self.0.extend(iter)
+        self.filters.extend(iter)
     }
 }
 
 impl Query {
+    /// Creates a new query that applies the given filters
+    pub fn new(filters: Vec<Filter>) -> Self {
+        Self {
+            filters,
+            ..Default::default()
+        }
+    }
+
+    /// The filters applied by the query, in order
+    #[inline]
+    pub fn filters(&self) -> &[Filter] {
+        &self.filters
+    }
+
+    /// Consumes query, returning the filters it applies
+    #[inline]
+    pub fn into_filters(self) -> Vec<Filter> {
+        self.filters
+    }
+
+    /// The clauses used to order the results of the query, in order of
+    /// precedence
+    #[inline]
+    pub fn order(&self) -> &[Order] {
+        &self.order
+    }
+
+    /// The maximum number of results returned by the query, if limited
+    #[inline]
+    pub fn get_limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// The number of results skipped before any are returned by the query
+    #[inline]
+    pub fn get_offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The cursor of the ent after which results of the query begin, if any
+    #[inline]
+    pub fn get_after(&self) -> Option<&Cursor> {
+        self.after.as_ref()
+    }
+
+    /// Returns true if the query has a cursor, an offset, or a limit, meaning
+    /// that which of the ents passing its filters are returned depends on
+    /// their order
+    #[inline]
+    pub fn is_paged(&self) -> bool {
+        self.after.is_some() || self.offset > 0 || self.limit.is_some()
+    }
+
+    /// Checks if the ent would be found by the query using the ent alone as
+    /// described by [`Filter::matches`], returning none if that depends on
+    /// other ents
+    ///
+    /// Filters are applied in order, where [`Filter::Union`] passes ents
+    /// that pass the filters before it or are found by its query, while
+    /// converting ents into the ents on an edge with [`Filter::IntoEdge`] or
+    /// [`Filter::IntoInverseEdge`] is never decided. The ordering, cursor,
+    /// offset, and limit of the query are ignored.
+    pub fn matches(&self, ent: &dyn Ent) -> Option<bool> {
+        let mut matched = Some(true);
+
+        for (i, filter) in self.filters.iter().enumerate() {
+            matched = match filter {
+                Filter::Union(query) if i == 0 => query.matches(ent),
+                Filter::Union(query) => any_match(matched, query.matches(ent)),
+                Filter::IntoEdge(_) | Filter::IntoInverseEdge(_) => None,
+                filter => all_match(matched, filter.matches(ent)),
+            };
+        }
+
+        matched
+    }
+
     /// Consumes query, producing a new query with the additional filter
     /// added to the end of the filters to be applied
-    pub fn chain(self, filter: Filter) -> Self {
-        let mut filters = self.0;
-        filters.push(filter);
-
-        Query::new(filters)
+    pub fn chain(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
     }
 
     /// Updates the query by adding an additional filter to the end
     pub fn add_filter(&mut self, filter: Filter) -> &mut Self {
-        self.0.push(filter);
+        self.filters.push(filter);
+        self
+    }
+
+    /// Consumes query, producing a new query with the additional ordering
+    /// clause, which only applies to results that are equal by all prior
+    /// ordering clauses
+    pub fn order_by(mut self, order: Order) -> Self {
+        self.order.push(order);
+        self
+    }
+
+    /// Updates the query by adding an additional ordering clause to the end
+    pub fn add_order(&mut self, order: Order) -> &mut Self {
+        self.order.push(order);
+        self
+    }
+
+    /// Consumes query, producing a new query with an additional ordering
+    /// clause by the id of ents
+    pub fn order_by_id(self, direction: OrderDirection) -> Self {
+        self.order_by(Order::new(OrderBy::Id, direction))
+    }
+
+    /// Consumes query, producing a new query with an additional ordering
+    /// clause by the time that ents were created
+    pub fn order_by_created(self, direction: OrderDirection) -> Self {
+        self.order_by(Order::new(OrderBy::Created, direction))
+    }
+
+    /// Consumes query, producing a new query with an additional ordering
+    /// clause by the time that ents were last updated
+    pub fn order_by_last_updated(self, direction: OrderDirection) -> Self {
+        self.order_by(Order::new(OrderBy::LastUpdated, direction))
+    }
+
+    /// Consumes query, producing a new query with an additional ordering
+    /// clause by the value of the named field of ents, compared the same way
+    /// as [`Order::compare`]
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::{Ent, Field, OrderDirection, Query, UntypedEnt};
+    ///
+    /// let ents = vec![(1, 1.7), (2, 1.2), (3, 1.0)].into_iter().map(|(id, x)| {
+    ///     Ok(Box::from(UntypedEnt::from_collections(id, vec![Field::new("x", x)], vec![]))
+    ///         as Box<dyn Ent>)
+    /// });
+    ///
+    /// let query = Query::default().order_by_field("x", OrderDirection::Ascending);
+    /// let ids: Vec<usize> = query
+    ///     .order_and_page(ents)
+    ///     .unwrap()
+    ///     .iter()
+    ///     .map(|ent| ent.id())
+    ///     .collect();
+    /// assert_eq!(ids, vec![3, 2, 1]);
+    /// ```
+    pub fn order_by_field<S: Into<String>>(self, name: S, direction: OrderDirection) -> Self {
+        self.order_by(Order::new(OrderBy::Field(name.into()), direction))
+    }
+
+    /// Consumes query, producing a new query that returns at most `limit`
+    /// results
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
         self
     }
 
+    /// Consumes query, producing a new query that skips the first `offset`
+    /// results
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Consumes query, producing a new query whose results begin after the
+    /// ent at the cursor's position, which is expected to have been created
+    /// with the same ordering clauses as the query
+    pub fn after(mut self, cursor: Cursor) -> Self {
+        self.after = Some(cursor);
+        self
+    }
+
+    /// Orders the ents found by the query's filters using the query's
+    /// ordering clauses followed by their ids, and then skips any ents at or
+    /// before the query's cursor, skips the query's offset, and applies the
+    /// query's limit. If the query has none of these, the ents are returned
+    /// as they are.
+    ///
+    /// When limited, no more than twice the offset plus limit of ents are
+    /// held at once, so the ents can be provided lazily by databases once
+    /// they have found the ids of all ents that pass the query's filters.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::{Ent, Field, OrderDirection, Query, UntypedEnt};
+    ///
+    /// let ents = vec![(1, 2), (2, 3), (3, 2), (4, 1)].into_iter().map(|(id, x)| {
+    ///     Ok(Box::from(UntypedEnt::from_collections(id, vec![Field::new("x", x)], vec![]))
+    ///         as Box<dyn Ent>)
+    /// });
+    ///
+    /// let query = Query::default()
+    ///     .order_by_field("x", OrderDirection::Descending)
+    ///     .offset(1)
+    ///     .limit(2);
+    ///
+    /// let ids: Vec<usize> = query
+    ///     .order_and_page(ents)
+    ///     .unwrap()
+    ///     .iter()
+    ///     .map(|ent| ent.id())
+    ///     .collect();
+    /// assert_eq!(ids, vec![1, 3]);
+    /// ```
+    pub fn order_and_page<I: IntoIterator<Item = DatabaseResult<Box<dyn Ent>>>>(
+        &self,
+        ents: I,
+    ) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+        if self.order.is_empty() && !self.is_paged() {
+            return ents.into_iter().collect();
+        }
+
+        let keep = self.limit.map(|limit| limit.saturating_add(self.offset));
+        let sort = |ents: &mut Vec<(Cursor, Box<dyn Ent>)>| {
+            ents.sort_by(|(a, _), (b, _)| a.compare(b, &self.order));
+        };
+
+        // Only the ents that could be part of the page are held, discarding
+        // the rest whenever twice as many as needed have accumulated
+        let mut selected = Vec::new();
+        for ent in ents {
+            let ent = ent?;
+            let position = Cursor::new(&self.order, ent.as_ref());
+            if let Some(after) = self.after.as_ref() {
+                if position.compare(after, &self.order) != Ordering::Greater {
+                    continue;
+                }
+            }
+
+            selected.push((position, ent));
+            if let Some(keep) = keep {
+                if selected.len() > keep.saturating_mul(2) {
+                    sort(&mut selected);
+                    selected.truncate(keep);
+                }
+            }
+        }
+
+        sort(&mut selected);
+        Ok(selected
+            .into_iter()
+            .map(|(_, ent)| ent)
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    /// Counts the ents that would be returned by the query given the ids of
+    /// all ents that pass the query's filters, which skips ids at or before
+    /// the query's cursor, skips the query's offset, and applies the query's
+    /// limit. Stops consuming ids once the limit has been reached.
+    ///
+    /// Returns none without consuming any ids if the query has both a cursor
+    /// and ordering clauses, as the ents themselves are needed to determine
+    /// their positions relative to the cursor.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::Query;
+    ///
+    /// let query = Query::default().offset(1).limit(2);
+    /// assert_eq!(query.count_ids(vec![1, 2, 3, 4]), Some(2));
+    /// assert_eq!(query.count_ids(vec![1, 2]), Some(1));
+    /// ```
+    pub fn count_ids<I: IntoIterator<Item = Id>>(&self, ids: I) -> Option<usize> {
+        if self.after.is_some() && !self.order.is_empty() {
+            return None;
+        }
+
+        // Without ordering clauses, positions are determined by ids alone,
+        // where no cursor is positioned before all ids
+        let after = self.after.as_ref().map(Cursor::id);
+        let keep = self
+            .limit
+            .map(|limit| limit.saturating_add(self.offset))
+            .unwrap_or(usize::MAX);
+
+        Some(
+            ids.into_iter()
+                .filter(|id| after < Some(*id))
+                .take(keep)
+                .count()
+                .saturating_sub(self.offset),
+        )
+    }
+
     pub fn where_id<P: Into<TypedPredicate<Id>>>(self, p: P) -> Self {
         self.chain(Filter::where_id(p))
     }
@@ -87,4 +429,169 @@ impl Query {
     pub fn where_into_edge<S: Into<String>>(self, name: S) -> Self {
         self.chain(Filter::where_into_edge(name))
     }
+
+    pub fn where_into_inverse_edge<S: Into<String>>(self, name: S) -> Self {
+        self.chain(Filter::where_into_inverse_edge(name))
+    }
+
+    pub fn where_and<I: IntoIterator<Item = Filter>>(self, filters: I) -> Self {
+        self.chain(Filter::and(filters))
+    }
+
+    pub fn where_or<I: IntoIterator<Item = Filter>>(self, filters: I) -> Self {
+        self.chain(Filter::or(filters))
+    }
+
+    pub fn where_not<F: Into<Filter>>(self, filter: F) -> Self {
+        self.chain(Filter::not(filter))
+    }
+
+    /// Consumes query, producing a new query that also finds the ents found
+    /// by the other query
+    pub fn union<Q: Into<Query>>(self, other: Q) -> Self {
+        self.chain(Filter::union(other))
+    }
+
+    /// Consumes query, producing a new query that only finds the ents that
+    /// are also found by the other query
+    pub fn intersect<Q: Into<Query>>(self, other: Q) -> Self {
+        self.chain(Filter::intersect(other))
+    }
+
+    /// Consumes query, producing a new query that only finds the ents that
+    /// are not found by the other query
+    pub fn except<Q: Into<Query>>(self, other: Q) -> Self {
+        self.chain(Filter::except(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Predicate as P;
+
+    #[test]
+    fn query_should_be_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>(_: T) {}
+        assert_send_sync(Query::default().where_field("a", P::lambda(|_| true)));
+    }
+
+    #[test]
+    fn matches_should_check_filters_against_ent_alone() {
+        use crate::{Edge, Field, TypedPredicate as TP, UntypedEnt};
+
+        let ent = UntypedEnt::from_collections(
+            3,
+            vec![Field::new("a", 5)],
+            vec![Edge::new("friends", vec![1, 2])],
+        );
+
+        assert_eq!(Query::default().matches(&ent), Some(true));
+        assert_eq!(
+            Query::default()
+                .where_id(TP::equals(3))
+                .where_field("a", P::greater_than(4))
+                .matches(&ent),
+            Some(true)
+        );
+        assert_eq!(
+            Query::default()
+                .where_field("a", P::greater_than(5))
+                .matches(&ent),
+            Some(false)
+        );
+        assert_eq!(
+            Query::default()
+                .where_field("missing", P::equals(5))
+                .matches(&ent),
+            Some(false)
+        );
+
+        // Edges with ents depend on those ents, unless the ent fails
+        // another filter regardless
+        assert_eq!(
+            Query::default()
+                .where_edge("friends", Filter::where_id(TP::equals(999)))
+                .matches(&ent),
+            None
+        );
+        assert_eq!(
+            Query::default()
+                .where_edge("friends", Filter::where_id(TP::equals(999)))
+                .where_id(TP::equals(4))
+                .matches(&ent),
+            Some(false)
+        );
+        assert_eq!(
+            Query::default()
+                .where_edge("enemies", Filter::where_id(TP::equals(1)))
+                .matches(&ent),
+            Some(false)
+        );
+        assert_eq!(
+            Query::default()
+                .where_id(TP::equals(3))
+                .where_into_inverse_edge("friends")
+                .matches(&ent),
+            None
+        );
+
+        // Set operations check the ent against their own query
+        assert_eq!(
+            Query::default()
+                .except(Query::default().where_id(TP::equals(3)))
+                .matches(&ent),
+            Some(false)
+        );
+
+        // Unions pass ents that pass either the filters before them or
+        // their own query
+        let union = |id| {
+            Query::default()
+                .where_field("a", P::equals(1))
+                .union(Query::default().where_id(TP::equals(id)))
+        };
+        assert_eq!(union(4).matches(&ent), Some(false));
+        assert_eq!(union(3).matches(&ent), Some(true));
+        assert_eq!(
+            Query::default()
+                .union(Query::default().where_id(TP::equals(4)))
+                .matches(&ent),
+            Some(false)
+        );
+    }
+
+    #[cfg(feature = "serde-1")]
+    #[test]
+    fn query_should_serialize_and_deserialize() {
+        use crate::TypedPredicate as TP;
+
+        let query = Query::default()
+            .where_id(TP::greater_than(3) & !TP::equals(5))
+            .where_field("name", P::text_contains_any_case_insensitive(vec!["abc"]))
+            .where_edge(
+                "friends",
+                Filter::where_type(TP::equals(String::from("ent"))),
+            )
+            .order_by_field("age", OrderDirection::Descending)
+            .limit(5)
+            .offset(1)
+            .after("0000000000000003".parse().unwrap());
+
+        let json = serde_json::to_string(&query).unwrap();
+        let other: Query = serde_json::from_str(&json).unwrap();
+        assert_eq!(format!("{:?}", other), format!("{:?}", query));
+    }
+
+    #[cfg(feature = "serde-1")]
+    #[test]
+    fn query_should_fail_to_serialize_if_it_has_a_lambda() {
+        use crate::TypedPredicate as TP;
+
+        let query = Query::default().where_field("a", P::not(P::lambda(|_| true)));
+        assert!(serde_json::to_string(&query).is_err());
+
+        let filter = Filter::where_id(TP::lambda(|id| id > 3));
+        assert!(serde_json::to_string(&filter).is_err());
+    }
 }
@@ -0,0 +1,122 @@
+use crate::{Ent, IndexKey, Value};
+use std::cmp::Ordering;
+
+/// Represents the part of an ent used to order the results of a query
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub enum OrderBy {
+    /// Orders by the ent's id
+    Id,
+
+    /// Orders by the ent's creation timestamp
+    Created,
+
+    /// Orders by the ent's last updated timestamp
+    LastUpdated,
+
+    /// Orders by the value of an ent's field
+    Field(String),
+}
+
+/// Represents the direction in which the results of a query are ordered
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub enum OrderDirection {
+    /// Orders from smallest to largest
+    Ascending,
+
+    /// Orders from largest to smallest
+    Descending,
+}
+
+/// Represents a single clause used to order the results of a query
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Order {
+    by: OrderBy,
+    direction: OrderDirection,
+}
+
+impl Order {
+    /// Creates a new ordering clause
+    pub fn new(by: OrderBy, direction: OrderDirection) -> Self {
+        Self { by, direction }
+    }
+
+    /// Creates a new ordering clause from smallest to largest
+    pub fn ascending(by: OrderBy) -> Self {
+        Self::new(by, OrderDirection::Ascending)
+    }
+
+    /// Creates a new ordering clause from largest to smallest
+    pub fn descending(by: OrderBy) -> Self {
+        Self::new(by, OrderDirection::Descending)
+    }
+
+    /// The part of an ent being ordered
+    #[inline]
+    pub fn by(&self) -> &OrderBy {
+        &self.by
+    }
+
+    /// The direction of the ordering
+    #[inline]
+    pub fn direction(&self) -> OrderDirection {
+        self.direction
+    }
+
+    /// Compares two ents using this ordering clause
+    ///
+    /// Field values are compared the same way that values are compared by
+    /// predicates. Ents missing the field, or whose value cannot be compared
+    /// (such as lists and maps), are ordered after all other ents when
+    /// ascending and before all other ents when descending.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::{Field, Order, OrderBy, UntypedEnt};
+    /// use std::cmp::Ordering;
+    ///
+    /// let a = UntypedEnt::from_collections(1, vec![Field::new("x", 5u8)], vec![]);
+    /// let b = UntypedEnt::from_collections(2, vec![Field::new("x", 3i64)], vec![]);
+    /// let c = UntypedEnt::from_collections(3, vec![], vec![]);
+    ///
+    /// let order = Order::ascending(OrderBy::Field(String::from("x")));
+    /// assert_eq!(order.compare(&a, &b), Ordering::Greater);
+    /// assert_eq!(order.compare(&a, &c), Ordering::Less);
+    ///
+    /// let order = Order::descending(OrderBy::Id);
+    /// assert_eq!(order.compare(&a, &b), Ordering::Greater);
+    /// ```
+    pub fn compare(&self, a: &dyn Ent, b: &dyn Ent) -> Ordering {
+        self.compare_keys(self.key(a).as_ref(), self.key(b).as_ref())
+    }
+
+    /// Returns the key of the ent used by this ordering clause, or none if
+    /// the ent does not have a comparable value for it
+    pub fn key(&self, ent: &dyn Ent) -> Option<IndexKey> {
+        match &self.by {
+            OrderBy::Id => IndexKey::from_value(&Value::from(ent.id())),
+            OrderBy::Created => IndexKey::from_value(&Value::from(ent.created())),
+            OrderBy::LastUpdated => IndexKey::from_value(&Value::from(ent.last_updated())),
+            OrderBy::Field(name) => ent.field(name).and_then(|v| IndexKey::from_value(&v)),
+        }
+    }
+
+    /// Compares two keys produced by [`Order::key`] using this ordering
+    /// clause
+    pub fn compare_keys(&self, a: Option<&IndexKey>, b: Option<&IndexKey>) -> Ordering {
+        let ordering = match (a, b) {
+            (Some(a), Some(b)) => a.cmp(b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+
+        match self.direction {
+            OrderDirection::Ascending => ordering,
+            OrderDirection::Descending => ordering.reverse(),
+        }
+    }
+}
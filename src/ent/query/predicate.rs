@@ -4,13 +4,18 @@ use std::{
     collections::{HashMap, HashSet},
     marker::PhantomData,
     ops::RangeInclusive,
-    rc::Rc,
+    sync::Arc,
 };
 
 /// Represents an untyped predicate that can be used to inspect a value for
 /// some specified condition
+///
+/// With the `serde-1` feature, predicates can be serialized and deserialized
+/// so long as they do not contain a [`Predicate::Lambda`], which fails to
+/// serialize as functions cannot be represented as data.
 #[derive(Clone, Derivative)]
 #[derivative(Debug, PartialEq)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
 pub enum Predicate {
     /// Will always be true (not same as equals(true))
     ///
@@ -390,16 +395,23 @@ pub enum Predicate {
     ///
     /// ```
     /// use entity::{Predicate, Value, ValueLike};
-    /// use std::rc::Rc;
+    /// use std::sync::Arc;
     /// let v = 123.into_value();
     ///
-    /// let p = Predicate::Lambda(Rc::new(|v| v == &123.into_value()));
+    /// let p = Predicate::Lambda(Arc::new(|v| v == &123.into_value()));
     /// assert_eq!(p.check(&v), true);
     ///
-    /// let p = Predicate::Lambda(Rc::new(|v| v == &456.into_value()));
+    /// let p = Predicate::Lambda(Arc::new(|v| v == &456.into_value()));
     /// assert_eq!(p.check(&v), false);
     /// ```
-    Lambda(#[derivative(Debug = "ignore", PartialEq = "ignore")] Rc<dyn Fn(&Value) -> bool>),
+    #[cfg_attr(
+        feature = "serde-1",
+        serde(serialize_with = "serialize_lambda", skip_deserializing)
+    )]
+    Lambda(
+        #[derivative(Debug = "ignore", PartialEq = "ignore")]
+        Arc<dyn Fn(&Value) -> bool + Send + Sync>,
+    ),
 
     /// Will be true if checked value is less than the specified value
     ///
@@ -998,6 +1010,18 @@ pub enum Predicate {
     Xor(Vec<Predicate>),
 }
 
+/// Fails to serialize a [`Predicate::Lambda`] as there is no way to
+/// represent its function
+#[cfg(feature = "serde-1")]
+fn serialize_lambda<S: serde::Serializer>(
+    _: &Arc<dyn Fn(&Value) -> bool + Send + Sync>,
+    _: S,
+) -> Result<S::Ok, S::Error> {
+    Err(serde::ser::Error::custom(
+        "Lambda predicates cannot be serialized",
+    ))
+}
+
 impl Predicate {
     /// Checks if the predicate is satisfied by the given value
     pub fn check(&self, value: &Value) -> bool {
@@ -1180,8 +1204,8 @@ impl Predicate {
     /// assert_eq!(p.check(&V::from(4)), true);
     /// assert_eq!(p.check(&V::from(1)), false);
     /// ```
-    pub fn lambda<F: 'static + Fn(&Value) -> bool>(f: F) -> Self {
-        Self::Lambda(Rc::new(f))
+    pub fn lambda<F: 'static + Fn(&Value) -> bool + Send + Sync>(f: F) -> Self {
+        Self::Lambda(Arc::new(f))
     }
 
     /// Creates a new predicate for [`Predicate::And`]
@@ -2193,7 +2217,15 @@ impl std::ops::Not for Predicate {
 /// Represents a typed [`Predicate`], ensuring that only valid conditions are
 /// used for a given type
 #[derive(Clone, Debug, PartialEq)]
-pub struct TypedPredicate<T: ValueLike>(Predicate, PhantomData<T>);
+#[cfg_attr(
+    feature = "serde-1",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent, bound = "")
+)]
+pub struct TypedPredicate<T: ValueLike>(
+    Predicate,
+    #[cfg_attr(feature = "serde-1", serde(skip))] PhantomData<T>,
+);
 
 impl<T: ValueLike> PartialEq<Predicate> for TypedPredicate<T> {
     fn eq(&self, other: &Predicate) -> bool {
@@ -2255,8 +2287,8 @@ impl<T: ValueLike> TypedPredicate<T> {
     /// assert_eq!(p.check(4), true);
     /// assert_eq!(p.check(1), false);
     /// ```
-    pub fn lambda<F: 'static + Fn(T) -> bool>(f: F) -> Self {
-        Self::new(Predicate::Lambda(Rc::new(
+    pub fn lambda<F: 'static + Fn(T) -> bool + Send + Sync>(f: F) -> Self {
+        Self::new(Predicate::Lambda(Arc::new(
             move |v| match T::try_from_value(v.clone()) {
                 Ok(x) => f(x),
                 Err(_) => false,
@@ -3030,10 +3062,15 @@ impl TypedPredicate<String> {
 /// This is required due to limitations in Rust's blanket impl functionality,
 /// which will be resolved once specialization is available.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde-1",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent, bound = "")
+)]
 pub struct MapTypedPredicate<T: ValueLike, C: IntoIterator<Item = (String, T)> + ValueLike>(
     Predicate,
-    PhantomData<T>,
-    PhantomData<C>,
+    #[cfg_attr(feature = "serde-1", serde(skip))] PhantomData<T>,
+    #[cfg_attr(feature = "serde-1", serde(skip))] PhantomData<C>,
 );
 
 impl<T: ValueLike, C: IntoIterator<Item = (String, T)> + ValueLike> PartialEq<Predicate>
@@ -0,0 +1,1664 @@
+use crate::{
+    Cursor, Filter, Number, NumberType, Order, OrderBy, OrderDirection, Predicate, Primitive,
+    Query, Value,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
+
+/// Represents an error encountered when parsing a query, filter, or
+/// predicate from text, indicating the byte position within the text where
+/// parsing failed
+#[derive(Clone, Debug, PartialEq, Eq, derive_more::Display, derive_more::Error)]
+#[display(fmt = "{} at position {}", message, position)]
+pub struct ParseQueryError {
+    position: usize,
+    message: String,
+}
+
+impl ParseQueryError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+
+    /// The byte position within the text where parsing failed
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The reason that parsing failed
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl FromStr for Query {
+    type Err = ParseQueryError;
+
+    /// Parses a query from text, which is made up of filters applied in
+    /// order followed by optional ordering clauses, limit, offset, and
+    /// cursor:
+    ///
+    /// ```text
+    /// query  := [filter (("and" | "|") filter)*]
+    ///           ["order" "by" order ("," order)*]
+    ///           ["limit" int] ["offset" int] ["after" string]
+    /// order  := ("id" | "created" | "last_updated" | "field" "(" name ")")
+    ///           ["asc" | "desc"]
+    /// filter := ("id" | "type" | "created" | "last_updated") predicate
+    ///         | ("assoc_created" | "assoc_data") predicate
+    ///         | "field" "(" name ")" predicate
+    ///         | "edge" "(" name ")" "->" filter
+    ///         | ("into_edge" | "into_inverse_edge") "(" name ")"
+    ///         | ("union" | "intersect" | "except") "(" query ")"
+    ///         | "not" filter
+    ///         | "(" filter (("and" | "or") filter)* ")"
+    ///         | ("and" | "or") "(" [filter ("," filter)*] ")"
+    /// ```
+    ///
+    /// Filters joined by `and` or `|` at the top level of a query are applied
+    /// one after another, with `|` conventionally separating filters such as
+    /// `into_edge` and `union` that change which ents are being filtered.
+    /// Filters combined within parentheses form a single filter, where `and`
+    /// and `or` cannot be mixed without further parentheses.
+    ///
+    /// Predicates are written as an operator followed by its value, such as
+    /// `= 3`, `>= 1.5`, `in [1, 2]`, `in 1..=5`, `contains "a"`,
+    /// `has_key "a" where > 3`, `starts_with "a"`, or `is_none`, where text
+    /// operators can be followed by `ignore_case`. They can be negated with
+    /// `not` and combined within parentheses such as `(> 1 and < 5)`.
+    ///
+    /// Unsuffixed integers are ids for `id`, timestamps for `created` and
+    /// `last_updated`, and `i32` otherwise, while unsuffixed decimals are
+    /// `f64`; other types of numbers are suffixed like `5u8` or `1.5f32`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::{Filter, Predicate as P, Query, TypedPredicate as TP};
+    ///
+    /// let query: Query = r#"
+    ///     type = "app::User" and field(age) > 30
+    ///     | edge(friends) -> field(name) starts_with "A" ignore_case
+    ///     limit 10
+    /// "#.parse().unwrap();
+    ///
+    /// assert_eq!(query.get_limit(), Some(10));
+    /// assert_eq!(query.to_string(), Query::default()
+    ///     .where_type(TP::equals(String::from("app::User")))
+    ///     .where_field("age", P::greater_than(30))
+    ///     .where_edge("friends", Filter::where_field(
+    ///         "name",
+    ///         P::text_starts_with_case_insensitive("A"),
+    ///     ))
+    ///     .limit(10)
+    ///     .to_string());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s)?;
+        let query = parser.parse_query()?;
+        parser.finish()?;
+        Ok(query)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = ParseQueryError;
+
+    /// Parses a single filter from text using the syntax of a query's
+    /// filters, as described by [`Query::from_str`]
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::Filter;
+    ///
+    /// let filter: Filter = "edge(friends) -> id in [1, 2]".parse().unwrap();
+    /// assert_eq!(filter.to_string(), "edge(friends) -> id in [1, 2]");
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s)?;
+        let filter = parser.parse_filter()?;
+        parser.finish()?;
+        Ok(filter)
+    }
+}
+
+impl FromStr for Predicate {
+    type Err = ParseQueryError;
+
+    /// Parses a single predicate from text using the syntax of a field's
+    /// predicate, as described by [`Query::from_str`]
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::{Predicate as P, Value as V};
+    ///
+    /// let p: P = "(> 3 and not = 5)".parse().unwrap();
+    /// assert_eq!(p.check(&V::from(4)), true);
+    /// assert_eq!(p.check(&V::from(5)), false);
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s)?;
+        let predicate = parser.parse_predicate(NumberType::I32)?;
+        parser.finish()?;
+        Ok(predicate)
+    }
+}
+
+impl fmt::Display for Query {
+    /// Writes the query as text that can be parsed back into the same query
+    /// so long as it does not contain any [`Predicate::Lambda`]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut clauses = Vec::new();
+
+        let mut filters = String::new();
+        for (i, filter) in self.filters().iter().enumerate() {
+            if i > 0 {
+                // Into edge filters and set operations change which ents are
+                // being filtered, so they are separated like the stages of a
+                // pipeline
+                let is_pipeline =
+                    is_pipeline_stage(filter) || is_pipeline_stage(&self.filters()[i - 1]);
+                filters.push_str(if is_pipeline { " | " } else { " and " });
+            }
+            filters.push_str(&filter.to_string());
+        }
+        if !filters.is_empty() {
+            clauses.push(filters);
+        }
+
+        if !self.order().is_empty() {
+            let order: Vec<String> = self
+                .order()
+                .iter()
+                .map(|order| {
+                    let by = match order.by() {
+                        OrderBy::Id => String::from("id"),
+                        OrderBy::Created => String::from("created"),
+                        OrderBy::LastUpdated => String::from("last_updated"),
+                        OrderBy::Field(name) => format!("field({})", Name(name)),
+                    };
+                    match order.direction() {
+                        OrderDirection::Ascending => format!("{} asc", by),
+                        OrderDirection::Descending => format!("{} desc", by),
+                    }
+                })
+                .collect();
+            clauses.push(format!("order by {}", order.join(", ")));
+        }
+
+        if let Some(limit) = self.get_limit() {
+            clauses.push(format!("limit {}", limit));
+        }
+
+        if self.get_offset() > 0 {
+            clauses.push(format!("offset {}", self.get_offset()));
+        }
+
+        if let Some(cursor) = self.get_after() {
+            clauses.push(format!("after \"{}\"", cursor));
+        }
+
+        write!(f, "{}", clauses.join(" "))
+    }
+}
+
+impl fmt::Display for Filter {
+    /// Writes the filter as text that can be parsed back into the same
+    /// filter so long as it does not contain any [`Predicate::Lambda`]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Id(p) => write!(f, "id {}", Text(p.as_untyped(), NumberType::Usize)),
+            Self::Type(p) => write!(f, "type {}", Text(p.as_untyped(), NumberType::I32)),
+            Self::Created(p) => write!(f, "created {}", Text(p.as_untyped(), NumberType::U64)),
+            Self::LastUpdated(p) => {
+                write!(f, "last_updated {}", Text(p.as_untyped(), NumberType::U64))
+            }
+            Self::Field(name, p) => {
+                write!(f, "field({}) {}", Name(name), Text(p, NumberType::I32))
+            }
+            Self::Edge(name, filter) => write!(f, "edge({}) -> {}", Name(name), filter),
+            Self::AssocCreated(p) => {
+                write!(f, "assoc_created {}", Text(p.as_untyped(), NumberType::U64))
+            }
+            Self::AssocData(p) => write!(f, "assoc_data {}", Text(p, NumberType::I32)),
+            Self::IntoEdge(name) => write!(f, "into_edge({})", Name(name)),
+            Self::IntoInverseEdge(name) => write!(f, "into_inverse_edge({})", Name(name)),
+            Self::Union(query) => write!(f, "union({})", query),
+            Self::Intersect(query) => write!(f, "intersect({})", query),
+            Self::Except(query) => write!(f, "except({})", query),
+            Self::And(filters) => write_combination(f, "and", filters),
+            Self::Or(filters) => write_combination(f, "or", filters),
+            Self::Not(filter) => write!(f, "not {}", filter),
+        }
+    }
+}
+
+/// Returns true if the filter changes which ents are being filtered when
+/// applied at the top level of a query
+fn is_pipeline_stage(filter: &Filter) -> bool {
+    matches!(
+        filter,
+        Filter::IntoEdge(_)
+            | Filter::IntoInverseEdge(_)
+            | Filter::Union(_)
+            | Filter::Intersect(_)
+            | Filter::Except(_)
+    )
+}
+
+/// Writes items combined by the operator within parentheses, or as a call
+/// to the operator if there are too few items to be combined
+fn write_combination<T: fmt::Display>(
+    f: &mut fmt::Formatter<'_>,
+    op: &str,
+    items: &[T],
+) -> fmt::Result {
+    let items: Vec<String> = items.iter().map(ToString::to_string).collect();
+    if items.len() > 1 {
+        write!(f, "({})", items.join(&format!(" {} ", op)))
+    } else {
+        write!(f, "{}({})", op, items.join(", "))
+    }
+}
+
+impl fmt::Display for Predicate {
+    /// Writes the predicate as text that can be parsed back into the same
+    /// predicate so long as it does not contain any [`Predicate::Lambda`],
+    /// which is written as `lambda`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Text(self, NumberType::I32))
+    }
+}
+
+/// Writes the name of a field or edge as an identifier if possible,
+/// otherwise as a quoted string
+struct Name<'a>(&'a str);
+
+impl fmt::Display for Name<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut chars = self.0.chars();
+        let is_ident = chars
+            .next()
+            .map_or(false, |c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+        if is_ident {
+            write!(f, "{}", self.0)
+        } else {
+            write!(f, "{}", Text(self.0, NumberType::I32))
+        }
+    }
+}
+
+/// Writes some part of a predicate as text, where numbers of the given type
+/// are written without a suffix
+struct Text<'a, T: ?Sized>(&'a T, NumberType);
+
+impl fmt::Display for Text<'_, str> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for c in self.0.chars() {
+            write_escaped(f, c, '"')?;
+        }
+        write!(f, "\"")
+    }
+}
+
+impl fmt::Display for Text<'_, Number> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Decimals are always read as f64 when unsuffixed, which is why
+        // they are never written with an f64 suffix
+        let ty = self.0.to_type();
+        let suffix = if ty == self.1 || ty == NumberType::F64 {
+            String::new()
+        } else {
+            ty.to_string()
+        };
+
+        match self.0 {
+            // Suffixes are separated from non-finite floats as they would
+            // otherwise be read as part of the word
+            Number::F32(x) if !x.is_finite() && !suffix.is_empty() => {
+                write!(f, "{:?}_{}", x, suffix)
+            }
+            Number::F64(x) if !x.is_finite() && !suffix.is_empty() => {
+                write!(f, "{:?}_{}", x, suffix)
+            }
+            Number::F32(x) => write!(f, "{:?}{}", x, suffix),
+            Number::F64(x) => write!(f, "{:?}{}", x, suffix),
+            Number::I128(x) => write!(f, "{}{}", x, suffix),
+            Number::I16(x) => write!(f, "{}{}", x, suffix),
+            Number::I32(x) => write!(f, "{}{}", x, suffix),
+            Number::I64(x) => write!(f, "{}{}", x, suffix),
+            Number::I8(x) => write!(f, "{}{}", x, suffix),
+            Number::Isize(x) => write!(f, "{}{}", x, suffix),
+            Number::U128(x) => write!(f, "{}{}", x, suffix),
+            Number::U16(x) => write!(f, "{}{}", x, suffix),
+            Number::U32(x) => write!(f, "{}{}", x, suffix),
+            Number::U64(x) => write!(f, "{}{}", x, suffix),
+            Number::U8(x) => write!(f, "{}{}", x, suffix),
+            Number::Usize(x) => write!(f, "{}{}", x, suffix),
+        }
+    }
+}
+
+impl fmt::Display for Text<'_, Value> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Value::List(x) => write!(f, "{}", Text(x.as_slice(), self.1)),
+            Value::Map(x) => {
+                // Maps are written with sorted keys so the same map is always
+                // written the same way
+                let mut entries: Vec<_> = x.iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.into_iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", Text(key.as_str(), self.1), Text(value, self.1))?;
+                }
+                write!(f, "}}")
+            }
+            Value::Optional(None) => write!(f, "none"),
+            Value::Optional(Some(x)) => write!(f, "some({})", Text(x.as_ref(), self.1)),
+            Value::Primitive(Primitive::Bool(x)) => write!(f, "{}", x),
+            Value::Primitive(Primitive::Char(x)) => {
+                write!(f, "'")?;
+                write_escaped(f, *x, '\'')?;
+                write!(f, "'")
+            }
+            Value::Primitive(Primitive::Number(x)) => write!(f, "{}", Text(x, self.1)),
+            Value::Primitive(Primitive::Unit) => write!(f, "()"),
+            Value::Text(x) => write!(f, "{}", Text(x.as_str(), self.1)),
+        }
+    }
+}
+
+impl fmt::Display for Text<'_, [Value]> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values: Vec<String> = self.0.iter().map(|x| Text(x, self.1).to_string()).collect();
+        write!(f, "[{}]", values.join(", "))
+    }
+}
+
+impl fmt::Display for Text<'_, [String]> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values: Vec<String> = self
+            .0
+            .iter()
+            .map(|x| Text(x.as_str(), self.1).to_string())
+            .collect();
+        write!(f, "[{}]", values.join(", "))
+    }
+}
+
+impl fmt::Display for Text<'_, HashSet<Value>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Sets are written in sorted order so the same set is always written
+        // the same way
+        let mut values: Vec<String> = self.0.iter().map(|x| Text(x, self.1).to_string()).collect();
+        values.sort();
+        write!(f, "[{}]", values.join(", "))
+    }
+}
+
+impl fmt::Display for Text<'_, HashSet<String>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut values: Vec<&String> = self.0.iter().collect();
+        values.sort();
+        let values: Vec<String> = values
+            .into_iter()
+            .map(|x| Text(x.as_str(), self.1).to_string())
+            .collect();
+        write!(f, "[{}]", values.join(", "))
+    }
+}
+
+impl fmt::Display for Text<'_, Predicate> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ty = self.1;
+        let combine = |f: &mut fmt::Formatter<'_>, op: &str, ps: &[Predicate]| {
+            let ps: Vec<Text<'_, Predicate>> = ps.iter().map(|p| Text(p, ty)).collect();
+            write_combination(f, op, &ps)
+        };
+
+        match self.0 {
+            Predicate::Always => write!(f, "always"),
+            Predicate::Never => write!(f, "never"),
+            Predicate::And(x) => combine(f, "and", x),
+            Predicate::Any(x) => write!(f, "any {}", Text(x.as_ref(), ty)),
+            Predicate::Contains(x) => write!(f, "contains {}", Text(x, ty)),
+            Predicate::ContainsAll(x) => write!(f, "contains_all {}", Text(x.as_slice(), ty)),
+            Predicate::ContainsAny(x) => write!(f, "contains_any {}", Text(x.as_slice(), ty)),
+            Predicate::Equals(x) => write!(f, "= {}", Text(x, ty)),
+            Predicate::GreaterThan(x) => write!(f, "> {}", Text(x, ty)),
+            Predicate::GreaterThanOrEquals(x) => write!(f, ">= {}", Text(x, ty)),
+            Predicate::HasKey(k) => write!(f, "has_key {}", Text(k.as_str(), ty)),
+            Predicate::HasKeyWhereValue(k, p) => write!(
+                f,
+                "has_key {} where {}",
+                Text(k.as_str(), ty),
+                Text(p.as_ref(), ty)
+            ),
+            Predicate::InRange(x) => {
+                write!(f, "in {}..={}", Text(x.start(), ty), Text(x.end(), ty))
+            }
+            Predicate::InSet(x) => write!(f, "in {}", Text(x, ty)),
+            Predicate::IsNone => write!(f, "is_none"),
+            Predicate::Lambda(_) => write!(f, "lambda"),
+            Predicate::LessThan(x) => write!(f, "< {}", Text(x, ty)),
+            Predicate::LessThanOrEquals(x) => write!(f, "<= {}", Text(x, ty)),
+            Predicate::Not(x) => write!(f, "not {}", Text(x.as_ref(), ty)),
+            Predicate::NotEquals(x) => write!(f, "!= {}", Text(x, ty)),
+            Predicate::NotInRange(x) => {
+                write!(f, "not_in {}..={}", Text(x.start(), ty), Text(x.end(), ty))
+            }
+            Predicate::NotInSet(x) => write!(f, "not_in {}", Text(x, ty)),
+            Predicate::NotNoneAnd(x) => write!(f, "not_none_and {}", Text(x.as_ref(), ty)),
+            Predicate::NoneOr(x) => write!(f, "none_or {}", Text(x.as_ref(), ty)),
+            Predicate::Or(x) => combine(f, "or", x),
+            Predicate::TextContainedIn(x) => {
+                write!(f, "contained_in {}", Text(x.as_str(), ty))
+            }
+            Predicate::TextContainedInCaseInsensitive(x) => {
+                write!(f, "contained_in {} ignore_case", Text(x.as_str(), ty))
+            }
+            Predicate::TextContainsAll(x) => {
+                write!(f, "text_contains_all {}", Text(x.as_slice(), ty))
+            }
+            Predicate::TextContainsAllCaseInsensitive(x) => write!(
+                f,
+                "text_contains_all {} ignore_case",
+                Text(x.as_slice(), ty)
+            ),
+            Predicate::TextContainsAny(x) => {
+                write!(f, "text_contains_any {}", Text(x.as_slice(), ty))
+            }
+            Predicate::TextContainsAnyCaseInsensitive(x) => write!(
+                f,
+                "text_contains_any {} ignore_case",
+                Text(x.as_slice(), ty)
+            ),
+            Predicate::TextEndsWith(x) => write!(f, "ends_with {}", Text(x.as_str(), ty)),
+            Predicate::TextEndsWithCaseInsensitive(x) => {
+                write!(f, "ends_with {} ignore_case", Text(x.as_str(), ty))
+            }
+            Predicate::TextEndsWithAny(x) => {
+                write!(f, "ends_with_any {}", Text(x.as_slice(), ty))
+            }
+            Predicate::TextEndsWithAnyCaseInsensitive(x) => {
+                write!(f, "ends_with_any {} ignore_case", Text(x.as_slice(), ty))
+            }
+            Predicate::TextEqualsCaseInsensitive(x) => {
+                write!(f, "= {} ignore_case", Text(x.as_str(), ty))
+            }
+            Predicate::TextNotEqualsCaseInsensitive(x) => {
+                write!(f, "!= {} ignore_case", Text(x.as_str(), ty))
+            }
+            Predicate::TextInSetCaseInsensitive(x) => {
+                write!(f, "in {} ignore_case", Text(x, ty))
+            }
+            Predicate::TextStartsWith(x) => write!(f, "starts_with {}", Text(x.as_str(), ty)),
+            Predicate::TextStartsWithCaseInsensitive(x) => {
+                write!(f, "starts_with {} ignore_case", Text(x.as_str(), ty))
+            }
+            Predicate::TextStartsWithAny(x) => {
+                write!(f, "starts_with_any {}", Text(x.as_slice(), ty))
+            }
+            Predicate::TextStartsWithAnyCaseInsensitive(x) => {
+                write!(f, "starts_with_any {} ignore_case", Text(x.as_slice(), ty))
+            }
+            Predicate::Xor(x) => combine(f, "xor", x),
+        }
+    }
+}
+
+/// Writes a character of a string or character literal, escaping it if
+/// needed
+fn write_escaped(f: &mut fmt::Formatter<'_>, c: char, quote: char) -> fmt::Result {
+    match c {
+        '\\' => write!(f, "\\\\"),
+        '\n' => write!(f, "\\n"),
+        '\r' => write!(f, "\\r"),
+        '\t' => write!(f, "\\t"),
+        '\0' => write!(f, "\\0"),
+        c if c == quote => write!(f, "\\{}", c),
+        c if c.is_control() => write!(f, "\\u{{{:x}}}", c as u32),
+        c => write!(f, "{}", c),
+    }
+}
+
+/// Represents a single token within text being parsed
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Text(String),
+    Char(char),
+    Number(String),
+    Symbol(&'static str),
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ident(x) => write!(f, "`{}`", x),
+            Self::Text(x) => write!(f, "{}", Text(x.as_str(), NumberType::I32)),
+            Self::Char(_) => write!(f, "character"),
+            Self::Number(x) => write!(f, "number {}", x),
+            Self::Symbol(x) => write!(f, "`{}`", x),
+            Self::End => write!(f, "end of input"),
+        }
+    }
+}
+
+/// Symbols in order of precedence, where longer symbols come before any
+/// symbols that they start with
+const SYMBOLS: &[&str] = &[
+    "->", "!=", ">=", "<=", "..=", "(", ")", "[", "]", "{", "}", ",", ":", "=", ">", "<", "|", "-",
+];
+
+/// Splits text into tokens alongside their byte positions, ending with
+/// [`Token::End`]
+fn tokenize(s: &str) -> Result<Vec<(usize, Token)>, ParseQueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut ident = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if !c.is_ascii_alphanumeric() && c != '_' {
+                    break;
+                }
+                ident.push(c);
+                chars.next();
+            }
+            tokens.push((start, Token::Ident(ident)));
+        } else if c.is_ascii_digit() {
+            let rest = &s[start..];
+            let len = number_len(rest);
+            tokens.push((start, Token::Number(rest[..len].to_string())));
+            while chars.peek().map_or(false, |(i, _)| *i < start + len) {
+                chars.next();
+            }
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, x)) if x == c => break,
+                    Some((i, '\\')) => text.push(unescape(&mut chars, i)?),
+                    Some((_, x)) => text.push(x),
+                    None => {
+                        return Err(ParseQueryError::new(start, "Unterminated literal"));
+                    }
+                }
+            }
+
+            if c == '"' {
+                tokens.push((start, Token::Text(text)));
+            } else {
+                let mut it = text.chars();
+                match (it.next(), it.next()) {
+                    (Some(x), None) => tokens.push((start, Token::Char(x))),
+                    _ => {
+                        return Err(ParseQueryError::new(
+                            start,
+                            "Character literal must contain exactly one character",
+                        ))
+                    }
+                }
+            }
+        } else {
+            let rest = &s[start..];
+            let symbol = SYMBOLS
+                .iter()
+                .find(|symbol| rest.starts_with(*symbol))
+                .ok_or_else(|| {
+                    ParseQueryError::new(start, format!("Unexpected character {:?}", c))
+                })?;
+            tokens.push((start, Token::Symbol(symbol)));
+            for _ in 0..symbol.len() {
+                chars.next();
+            }
+        }
+    }
+
+    tokens.push((s.len(), Token::End));
+    Ok(tokens)
+}
+
+/// Returns the byte length of the number at the start of the text, which
+/// includes any fraction, exponent, and type suffix
+fn number_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let digits = |i: usize| bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let is_digit = |i: usize| bytes.get(i).map_or(false, u8::is_ascii_digit);
+
+    let mut len = digits(0);
+
+    // A dot is only part of the number if followed by a digit, which allows
+    // ranges such as 1..=5
+    if bytes.get(len) == Some(&b'.') && is_digit(len + 1) {
+        len += 1 + digits(len + 1);
+    }
+
+    if matches!(bytes.get(len), Some(b'e') | Some(b'E')) {
+        if is_digit(len + 1) {
+            len += 1 + digits(len + 1);
+        } else if matches!(bytes.get(len + 1), Some(b'+') | Some(b'-')) && is_digit(len + 2) {
+            len += 2 + digits(len + 2);
+        }
+    }
+
+    len + bytes[len..]
+        .iter()
+        .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+        .count()
+}
+
+/// Reads the escape sequence following a backslash at the given position
+fn unescape(
+    chars: &mut std::iter::Peekable<std::str::CharIndices<'_>>,
+    position: usize,
+) -> Result<char, ParseQueryError> {
+    let invalid = || ParseQueryError::new(position, "Invalid escape sequence");
+
+    match chars.next().map(|(_, c)| c) {
+        Some('\\') => Ok('\\'),
+        Some('"') => Ok('"'),
+        Some('\'') => Ok('\''),
+        Some('n') => Ok('\n'),
+        Some('r') => Ok('\r'),
+        Some('t') => Ok('\t'),
+        Some('0') => Ok('\0'),
+        Some('u') => {
+            if chars.next().map(|(_, c)| c) != Some('{') {
+                return Err(invalid());
+            }
+
+            let mut hex = String::new();
+            loop {
+                match chars.next().map(|(_, c)| c) {
+                    Some('}') => break,
+                    Some(c) if c.is_ascii_hexdigit() => hex.push(c),
+                    _ => return Err(invalid()),
+                }
+            }
+
+            u32::from_str_radix(&hex, 16)
+                .ok()
+                .and_then(std::char::from_u32)
+                .ok_or_else(invalid)
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Parses a number from its digits, which may be preceded by a minus sign,
+/// and an optional type suffix. Unsuffixed integers are parsed as the
+/// default type if they fit, while unsuffixed decimals are parsed as `f64`.
+fn number_from_digits(
+    digits: &str,
+    suffix: Option<NumberType>,
+    default: NumberType,
+) -> Option<Number> {
+    fn parse_as(digits: &str, ty: NumberType) -> Option<Number> {
+        match ty {
+            NumberType::F32 => digits.parse().ok().map(Number::F32),
+            NumberType::F64 => digits.parse().ok().map(Number::F64),
+            NumberType::I128 => digits.parse().ok().map(Number::I128),
+            NumberType::I16 => digits.parse().ok().map(Number::I16),
+            NumberType::I32 => digits.parse().ok().map(Number::I32),
+            NumberType::I64 => digits.parse().ok().map(Number::I64),
+            NumberType::I8 => digits.parse().ok().map(Number::I8),
+            NumberType::Isize => digits.parse().ok().map(Number::Isize),
+            NumberType::U128 => digits.parse().ok().map(Number::U128),
+            NumberType::U16 => digits.parse().ok().map(Number::U16),
+            NumberType::U32 => digits.parse().ok().map(Number::U32),
+            NumberType::U64 => digits.parse().ok().map(Number::U64),
+            NumberType::U8 => digits.parse().ok().map(Number::U8),
+            NumberType::Usize => digits.parse().ok().map(Number::Usize),
+        }
+    }
+
+    // Exponents and non-finite words such as inf are only valid for decimals
+    let is_decimal = digits.contains(|c: char| c == '.' || c.is_ascii_alphabetic());
+    match suffix {
+        Some(ty) => parse_as(digits, ty),
+        None if is_decimal => parse_as(digits, NumberType::F64),
+        None => [default, NumberType::I64, NumberType::I128, NumberType::U128]
+            .iter()
+            .find_map(|ty| parse_as(digits, *ty)),
+    }
+}
+
+/// Represents a parser of queries, filters, and predicates over the tokens
+/// of some text
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+    depth: usize,
+}
+
+impl Parser {
+    /// Maximum depth that filters, predicates, and values can be nested
+    /// within each other, which keeps deeply nested input from exhausting
+    /// the stack
+    const MAX_DEPTH: usize = 128;
+
+    fn new(s: &str) -> Result<Self, ParseQueryError> {
+        Ok(Self {
+            tokens: tokenize(s)?,
+            index: 0,
+            depth: 0,
+        })
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.index].1
+    }
+
+    fn position(&self) -> usize {
+        self.tokens[self.index].0
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.index].1.clone();
+        if self.index + 1 < self.tokens.len() {
+            self.index += 1;
+        }
+        token
+    }
+
+    fn error<T>(&self, position: usize, message: impl Into<String>) -> Result<T, ParseQueryError> {
+        Err(ParseQueryError::new(position, message))
+    }
+
+    /// Fails with an error about the next token not being what is expected
+    fn unexpected<T>(&self, expected: &str) -> Result<T, ParseQueryError> {
+        self.error(
+            self.position(),
+            format!("Expected {}, found {}", expected, self.peek()),
+        )
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Token::Ident(x) if x == keyword)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let is_keyword = self.is_keyword(keyword);
+        if is_keyword {
+            self.advance();
+        }
+        is_keyword
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let is_symbol = matches!(self.peek(), Token::Symbol(x) if *x == symbol);
+        if is_symbol {
+            self.advance();
+        }
+        is_symbol
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), ParseQueryError> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            self.unexpected(&format!("`{}`", keyword))
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), ParseQueryError> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            self.unexpected(&format!("`{}`", symbol))
+        }
+    }
+
+    /// Runs the function one level deeper within the input, failing if the
+    /// input is nested deeper than the maximum depth
+    fn nested<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, ParseQueryError>,
+    ) -> Result<T, ParseQueryError> {
+        if self.depth >= Self::MAX_DEPTH {
+            return self.error(
+                self.position(),
+                format!("Nesting exceeds maximum depth of {}", Self::MAX_DEPTH),
+            );
+        }
+
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn finish(&self) -> Result<(), ParseQueryError> {
+        match self.peek() {
+            Token::End => Ok(()),
+            _ => self.unexpected("end of input"),
+        }
+    }
+
+    fn parse_query(&mut self) -> Result<Query, ParseQueryError> {
+        let mut query = Query::default();
+
+        let has_filters = !matches!(self.peek(), Token::End | Token::Symbol(")"))
+            && !["order", "limit", "offset", "after"]
+                .iter()
+                .any(|keyword| self.is_keyword(keyword));
+        if has_filters {
+            loop {
+                query.add_filter(self.parse_filter()?);
+                if self.is_keyword("or") {
+                    return self.error(
+                        self.position(),
+                        "Filters can only be combined with `or` within parentheses",
+                    );
+                }
+                if !self.eat_keyword("and") && !self.eat_symbol("|") {
+                    break;
+                }
+            }
+        }
+
+        if self.eat_keyword("order") {
+            self.expect_keyword("by")?;
+            loop {
+                let by = match self.peek() {
+                    Token::Ident(x) if x == "id" => OrderBy::Id,
+                    Token::Ident(x) if x == "created" => OrderBy::Created,
+                    Token::Ident(x) if x == "last_updated" => OrderBy::LastUpdated,
+                    Token::Ident(x) if x == "field" => {
+                        self.advance();
+                        OrderBy::Field(self.parse_name()?)
+                    }
+                    _ => return self.unexpected("ordering clause"),
+                };
+                if !matches!(by, OrderBy::Field(_)) {
+                    self.advance();
+                }
+
+                let direction = if self.eat_keyword("desc") {
+                    OrderDirection::Descending
+                } else {
+                    self.eat_keyword("asc");
+                    OrderDirection::Ascending
+                };
+                query.add_order(Order::new(by, direction));
+
+                if !self.eat_symbol(",") {
+                    break;
+                }
+            }
+        }
+
+        if self.eat_keyword("limit") {
+            query = query.limit(self.parse_count()?);
+        }
+
+        if self.eat_keyword("offset") {
+            query = query.offset(self.parse_count()?);
+        }
+
+        if self.eat_keyword("after") {
+            let position = self.position();
+            let cursor = self.parse_text()?;
+            match cursor.parse::<Cursor>() {
+                Ok(cursor) => query = query.after(cursor),
+                Err(x) => return self.error(position, x.to_string()),
+            }
+        }
+
+        Ok(query)
+    }
+
+    fn parse_filter(&mut self) -> Result<Filter, ParseQueryError> {
+        self.nested(Self::parse_filter_body)
+    }
+
+    fn parse_filter_body(&mut self) -> Result<Filter, ParseQueryError> {
+        let filter = match self.peek() {
+            Token::Ident(x) if x == "id" => {
+                self.advance();
+                Filter::Id(self.parse_predicate(NumberType::Usize)?.into())
+            }
+            Token::Ident(x) if x == "type" => {
+                self.advance();
+                Filter::Type(self.parse_predicate(NumberType::I32)?.into())
+            }
+            Token::Ident(x) if x == "created" => {
+                self.advance();
+                Filter::Created(self.parse_predicate(NumberType::U64)?.into())
+            }
+            Token::Ident(x) if x == "last_updated" => {
+                self.advance();
+                Filter::LastUpdated(self.parse_predicate(NumberType::U64)?.into())
+            }
+            Token::Ident(x) if x == "field" => {
+                self.advance();
+                let name = self.parse_name()?;
+                Filter::Field(name, self.parse_predicate(NumberType::I32)?)
+            }
+            Token::Ident(x) if x == "edge" => {
+                self.advance();
+                let name = self.parse_name()?;
+                self.expect_symbol("->")?;
+                Filter::Edge(name, Box::new(self.parse_filter()?))
+            }
+            Token::Ident(x) if x == "assoc_created" => {
+                self.advance();
+                Filter::AssocCreated(self.parse_predicate(NumberType::U64)?.into())
+            }
+            Token::Ident(x) if x == "assoc_data" => {
+                self.advance();
+                Filter::AssocData(self.parse_predicate(NumberType::I32)?)
+            }
+            Token::Ident(x) if x == "into_edge" => {
+                self.advance();
+                Filter::IntoEdge(self.parse_name()?)
+            }
+            Token::Ident(x) if x == "into_inverse_edge" => {
+                self.advance();
+                Filter::IntoInverseEdge(self.parse_name()?)
+            }
+            Token::Ident(x) if x == "union" || x == "intersect" || x == "except" => {
+                let op = x.to_string();
+                self.advance();
+                self.expect_symbol("(")?;
+                let query = Box::new(self.parse_query()?);
+                self.expect_symbol(")")?;
+                match op.as_str() {
+                    "union" => Filter::Union(query),
+                    "intersect" => Filter::Intersect(query),
+                    _ => Filter::Except(query),
+                }
+            }
+            Token::Ident(x) if x == "not" => {
+                self.advance();
+                Filter::Not(Box::new(self.parse_filter()?))
+            }
+            Token::Ident(x) if x == "and" || x == "or" => {
+                let is_and = x == "and";
+                self.advance();
+                self.expect_symbol("(")?;
+                let filters = self.parse_sequence(")", Self::parse_filter)?;
+                if is_and {
+                    Filter::And(filters)
+                } else {
+                    Filter::Or(filters)
+                }
+            }
+            Token::Symbol("(") => {
+                self.advance();
+                match self.parse_combination(&["and", "or"], Self::parse_filter)? {
+                    (Some(op), filters) if op == "and" => Filter::And(filters),
+                    (Some(_), filters) => Filter::Or(filters),
+                    (None, mut filters) => filters.remove(0),
+                }
+            }
+            _ => return self.unexpected("filter"),
+        };
+
+        Ok(filter)
+    }
+
+    /// Parses the parenthesized name of a field or edge
+    fn parse_name(&mut self) -> Result<String, ParseQueryError> {
+        self.expect_symbol("(")?;
+        let name = match self.peek() {
+            Token::Ident(x) | Token::Text(x) => x.to_string(),
+            _ => return self.unexpected("name"),
+        };
+        self.advance();
+        self.expect_symbol(")")?;
+        Ok(name)
+    }
+
+    fn parse_count(&mut self) -> Result<usize, ParseQueryError> {
+        match self.peek() {
+            Token::Number(x) => match x.parse() {
+                Ok(x) => {
+                    self.advance();
+                    Ok(x)
+                }
+                Err(_) => self.unexpected("count"),
+            },
+            _ => self.unexpected("count"),
+        }
+    }
+
+    fn parse_text(&mut self) -> Result<String, ParseQueryError> {
+        match self.peek() {
+            Token::Text(x) => {
+                let x = x.to_string();
+                self.advance();
+                Ok(x)
+            }
+            _ => self.unexpected("string"),
+        }
+    }
+
+    fn parse_texts(&mut self) -> Result<Vec<String>, ParseQueryError> {
+        self.expect_symbol("[")?;
+        self.parse_sequence("]", Self::parse_text)
+    }
+
+    fn parse_values(&mut self, default: NumberType) -> Result<Vec<Value>, ParseQueryError> {
+        self.expect_symbol("[")?;
+        self.parse_sequence("]", |parser| parser.parse_value(default))
+    }
+
+    /// Parses items separated by commas until the closing symbol, where the
+    /// opening symbol has already been parsed
+    fn parse_sequence<T>(
+        &mut self,
+        close: &str,
+        mut f: impl FnMut(&mut Self) -> Result<T, ParseQueryError>,
+    ) -> Result<Vec<T>, ParseQueryError> {
+        let mut items = Vec::new();
+        if self.eat_symbol(close) {
+            return Ok(items);
+        }
+
+        loop {
+            items.push(f(self)?);
+            if self.eat_symbol(close) {
+                return Ok(items);
+            }
+            self.expect_symbol(",")?;
+        }
+    }
+
+    fn parse_value(&mut self, default: NumberType) -> Result<Value, ParseQueryError> {
+        self.nested(|parser| parser.parse_value_body(default))
+    }
+
+    fn parse_value_body(&mut self, default: NumberType) -> Result<Value, ParseQueryError> {
+        let position = self.position();
+        let value = match self.advance() {
+            Token::Text(x) => Value::Text(x),
+            Token::Char(x) => Value::Primitive(Primitive::Char(x)),
+            Token::Number(x) => self.parse_number(position, &x, false, default)?,
+            Token::Symbol("-") => {
+                let position = self.position();
+                match self.advance() {
+                    Token::Number(x) | Token::Ident(x) => {
+                        self.parse_number(position, &x, true, default)?
+                    }
+                    _ => return self.error(position, "Expected number after `-`"),
+                }
+            }
+            Token::Symbol("(") => {
+                self.expect_symbol(")")?;
+                Value::Primitive(Primitive::Unit)
+            }
+            Token::Symbol("[") => {
+                Value::List(self.parse_sequence("]", |parser| parser.parse_value(default))?)
+            }
+            Token::Symbol("{") => Value::Map(
+                self.parse_sequence("}", |parser| {
+                    let key = parser.parse_text()?;
+                    parser.expect_symbol(":")?;
+                    Ok((key, parser.parse_value(default)?))
+                })?
+                .into_iter()
+                .collect::<HashMap<String, Value>>(),
+            ),
+            Token::Ident(x) if x == "true" => Value::Primitive(Primitive::Bool(true)),
+            Token::Ident(x) if x == "false" => Value::Primitive(Primitive::Bool(false)),
+            Token::Ident(x) if x == "none" => Value::Optional(None),
+            Token::Ident(x) if x == "some" => {
+                self.expect_symbol("(")?;
+                let value = self.parse_value(default)?;
+                self.expect_symbol(")")?;
+                Value::Optional(Some(Box::new(value)))
+            }
+            Token::Ident(x) => self.parse_number(position, &x, false, default)?,
+            token => return self.error(position, format!("Expected value, found {}", token)),
+        };
+
+        Ok(value)
+    }
+
+    /// Parses a number or non-finite float such as `inf` or `NaN_f32`
+    fn parse_number(
+        &self,
+        position: usize,
+        s: &str,
+        negative: bool,
+        default: NumberType,
+    ) -> Result<Value, ParseQueryError> {
+        let invalid = || {
+            self.error(
+                position,
+                format!("Expected value, found {}", Token::Ident(s.to_string())),
+            )
+        };
+
+        let (digits, suffix) = match ["inf", "NaN"].iter().find(|x| s.starts_with(*x)) {
+            Some(x) => (*x, s[x.len()..].strip_prefix('_')),
+            None => match s.find(&['i', 'u', 'f'][..]) {
+                Some(i) => (&s[..i], Some(&s[i..])),
+                None => (s, None),
+            },
+        };
+
+        let is_float_word = digits == "inf" || digits == "NaN";
+        if !digits.starts_with(|c: char| c.is_ascii_digit()) && !is_float_word {
+            return invalid();
+        }
+
+        let suffix = match suffix.map(NumberType::from_str) {
+            Some(Ok(ty)) => Some(ty),
+            Some(Err(_)) => return invalid(),
+            None if s.len() > digits.len() => return invalid(),
+            None => None,
+        };
+
+        let digits = if negative {
+            format!("-{}", digits)
+        } else {
+            digits.to_string()
+        };
+
+        match number_from_digits(&digits, suffix, default) {
+            Some(x) => Ok(Value::Primitive(Primitive::Number(x))),
+            None => self.error(position, format!("Invalid number {}", digits)),
+        }
+    }
+
+    fn parse_predicate(&mut self, default: NumberType) -> Result<Predicate, ParseQueryError> {
+        self.nested(|parser| parser.parse_predicate_body(default))
+    }
+
+    fn parse_predicate_body(&mut self, default: NumberType) -> Result<Predicate, ParseQueryError> {
+        let position = self.position();
+        let keyword = match self.advance() {
+            Token::Symbol("(") => {
+                let (op, mut ps) = self.parse_combination(&["and", "or", "xor"], |parser| {
+                    parser.parse_predicate(default)
+                })?;
+                return Ok(match op {
+                    Some(op) => combine(&op, ps),
+                    None => ps.remove(0),
+                });
+            }
+            Token::Symbol(x) => x.to_string(),
+            Token::Ident(x) => x,
+            token => return self.error(position, format!("Expected predicate, found {}", token)),
+        };
+
+        let predicate = match keyword.as_str() {
+            "always" => Predicate::Always,
+            "never" => Predicate::Never,
+            "is_none" => Predicate::IsNone,
+            "lambda" => return self.error(position, "Lambda predicates cannot be parsed"),
+            "and" | "or" | "xor" => {
+                self.expect_symbol("(")?;
+                let ps = self.parse_sequence(")", |parser| parser.parse_predicate(default))?;
+                combine(&keyword, ps)
+            }
+            "not" => Predicate::Not(Box::new(self.parse_predicate(default)?)),
+            "any" => Predicate::Any(Box::new(self.parse_predicate(default)?)),
+            "not_none_and" => Predicate::NotNoneAnd(Box::new(self.parse_predicate(default)?)),
+            "none_or" => Predicate::NoneOr(Box::new(self.parse_predicate(default)?)),
+            "=" => Predicate::Equals(self.parse_value(default)?),
+            "!=" => Predicate::NotEquals(self.parse_value(default)?),
+            ">" => Predicate::GreaterThan(self.parse_value(default)?),
+            ">=" => Predicate::GreaterThanOrEquals(self.parse_value(default)?),
+            "<" => Predicate::LessThan(self.parse_value(default)?),
+            "<=" => Predicate::LessThanOrEquals(self.parse_value(default)?),
+            "contains" => Predicate::Contains(self.parse_value(default)?),
+            "contains_all" => Predicate::ContainsAll(self.parse_values(default)?),
+            "contains_any" => Predicate::ContainsAny(self.parse_values(default)?),
+            "has_key" => {
+                let key = self.parse_text()?;
+                if self.eat_keyword("where") {
+                    Predicate::HasKeyWhereValue(key, Box::new(self.parse_predicate(default)?))
+                } else {
+                    Predicate::HasKey(key)
+                }
+            }
+            "in" | "not_in" => {
+                let is_in = keyword == "in";
+                if matches!(self.peek(), Token::Symbol("[")) {
+                    let set = self.parse_values(default)?.into_iter().collect();
+                    if is_in {
+                        Predicate::InSet(set)
+                    } else {
+                        Predicate::NotInSet(set)
+                    }
+                } else {
+                    let start = self.parse_value(default)?;
+                    self.expect_symbol("..=")?;
+                    let range = start..=self.parse_value(default)?;
+                    if is_in {
+                        Predicate::InRange(range)
+                    } else {
+                        Predicate::NotInRange(range)
+                    }
+                }
+            }
+            "starts_with" => Predicate::TextStartsWith(self.parse_text()?),
+            "starts_with_any" => Predicate::TextStartsWithAny(self.parse_texts()?),
+            "ends_with" => Predicate::TextEndsWith(self.parse_text()?),
+            "ends_with_any" => Predicate::TextEndsWithAny(self.parse_texts()?),
+            "contained_in" => Predicate::TextContainedIn(self.parse_text()?),
+            "text_contains_all" => Predicate::TextContainsAll(self.parse_texts()?),
+            "text_contains_any" => Predicate::TextContainsAny(self.parse_texts()?),
+            _ => {
+                return self.error(
+                    position,
+                    format!("Expected predicate, found {}", Token::Ident(keyword)),
+                )
+            }
+        };
+
+        let position = self.position();
+        if self.eat_keyword("ignore_case") {
+            match ignore_case(predicate) {
+                Some(predicate) => Ok(predicate),
+                None => self.error(position, "Predicate does not support `ignore_case`"),
+            }
+        } else {
+            Ok(predicate)
+        }
+    }
+
+    /// Parses items combined by the same operator until the closing
+    /// parenthesis, where the opening parenthesis has already been parsed,
+    /// returning the operator if more than one item was parsed
+    fn parse_combination<T>(
+        &mut self,
+        ops: &[&str],
+        mut f: impl FnMut(&mut Self) -> Result<T, ParseQueryError>,
+    ) -> Result<(Option<String>, Vec<T>), ParseQueryError> {
+        let mut items = vec![f(self)?];
+        let mut op: Option<String> = None;
+
+        while !self.eat_symbol(")") {
+            let position = self.position();
+            let next = match self.peek() {
+                Token::Ident(x) if ops.contains(&x.as_str()) => x.to_string(),
+                _ => {
+                    let ops: Vec<String> = ops.iter().map(|op| format!("`{}`", op)).collect();
+                    return self.unexpected(&format!("{}, or `)`", ops.join(", ")));
+                }
+            };
+
+            match op.as_ref() {
+                Some(op) if op != &next => {
+                    return self.error(
+                        position,
+                        format!("Cannot mix `{}` with `{}` without parentheses", op, next),
+                    )
+                }
+                _ => op = Some(next),
+            }
+
+            self.advance();
+            items.push(f(self)?);
+        }
+
+        Ok((op, items))
+    }
+}
+
+/// Combines predicates using the operator of the given name
+fn combine(op: &str, ps: Vec<Predicate>) -> Predicate {
+    match op {
+        "and" => Predicate::And(ps),
+        "or" => Predicate::Or(ps),
+        _ => Predicate::Xor(ps),
+    }
+}
+
+/// Converts a predicate into its case insensitive form, if it has one
+fn ignore_case(predicate: Predicate) -> Option<Predicate> {
+    let into_text = |value: Value| match value {
+        Value::Text(x) => Some(x),
+        _ => None,
+    };
+
+    Some(match predicate {
+        Predicate::Equals(x) => Predicate::TextEqualsCaseInsensitive(into_text(x)?),
+        Predicate::NotEquals(x) => Predicate::TextNotEqualsCaseInsensitive(into_text(x)?),
+        Predicate::InSet(x) => Predicate::TextInSetCaseInsensitive(
+            x.into_iter().map(into_text).collect::<Option<_>>()?,
+        ),
+        Predicate::TextContainedIn(x) => Predicate::TextContainedInCaseInsensitive(x),
+        Predicate::TextContainsAll(x) => Predicate::TextContainsAllCaseInsensitive(x),
+        Predicate::TextContainsAny(x) => Predicate::TextContainsAnyCaseInsensitive(x),
+        Predicate::TextEndsWith(x) => Predicate::TextEndsWithCaseInsensitive(x),
+        Predicate::TextEndsWithAny(x) => Predicate::TextEndsWithAnyCaseInsensitive(x),
+        Predicate::TextStartsWith(x) => Predicate::TextStartsWithCaseInsensitive(x),
+        Predicate::TextStartsWithAny(x) => Predicate::TextStartsWithAnyCaseInsensitive(x),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Predicate as P, TypedPredicate as TP};
+
+    /// Asserts that the query is written as the text, which parses back into
+    /// a query that is written the same way
+    fn assert_round_trip(query: Query, text: &str) {
+        assert_eq!(query.to_string(), text);
+
+        let parsed: Query = text.parse().unwrap();
+        assert_eq!(parsed.to_string(), text);
+        assert_eq!(format!("{:?}", parsed), format!("{:?}", query));
+    }
+
+    #[test]
+    fn query_should_round_trip_through_text() {
+        assert_round_trip(Query::default(), "");
+
+        assert_round_trip(
+            Query::default()
+                .where_id(TP::equals(5))
+                .where_type(TP::not_equals(String::from("app::User")))
+                .where_created(TP::in_range(10..=20))
+                .where_last_updated(TP::less_than(99)),
+            r#"id = 5 and type != "app::User" and created in 10..=20 and last_updated < 99"#,
+        );
+
+        assert_round_trip(
+            Query::default()
+                .where_field("age", P::greater_than_or_equals(30u8))
+                .where_into_edge("friends")
+                .where_into_inverse_edge("author")
+                .where_field("my name", P::text_ends_with_any(vec!["a", "\"b\"\n"]))
+                .where_edge(
+                    "manager",
+                    Filter::where_edge("team", Filter::where_id(TP::always())),
+                )
+                .where_edge(
+                    "groups",
+                    Filter::where_assoc_created(TP::greater_than(100))
+                        & Filter::where_assoc_data(P::equals(String::from("admin"))),
+                )
+                .order_by_field("age", OrderDirection::Descending)
+                .order_by_id(OrderDirection::Ascending)
+                .limit(10)
+                .offset(5)
+                .after("0000000000000003".parse().unwrap()),
+            concat!(
+                r#"field(age) >= 30u8 | into_edge(friends) | into_inverse_edge(author) | "#,
+                r#"field("my name") "#,
+                r#"ends_with_any ["a", "\"b\"\n"] and edge(manager) -> edge(team) -> id always "#,
+                r#"and edge(groups) -> (assoc_created > 100 and assoc_data = "admin") "#,
+                r#"order by field(age) desc, id asc limit 10 offset 5 after "0000000000000003""#,
+            ),
+        );
+    }
+
+    #[test]
+    fn filter_combinations_should_round_trip_through_text() {
+        assert_round_trip(
+            Query::default()
+                .where_or(vec![
+                    Filter::where_field("name", P::text_starts_with("A")),
+                    Filter::where_edge("manager", Filter::where_id(TP::always())),
+                ])
+                .where_not(Filter::and(vec![Filter::where_id(TP::equals(3))]))
+                .where_and(vec![
+                    Filter::where_created(TP::greater_than(1)),
+                    Filter::or(vec![Filter::not(Filter::where_into_edge("x"))]),
+                ])
+                .where_or(Vec::new()),
+            concat!(
+                r#"(field(name) starts_with "A" or edge(manager) -> id always) and "#,
+                r#"not and(id = 3) and (created > 1 and or(not into_edge(x))) and or()"#,
+            ),
+        );
+    }
+
+    #[test]
+    fn set_operations_should_round_trip_through_text() {
+        assert_round_trip(
+            Query::default()
+                .where_into_edge("friends")
+                .where_into_edge("friends")
+                .except(
+                    Query::default()
+                        .where_id(TP::equals(1))
+                        .where_into_edge("friends"),
+                )
+                .union(
+                    Query::default()
+                        .where_field("age", P::greater_than(3))
+                        .order_by_id(OrderDirection::Descending)
+                        .limit(2),
+                )
+                .where_type(TP::equals(String::from("a")))
+                .where_not(Filter::intersect(Query::default())),
+            concat!(
+                "into_edge(friends) | into_edge(friends) | except(id = 1 | into_edge(friends)) | ",
+                r#"union(field(age) > 3 order by id desc limit 2) | type = "a" and not intersect()"#,
+            ),
+        );
+    }
+
+    #[test]
+    fn predicate_should_round_trip_through_text() {
+        let cases: Vec<(Predicate, &str)> = vec![
+            (P::Never, "never"),
+            (P::IsNone, "is_none"),
+            (
+                P::and(vec![P::greater_than(1), P::less_than(5)]),
+                "(> 1 and < 5)",
+            ),
+            (P::Or(vec![P::equals(1)]), "or(= 1)"),
+            (P::Xor(Vec::new()), "xor()"),
+            (
+                P::or(vec![P::Always, P::and(vec![P::Never, P::Always])]),
+                "(always or (never and always))",
+            ),
+            (P::not(P::equals(-3i64)), "not = -3i64"),
+            (P::any(P::equals(1.5)), "any = 1.5"),
+            (P::equals(2.0f32), "= 2.0f32"),
+            (P::contains('\''), r"contains '\''"),
+            (
+                P::contains_all(vec![true, false]),
+                "contains_all [true, false]",
+            ),
+            (
+                P::contains_any(vec![
+                    Value::Primitive(Primitive::Unit),
+                    Value::from(Some(3u8)),
+                    Value::from(None::<u8>),
+                ]),
+                "contains_any [(), some(3u8), none]",
+            ),
+            (P::equals(vec![vec![1], vec![]]), "= [[1], []]"),
+            (
+                P::has_key_where_value(
+                    "a",
+                    P::equals(Value::from(
+                        vec![(String::from("y"), 2), (String::from("x"), 1)]
+                            .into_iter()
+                            .collect::<HashMap<String, i32>>(),
+                    )),
+                ),
+                r#"has_key "a" where = {"x": 1, "y": 2}"#,
+            ),
+            (P::has_key("a"), r#"has_key "a""#),
+            (P::in_set(vec![3, 1, 2]), "in [1, 2, 3]"),
+            (P::not_in_set(Vec::<i32>::new()), "not_in []"),
+            (P::not_in_range(-1..=1), "not_in -1..=1"),
+            (
+                P::not_none_and(P::text_contained_in("abc")),
+                r#"not_none_and contained_in "abc""#,
+            ),
+            (
+                P::none_or(P::text_starts_with("a")),
+                r#"none_or starts_with "a""#,
+            ),
+            (
+                P::text_contains_all(vec!["a"]),
+                r#"text_contains_all ["a"]"#,
+            ),
+            (
+                P::text_contains_any(vec!["a"]),
+                r#"text_contains_any ["a"]"#,
+            ),
+            (P::text_ends_with("a"), r#"ends_with "a""#),
+            (
+                P::text_starts_with_any(vec!["a"]),
+                r#"starts_with_any ["a"]"#,
+            ),
+            (
+                P::text_equals_case_insensitive("\u{1}"),
+                r#"= "\u{1}" ignore_case"#,
+            ),
+            (
+                P::text_not_equals_case_insensitive("a"),
+                r#"!= "a" ignore_case"#,
+            ),
+            (
+                P::text_in_set_case_insensitive(vec!["b", "a"]),
+                r#"in ["a", "b"] ignore_case"#,
+            ),
+            (
+                P::text_contained_in_case_insensitive("a"),
+                r#"contained_in "a" ignore_case"#,
+            ),
+            (
+                P::text_contains_all_case_insensitive(vec!["a"]),
+                r#"text_contains_all ["a"] ignore_case"#,
+            ),
+            (
+                P::text_contains_any_case_insensitive(vec!["a"]),
+                r#"text_contains_any ["a"] ignore_case"#,
+            ),
+            (
+                P::text_ends_with_case_insensitive("a"),
+                r#"ends_with "a" ignore_case"#,
+            ),
+            (
+                P::text_ends_with_any_case_insensitive(vec!["a"]),
+                r#"ends_with_any ["a"] ignore_case"#,
+            ),
+            (
+                P::text_starts_with_case_insensitive("a"),
+                r#"starts_with "a" ignore_case"#,
+            ),
+            (
+                P::text_starts_with_any_case_insensitive(vec!["a"]),
+                r#"starts_with_any ["a"] ignore_case"#,
+            ),
+        ];
+
+        for (predicate, text) in cases {
+            assert_eq!(predicate.to_string(), text);
+
+            let parsed: Predicate = text.parse().unwrap();
+            assert_eq!(parsed.to_string(), text);
+            assert_eq!(parsed, predicate, "{}", text);
+        }
+
+        // Non-finite numbers are not equal to themselves, so they are
+        // compared by their debug output instead
+        for (predicate, text) in vec![
+            (P::equals(f64::INFINITY), "= inf"),
+            (P::equals(-f32::INFINITY), "= -inf_f32"),
+            (P::equals(f64::NAN), "= NaN"),
+        ] {
+            assert_eq!(predicate.to_string(), text);
+            let parsed: Predicate = text.parse().unwrap();
+            assert_eq!(format!("{:?}", parsed), format!("{:?}", predicate));
+        }
+
+        let text = format!("= {}u128", u128::MAX);
+        assert_eq!(P::equals(u128::MAX).to_string(), text);
+        assert_eq!(text.parse::<Predicate>(), Ok(P::equals(u128::MAX)));
+    }
+
+    #[test]
+    fn filter_should_use_subject_type_for_unsuffixed_numbers() {
+        let filter: Filter = "id in [1, 2usize, 3u8]".parse().unwrap();
+        assert_eq!(filter.to_string(), "id in [1, 2, 3u8]");
+
+        let filter: Filter = "created > 5".parse().unwrap();
+        assert_eq!(
+            format!("{:?}", filter),
+            format!("{:?}", Filter::where_created(TP::greater_than(5)))
+        );
+    }
+
+    #[test]
+    fn parse_should_report_position_of_errors() {
+        let err = |s: &str| s.parse::<Query>().unwrap_err();
+
+        assert_eq!(err("id =").position(), 4);
+        assert_eq!(err("id = 3 and bad").position(), 11);
+        assert_eq!(err("field(a) > \"abc").position(), 11);
+        assert_eq!(err("field(a) = 3 ignore_case").position(), 13);
+        assert_eq!(err("field(a) (> 1 and < 2 or = 3)").position(), 22);
+        assert_eq!(err("field(a) lambda").position(), 9);
+        assert_eq!(err("id = 3 limit x").position(), 13);
+        assert_eq!(err("id = 3 after \"zz\"").position(), 13);
+        assert_eq!(err("id = 3 $").position(), 7);
+        assert_eq!(err("id = 3 limit 5 id = 4").position(), 15);
+        assert_eq!(err("id = 3 or id = 4").position(), 7);
+        assert_eq!(err("(id = 3 or id = 4 and id = 5)").position(), 18);
+    }
+
+    #[test]
+    fn parse_should_fail_if_input_is_nested_too_deeply() {
+        let nested = |open: &str, inner: &str, close: &str, depth: usize| {
+            format!("{}{}{}", open.repeat(depth), inner, close.repeat(depth))
+        };
+
+        // Nesting up to the maximum depth is still parsed
+        assert!(nested("(", "id = 3", ")", 100).parse::<Query>().is_ok());
+        assert!(nested("not ", "= 3", "", 100).parse::<Predicate>().is_ok());
+
+        let err = nested("(", "id = 3", ")", 5000)
+            .parse::<Query>()
+            .unwrap_err();
+        assert_eq!(err.position(), Parser::MAX_DEPTH);
+        assert_eq!(err.message(), "Nesting exceeds maximum depth of 128");
+
+        let too_deep = |err: ParseQueryError| err.message().contains("maximum depth");
+        for text in vec![
+            nested("edge(a) -> ", "id = 3", "", 5000),
+            nested("union(", "", ")", 5000),
+            format!("field(a) {}", nested("(", "= 3", ")", 5000)),
+            format!("field(a) = {}", nested("[", "3", "]", 5000)),
+        ] {
+            assert!(too_deep(text.parse::<Query>().unwrap_err()), "{}", text);
+        }
+    }
+
+    #[test]
+    fn display_should_write_lambdas_that_fail_to_parse() {
+        let query = Query::default().where_field("a", P::lambda(|_| true));
+        assert_eq!(query.to_string(), "field(a) lambda");
+        assert!(query.to_string().parse::<Query>().is_err());
+    }
+}
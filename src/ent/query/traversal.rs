@@ -0,0 +1,409 @@
+use crate::{DatabaseResult, Ent, Filter, Id};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Represents the order in which a [`Traversal`] visits ents
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub enum TraversalOrder {
+    /// Visits all ents a number of hops away before any ents further away,
+    /// meaning that each ent is reached by a shortest path
+    BreadthFirst,
+
+    /// Visits all ents reachable through an ent before moving on to the
+    /// next ent at the same depth
+    DepthFirst,
+}
+
+impl Default for TraversalOrder {
+    /// Visits ents breadth first by default
+    fn default() -> Self {
+        Self::BreadthFirst
+    }
+}
+
+/// Represents the ids of ents visited in order to reach the last ent of a
+/// [`Traversal`], starting with one of the traversal's ents
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Path(Vec<Id>);
+
+impl Path {
+    /// The ids of the ents along the path, in order
+    #[inline]
+    pub fn ids(&self) -> &[Id] {
+        &self.0
+    }
+
+    /// Consumes the path, returning the ids of the ents along it
+    #[inline]
+    pub fn into_ids(self) -> Vec<Id> {
+        self.0
+    }
+
+    /// The id of the ent where the path starts
+    #[inline]
+    pub fn start(&self) -> Id {
+        self.0[0]
+    }
+
+    /// The id of the ent reached by the path
+    #[inline]
+    pub fn end(&self) -> Id {
+        self.0[self.0.len() - 1]
+    }
+
+    /// The number of edges followed along the path
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.0.len() - 1
+    }
+
+    fn push(&self, id: Id) -> Self {
+        let mut ids = self.0.clone();
+        ids.push(id);
+        Self(ids)
+    }
+}
+
+/// Represents a walk through a database that starts at one or more ents and
+/// repeatedly follows their named edges, visiting each ent at most once
+///
+/// Each edge can have a filter that ents must pass to be reached through
+/// it, which is checked against each ent as if nested within another filter.
+/// By default, ents are visited breadth first with no limit to the number of
+/// hops, and only the ents reached by following edges are returned.
+///
+/// ## Examples
+///
+/// ```
+/// use entity::{Edge, Ent, Traversal, UntypedEnt};
+///
+/// let ents = vec![
+///     UntypedEnt::from_collections(1, vec![], vec![Edge::new("friends", vec![2, 3])]),
+///     UntypedEnt::from_collections(2, vec![], vec![Edge::new("friends", vec![1, 4])]),
+///     UntypedEnt::from_collections(3, vec![], vec![]),
+///     UntypedEnt::from_collections(4, vec![], vec![Edge::new("friends", vec![5])]),
+///     UntypedEnt::from_collections(5, vec![], vec![]),
+/// ];
+///
+/// let visited = Traversal::new(vec![1])
+///     .follow("friends")
+///     .max_depth(2)
+///     .apply(|id, _| Ok(ents.get(id - 1).map(|ent| Box::from(ent.clone()) as Box<dyn Ent>)))
+///     .unwrap();
+///
+/// let ids: Vec<Vec<usize>> = visited.into_iter().map(|(path, _)| path.into_ids()).collect();
+/// assert_eq!(ids, vec![vec![1, 2], vec![1, 3], vec![1, 2, 4]]);
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Traversal {
+    start: Vec<Id>,
+    edges: Vec<(String, Option<Filter>)>,
+    order: TraversalOrder,
+    min_depth: usize,
+    max_depth: Option<usize>,
+    to: Option<Id>,
+}
+
+impl Traversal {
+    /// Creates a new traversal starting at the ents with the given ids,
+    /// which follows no edges
+    pub fn new<I: IntoIterator<Item = Id>>(start: I) -> Self {
+        Self {
+            start: start.into_iter().collect(),
+            edges: Vec::new(),
+            order: TraversalOrder::default(),
+            min_depth: 1,
+            max_depth: None,
+            to: None,
+        }
+    }
+
+    /// The ids of the ents where the traversal starts
+    #[inline]
+    pub fn start(&self) -> &[Id] {
+        &self.start
+    }
+
+    /// The names of the edges followed by the traversal, alongside the
+    /// filter that ents must pass to be reached through each edge
+    #[inline]
+    pub fn edges(&self) -> &[(String, Option<Filter>)] {
+        &self.edges
+    }
+
+    /// The order in which ents are visited
+    #[inline]
+    pub fn order(&self) -> TraversalOrder {
+        self.order
+    }
+
+    /// The fewest hops away an ent must be to be returned
+    #[inline]
+    pub fn get_min_depth(&self) -> usize {
+        self.min_depth
+    }
+
+    /// The most hops away an ent can be to be visited, if limited
+    #[inline]
+    pub fn get_max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// The id of the ent that ends the traversal once reached, if any
+    #[inline]
+    pub fn get_to(&self) -> Option<Id> {
+        self.to
+    }
+
+    /// Follows the named edge of each ent visited
+    pub fn follow<S: Into<String>>(mut self, edge: S) -> Self {
+        self.edges.push((edge.into(), None));
+        self
+    }
+
+    /// Follows the named edge of each ent visited, only reaching the ents
+    /// on the edge that pass the filter
+    pub fn follow_where<S: Into<String>, F: Into<Filter>>(mut self, edge: S, filter: F) -> Self {
+        self.edges.push((edge.into(), Some(filter.into())));
+        self
+    }
+
+    /// Visits ents breadth first, which is the default
+    pub fn breadth_first(mut self) -> Self {
+        self.order = TraversalOrder::BreadthFirst;
+        self
+    }
+
+    /// Visits ents depth first
+    pub fn depth_first(mut self) -> Self {
+        self.order = TraversalOrder::DepthFirst;
+        self
+    }
+
+    /// Only returns ents at least the given number of hops away, where a
+    /// depth of 0 includes the ents where the traversal starts
+    pub fn min_depth(mut self, depth: usize) -> Self {
+        self.min_depth = depth;
+        self
+    }
+
+    /// Only visits ents at most the given number of hops away
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Ends the traversal once the ent with the given id is reached,
+    /// returning only the path to that ent
+    pub fn to(mut self, id: Id) -> Self {
+        self.to = Some(id);
+        self
+    }
+
+    /// Walks through the ents loaded by the function, producing the path
+    /// taken to reach each ent visited alongside the loaded ent in the order
+    /// they were visited
+    ///
+    /// The function is given the id of an ent alongside the filter of the
+    /// edge used to reach it, if any, and returns the ent if it exists and
+    /// passes the filter.
+    pub fn apply<F>(&self, mut load: F) -> DatabaseResult<Vec<(Path, Box<dyn Ent>)>>
+    where
+        F: FnMut(Id, Option<&Filter>) -> DatabaseResult<Option<Box<dyn Ent>>>,
+    {
+        let mut visited = Vec::new();
+
+        // Ents are only returned the first time they are visited, but are
+        // visited again when depth first finds a shorter path to them so
+        // that ents within the max depth are still reached through them
+        let mut returned = HashSet::new();
+        let mut depths: HashMap<Id, usize> = HashMap::new();
+
+        let mut pending: VecDeque<(Path, Option<&Filter>)> = self
+            .start
+            .iter()
+            .map(|id| (Path(vec![*id]), None))
+            .collect();
+        if self.order == TraversalOrder::DepthFirst {
+            pending = pending.into_iter().rev().collect();
+        }
+
+        while let Some((path, filter)) = match self.order {
+            TraversalOrder::BreadthFirst => pending.pop_front(),
+            TraversalOrder::DepthFirst => pending.pop_back(),
+        } {
+            let (id, depth) = (path.end(), path.depth());
+            if depths.get(&id).map_or(false, |d| *d <= depth) {
+                continue;
+            }
+
+            let ent = match load(id, filter)? {
+                Some(ent) => ent,
+                None => continue,
+            };
+            depths.insert(id, depth);
+
+            if self.to == Some(id) {
+                return Ok(vec![(path, ent)]);
+            }
+
+            if self.max_depth.map_or(true, |max| depth < max) {
+                let mut next = Vec::new();
+                for (name, filter) in self.edges.iter() {
+                    if let Some(edge) = ent.edge(name) {
+                        for id in edge.to_ids() {
+                            if depths.get(&id).map_or(true, |d| *d > depth + 1) {
+                                next.push((path.push(id), filter.as_ref()));
+                            }
+                        }
+                    }
+                }
+
+                // Ents are pushed in reverse when depth first so that the
+                // first ent on the first edge is visited next
+                match self.order {
+                    TraversalOrder::BreadthFirst => pending.extend(next),
+                    TraversalOrder::DepthFirst => pending.extend(next.into_iter().rev()),
+                }
+            }
+
+            if self.to.is_none() && depth >= self.min_depth && returned.insert(id) {
+                visited.push((path, ent));
+            }
+        }
+
+        Ok(if self.to.is_some() {
+            Vec::new()
+        } else {
+            visited
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Edge, TypedPredicate as TP, UntypedEnt};
+
+    /// Creates ents connected through their friends edge:
+    ///
+    /// 1 -> 2, 3
+    /// 2 -> 1, 3
+    /// 3 -> 4
+    /// 4 -> 5
+    /// 5 -> 6
+    /// 6 -> (none)
+    fn ents() -> Vec<UntypedEnt> {
+        vec![vec![2, 3], vec![1, 3], vec![4], vec![5], vec![6], vec![]]
+            .into_iter()
+            .enumerate()
+            .map(|(i, ids)| {
+                UntypedEnt::from_collections(i + 1, vec![], vec![Edge::new("friends", ids)])
+            })
+            .collect()
+    }
+
+    fn apply(traversal: Traversal) -> Vec<Vec<Id>> {
+        let ents = ents();
+        traversal
+            .apply(|id, filter| {
+                let passes = match filter {
+                    Some(Filter::Id(p)) => p.check(id),
+                    Some(_) => unreachable!("Only id filters are used by tests"),
+                    None => true,
+                };
+                Ok(ents
+                    .get(id.wrapping_sub(1))
+                    .filter(|_| passes)
+                    .map(|ent| Box::from(ent.clone()) as Box<dyn Ent>))
+            })
+            .unwrap()
+            .into_iter()
+            .map(|(path, _)| path.into_ids())
+            .collect()
+    }
+
+    #[test]
+    fn apply_should_visit_ents_breadth_first_by_shortest_path() {
+        let paths = apply(Traversal::new(vec![1]).follow("friends"));
+        assert_eq!(
+            paths,
+            vec![
+                vec![1, 2],
+                vec![1, 3],
+                vec![1, 3, 4],
+                vec![1, 3, 4, 5],
+                vec![1, 3, 4, 5, 6],
+            ]
+        );
+    }
+
+    #[test]
+    fn apply_should_visit_ents_depth_first() {
+        let paths = apply(Traversal::new(vec![1]).follow("friends").depth_first());
+        assert_eq!(
+            paths,
+            vec![
+                vec![1, 2],
+                vec![1, 2, 3],
+                vec![1, 2, 3, 4],
+                vec![1, 2, 3, 4, 5],
+                vec![1, 2, 3, 4, 5, 6],
+            ]
+        );
+    }
+
+    #[test]
+    fn apply_should_only_visit_ents_within_depth_limits() {
+        let paths = apply(
+            Traversal::new(vec![1])
+                .follow("friends")
+                .min_depth(0)
+                .max_depth(2),
+        );
+        assert_eq!(paths, vec![vec![1], vec![1, 2], vec![1, 3], vec![1, 3, 4]]);
+
+        // Ents first reached by a longer path are visited again when a
+        // shorter path is found, so ents beyond them are still reached
+        let paths = apply(
+            Traversal::new(vec![1])
+                .follow("friends")
+                .depth_first()
+                .max_depth(2),
+        );
+        assert_eq!(paths, vec![vec![1, 2], vec![1, 2, 3], vec![1, 3, 4]]);
+    }
+
+    #[test]
+    fn apply_should_only_reach_ents_passing_the_filter_of_the_edge() {
+        let paths = apply(
+            Traversal::new(vec![1]).follow_where("friends", Filter::where_id(TP::not_equals(2))),
+        );
+        assert_eq!(
+            paths,
+            vec![
+                vec![1, 3],
+                vec![1, 3, 4],
+                vec![1, 3, 4, 5],
+                vec![1, 3, 4, 5, 6]
+            ]
+        );
+    }
+
+    #[test]
+    fn apply_should_skip_ents_that_do_not_exist() {
+        let paths = apply(Traversal::new(vec![999, 5]).follow("friends"));
+        assert_eq!(paths, vec![vec![5, 6]]);
+    }
+
+    #[test]
+    fn apply_should_only_return_path_to_target_if_reached() {
+        let paths = apply(Traversal::new(vec![1]).follow("friends").to(5));
+        assert_eq!(paths, vec![vec![1, 3, 4, 5]]);
+
+        let paths = apply(Traversal::new(vec![5]).follow("friends").to(1));
+        assert!(paths.is_empty());
+    }
+}
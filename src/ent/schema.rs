@@ -0,0 +1,426 @@
+use super::{EdgeDefinition, EntType, FieldDefinition};
+use std::collections::BTreeMap;
+
+/// Represents the description of an edge of some type of ent, which is its
+/// definition alongside the type of the ents it points to
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub struct EdgeSchema {
+    definition: EdgeDefinition,
+    target: String,
+}
+
+impl EdgeSchema {
+    /// Creates a new description of the edge with the given definition,
+    /// pointing to ents of the given type
+    pub fn new<T: Into<String>>(definition: EdgeDefinition, target: T) -> Self {
+        Self {
+            definition,
+            target: target.into(),
+        }
+    }
+
+    /// The name of the edge
+    #[inline]
+    pub fn name(&self) -> &str {
+        self.definition.name()
+    }
+
+    /// The definition of the edge
+    #[inline]
+    pub fn definition(&self) -> &EdgeDefinition {
+        &self.definition
+    }
+
+    /// The type of the ents that the edge points to, which is the type of
+    /// the wrapper for edges that point to one of many types of ents
+    #[inline]
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+}
+
+/// Represents the description of a type of ent, which is comprised of the
+/// type alongside the definitions of its fields and edges
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub struct TypeSchema {
+    r#type: String,
+    fields: Vec<FieldDefinition>,
+    edges: Vec<EdgeSchema>,
+}
+
+impl TypeSchema {
+    /// Creates a new description of the type of ent with the given fields
+    /// and edges
+    pub fn new<T, F, E>(r#type: T, fields: F, edges: E) -> Self
+    where
+        T: Into<String>,
+        F: IntoIterator<Item = FieldDefinition>,
+        E: IntoIterator<Item = EdgeSchema>,
+    {
+        Self {
+            r#type: r#type.into(),
+            fields: fields.into_iter().collect(),
+            edges: edges.into_iter().collect(),
+        }
+    }
+
+    /// The type of ent being described, matching [`EntType::type_str`]
+    #[inline]
+    pub fn r#type(&self) -> &str {
+        &self.r#type
+    }
+
+    /// The definitions of the fields of the type of ent
+    #[inline]
+    pub fn fields(&self) -> &[FieldDefinition] {
+        &self.fields
+    }
+
+    /// The descriptions of the edges of the type of ent
+    #[inline]
+    pub fn edges(&self) -> &[EdgeSchema] {
+        &self.edges
+    }
+
+    /// Returns the definition of the field with the given name
+    pub fn field(&self, name: &str) -> Option<&FieldDefinition> {
+        self.fields.iter().find(|f| f.name() == name)
+    }
+
+    /// Returns the description of the edge with the given name
+    pub fn edge(&self, name: &str) -> Option<&EdgeSchema> {
+        self.edges.iter().find(|e| e.name() == name)
+    }
+}
+
+/// Represents an ent that can describe its type without an instance of it
+pub trait EntSchema: EntType {
+    /// Returns the description of the ent's type
+    fn schema() -> TypeSchema;
+}
+
+/// Represents a single difference between two schemas
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub enum SchemaChange {
+    /// Type of ent only found in the newer schema
+    TypeAdded { r#type: String },
+
+    /// Type of ent only found in the older schema
+    TypeRemoved { r#type: String },
+
+    /// Field only found in the newer schema
+    FieldAdded { r#type: String, name: String },
+
+    /// Field only found in the older schema
+    FieldRemoved { r#type: String, name: String },
+
+    /// Field whose definition differs between the schemas
+    FieldChanged { r#type: String, name: String },
+
+    /// Edge only found in the newer schema
+    EdgeAdded { r#type: String, name: String },
+
+    /// Edge only found in the older schema
+    EdgeRemoved { r#type: String, name: String },
+
+    /// Edge whose description differs between the schemas
+    EdgeChanged { r#type: String, name: String },
+}
+
+/// Represents a registry of the descriptions of types of ents, ordered by
+/// their types
+///
+/// ## Examples
+///
+/// ```
+/// use entity::{
+///     EdgeDefinition, EdgeSchema, EdgeValueType, FieldDefinition, Schema, SchemaChange,
+///     TypeSchema, ValueType,
+/// };
+///
+/// let mut schema = Schema::new();
+/// schema.insert(TypeSchema::new(
+///     "user",
+///     vec![FieldDefinition::new("name", ValueType::Text)],
+///     vec![EdgeSchema::new(EdgeDefinition::new("friends", EdgeValueType::Many), "user")],
+/// ));
+/// assert_eq!(schema.types().collect::<Vec<_>>(), vec!["user"]);
+/// assert_eq!(schema.get("user").unwrap().edge("friends").unwrap().target(), "user");
+///
+/// let mut other = schema.clone();
+/// other.insert(TypeSchema::new("post", vec![], vec![]));
+/// assert_ne!(schema, other);
+/// assert_eq!(
+///     schema.diff(&other),
+///     vec![SchemaChange::TypeAdded { r#type: String::from("post") }],
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde-1", serde(transparent))]
+pub struct Schema {
+    types: BTreeMap<String, TypeSchema>,
+}
+
+impl Schema {
+    /// Creates a new, empty schema
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the description of the ent's type, replacing any
+    /// description already registered for the type
+    pub fn register<E: EntSchema>(&mut self) -> &mut Self {
+        self.insert(E::schema())
+    }
+
+    /// Inserts the description of a type of ent, replacing any description
+    /// already registered for the type
+    pub fn insert(&mut self, schema: TypeSchema) -> &mut Self {
+        self.types.insert(schema.r#type.clone(), schema);
+        self
+    }
+
+    /// Removes the description of the given type of ent, returning it if
+    /// it was registered
+    pub fn remove(&mut self, r#type: &str) -> Option<TypeSchema> {
+        self.types.remove(r#type)
+    }
+
+    /// Returns the description of the given type of ent
+    pub fn get(&self, r#type: &str) -> Option<&TypeSchema> {
+        self.types.get(r#type)
+    }
+
+    /// Returns true if the given type of ent is registered
+    pub fn contains(&self, r#type: &str) -> bool {
+        self.types.contains_key(r#type)
+    }
+
+    /// Returns the registered types of ents in order
+    pub fn types(&self) -> impl Iterator<Item = &str> {
+        self.types.keys().map(String::as_str)
+    }
+
+    /// Returns the descriptions of the registered types of ents in order of
+    /// their types
+    pub fn iter(&self) -> impl Iterator<Item = &TypeSchema> {
+        self.types.values()
+    }
+
+    /// Returns the total types of ents registered
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    /// Returns true if no types of ents are registered
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    /// Produces the changes needed to go from this schema to the other
+    /// schema, ordered by type of ent and then by fields before edges
+    pub fn diff(&self, other: &Schema) -> Vec<SchemaChange> {
+        let mut changes = Vec::new();
+        let mut types: Vec<&String> = self.types.keys().chain(other.types.keys()).collect();
+        types.sort();
+        types.dedup();
+
+        for ty in types {
+            match (self.types.get(ty), other.types.get(ty)) {
+                (Some(old), Some(new)) => diff_type(old, new, &mut changes),
+                (Some(_), None) => changes.push(SchemaChange::TypeRemoved {
+                    r#type: ty.to_string(),
+                }),
+                (None, Some(_)) => changes.push(SchemaChange::TypeAdded {
+                    r#type: ty.to_string(),
+                }),
+                (None, None) => unreachable!(),
+            }
+        }
+
+        changes
+    }
+}
+
+impl Extend<TypeSchema> for Schema {
+    fn extend<I: IntoIterator<Item = TypeSchema>>(&mut self, iter: I) {
+        for schema in iter {
+            self.insert(schema);
+        }
+    }
+}
+
+impl std::iter::FromIterator<TypeSchema> for Schema {
+    fn from_iter<I: IntoIterator<Item = TypeSchema>>(iter: I) -> Self {
+        let mut schema = Self::new();
+        schema.extend(iter);
+        schema
+    }
+}
+
+fn diff_type(old: &TypeSchema, new: &TypeSchema, changes: &mut Vec<SchemaChange>) {
+    let ty = || old.r#type.to_string();
+
+    for f in old.fields() {
+        match new.field(f.name()) {
+            Some(x) if x == f => {}
+            Some(_) => changes.push(SchemaChange::FieldChanged {
+                r#type: ty(),
+                name: f.name().to_string(),
+            }),
+            None => changes.push(SchemaChange::FieldRemoved {
+                r#type: ty(),
+                name: f.name().to_string(),
+            }),
+        }
+    }
+    for f in new
+        .fields()
+        .iter()
+        .filter(|f| old.field(f.name()).is_none())
+    {
+        changes.push(SchemaChange::FieldAdded {
+            r#type: ty(),
+            name: f.name().to_string(),
+        });
+    }
+
+    for e in old.edges() {
+        match new.edge(e.name()) {
+            Some(x) if x == e => {}
+            Some(_) => changes.push(SchemaChange::EdgeChanged {
+                r#type: ty(),
+                name: e.name().to_string(),
+            }),
+            None => changes.push(SchemaChange::EdgeRemoved {
+                r#type: ty(),
+                name: e.name().to_string(),
+            }),
+        }
+    }
+    for e in new.edges().iter().filter(|e| old.edge(e.name()).is_none()) {
+        changes.push(SchemaChange::EdgeAdded {
+            r#type: ty(),
+            name: e.name().to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EdgeDeletionPolicy, EdgeValueType, FieldAttribute, NumberType, ValueType};
+
+    fn user_schema() -> TypeSchema {
+        TypeSchema::new(
+            "user",
+            vec![
+                FieldDefinition::new("name", ValueType::Text),
+                FieldDefinition::new_with_attributes(
+                    "email",
+                    ValueType::Text,
+                    vec![FieldAttribute::Indexed, FieldAttribute::Unique],
+                ),
+            ],
+            vec![
+                EdgeSchema::new(EdgeDefinition::new("friends", EdgeValueType::Many), "user"),
+                EdgeSchema::new(
+                    EdgeDefinition::new_with_deletion_policy(
+                        "posts",
+                        EdgeValueType::Many,
+                        EdgeDeletionPolicy::DeepDelete,
+                    ),
+                    "post",
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn diff_should_be_empty_if_schemas_are_equal() {
+        let schema: Schema = vec![user_schema()].into_iter().collect();
+        assert!(schema.diff(&schema.clone()).is_empty());
+    }
+
+    #[test]
+    fn diff_should_report_changes_to_types_fields_and_edges() {
+        let old: Schema = vec![user_schema(), TypeSchema::new("comment", vec![], vec![])]
+            .into_iter()
+            .collect();
+
+        let new: Schema = vec![
+            TypeSchema::new(
+                "user",
+                vec![
+                    FieldDefinition::new("email", ValueType::Text),
+                    FieldDefinition::new("age", ValueType::from(NumberType::U8)),
+                ],
+                vec![
+                    EdgeSchema::new(EdgeDefinition::new("friends", EdgeValueType::Many), "user"),
+                    EdgeSchema::new(
+                        EdgeDefinition::new_with_deletion_policy(
+                            "posts",
+                            EdgeValueType::Many,
+                            EdgeDeletionPolicy::DeepDelete,
+                        ),
+                        "article",
+                    ),
+                    EdgeSchema::new(
+                        EdgeDefinition::new("parent", EdgeValueType::MaybeOne),
+                        "user",
+                    ),
+                ],
+            ),
+            TypeSchema::new("post", vec![], vec![]),
+        ]
+        .into_iter()
+        .collect();
+
+        let s = String::from;
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                SchemaChange::TypeRemoved {
+                    r#type: s("comment")
+                },
+                SchemaChange::TypeAdded { r#type: s("post") },
+                SchemaChange::FieldRemoved {
+                    r#type: s("user"),
+                    name: s("name")
+                },
+                SchemaChange::FieldChanged {
+                    r#type: s("user"),
+                    name: s("email")
+                },
+                SchemaChange::FieldAdded {
+                    r#type: s("user"),
+                    name: s("age")
+                },
+                SchemaChange::EdgeChanged {
+                    r#type: s("user"),
+                    name: s("posts")
+                },
+                SchemaChange::EdgeAdded {
+                    r#type: s("user"),
+                    name: s("parent")
+                },
+            ]
+        );
+    }
+
+    #[cfg(feature = "serde-1")]
+    #[test]
+    fn schema_should_serialize_and_deserialize() {
+        let schema: Schema = vec![user_schema(), TypeSchema::new("post", vec![], vec![])]
+            .into_iter()
+            .collect();
+
+        let json = serde_json::to_string(&schema).unwrap();
+        let other: Schema = serde_json::from_str(&json).unwrap();
+        assert_eq!(other, schema);
+    }
+}
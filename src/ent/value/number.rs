@@ -4,6 +4,7 @@ use paste::paste;
 use std::{
     cmp::Ordering,
     hash::{Hash, Hasher},
+    ops::Add,
 };
 use strum::{Display, EnumDiscriminants, EnumString};
 
@@ -304,6 +305,29 @@ impl Number {
     pub fn to_type(&self) -> NumberType {
         self.into()
     }
+
+    /// Returns the magnitude of the number as its integral part alongside
+    /// the bits of its fractional part as an `f64`, which together order
+    /// magnitudes exactly
+    ///
+    /// Floats too large for a `u128` to hold their integral part have no
+    /// fractional part, so their own bits are provided in its place to keep
+    /// them ordered relative to one another.
+    pub(crate) fn magnitude(&self) -> (u128, u64) {
+        let fraction = |x: f64| {
+            if x >= u128::MAX as f64 {
+                x.to_bits()
+            } else {
+                x.fract().to_bits()
+            }
+        };
+
+        match self.to_absolute() {
+            Self::F32(x) => (x as u128, fraction(f64::from(x))),
+            Self::F64(x) => (x as u128, fraction(x)),
+            x => (x.to_u128(), 0),
+        }
+    }
 }
 
 /// Represents some data that can be converted to and from a [`Number`]
@@ -522,17 +546,14 @@ impl PartialOrd for Number {
         }
 
         match (self.sign(), other.sign()) {
-            (NumberSign::Negative, NumberSign::Negative) => Some(
-                other
-                    .to_absolute()
-                    .to_u128()
-                    .cmp(&self.to_absolute().to_u128()),
-            ),
+            (NumberSign::Negative, NumberSign::Negative) => {
+                Some(other.magnitude().cmp(&self.magnitude()))
+            }
             (NumberSign::Negative, NumberSign::Positive) => Some(Ordering::Less),
             (NumberSign::Negative, NumberSign::Zero) => Some(Ordering::Less),
             (NumberSign::Positive, NumberSign::Negative) => Some(Ordering::Greater),
             (NumberSign::Positive, NumberSign::Positive) => {
-                Some(self.to_u128().cmp(&other.to_u128()))
+                Some(self.magnitude().cmp(&other.magnitude()))
             }
             (NumberSign::Positive, NumberSign::Zero) => Some(Ordering::Greater),
             (NumberSign::Zero, NumberSign::Negative) => Some(Ordering::Greater),
@@ -542,6 +563,50 @@ impl PartialOrd for Number {
     }
 }
 
+impl Add for Number {
+    type Output = Self;
+
+    /// Adds two numbers, producing an exact `u128` or `i128` if both numbers
+    /// are integers whose sum fits within one, and otherwise producing an
+    /// `f64`
+    ///
+    /// ```
+    /// use entity::Number;
+    ///
+    /// assert!(matches!(Number::from(3u8) + Number::from(4i64), Number::U128(7)));
+    /// assert!(matches!(Number::from(3u8) + Number::from(-4i64), Number::I128(-1)));
+    /// assert!(matches!(Number::from(u128::MAX) + Number::from(1u8), Number::F64(_)));
+    /// assert!(matches!(Number::from(0.5f32) + Number::from(2), Number::F64(x) if x == 2.5));
+    /// ```
+    fn add(self, other: Self) -> Self::Output {
+        if !self.is_float() && !other.is_float() {
+            let sum = if !self.is_negative() && !other.is_negative() {
+                self.to_u128().checked_add(other.to_u128()).map(Self::U128)
+            } else {
+                match (i128::try_from_number(self), i128::try_from_number(other)) {
+                    (Ok(a), Ok(b)) => a.checked_add(b).map(Self::I128),
+
+                    // Only a positive number can be too large for an i128,
+                    // so the other number is negative and shrinks it, where
+                    // the wrapping absolute value of i128::MIN is still
+                    // correct once cast to a u128
+                    (Err(big), Ok(x)) | (Ok(x), Err(big)) => big
+                        .to_u128()
+                        .checked_sub(x.wrapping_abs() as u128)
+                        .map(Self::U128),
+                    (Err(_), Err(_)) => None,
+                }
+            };
+
+            if let Some(sum) = sum {
+                return sum;
+            }
+        }
+
+        Self::F64(self.to_f64() + other.to_f64())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::float_cmp)]
@@ -791,6 +856,33 @@ mod tests {
         assert_eq!(a.partial_cmp(&b), Some(Ordering::Less));
     }
 
+    #[test]
+    fn partial_cmp_should_compare_fractional_parts_of_floats() {
+        let a = Number::from(1.2f64);
+        let b = Number::from(1.7f32);
+        assert_eq!(a.partial_cmp(&b), Some(Ordering::Less));
+
+        let a = Number::from(1.5f64);
+        let b = Number::from(1u8);
+        assert_eq!(a.partial_cmp(&b), Some(Ordering::Greater));
+
+        let a = Number::from(-1.5f64);
+        let b = Number::from(-1i8);
+        assert_eq!(a.partial_cmp(&b), Some(Ordering::Less));
+
+        let a = Number::from(2.0f32);
+        let b = Number::from(2u64);
+        assert_eq!(a.partial_cmp(&b), Some(Ordering::Equal));
+
+        let a = Number::from(1e39f64);
+        let b = Number::from(1e40f64);
+        assert_eq!(a.partial_cmp(&b), Some(Ordering::Less));
+
+        let a = Number::from(u128::MAX);
+        let b = Number::from(1e39f64);
+        assert_eq!(a.partial_cmp(&b), Some(Ordering::Less));
+    }
+
     #[test]
     fn partial_cmp_should_return_greater_than_if_positive_and_other_zero() {
         let a = Number::from(1isize);
@@ -1260,4 +1352,31 @@ mod tests {
         check_try_from_number!(u8 min can be pulled from f32 f64 u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
         check_try_from_number!(u8 max can be pulled from f32 f64 u8 u16 u32 u64 u128 usize i16 i32 i64 i128 isize);
     }
+
+    #[test]
+    fn add_should_produce_exact_integer_if_sum_fits() {
+        let sum = Number::from(u64::MAX) + Number::from(1u8);
+        assert!(matches!(sum, Number::U128(x) if x == u64::MAX as u128 + 1));
+
+        let sum = Number::from(i64::MIN) + Number::from(-1i8);
+        assert!(matches!(sum, Number::I128(x) if x == i64::MIN as i128 - 1));
+
+        let sum = Number::from(u128::MAX) + Number::from(-1);
+        assert!(matches!(sum, Number::U128(x) if x == u128::MAX - 1));
+
+        let sum = Number::from(-5) + Number::from(5usize);
+        assert!(matches!(sum, Number::I128(0)));
+    }
+
+    #[test]
+    fn add_should_produce_float_if_either_number_is_float_or_sum_does_not_fit() {
+        let sum = Number::from(1.5f64) + Number::from(1u8);
+        assert!(matches!(sum, Number::F64(x) if x == 2.5));
+
+        let sum = Number::from(u128::MAX) + Number::from(1);
+        assert!(matches!(sum, Number::F64(_)));
+
+        let sum = Number::from(i128::MIN) + Number::from(-1);
+        assert!(matches!(sum, Number::F64(_)));
+    }
 }
@@ -18,6 +18,53 @@ pub use entity_macros::*;
 pub mod vendor {
     /// Re-exported macros, useful only to [`entity_macros`] crate
     pub mod macros {
+        use std::collections::{
+            BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque,
+        };
+
+        /// Represents a value whose length can be validated, where text is
+        /// measured in characters rather than bytes and collections are
+        /// measured in items
+        pub trait Length {
+            /// Returns the length of the value
+            fn length(&self) -> usize;
+        }
+
+        impl Length for str {
+            fn length(&self) -> usize {
+                self.chars().count()
+            }
+        }
+
+        impl Length for String {
+            fn length(&self) -> usize {
+                self.as_str().length()
+            }
+        }
+
+        macro_rules! impl_length {
+            ($($name:ident<$($param:ident),+>),+ $(,)?) => {
+                $(
+                    impl<$($param),+> Length for $name<$($param),+> {
+                        fn length(&self) -> usize {
+                            self.len()
+                        }
+                    }
+                )+
+            };
+        }
+
+        impl_length!(
+            Vec<T>,
+            VecDeque<T>,
+            LinkedList<T>,
+            BinaryHeap<T>,
+            HashMap<K, V>,
+            HashSet<T>,
+            BTreeMap<K, V>,
+            BTreeSet<T>,
+        );
+
         /// Re-export of serde
         pub mod serde {
             /// Indicates whether or not the included serde derive macros are
@@ -55,5 +102,54 @@ pub mod vendor {
             #[cfg(not(feature = "typetag"))]
             pub use ::entity_noop_macros::noop_attr as serde;
         }
+
+        /// Re-export of regex
+        pub mod regex {
+            /// Indicates whether or not patterns are matched using regex
+            /// as a result of the feature existing (true) or fail to compile
+            /// (false)
+            #[inline]
+            pub const fn exists() -> bool {
+                cfg!(feature = "regex")
+            }
+
+            #[cfg(feature = "regex")]
+            pub use ::lazy_static::lazy_static;
+
+            #[cfg(feature = "regex")]
+            pub use ::regex_crate::Regex;
+
+            pub use crate::__entity_regex_is_match as is_match;
+        }
     }
 }
+
+/// Returns whether or not the text matches the pattern, which is compiled
+/// once the first time it is matched by this expansion of the macro
+///
+/// The pattern is expected to have already been checked by the macro
+/// generating this call.
+#[cfg(feature = "regex")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __entity_regex_is_match {
+    ($pattern:expr, $text:expr) => {{
+        $crate::vendor::macros::regex::lazy_static! {
+            static ref REGEX: $crate::vendor::macros::regex::Regex =
+                $crate::vendor::macros::regex::Regex::new($pattern)
+                    .expect("Invalid pattern");
+        }
+        REGEX.is_match($text)
+    }};
+}
+
+/// Fails to compile, as matching patterns requires the regex feature
+#[cfg(not(feature = "regex"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __entity_regex_is_match {
+    ($pattern:expr, $text:expr) => {{
+        ::std::compile_error!("Validating a field with regex requires the regex feature of entity");
+        false
+    }};
+}